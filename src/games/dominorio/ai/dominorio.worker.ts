@@ -12,7 +12,7 @@ import openingBook from './book.json';
 
 // WASM module type (will be dynamically imported if available)
 interface WasmEngine {
-  new(tt_size_bits: number): WasmEngine;
+  new(tt_mb: number, seed: number): WasmEngine;
   search(
     occupied_low: number,
     occupied_high: number,
@@ -29,8 +29,13 @@ interface WasmEngine {
     tt_hits: bigint;
     tt_probes: bigint;
     score: number;
+    mate_in?: number;
   };
   clear_tt(): void;
+  set_skill_level(level: number): void;
+  set_blunder_rate(rate: number): void;
+  clear_skill_level(): void;
+  readonly skill_level: number | undefined;
 }
 
 // State
@@ -52,9 +57,11 @@ async function init(): Promise<void> {
     // Try to load WASM module
     const wasmModule = await import('./wasm/pkg/dominorio_ai.js');
     await wasmModule.default();
-    wasmEngine = new wasmModule.DominorioEngine(18); // 256K entries TT
+    // Seed logged so a reported game can be replayed with the same random choices
+    const seed = (Math.random() * 0x1_0000_0000) >>> 0;
+    wasmEngine = new wasmModule.DominorioEngine(8, seed); // 8 MB TT
     useWasm = true;
-    console.log('[DominorioAI] WASM engine initialized');
+    console.log(`[DominorioAI] WASM engine initialized (seed ${seed})`);
   } catch (e) {
     console.warn('[DominorioAI] WASM not available, using TypeScript fallback:', e);
     useWasm = false;
@@ -263,7 +270,8 @@ function searchWASM(
   occupiedLow: number,
   occupiedHigh: number,
   side: Side,
-  params: DifficultyParams
+  params: DifficultyParams,
+  skillLevel: number | undefined
 ): AIResponse {
   if (!wasmEngine) {
     throw new Error('WASM engine not initialized');
@@ -271,13 +279,24 @@ function searchWASM(
   
   const startTime = performance.now();
   
+  if (skillLevel !== wasmEngine.skill_level) {
+    if (skillLevel === undefined) {
+      wasmEngine.clear_skill_level();
+    } else {
+      wasmEngine.set_skill_level(skillLevel);
+    }
+  }
+  
+  const blunderRate = params.blunderRate ?? 0;
+  wasmEngine.set_blunder_rate(blunderRate);
+  
   const result = wasmEngine.search(
     occupiedLow,
     occupiedHigh,
     side,
     params.timeBudgetMs,
     params.maxDepth,
-    params.topN,
+    blunderRate > 0 ? 0 : params.topN,
     params.scoreDelta
   );
   
@@ -293,6 +312,7 @@ function searchWASM(
     elapsedMs: performance.now() - startTime,
     ttHitRate: ttProbes > 0 ? ttHits / ttProbes : 0,
     score: result.score,
+    mateIn: result.mate_in,
     fromBook: false,
   };
 }
@@ -337,7 +357,8 @@ function handleSearch(request: AIRequest): AIResponse {
       request.occupiedLow,
       request.occupiedHigh,
       request.sideToMove,
-      effectiveParams
+      effectiveParams,
+      request.skillLevel
     );
   } else {
     return searchTS(
@@ -25,6 +25,11 @@ export interface DifficultyParams {
   topN: number;
   /** Score delta within which moves are considered equivalent */
   scoreDelta: number;
+  /**
+   * Target rate of human-like mistakes made by the WASM engine, which then
+   * ignores topN/scoreDelta (the TypeScript fallback keeps using them)
+   */
+  blunderRate?: number;
 }
 
 export const DIFFICULTY_PRESETS: Record<AIDifficulty, DifficultyParams> = {
@@ -33,6 +38,7 @@ export const DIFFICULTY_PRESETS: Record<AIDifficulty, DifficultyParams> = {
     maxDepth: 4,
     topN: 5,
     scoreDelta: 50,
+    blunderRate: 0.3,
   },
   medium: {
     timeBudgetMs: 600,
@@ -69,6 +75,8 @@ export interface AIRequest {
   difficulty: AIDifficulty;
   /** Number of half-moves played (for opening book) */
   plyCount: number;
+  /** Calibrated engine skill level (0..=20); overrides topN/scoreDelta (WASM only) */
+  skillLevel?: number;
 }
 
 /** Response sent from Worker to UI */
@@ -88,6 +96,11 @@ export interface AIResponse {
   ttHitRate: number;
   /** Best score found */
   score: number;
+  /**
+   * Turns of the side to move until a proven result, counting a turn where
+   * it is stuck: >= 1 when it wins, <= -1 when it loses (-1: stuck now)
+   */
+  mateIn?: number;
   /** Whether move came from opening book */
   fromBook: boolean;
 }
@@ -1,4 +1,4 @@
-import { useState, useEffect, useCallback, useRef } from 'react';
+import { useState, useEffect, useCallback, useMemo, useRef } from 'react';
 import { GameLayout } from '../../components/GameLayout';
 import { PlayerInfo } from '../../components/PlayerInfo';
 import { WinnerAnnouncement } from '../../components/WinnerAnnouncement';
@@ -14,6 +14,8 @@ import {
   trocarOrientacoes,
   recusarTroca,
   decidirTrocaComputador,
+  calcularMetricasCompletas,
+  carregarMetricasMotor,
 } from './logic';
 import { GameMode, Player } from '../../types';
 import { QuelhasAIClient, INITIAL_METRICS, type AIDifficulty, type AIMetrics } from './ai';
@@ -45,6 +47,23 @@ export function QuelhasGame({ onVoltar }: QuelhasGameProps) {
   const [aiMetrics, setAiMetrics] = useState<AIMetrics>(INITIAL_METRICS);
   const [aiReady, setAiReady] = useState(false);
   const aiClientRef = useRef<QuelhasAIClient | null>(null);
+  const [metricasDoMotor, setMetricasDoMotor] = useState(false);
+
+  // Métricas do painel de estratégia: as do motor (wasm) quando disponíveis
+  useEffect(() => {
+    let ativo = true;
+    carregarMetricasMotor().then(ok => {
+      if (ativo) setMetricasDoMotor(ok);
+    });
+    return () => {
+      ativo = false;
+    };
+  }, []);
+
+  const metricas = useMemo(
+    () => calcularMetricasCompletas(state.tabuleiro),
+    [state.tabuleiro, metricasDoMotor]
+  );
 
   // Inicializar cliente de IA (Worker) uma vez
   useEffect(() => {
@@ -320,6 +339,26 @@ export function QuelhasGame({ onVoltar }: QuelhasGameProps) {
           </p>
         </div>
 
+        {/* Painel de estratégia */}
+        <div className="bg-white border-2 border-gray-200 rounded-xl p-3">
+          <p className="text-gray-700 font-semibold text-sm mb-2 text-center">
+            📊 Estratégia (jogadas mínimas–máximas)
+          </p>
+          <div className="grid grid-cols-2 gap-3 text-xs">
+            {([
+              ['Vertical', metricas.vertical, 'text-pink-600'],
+              ['Horizontal', metricas.horizontal, 'text-cyan-600'],
+            ] as const).map(([nome, m, cor]) => (
+              <div key={nome} className="text-center">
+                <p className={`font-semibold ${cor}`}>{nome}</p>
+                <p>Total: {m.min}–{m.max}</p>
+                <p>Exclusivas: {m.minExclusivo}–{m.maxExclusivo}</p>
+                <p>Partilhadas: {m.minPartilhado}–{m.maxPartilhado}</p>
+              </div>
+            ))}
+          </div>
+        </div>
+
         {/* Tabuleiro */}
         <div className="game-container">
           <div className="aspect-square max-w-lg mx-auto">
@@ -0,0 +1,24 @@
+import { test, expect, describe } from "bun:test";
+import { evaluateMisere } from "./engine";
+
+// Tudo ocupado exceto as células indicadas (índice = linha * 10 + coluna).
+function ocupadoExceto(livres: number[]) {
+  let low = (1n << 64n) - 1n;
+  let high = (1n << 36n) - 1n;
+  for (const idx of livres) {
+    if (idx < 64) low &= ~(1n << BigInt(idx));
+    else high &= ~(1n << BigInt(idx - 64));
+  }
+  return { low, high };
+}
+
+describe("Quelhas - Avaliação Misère do Motor", () => {
+  test("ficar sem jogadas é bom para quem joga", () => {
+    // linha 0 livre (bloco horizontal de 10) e as células (5,0)-(6,0):
+    // a Vertical só tem uma jogada, a Horizontal tem muitas
+    const occ = ocupadoExceto([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 50, 60]);
+
+    expect(evaluateMisere(occ, 0)).toBeGreaterThan(0);
+    expect(evaluateMisere(occ, 1)).toBeLessThan(0);
+  });
+});
@@ -282,7 +282,7 @@ function computeMetricsForOrient(occ: Occ, myOrient: 0 | 1, oppPlayableMask: Occ
   return { min, max, minExcl, maxExcl };
 }
 
-function evaluateMisere(occ: Occ, sideToMove: 0 | 1): number {
+export function evaluateMisere(occ: Occ, sideToMove: 0 | 1): number {
   const myOrient = sideToMove;
   const oppOrient = (1 - sideToMove) as 0 | 1;
 
@@ -330,7 +330,10 @@ function evaluateMisere(occ: Occ, sideToMove: 0 | 1): number {
   const effMy = my.min > 0 ? my.max / my.min : 0;
   score += (effMy - effOpp) * 10;
 
-  return score;
+  // Os termos acima medem a folga de `my` como numa avaliação de jogo normal.
+  // Em misère quem fica sem jogadas ganha: a folga de quem joga é-lhe
+  // desfavorável, daí o sinal.
+  return -score;
 }
 
 function hashStringToU32(str: string): number {
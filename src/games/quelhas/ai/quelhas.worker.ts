@@ -7,7 +7,7 @@ function post(msg: AIResponse) {
 }
 
 interface WasmEngine {
-  new(tt_size_bits: number): WasmEngine;
+  new(tt_mb: number, seed: number): WasmEngine;
   search(
     low_lo: number,
     low_hi: number,
@@ -26,13 +26,24 @@ interface WasmEngine {
     tt_hits: bigint;
     tt_probes: bigint;
     score: number;
+    mate_in?: number;
   };
   clear_tt(): void;
+  set_skill_level(level: number): void;
+  set_blunder_rate(rate: number): void;
+  clear_skill_level(): void;
+  readonly skill_level: number | undefined;
 }
 
 let wasmEngine: WasmEngine | null = null;
 let useWasm = false;
 
+function applySkillLevel(engine: WasmEngine, level: number | undefined) {
+  if (level === engine.skill_level) return;
+  if (level === undefined) engine.clear_skill_level();
+  else engine.set_skill_level(level);
+}
+
 function boardToU64Parts(tabuleiro: ('vazia' | 'ocupada')[][]): {
   lowLo: number;
   lowHi: number;
@@ -78,9 +89,10 @@ async function init(): Promise<void> {
   try {
     const wasmModule = await import('./wasm/pkg/quelhas_wasm.js');
     await wasmModule.default();
-    wasmEngine = new wasmModule.QuelhasEngine(18);
+    const seed = (Math.random() * 0x1_0000_0000) >>> 0;
+    wasmEngine = new wasmModule.QuelhasEngine(8, seed);
     useWasm = true;
-    console.log('[QuelhasAI] WASM engine initialized');
+    console.log(`[QuelhasAI] WASM engine initialized (seed ${seed})`);
   } catch (e) {
     console.warn('[QuelhasAI] WASM not available, using TypeScript fallback:', e);
     useWasm = false;
@@ -106,6 +118,7 @@ self.onmessage = (event: MessageEvent<AIRequest>) => {
           elapsedMs: number;
           ttHitRate: number;
           score: number;
+          mateIn?: number;
           fromBook: boolean;
         };
 
@@ -113,6 +126,9 @@ self.onmessage = (event: MessageEvent<AIRequest>) => {
       const { lowLo, lowHi, highLo, highHi } = boardToU64Parts(req.tabuleiro);
       const side = req.orientacaoIA === 'vertical' ? 0 : 1;
       const startTime = performance.now();
+      applySkillLevel(wasmEngine, req.skillLevel);
+      const blunderRate = preset.blunderRate ?? 0;
+      wasmEngine.set_blunder_rate(blunderRate);
       const r = wasmEngine.search(
         lowLo,
         lowHi,
@@ -121,7 +137,7 @@ self.onmessage = (event: MessageEvent<AIRequest>) => {
         side,
         timeBudgetMs,
         preset.maxDepth,
-        preset.topN,
+        blunderRate > 0 ? 0 : preset.topN,
         preset.scoreDelta
       );
       const ttProbes = Number(r.tt_probes);
@@ -133,6 +149,7 @@ self.onmessage = (event: MessageEvent<AIRequest>) => {
         elapsedMs: performance.now() - startTime,
         ttHitRate: ttProbes > 0 ? ttHits / ttProbes : 0,
         score: r.score,
+        mateIn: r.mate_in,
         fromBook: false,
       };
     } else {
@@ -153,6 +170,7 @@ self.onmessage = (event: MessageEvent<AIRequest>) => {
       elapsedMs: result.elapsedMs,
       ttHitRate: result.ttHitRate,
       score: result.score,
+      mateIn: 'mateIn' in result ? result.mateIn : undefined,
       fromBook: result.fromBook,
     });
   } catch (e) {
@@ -7,10 +7,12 @@ export interface DifficultyParams {
   maxDepth: number;
   topN: number;
   scoreDelta: number;
+  /** Taxa alvo de erros "humanos" do motor WASM (que então ignora topN/scoreDelta) */
+  blunderRate?: number;
 }
 
 export const DIFFICULTY_PRESETS: Record<AIDifficulty, DifficultyParams> = {
-  easy: { timeBudgetMs: 250, maxDepth: 5, topN: 6, scoreDelta: 120 },
+  easy: { timeBudgetMs: 250, maxDepth: 5, topN: 6, scoreDelta: 120, blunderRate: 0.3 },
   medium: { timeBudgetMs: 900, maxDepth: 9, topN: 3, scoreDelta: 60 },
   hard: { timeBudgetMs: 2500, maxDepth: 18, topN: 0, scoreDelta: 0 },
 };
@@ -43,6 +45,8 @@ export type AIRequest = {
   orientacaoAdv: Orientacao;
   timeBudgetMs?: number;
   difficulty: AIDifficulty;
+  /** Calibrated engine skill level (0..=20); overrides the preset's topN/scoreDelta (WASM only) */
+  skillLevel?: number;
 };
 
 export type AIResponse =
@@ -56,6 +60,12 @@ export type AIResponse =
       elapsedMs: number;
       ttHitRate: number;
       score: number;
+      /**
+       * Turns of the side to move until a proven result, counting the turn
+       * where it is stuck: >= 1 when it wins (misère: 1 = already stuck),
+       * <= -1 when it loses (its last move leaves the opponent stuck)
+       */
+      mateIn?: number;
       fromBook: boolean;
     }
   | {
@@ -1,4 +1,5 @@
 import { test, expect, describe } from "bun:test";
+import { readFileSync } from "node:fs";
 import { 
   criarEstadoInicial, 
   criarTabuleiroInicial,
@@ -19,6 +20,7 @@ import {
   classificarBlocos,
   calcularMetricasDeBlocos,
   calcularMetricasCompletas,
+  calcularMetricasTypeScript,
   parseTabuleiroASCII,
   gerarCandidatos,
 } from "./logic";
@@ -823,6 +825,29 @@ describe("Quelhas - Exemplo do Utilizador (Vertical Ganha)", () => {
   });
 });
 
+describe("Quelhas - Métricas iguais às do motor", () => {
+  // Os mesmos casos são verificados em quelhas-core (`board_metrics`): sem
+  // wasm, o painel de estratégia conta os blocos como a IA
+  const casos = readFileSync(new URL("./metricas.casos.txt", import.meta.url), "utf8")
+    .split(/\n\s*\n/)
+    .map(caso => caso.split("\n").filter(linha => linha && !linha.startsWith("//")))
+    .filter(linhas => linhas.length > 0);
+
+  test("calcularMetricasTypeScript dá o mesmo que board_metrics", () => {
+    expect(casos.length).toBe(8);
+    for (const linhas of casos) {
+      const metricas = calcularMetricasTypeScript(parseTabuleiroASCII(linhas.slice(0, 10).join("\n")));
+      for (const linha of linhas.slice(10)) {
+        const [lado, ...esperado] = linha.split(" ");
+        const m = lado === "v" ? metricas.vertical : metricas.horizontal;
+        const blocos = m.blocos.map(b => `${b.indiceFixo}:${b.inicio}:${b.comprimento}${b.exclusivo ? "e" : "p"}`);
+        const obtido = [m.min, m.max, m.minExclusivo, m.maxExclusivo, m.minPartilhado, m.maxPartilhado, ...blocos];
+        expect(obtido.join(" ")).toBe(esperado.join(" "));
+      }
+    }
+  });
+});
+
 describe("Quelhas - Geração de Candidatos", () => {
   test("gerarCandidatos deve incluir segmentos de tamanho 2 nas extremidades", () => {
     const tabuleiro = criarTabuleiroInicial();
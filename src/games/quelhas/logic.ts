@@ -186,9 +186,122 @@ export function calcularMetricasDeBlocos(blocos: Bloco[]): MetricasJogador {
 }
 
 /**
- * Calcula métricas completas para ambos os jogadores.
+ * Métricas de um jogador tal como o `quelhas_wasm` as devolve (`PlayerMetrics`).
+ */
+interface MetricasJogadorMotor {
+  readonly min: number;
+  readonly max: number;
+  readonly min_exclusive: number;
+  readonly max_exclusive: number;
+  readonly min_shared: number;
+  readonly max_shared: number;
+  readonly run_starts: Uint8Array;
+  readonly run_lengths: Uint8Array;
+  readonly run_lines: Uint8Array;
+  readonly run_exclusive: Uint8Array;
+  free(): void;
+}
+
+interface MetricasMotor {
+  readonly vertical: MetricasJogadorMotor;
+  readonly horizontal: MetricasJogadorMotor;
+  free(): void;
+}
+
+type BoardMetricsMotor = (lowLo: number, lowHi: number, highLo: number, highHi: number) => MetricasMotor;
+
+// `board_metrics` do quelhas-core, depois de carregado o wasm
+let boardMetricsMotor: BoardMetricsMotor | null = null;
+
+/**
+ * Carrega as métricas do motor (quelhas-core via wasm). Depois disso
+ * `calcularMetricasCompletas` devolve exatamente o que a IA vê; se o wasm
+ * não estiver disponível fica a implementação TypeScript.
+ */
+export async function carregarMetricasMotor(): Promise<boolean> {
+  if (boardMetricsMotor) return true;
+  try {
+    const wasmModule = await import('./ai/wasm/pkg/quelhas_wasm.js');
+    await wasmModule.default();
+    boardMetricsMotor = wasmModule.board_metrics;
+    return true;
+  } catch (e) {
+    console.warn('[Quelhas] Métricas do motor indisponíveis, a usar TypeScript:', e);
+    return false;
+  }
+}
+
+/**
+ * Indica se as métricas vêm do motor (wasm) ou da implementação TypeScript.
+ */
+export function metricasDoMotorAtivas(): boolean {
+  return boardMetricsMotor !== null;
+}
+
+function converterMetricasMotor(m: MetricasJogadorMotor, orientacao: Orientacao): MetricasJogador {
+  const inicios = m.run_starts;
+  const linhas = m.run_lines;
+  const exclusivos = m.run_exclusive;
+  const blocos: Bloco[] = Array.from(m.run_lengths, (comprimento, i) => ({
+    inicio: inicios[i]!,
+    comprimento,
+    indiceFixo: linhas[i]!,
+    orientacao,
+    exclusivo: exclusivos[i] === 1,
+  }));
+  const metricas: MetricasJogador = {
+    blocos,
+    min: m.min,
+    max: m.max,
+    minExclusivo: m.min_exclusive,
+    maxExclusivo: m.max_exclusive,
+    minPartilhado: m.min_shared,
+    maxPartilhado: m.max_shared,
+  };
+  m.free();
+  return metricas;
+}
+
+function calcularMetricasMotor(boardMetrics: BoardMetricsMotor, tabuleiro: Celula[][]): MetricasCompletas {
+  let low = 0n;
+  let high = 0n;
+  for (let linha = 0; linha < TAMANHO_TABULEIRO; linha++) {
+    for (let coluna = 0; coluna < TAMANHO_TABULEIRO; coluna++) {
+      if (tabuleiro[linha]![coluna] === 'ocupada') {
+        const idx = linha * TAMANHO_TABULEIRO + coluna;
+        if (idx < 64) low |= 1n << BigInt(idx);
+        else high |= 1n << BigInt(idx - 64);
+      }
+    }
+  }
+  const metricas = boardMetrics(
+    Number(low & 0xffffffffn) >>> 0,
+    Number(low >> 32n) >>> 0,
+    Number(high & 0xffffffffn) >>> 0,
+    Number(high >> 32n) >>> 0
+  );
+  const resultado = {
+    vertical: converterMetricasMotor(metricas.vertical, 'vertical'),
+    horizontal: converterMetricasMotor(metricas.horizontal, 'horizontal'),
+  };
+  metricas.free();
+  return resultado;
+}
+
+/**
+ * Calcula métricas completas para ambos os jogadores: com o motor carregado
+ * (`carregarMetricasMotor`) usa o quelhas-core, senão a versão TypeScript.
  */
 export function calcularMetricasCompletas(tabuleiro: Celula[][]): MetricasCompletas {
+  if (boardMetricsMotor) return calcularMetricasMotor(boardMetricsMotor, tabuleiro);
+  return calcularMetricasTypeScript(tabuleiro);
+}
+
+/**
+ * Implementação TypeScript das métricas, usada quando o wasm não está
+ * disponível (os casos de `metricas.casos.txt` garantem que coincide).
+ */
+export function calcularMetricasTypeScript(tabuleiro: Celula[][]): MetricasCompletas {
   // Extrair blocos brutos
   const blocosVerticalBrutos = extrairBlocos(tabuleiro, 'vertical');
   const blocosHorizontalBrutos = extrairBlocos(tabuleiro, 'horizontal');
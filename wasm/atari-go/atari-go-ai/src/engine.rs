@@ -0,0 +1,322 @@
+//! Motor de pesquisa: Atari Go sobre a pesquisa alfa-beta partilhada (`crjm_search`)
+//!
+//! As capturas acabam o jogo, por isso a pesquisa nunca as aplica: um lance
+//! que captura marca a posição como perdida para quem joga a seguir. Com uma
+//! captura disponível só se pesquisam capturas e com uma cadeia em atari só
+//! as casas que a salvam (qualquer outro lance perde já). Nas folhas, a
+//! leitura de ataris (`tactics`) transforma escadas em vitórias provadas; na
+//! raiz, a leitura com redes é tentada antes da pesquisa.
+
+use crjm_search::{pick_root, Aspiration, Game};
+
+use atari_go_core::{squares, Board, Move};
+
+use crate::eval::{self, MATE, MATE_BOUND};
+use crate::rng::Rng;
+use crate::tactics::{self, Reader};
+use crate::tt::TranspositionTable;
+use crate::zobrist::ZobristKeys;
+
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    /// variante principal, a começar em `best_move`
+    pub pv: Vec<Move>,
+    pub depth_reached: u32,
+    pub nodes_searched: u64,
+    pub tt_hits: u64,
+    pub tt_probes: u64,
+    pub score: i32,
+}
+
+/// Leitura na raiz: ataris e redes, com orçamento generoso
+const ROOT_READING: tactics::Limits = tactics::Limits { depth: 24, nets: 1, budget: 200_000 };
+/// Leitura nas folhas: só ataris (escadas), com orçamento pequeno
+const LEAF_READING: tactics::Limits = tactics::Limits { depth: 8, nets: 0, budget: 48 };
+
+/// Janela de aspiration (alargada só do lado que falhou)
+const ASPIRATION: Aspiration = Aspiration { initial: 60, min: 30, max: 600 };
+
+/// Posição vista pela pesquisa: o hash viaja com o tabuleiro
+#[derive(Clone, Copy)]
+struct Position {
+    board: Board,
+    side: u8,
+    hash: u64,
+    /// o adversário acabou de capturar: quem joga perdeu
+    lost: bool,
+}
+
+/// Atari Go para a pesquisa partilhada
+struct AtariGo<'a> {
+    zobrist: &'a ZobristKeys,
+    /// leitura de escadas nas folhas
+    reading: bool,
+}
+
+impl Game for AtariGo<'_> {
+    type Position = Position;
+    type Move = Move;
+    type TableScore = i16;
+    type TableMove = u8;
+
+    const MATE: i32 = MATE;
+    const MATE_BOUND: i32 = MATE_BOUND;
+
+    fn moves(&mut self, pos: &Position) -> Vec<Move> {
+        if pos.lost {
+            return Vec::new();
+        }
+        let legal = pos.board.legal_mask(pos.side);
+        let captures = pos.board.atari_points(1 - pos.side);
+        if captures != 0 {
+            return squares(captures).collect();
+        }
+        // em atari: salvar a cadeia ou perder (se nada salva, todos perdem)
+        let saves = pos.board.atari_points(pos.side) & legal;
+        squares(if saves != 0 { saves } else { legal }).collect()
+    }
+
+    fn has_moves(&mut self, pos: &Position) -> bool {
+        !pos.lost && pos.board.legal_mask(pos.side) != 0
+    }
+
+    fn is_legal(&mut self, pos: &Position, mv: Move) -> bool {
+        !pos.lost && pos.board.is_legal(mv, pos.side)
+    }
+
+    fn apply(&self, pos: &Position, mv: Move) -> Position {
+        let hash = self.zobrist.update(pos.hash, mv, pos.side);
+        if pos.board.atari_points(1 - pos.side) & (1 << mv) != 0 {
+            return Position { board: pos.board, side: 1 - pos.side, hash, lost: true };
+        }
+        let mut board = pos.board;
+        board.play(mv, pos.side);
+        Position { board, side: 1 - pos.side, hash, lost: false }
+    }
+
+    // capturado perde; sem lances legais é empate
+    fn stuck(&self, pos: &Position, ply: u32) -> i32 {
+        if pos.lost {
+            -MATE + ply as i32
+        } else {
+            0
+        }
+    }
+
+    fn evaluate(&mut self, pos: &Position, _key: u64, ply: u32) -> i32 {
+        let ply = ply as i32;
+        if pos.board.atari_points(1 - pos.side) != 0 {
+            return MATE - (ply + 1);
+        }
+        if self.reading {
+            if let Some(line) = Reader::new(LEAF_READING).forced_win(&pos.board, pos.side) {
+                return MATE - (ply + line.len() as i32);
+            }
+        }
+        eval::evaluate(&pos.board, pos.side)
+    }
+
+    fn key(&self, pos: &Position) -> u64 {
+        pos.hash
+    }
+
+    fn pack_move(&self, _pos: &Position, mv: Move) -> u8 {
+        mv
+    }
+
+    fn unpack_move(&self, _pos: &Position, mv: u8) -> Move {
+        mv
+    }
+
+    fn order_score(&mut self, pos: &Position, mv: Move, _depth: u32) -> i32 {
+        eval::move_score(&pos.board, mv, pos.side)
+    }
+}
+
+pub struct Searcher<'a> {
+    search: crjm_search::Searcher<'a, AtariGo<'a>>,
+    rng: &'a mut Rng,
+}
+
+impl<'a> Searcher<'a> {
+    pub fn new(
+        tt: &'a mut TranspositionTable,
+        zobrist: &'a ZobristKeys,
+        rng: &'a mut Rng,
+        deadline_ms: f64,
+        max_depth: u32,
+    ) -> Self {
+        let game = AtariGo { zobrist, reading: true };
+        let limits = crjm_search::Limits::new(deadline_ms, max_depth);
+        Self {
+            search: crjm_search::Searcher::new(game, tt, limits).with_aspiration(ASPIRATION),
+            rng,
+        }
+    }
+
+    /// Pesquisa com aprofundamento iterativo; com `top_n > 0` escolhe ao acaso
+    /// entre os `top_n` melhores lances a menos de `score_delta` do melhor.
+    /// Uma vitória lida na raiz é sempre jogada.
+    pub fn iterative_deepening(
+        &mut self,
+        board: &Board,
+        side: u8,
+        top_n: u32,
+        score_delta: i32,
+        now: impl Fn() -> f64,
+    ) -> SearchResult {
+        let mut reader = Reader::new(ROOT_READING);
+        if let Some(line) = reader.forced_win(board, side) {
+            let plies = line.len() as u32;
+            return SearchResult {
+                best_move: line.first().copied(),
+                pv: line,
+                depth_reached: plies,
+                nodes_searched: reader.nodes(),
+                tt_hits: 0,
+                tt_probes: 0,
+                score: MATE - plies as i32,
+            };
+        }
+
+        let hash = self.search.game().zobrist.hash(board, side);
+        let outcome = self.search.iterative_deepening(Position { board: *board, side, hash, lost: false }, now);
+        let mut best_move = outcome.best_move;
+        let mut best_score = outcome.score;
+
+        if let Some(idx) = pick_root(outcome.root.iter().map(|&(_, s)| s), top_n, score_delta, self.rng) {
+            (best_move, best_score) = (Some(outcome.root[idx].0), outcome.root[idx].1);
+        }
+
+        let mut pv = outcome.pv;
+        if best_move != pv.first().copied() {
+            // a escolha não foi o lance principal: a variante não se aplica
+            pv = best_move.into_iter().collect();
+        }
+        SearchResult {
+            best_move,
+            pv,
+            depth_reached: outcome.depth,
+            nodes_searched: outcome.stats.nodes + reader.nodes(),
+            tt_hits: outcome.stats.tt_hits,
+            tt_probes: outcome.stats.tt_probes,
+            score: best_score,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atari_go_core::{apply_move, generate_moves, BLACK, BOARD_SIZE, WHITE};
+    use std::collections::HashMap;
+
+    fn sq(linha: usize, coluna: usize) -> Move {
+        (linha * BOARD_SIZE + coluna) as Move
+    }
+
+    fn bits(cells: &[(usize, usize)]) -> u128 {
+        cells.iter().fold(0, |acc, &(l, c)| acc | 1 << sq(l, c))
+    }
+
+    /// Quem joga ganha por força em no máximo `depth` lances seus?
+    /// (minimax exato, sem podas nem heurísticas)
+    fn wins_within(board: &Board, side: u8, depth: u32, memo: &mut HashMap<(u128, u128, u8, u32), bool>) -> bool {
+        if board.atari_points(1 - side) != 0 {
+            return true;
+        }
+        if depth <= 1 {
+            return false;
+        }
+        let key = (board.stones(BLACK), board.stones(WHITE), side, depth);
+        if let Some(&v) = memo.get(&key) {
+            return v;
+        }
+        let v = generate_moves(board, side).into_iter().any(|mv| {
+            let child = apply_move(board, mv, side);
+            child.atari_points(side) == 0 && {
+                let replies = generate_moves(&child, 1 - side);
+                !replies.is_empty()
+                    && replies.into_iter().all(|r| wins_within(&apply_move(&child, r, 1 - side), side, depth - 1, memo))
+            }
+        });
+        memo.insert(key, v);
+        v
+    }
+
+    fn search(board: &Board, side: u8, depth: u32) -> SearchResult {
+        let zobrist = ZobristKeys::new();
+        let mut tt = TranspositionTable::new(1);
+        let mut rng = Rng::new(1);
+        Searcher::new(&mut tt, &zobrist, &mut rng, f64::MAX, depth).iterative_deepening(board, side, 0, 0, || 0.0)
+    }
+
+    #[test]
+    fn ladders_are_read_to_the_end() {
+        // pedra branca em (2,2) apanhada em escada até ao canto inferior
+        // direito; sem pedras a quebrar, a escada funciona
+        let board = Board::from_stones(bits(&[(1, 2), (2, 1), (3, 1)]), bits(&[(2, 2)]));
+        let line = Reader::new(ROOT_READING).ladder(&board, sq(2, 2), BLACK).expect("escada");
+        assert!(line.len() > 9);
+        // com uma pedra branca no caminho, a escada falha
+        let broken = Board::from_stones(bits(&[(1, 2), (2, 1), (3, 1)]), bits(&[(2, 2), (6, 6)]));
+        assert_eq!(Reader::new(ROOT_READING).ladder(&broken, sq(2, 2), BLACK), None);
+
+        let result = search(&board, BLACK, 2);
+        assert!(result.score >= MATE_BOUND);
+        let (mut b, mut s) = (board, BLACK);
+        for &mv in &result.pv {
+            assert!(b.is_legal(mv, s));
+            b = apply_move(&b, mv, s);
+            s = 1 - s;
+        }
+        assert_eq!(b.chain_at(sq(2, 2)), None);
+    }
+
+    #[test]
+    fn double_atari_wins_and_a_single_atari_is_answered() {
+        // duas pedras brancas com duas liberdades, uma delas (4,4) em comum
+        let board = Board::from_stones(bits(&[(2, 4), (3, 3), (5, 3), (6, 4)]), bits(&[(3, 4), (5, 4)]));
+        let result = search(&board, BLACK, 2);
+        assert_eq!(result.best_move, Some(sq(4, 4)));
+        assert_eq!(eval::mate_in(result.score), Some(2));
+
+        // brancas a jogar salvam-se em (4,4) e não perdem logo
+        let result = search(&board, WHITE, 3);
+        assert_eq!(result.best_move, Some(sq(4, 4)));
+    }
+
+    #[test]
+    fn short_wins_agree_with_exact_reading() {
+        let mut rng = Rng::new(9);
+        let mut memo = HashMap::new();
+        let mut proven = 0;
+        for _ in 0..30 {
+            // posição aleatória cheia de cadeias curtas no meio do tabuleiro
+            let mut board = Board::default();
+            let mut side = BLACK;
+            for _ in 0..24 {
+                let moves: Vec<Move> = generate_moves(&board, side)
+                    .into_iter()
+                    .filter(|&mv| board.captures(mv, side) == 0 && (sq(2, 2)..=sq(6, 6)).contains(&mv))
+                    .collect();
+                if moves.is_empty() {
+                    break;
+                }
+                board = apply_move(&board, moves[rng.below(moves.len())], side);
+                side = 1 - side;
+            }
+            // vitória em dois lances (três meios-lances) ou menos
+            let result = search(&board, side, 3);
+            let short = result.score >= MATE - 3;
+            assert_eq!(short, wins_within(&board, side, 2, &mut memo));
+            if short {
+                proven += 1;
+                let best = result.best_move.unwrap();
+                let child = apply_move(&board, best, side);
+                assert!(board.captures(best, side) != 0 || child.atari_points(side) == 0);
+            }
+        }
+        assert!(proven > 0);
+    }
+}
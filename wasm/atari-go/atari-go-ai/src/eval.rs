@@ -0,0 +1,119 @@
+//! Avaliação heurística (primeira captura ganha)
+//!
+//! Só é chamada quando quem joga não captura já: as capturas imediatas são
+//! tratadas pelo motor como vitórias.
+
+use atari_go_core::{adjacent, neighbours, Board, Chain, Move, BOARD_SIZE};
+
+pub const MATE: i32 = 30_000;
+/// Um jogo tem no máximo 81 lances: scores a partir daqui são provados
+pub const MATE_BOUND: i32 = MATE - 100;
+
+/// Penalização de uma cadeia por número de liberdades (1, 2, 3)
+const SHORT_OF_LIBERTIES: [i32; 3] = [120, 45, 12];
+/// Peso de cada casa vazia ao lado das nossas pedras
+const INFLUENCE_WEIGHT: i32 = 4;
+/// Custo de cada cadeia separada (ligar pedras é bom)
+const CHAIN_COST: i32 = 6;
+/// Corrida de liberdades entre duas cadeias adjacentes com poucas liberdades
+const RACE_WEIGHT: i32 = 30;
+/// Vantagem de ter a vez
+const TEMPO: i32 = 8;
+
+/// Vezes de jogar de quem tem a vez até o jogo acabar, para um score
+/// provado; `None` para scores heurísticos
+///
+/// Conta também a vez em que o jogo já acabou: pelo menos 1 quando ganha
+/// (o último lance captura), no máximo -1 quando perde (-1: já capturado).
+pub fn mate_in(score: i32) -> Option<i32> {
+    if score >= MATE_BOUND {
+        Some((MATE - score + 1) / 2)
+    } else if score <= -MATE_BOUND {
+        Some(-((MATE + score) / 2 + 1))
+    } else {
+        None
+    }
+}
+
+fn shortage(chain: &Chain) -> i32 {
+    match chain.liberties() {
+        n @ 1..=3 => SHORT_OF_LIBERTIES[n as usize - 1],
+        _ => 0,
+    }
+}
+
+/// Corridas entre as cadeias de `side` e as adversárias encostadas, as duas
+/// com no máximo 3 liberdades: ganha quem tiver mais (a vez desempata)
+fn races(board: &Board, side: u8) -> i32 {
+    let mut score = 0;
+    for mine in board.chains(side).filter(|c| c.liberties() <= 3) {
+        let around = neighbours(mine.stones);
+        for theirs in board.chains(1 - side).filter(|c| c.liberties() <= 3 && c.stones & around != 0) {
+            score += if mine.liberties() >= theirs.liberties() { RACE_WEIGHT } else { -RACE_WEIGHT };
+        }
+    }
+    score
+}
+
+/// Score para quem joga (positivo = bom para `side`)
+pub fn evaluate(board: &Board, side: u8) -> i32 {
+    let empty = board.empty();
+    let mut score = TEMPO + races(board, side);
+    for (color, sign) in [(side, 1), (1 - side, -1)] {
+        let influence = (adjacent(board.stones(color)) & empty).count_ones() as i32;
+        let mut term = INFLUENCE_WEIGHT * influence;
+        for chain in board.chains(color) {
+            term -= CHAIN_COST + shortage(&chain);
+        }
+        score += sign * term;
+    }
+    score
+}
+
+/// Ordenação barata: ataris e salvamentos primeiro, depois casas com
+/// liberdades perto do centro
+#[inline]
+pub fn move_score(board: &Board, mv: Move, side: u8) -> i32 {
+    let bit = 1u128 << mv;
+    let around = neighbours(bit);
+    let mut score = 10 * (around & board.empty()).count_ones() as i32;
+    for sq in atari_go_core::squares(around & board.occupied()) {
+        let Some(chain) = board.chain_at(sq) else { continue };
+        let mine = chain.stones & board.stones(side) != 0;
+        score += match (mine, chain.liberties()) {
+            (true, 1) => 500,
+            (true, 2) => 60,
+            (false, 2) => 400,
+            (false, 3) => 100,
+            _ => 0,
+        };
+    }
+    let (linha, coluna) = (mv as i32 / BOARD_SIZE as i32, mv as i32 % BOARD_SIZE as i32);
+    score - 2 * ((linha - 4).abs() + (coluna - 4).abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atari_go_core::{BLACK, WHITE};
+
+    #[test]
+    fn mate_in_counts_moves_of_each_side() {
+        assert_eq!(mate_in(MATE - 1), Some(1));
+        assert_eq!(mate_in(MATE - 5), Some(3));
+        assert_eq!(mate_in(-MATE), Some(-1));
+        assert_eq!(mate_in(-(MATE - 2)), Some(-2));
+        assert_eq!(mate_in(-(MATE - 4)), Some(-3));
+        assert_eq!(mate_in(250), None);
+    }
+
+    #[test]
+    fn short_liberties_cost_and_symmetry_only_counts_the_tempo() {
+        let board = Board::from_stones(1 << 40, 1 << 30);
+        let mirrored = Board::from_stones(1 << 30, 1 << 40);
+        assert_eq!(evaluate(&board, BLACK), evaluate(&mirrored, WHITE));
+        // pedra preta no canto com duas liberdades contra uma branca no meio
+        let corner = Board::from_stones(1, 1 << 40);
+        assert!(evaluate(&corner, BLACK) < evaluate(&corner, WHITE));
+    }
+}
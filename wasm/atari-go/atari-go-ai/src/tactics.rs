@@ -0,0 +1,183 @@
+//! Leitura tática: pesquisa no espaço de ameaças.
+//!
+//! O atacante só joga lances forçantes e o defensor só as respostas que
+//! importam, por isso uma vitória encontrada é provada:
+//! - atari (e atari duplo): o defensor tem de ocupar uma das casas de captura
+//!   (qualquer outro lance deixa o atacante capturar, e o defensor não tem
+//!   capturas, senão o lance não era jogado); com duas casas só salva uma;
+//! - rede: um lance que não dá atari a uma cadeia com duas liberdades; aqui
+//!   o defensor não é forçado e todas as respostas são lidas.
+//!
+//! As escadas são sequências de ataris e saem da primeira regra; as corridas
+//! de liberdades curtas também, já que cada atari obriga a responder.
+
+use atari_go_core::{adjacent, apply_move, squares, Board, Move};
+
+/// Limites da leitura
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    /// lances do atacante (o último é a captura)
+    pub depth: u32,
+    /// redes permitidas em cada linha
+    pub nets: u32,
+    /// nós até desistir (sem prova)
+    pub budget: u64,
+}
+
+/// Leitor de ameaças com contagem de nós
+pub struct Reader {
+    limits: Limits,
+    nodes: u64,
+}
+
+impl Reader {
+    pub fn new(limits: Limits) -> Self {
+        Self { limits, nodes: 0 }
+    }
+
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Linha que ganha por força para `side` (a jogar): lances alternados a
+    /// acabar na captura, com a defesa mais longa. As profundidades são lidas
+    /// por ordem, por isso a vitória encontrada é a mais curta.
+    pub fn forced_win(&mut self, board: &Board, side: u8) -> Option<Vec<Move>> {
+        for depth in 1..=self.limits.depth {
+            if let Some(line) = self.attack(board, side, depth, self.limits.nets) {
+                return Some(line);
+            }
+            if self.nodes > self.limits.budget {
+                break;
+            }
+        }
+        None
+    }
+
+    /// Linha que captura a cadeia da pedra em `target` só com ataris
+    /// (escadas incluídas), com `side` a jogar
+    pub fn ladder(&mut self, board: &Board, target: Move, side: u8) -> Option<Vec<Move>> {
+        let chain = board.chain_at(target)?;
+        if chain.stones & board.stones(side) != 0 {
+            return None;
+        }
+        self.ladder_attack(board, target, side, self.limits.depth)
+    }
+
+    fn out_of_budget(&mut self) -> bool {
+        self.nodes += 1;
+        self.nodes > self.limits.budget
+    }
+
+    fn attack(&mut self, board: &Board, side: u8, depth: u32, nets: u32) -> Option<Vec<Move>> {
+        if self.out_of_budget() {
+            return None;
+        }
+        let captures = board.atari_points(1 - side);
+        if captures != 0 {
+            return Some(vec![captures.trailing_zeros() as Move]);
+        }
+        if depth <= 1 {
+            return None;
+        }
+
+        let mut ataris = 0u128;
+        let mut net_points = 0u128;
+        for chain in board.chains(1 - side) {
+            match chain.liberties() {
+                2 => {
+                    ataris |= chain.libs;
+                    // casas à volta das liberdades fecham a fuga
+                    net_points |= adjacent(chain.libs) & board.empty() & !chain.libs;
+                }
+                3 => net_points |= chain.libs,
+                _ => {}
+            }
+        }
+        if nets == 0 {
+            net_points = 0;
+        }
+
+        for mv in squares(ataris).chain(squares(net_points & !ataris)) {
+            if !board.is_legal(mv, side) {
+                continue;
+            }
+            let child = apply_move(board, mv, side);
+            // o defensor capturava logo
+            if child.atari_points(side) != 0 {
+                continue;
+            }
+            let threats = child.atari_points(1 - side);
+            let line = if threats != 0 {
+                // ocupar uma casa de captura ou perder já (qualquer lance serve)
+                let replies = child.legal_mask(1 - side);
+                let fills = threats & replies;
+                self.defend(&child, side, if fills != 0 { fills } else { replies }, depth, nets)
+            } else if nets > 0 {
+                self.defend(&child, side, child.legal_mask(1 - side), depth, nets - 1)
+            } else {
+                None
+            };
+            if let Some(mut line) = line {
+                line.insert(0, mv);
+                return Some(line);
+            }
+            if self.nodes > self.limits.budget {
+                return None;
+            }
+        }
+        None
+    }
+
+    /// Todas as `replies` do defensor perdem? Devolve a mais longa seguida
+    /// da linha do atacante; sem respostas o jogo fica empatado
+    fn defend(&mut self, board: &Board, side: u8, replies: u128, depth: u32, nets: u32) -> Option<Vec<Move>> {
+        let mut longest: Option<Vec<Move>> = None;
+        for reply in squares(replies) {
+            let child = apply_move(board, reply, 1 - side);
+            let mut line = self.attack(&child, side, depth - 1, nets)?;
+            line.insert(0, reply);
+            if longest.as_ref().is_none_or(|l| line.len() > l.len()) {
+                longest = Some(line);
+            }
+        }
+        longest
+    }
+
+    fn ladder_attack(&mut self, board: &Board, target: Move, side: u8, depth: u32) -> Option<Vec<Move>> {
+        if self.out_of_budget() {
+            return None;
+        }
+        let chain = board.chain_at(target)?;
+        if chain.liberties() == 1 {
+            return Some(vec![chain.libs.trailing_zeros() as Move]);
+        }
+        if chain.liberties() > 2 || depth <= 1 {
+            return None;
+        }
+        for mv in squares(chain.libs) {
+            if !board.is_legal(mv, side) {
+                continue;
+            }
+            let child = apply_move(board, mv, side);
+            if child.atari_points(side) != 0 {
+                continue;
+            }
+            let escape = child.chain_at(target)?.libs.trailing_zeros() as Move;
+            if !child.is_legal(escape, 1 - side) {
+                // não pode fugir: joga outro lance qualquer e é capturado
+                let Some(other) = squares(child.legal_mask(1 - side)).next() else {
+                    continue;
+                };
+                return Some(vec![mv, other, escape]);
+            }
+            // a fuga é a única resposta (o defensor não tem capturas)
+            let grand = apply_move(&child, escape, 1 - side);
+            if let Some(mut line) = self.ladder_attack(&grand, target, side, depth - 1) {
+                line.splice(0..0, [mv, escape]);
+                return Some(line);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,4 @@
+//! Tabela de transposição: a tabela por buckets de `crjm_search` com scores
+//! de 16 bits e lances de uma casa (4 entradas em 36 bytes).
+
+pub type TranspositionTable = crjm_search::TranspositionTable<i16, u8>;
@@ -0,0 +1,49 @@
+use atari_go_core::{Board, Move, BLACK, CELL_COUNT, WHITE};
+
+use crjm_search::zobrist::KeyStream;
+
+#[derive(Clone)]
+pub struct ZobristKeys {
+    /// chaves por cor (pretas, brancas) e casa
+    pub stones: [[u64; CELL_COUNT]; 2],
+    pub side: u64,
+}
+
+pub const DEFAULT_ZOBRIST_SEED: u64 = 0xA7A2_1960;
+
+impl ZobristKeys {
+    /// Chaves fixas (iguais em todas as execuções).
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_ZOBRIST_SEED)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        let mut keys = KeyStream::new(seed);
+        Self { stones: [keys.keys(), keys.keys()], side: keys.key() }
+    }
+
+    pub fn hash(&self, board: &Board, side_to_move: u8) -> u64 {
+        let mut h = 0u64;
+        for color in [BLACK, WHITE] {
+            for sq in atari_go_core::squares(board.stones(color)) {
+                h ^= self.stones[color as usize][sq as usize];
+            }
+        }
+        if side_to_move != BLACK {
+            h ^= self.side;
+        }
+        h
+    }
+
+    /// Hash depois de `side` jogar `mv` sem capturar (passa também a vez)
+    #[inline]
+    pub fn update(&self, hash: u64, mv: Move, side: u8) -> u64 {
+        hash ^ self.stones[side as usize][mv as usize] ^ self.side
+    }
+}
+
+impl Default for ZobristKeys {
+    fn default() -> Self {
+        Self::new()
+    }
+}
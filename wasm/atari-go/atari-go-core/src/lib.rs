@@ -0,0 +1,401 @@
+//! Regras de Atari Go (primeira captura ganha) no tabuleiro 9×9.
+//!
+//! As pretas (lado 0) começam. Uma cadeia sem liberdades é capturada e quem
+//! captura primeiro ganha. O suicídio é proibido, exceto quando a jogada
+//! captura. Sem jogadas legais o jogo fica empatado.
+//!
+//! Casa `linha * 9 + coluna`, com a linha 0 em cima; cada cor é um `u128` com
+//! as 81 casas nos bits baixos. As cadeias e as respetivas liberdades são
+//! mantidas incrementalmente: jogar junta cadeias e tira uma liberdade às
+//! vizinhas, e só uma captura (que acaba o jogo) reconstrói tudo.
+
+pub const BOARD_SIZE: usize = 9;
+pub const CELL_COUNT: usize = BOARD_SIZE * BOARD_SIZE;
+
+pub const BLACK: u8 = 0;
+pub const WHITE: u8 = 1;
+
+/// As 81 casas
+pub const BOARD_MASK: u128 = (1 << CELL_COUNT) - 1;
+
+const fn column_mask(excluded: usize) -> u128 {
+    let mut mask = 0u128;
+    let mut sq = 0;
+    while sq < CELL_COUNT {
+        if sq % BOARD_SIZE != excluded {
+            mask |= 1 << sq;
+        }
+        sq += 1;
+    }
+    mask
+}
+
+const NOT_FIRST_COL: u128 = column_mask(0);
+const NOT_LAST_COL: u128 = column_mask(BOARD_SIZE - 1);
+
+/// Lance = índice da casa (0..81)
+pub type Move = u8;
+
+/// Pedras ortogonalmente ligadas da mesma cor e as casas vazias à volta
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Chain {
+    pub stones: u128,
+    pub libs: u128,
+}
+
+impl Chain {
+    #[inline]
+    pub fn liberties(&self) -> u32 {
+        self.libs.count_ones()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Board {
+    stones: [u128; 2],
+    /// cadeia de cada pedra; o índice de uma cadeia é uma das suas casas
+    chain_of: [u8; CELL_COUNT],
+    chains: [Chain; CELL_COUNT],
+    /// índices das cadeias de cada cor
+    heads: [u128; 2],
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self {
+            stones: [0; 2],
+            chain_of: [0; CELL_COUNT],
+            chains: [Chain::default(); CELL_COUNT],
+            heads: [0; 2],
+        }
+    }
+}
+
+/// Dois tabuleiros são iguais se tiverem as mesmas pedras (as cadeias
+/// decorrem das pedras)
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.stones == other.stones
+    }
+}
+
+impl Eq for Board {}
+
+impl Board {
+    /// Tabuleiro com estas pedras, cadeias calculadas de raiz
+    pub fn from_stones(black: u128, white: u128) -> Self {
+        let mut board = Self {
+            stones: [black & BOARD_MASK, white & BOARD_MASK & !black],
+            ..Self::default()
+        };
+        let empty = board.empty();
+        for color in [BLACK, WHITE] {
+            let mut rest = board.stones[color as usize];
+            while rest != 0 {
+                let seed = rest & rest.wrapping_neg();
+                let stones = flood(seed, board.stones[color as usize]);
+                let id = seed.trailing_zeros() as usize;
+                board.chains[id] = Chain { stones, libs: neighbours(stones) & empty };
+                for sq in squares(stones) {
+                    board.chain_of[sq as usize] = id as u8;
+                }
+                board.heads[color as usize] |= seed;
+                rest &= !stones;
+            }
+        }
+        board
+    }
+
+    /// Tabuleiro a partir de 81 casas (0 = vazia, 1 = preta, 2 = branca);
+    /// `None` com outro tamanho ou outros valores
+    pub fn from_cells(cells: &[u8]) -> Option<Self> {
+        if cells.len() != CELL_COUNT {
+            return None;
+        }
+        let (mut black, mut white) = (0u128, 0u128);
+        for (sq, &cell) in cells.iter().enumerate() {
+            match cell {
+                0 => {}
+                1 => black |= 1 << sq,
+                2 => white |= 1 << sq,
+                _ => return None,
+            }
+        }
+        Some(Self::from_stones(black, white))
+    }
+
+    #[inline]
+    pub fn stones(&self, color: u8) -> u128 {
+        self.stones[color as usize]
+    }
+
+    #[inline]
+    pub fn occupied(&self) -> u128 {
+        self.stones[0] | self.stones[1]
+    }
+
+    #[inline]
+    pub fn empty(&self) -> u128 {
+        !self.occupied() & BOARD_MASK
+    }
+
+    /// Cadeia da pedra em `sq`, se houver pedra
+    #[inline]
+    pub fn chain_at(&self, sq: Move) -> Option<Chain> {
+        (self.occupied() & (1 << sq) != 0).then(|| self.chains[self.chain_of[sq as usize] as usize])
+    }
+
+    /// Cadeias de `color`
+    pub fn chains(&self, color: u8) -> impl Iterator<Item = Chain> + '_ {
+        squares(self.heads[color as usize]).map(move |id| self.chains[id as usize])
+    }
+
+    /// Casas onde o adversário de `color` captura já: a última liberdade de
+    /// cada cadeia de `color` em atari
+    pub fn atari_points(&self, color: u8) -> u128 {
+        self.chains(color).filter(|c| c.liberties() == 1).fold(0, |acc, c| acc | c.libs)
+    }
+
+    /// Pedras adversárias que `side` captura jogando em `mv`
+    pub fn captures(&self, mv: Move, side: u8) -> u128 {
+        let bit = 1u128 << mv;
+        squares(neighbours(bit) & self.stones[1 - side as usize])
+            .map(|sq| self.chains[self.chain_of[sq as usize] as usize])
+            .filter(|c| c.libs == bit)
+            .fold(0, |acc, c| acc | c.stones)
+    }
+
+    /// Casa vazia e não suicida (ou suicida mas a capturar)
+    pub fn is_legal(&self, mv: Move, side: u8) -> bool {
+        if mv as usize >= CELL_COUNT {
+            return false;
+        }
+        let bit = 1u128 << mv;
+        if self.occupied() & bit != 0 {
+            return false;
+        }
+        let around = neighbours(bit);
+        if around & self.empty() != 0 {
+            return true;
+        }
+        // rodeada de pedras: precisa de uma cadeia amiga com outra liberdade
+        // ou de capturar
+        squares(around & self.stones[side as usize]).any(|sq| self.chains[self.chain_of[sq as usize] as usize].libs != bit)
+            || self.captures(mv, side) != 0
+    }
+
+    /// Casas onde `side` pode jogar
+    pub fn legal_mask(&self, side: u8) -> u128 {
+        let empty = self.empty();
+        // com uma casa vazia ao lado nunca é suicídio
+        let mut mask = empty & adjacent(empty);
+        for sq in squares(empty & !mask) {
+            if self.is_legal(sq, side) {
+                mask |= 1 << sq;
+            }
+        }
+        mask
+    }
+
+    /// Joga `mv` (que tem de ser legal) e devolve as pedras capturadas.
+    ///
+    /// Sem captura a atualização é incremental; com captura as pedras saem e
+    /// as cadeias são recalculadas.
+    pub fn play(&mut self, mv: Move, side: u8) -> u128 {
+        let (me, them) = (side as usize, 1 - side as usize);
+        let bit = 1u128 << mv;
+        self.stones[me] |= bit;
+        let around = neighbours(bit);
+
+        // junta as cadeias amigas vizinhas na da primeira (ou numa nova)
+        let mut chain = Chain { stones: bit, libs: around & self.empty() };
+        let mut id = mv as usize;
+        let mut kept = 0u128;
+        for sq in squares(around & self.stones[me] & !bit) {
+            let c = self.chain_of[sq as usize] as usize;
+            if chain.stones & (1 << sq) != 0 {
+                continue;
+            }
+            let other = self.chains[c];
+            if id == mv as usize {
+                id = c;
+                kept = other.stones;
+            } else {
+                self.heads[me] &= !(1 << c);
+            }
+            chain.stones |= other.stones;
+            chain.libs |= other.libs;
+        }
+        chain.libs &= !bit;
+        for sq in squares(chain.stones & !kept) {
+            self.chain_of[sq as usize] = id as u8;
+        }
+        self.chains[id] = chain;
+        self.heads[me] |= 1 << id;
+
+        let mut captured = 0u128;
+        for sq in squares(around & self.stones[them]) {
+            let c = self.chain_of[sq as usize] as usize;
+            self.chains[c].libs &= !bit;
+            if self.chains[c].libs == 0 {
+                captured |= self.chains[c].stones;
+            }
+        }
+        if captured != 0 {
+            self.stones[them] &= !captured;
+            *self = Self::from_stones(self.stones[0], self.stones[1]);
+        }
+        captured
+    }
+}
+
+pub fn generate_moves(board: &Board, side: u8) -> Vec<Move> {
+    squares(board.legal_mask(side)).collect()
+}
+
+#[inline]
+pub fn count_moves(board: &Board, side: u8) -> u32 {
+    board.legal_mask(side).count_ones()
+}
+
+#[inline]
+pub fn is_legal(board: &Board, mv: Move, side: u8) -> bool {
+    board.is_legal(mv, side)
+}
+
+/// Tabuleiro depois de `side` jogar `mv`, capturas já retiradas
+#[inline]
+pub fn apply_move(board: &Board, mv: Move, side: u8) -> Board {
+    let mut next = *board;
+    next.play(mv, side);
+    next
+}
+
+/// Casas ortogonalmente adjacentes a alguma casa de `mask` (podem estar em `mask`)
+#[inline]
+pub fn adjacent(mask: u128) -> u128 {
+    ((mask << BOARD_SIZE) | (mask >> BOARD_SIZE) | ((mask << 1) & NOT_FIRST_COL) | ((mask >> 1) & NOT_LAST_COL)) & BOARD_MASK
+}
+
+/// `mask` mais as casas ortogonalmente adjacentes
+#[inline]
+pub fn spread(mask: u128) -> u128 {
+    mask | adjacent(mask)
+}
+
+/// Casas ortogonalmente adjacentes a alguma casa de `mask` (sem `mask`)
+#[inline]
+pub fn neighbours(mask: u128) -> u128 {
+    spread(mask) & !mask
+}
+
+/// Casas de `mask`, da mais baixa para a mais alta
+#[inline]
+pub fn squares(mut mask: u128) -> impl Iterator<Item = Move> {
+    std::iter::from_fn(move || {
+        (mask != 0).then(|| {
+            let sq = mask.trailing_zeros() as Move;
+            mask &= mask - 1;
+            sq
+        })
+    })
+}
+
+/// Componente conexa (ortogonal) de `mask` que contém as casas de `seed`
+pub fn flood(seed: u128, mask: u128) -> u128 {
+    let mut region = seed & mask;
+    loop {
+        let grown = spread(region) & mask;
+        if grown == region {
+            return region;
+        }
+        region = grown;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sq(linha: usize, coluna: usize) -> Move {
+        (linha * BOARD_SIZE + coluna) as Move
+    }
+
+    fn bits(cells: &[Move]) -> u128 {
+        cells.iter().fold(0, |acc, &c| acc | 1 << c)
+    }
+
+    /// Gerador pequeno só para os testes
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn liberties_depend_on_the_edges() {
+        let board = Board::from_stones(bits(&[sq(0, 0), sq(0, 4), sq(4, 4)]), 0);
+        assert_eq!(board.chain_at(sq(0, 0)).unwrap().liberties(), 2);
+        assert_eq!(board.chain_at(sq(0, 4)).unwrap().liberties(), 3);
+        assert_eq!(board.chain_at(sq(4, 4)).unwrap().liberties(), 4);
+        assert_eq!(board.chain_at(sq(8, 8)), None);
+        assert_eq!(board.chains(BLACK).count(), 3);
+    }
+
+    #[test]
+    fn playing_merges_chains() {
+        let mut board = Board::from_stones(bits(&[sq(4, 3), sq(4, 5)]), bits(&[sq(3, 4)]));
+        assert_eq!(board.play(sq(4, 4), BLACK), 0);
+        let chain = board.chain_at(sq(4, 3)).unwrap();
+        assert_eq!(chain.stones, bits(&[sq(4, 3), sq(4, 4), sq(4, 5)]));
+        assert_eq!(chain.liberties(), 7);
+        assert_eq!(board.chains(BLACK).count(), 1);
+        assert_eq!(board.chain_at(sq(3, 4)).unwrap().libs, bits(&[sq(2, 4), sq(3, 3), sq(3, 5)]));
+    }
+
+    #[test]
+    fn suicide_is_illegal_unless_it_captures() {
+        // o canto (0,0) está rodeado por pretas
+        let board = Board::from_stones(bits(&[sq(0, 1), sq(1, 0)]), 0);
+        assert!(!board.is_legal(sq(0, 0), WHITE));
+        assert!(board.is_legal(sq(0, 0), BLACK));
+        assert_eq!(board.legal_mask(WHITE) & (1 << sq(0, 0)), 0);
+
+        // com (0,1) em atari, jogar no canto captura
+        let board = Board::from_stones(bits(&[sq(0, 1), sq(1, 0)]), bits(&[sq(0, 2), sq(1, 1)]));
+        assert_eq!(board.atari_points(BLACK), 1 << sq(0, 0));
+        assert!(board.is_legal(sq(0, 0), WHITE));
+        assert_eq!(board.captures(sq(0, 0), WHITE), 1 << sq(0, 1));
+        let after = apply_move(&board, sq(0, 0), WHITE);
+        assert_eq!(after.stones(BLACK), 1 << sq(1, 0));
+        assert_eq!(after.chain_at(sq(0, 0)).unwrap().libs, 1 << sq(0, 1));
+        assert_eq!(Board::from_cells(&[0; 80]), None);
+    }
+
+    #[test]
+    fn incremental_chains_match_a_rebuild() {
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        for _ in 0..40 {
+            let mut board = Board::default();
+            let mut side = BLACK;
+            loop {
+                let moves = generate_moves(&board, side);
+                if moves.is_empty() {
+                    break;
+                }
+                let mv = moves[next(&mut state) as usize % moves.len()];
+                let captured = board.play(mv, side);
+                let rebuilt = Board::from_stones(board.stones(BLACK), board.stones(WHITE));
+                for cell in 0..CELL_COUNT as Move {
+                    assert_eq!(board.chain_at(cell), rebuilt.chain_at(cell));
+                }
+                assert_eq!(board.chains(side).count(), rebuilt.chains(side).count());
+                if captured != 0 {
+                    break;
+                }
+                side = 1 - side;
+            }
+        }
+    }
+}
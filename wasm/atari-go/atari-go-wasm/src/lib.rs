@@ -0,0 +1,151 @@
+//! Atari Go WASM Engine
+//!
+//! - 9x9 board, black and white as u128 bitboards with incremental chains
+//! - Negamax + alpha-beta + PVS with aspiration windows, TT, killers (`crjm_search`)
+//! - Threat-space reading (ataris, double ataris, ladders, nets) at the root
+//!   and ladder reading at the leaves (`atari_go_ai::tactics`)
+//! - Root randomization for easier difficulties (top_n + score_delta)
+
+use wasm_bindgen::prelude::*;
+
+use atari_go_ai::engine as ai_engine;
+use atari_go_ai::eval;
+use atari_go_ai::rng::Rng;
+use atari_go_ai::tt::TranspositionTable;
+use atari_go_ai::zobrist::ZobristKeys;
+use atari_go_core::{Board, Move};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = Date)]
+    fn now() -> f64;
+}
+
+#[wasm_bindgen(start)]
+pub fn init() {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}
+
+#[wasm_bindgen]
+pub struct SearchResult {
+    /// Point `row * 9 + col`, or -1 if the side to move has no move
+    pub best_move: i32,
+    pub depth_reached: u32,
+    pub nodes_searched: u64,
+    pub elapsed_ms: f64,
+    pub tt_hits: u64,
+    pub tt_probes: u64,
+    pub score: i32,
+    /// Permille of the transposition table filled by this search
+    pub hashfull: u32,
+    /// Moves until the first capture, in turns of the side to move: a proven win (≥ 1) or loss (≤ -1)
+    pub mate_in: Option<i32>,
+    pv: Vec<Move>,
+}
+
+#[wasm_bindgen]
+impl SearchResult {
+    /// Principal variation, starting with `best_move` (empty if there is none)
+    #[wasm_bindgen(getter)]
+    pub fn pv(&self) -> Vec<Move> {
+        self.pv.clone()
+    }
+}
+
+fn board_from(cells: &[u8]) -> Result<Board, JsValue> {
+    Board::from_cells(cells).ok_or_else(|| JsValue::from_str("expected 81 cells with values 0 (empty), 1 (black) or 2 (white)"))
+}
+
+/// Legal points for `side` (0 = black, 1 = white); `cells` holds the 81
+/// points row by row (0 = empty, 1 = black, 2 = white)
+#[wasm_bindgen]
+pub fn legal_moves(cells: &[u8], side: u8) -> Result<Vec<Move>, JsValue> {
+    Ok(atari_go_core::generate_moves(&board_from(cells)?, side))
+}
+
+const MAX_TT_MB: u32 = 256;
+
+#[wasm_bindgen]
+pub struct AtariGoEngine {
+    tt: TranspositionTable,
+    zobrist: ZobristKeys,
+    rng: Rng,
+    seed: u32,
+}
+
+#[wasm_bindgen]
+impl AtariGoEngine {
+    /// Engine with a transposition table of `tt_mb` megabytes; `seed` drives
+    /// every random choice, so the same seed and the same requests (with
+    /// depth rather than time limits) replay the same moves
+    #[wasm_bindgen(constructor)]
+    pub fn new(tt_mb: u32, seed: u32) -> AtariGoEngine {
+        AtariGoEngine {
+            tt: TranspositionTable::new(tt_mb.min(MAX_TT_MB) as usize),
+            zobrist: ZobristKeys::new(),
+            rng: Rng::new(seed as u64),
+            seed,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// Restarts the random stream from `seed` (e.g. to replay a reported game)
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed;
+        self.rng = Rng::new(seed as u64);
+    }
+
+    pub fn clear_tt(&mut self) {
+        self.tt.clear();
+    }
+
+    /// Reallocates the transposition table with `tt_mb` megabytes (clears it)
+    pub fn resize_tt(&mut self, tt_mb: u32) {
+        self.tt.resize(tt_mb.min(MAX_TT_MB) as usize);
+    }
+
+    /// Permille of the transposition table used by the current search
+    pub fn hashfull(&self) -> u32 {
+        self.tt.hashfull()
+    }
+
+    /// Best move for `side` (0 = black, 1 = white) on `cells` (see
+    /// `legal_moves`); with `top_n > 0` picks at random among the `top_n`
+    /// best moves within `score_delta` of the best. A forced capture found
+    /// by the reading is always played.
+    pub fn search(
+        &mut self,
+        cells: &[u8],
+        side: u8,
+        time_budget_ms: f64,
+        max_depth: u32,
+        top_n: u32,
+        score_delta: i32,
+    ) -> Result<SearchResult, JsValue> {
+        let board = board_from(cells)?;
+
+        self.tt.new_search();
+        let deadline = now() + time_budget_ms;
+
+        let result = ai_engine::Searcher::new(&mut self.tt, &self.zobrist, &mut self.rng, deadline, max_depth)
+            .iterative_deepening(&board, side, top_n, score_delta, now);
+
+        Ok(SearchResult {
+            best_move: result.best_move.map(|m| m as i32).unwrap_or(-1),
+            depth_reached: result.depth_reached,
+            nodes_searched: result.nodes_searched,
+            elapsed_ms: now() - (deadline - time_budget_ms),
+            tt_hits: result.tt_hits,
+            tt_probes: result.tt_probes,
+            score: result.score,
+            hashfull: self.tt.hashfull(),
+            mate_in: eval::mate_in(result.score),
+            pv: result.pv,
+        })
+    }
+}
@@ -0,0 +1,27 @@
+//! With the `header` feature, regenerates `include/crjm_ffi.h` from the C ABI
+//! in `src/lib.rs`:
+//!
+//! ```text
+//! cargo build --features header
+//! ```
+//!
+//! Without it the build script does nothing: the checked-in header is the
+//! source of truth, and crates that depend on this one never need cbindgen.
+
+#[cfg(feature = "header")]
+fn main() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let dir = std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR");
+    match cbindgen::generate(&dir) {
+        Ok(bindings) => {
+            bindings.write_to_file(format!("{dir}/include/crjm_ffi.h"));
+        }
+        Err(e) => println!("cargo:warning=crjm_ffi.h not regenerated: {e}"),
+    }
+}
+
+#[cfg(not(feature = "header"))]
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+}
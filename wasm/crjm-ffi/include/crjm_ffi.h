@@ -0,0 +1,209 @@
+#ifndef CRJM_FFI_H
+#define CRJM_FFI_H
+
+/* Generated with cbindgen:0.29.4 */
+
+/* Generated by cbindgen from src/lib.rs (cargo build --features header); do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Result of every call
+ */
+enum CrjmStatus
+#if defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+  : int32_t
+#endif // defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+ {
+  CRJM_STATUS_OK = 0,
+  /**
+   * A required pointer argument was NULL
+   */
+  CRJM_STATUS_NULL_ARGUMENT = 1,
+  /**
+   * A string argument was not UTF-8
+   */
+  CRJM_STATUS_INVALID_UTF8 = 2,
+  CRJM_STATUS_UNKNOWN_GAME = 3,
+  CRJM_STATUS_INVALID_POSITION = 4,
+  CRJM_STATUS_ILLEGAL_MOVE = 5,
+  CRJM_STATUS_GAME_OVER = 6,
+  /**
+   * The engine panicked; free it and create another
+   */
+  CRJM_STATUS_PANIC = 7,
+};
+#ifndef __cplusplus
+#if __STDC_VERSION__ >= 202311L
+typedef enum CrjmStatus CrjmStatus;
+#else
+typedef int32_t CrjmStatus;
+#endif // __STDC_VERSION__ >= 202311L
+#endif // __cplusplus
+
+/**
+ * Opaque engine: one game's position plus its search tables
+ */
+typedef struct CrjmEngine CrjmEngine;
+
+typedef struct CrjmGameStatus {
+  uint8_t side_to_move;
+  bool finished;
+  /**
+   * Winning side (0 or 1), or -1 while playing and on a draw
+   */
+  int8_t winner;
+} CrjmGameStatus;
+
+/**
+ * Search limits; zero `max_depth` or `max_playouts` means no limit
+ */
+typedef struct CrjmSearchOptions {
+  double time_ms;
+  uint32_t max_depth;
+  /**
+   * Produto only
+   */
+  uint64_t max_playouts;
+  /**
+   * Random pick among the `top_n` best moves within `score_delta` (0 = best)
+   */
+  uint32_t top_n;
+  int32_t score_delta;
+} CrjmSearchOptions;
+
+typedef struct CrjmSearchInfo {
+  /**
+   * For the side to move
+   */
+  int32_t score;
+  uint32_t depth;
+  uint64_t nodes;
+  double elapsed_ms;
+} CrjmSearchInfo;
+
+
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Library version, a static string (do not free)
+ */
+const char *crjm_version(void);
+
+/**
+ * Message of the last failed call on this thread (free it with
+ * `crjm_string_free`), or NULL
+ */
+char *crjm_last_error(void);
+
+/**
+ * Releases a string returned by this library
+ *
+ * # Safety
+ * `s` is NULL or a string from this library not yet freed.
+ */
+void crjm_string_free(char *s);
+
+/**
+ * Creates an engine for `game` (a tournament `GameId`) at the initial
+ * position, with a transposition table of `tt_mb` megabytes (at most 256)
+ *
+ * # Safety
+ * `game` is a NUL-terminated string and `out` is valid for writes.
+ */
+CrjmStatus crjm_engine_new(const char *game,
+                           uint32_t tt_mb,
+                           uint64_t seed,
+                           struct CrjmEngine **out);
+
+/**
+ * Destroys an engine (NULL is ignored)
+ *
+ * # Safety
+ * `engine` is NULL or from `crjm_engine_new`, not yet freed.
+ */
+void crjm_engine_free(struct CrjmEngine *engine);
+
+/**
+ * Back to the initial position, keeping the tables
+ *
+ * # Safety
+ * `engine` is a live engine.
+ */
+CrjmStatus crjm_engine_new_game(struct CrjmEngine *engine);
+
+/**
+ * Replaces the position (the engine keeps its position on error)
+ *
+ * # Safety
+ * `engine` is a live engine and `notation` a NUL-terminated string.
+ */
+CrjmStatus crjm_engine_set_position(struct CrjmEngine *engine, const char *notation);
+
+/**
+ * Current position in notation, into `*out`
+ *
+ * # Safety
+ * `engine` is a live engine and `out` is valid for writes.
+ */
+CrjmStatus crjm_engine_position(const struct CrjmEngine *engine, char **out);
+
+/**
+ * Space-separated legal moves into `*out` (empty once the game is over)
+ *
+ * # Safety
+ * `engine` is a live engine and `out` is valid for writes.
+ */
+CrjmStatus crjm_engine_legal_moves(const struct CrjmEngine *engine, char **out);
+
+/**
+ * Plays `mv` for the side to move
+ *
+ * # Safety
+ * `engine` is a live engine and `mv` a NUL-terminated string.
+ */
+CrjmStatus crjm_engine_apply_move(struct CrjmEngine *engine, const char *mv);
+
+/**
+ * Side to move and result so far
+ *
+ * # Safety
+ * `engine` is a live engine and `out` is valid for writes.
+ */
+CrjmStatus crjm_engine_status(const struct CrjmEngine *engine, struct CrjmGameStatus *out);
+
+/**
+ * Searches the current position. `options` may be NULL for the defaults
+ * (one second, no depth limit); `info`, `best_move` and `pv` may be NULL
+ * when not wanted. `*best_move` is NULL when there is no move, `*pv` holds
+ * space-separated moves.
+ *
+ * # Safety
+ * `engine` is a live engine; the other pointers are NULL or valid.
+ */
+CrjmStatus crjm_engine_search(struct CrjmEngine *engine,
+                              const struct CrjmSearchOptions *options,
+                              struct CrjmSearchInfo *info,
+                              char **best_move,
+                              char **pv);
+
+/**
+ * Forgets what earlier searches stored
+ *
+ * # Safety
+ * `engine` is a live engine.
+ */
+CrjmStatus crjm_engine_clear_tables(struct CrjmEngine *engine);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CRJM_FFI_H */
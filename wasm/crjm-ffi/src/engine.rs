@@ -0,0 +1,251 @@
+//! Game-agnostic native engine: one game's position plus its search tables
+//!
+//! Positions and moves cross the boundary as text (see [`crate::notation`]),
+//! so every host (C, Bun, a line protocol) talks to every game the same way.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::games::{self, Game, Hooks};
+
+/// Largest transposition table an engine may allocate
+pub const MAX_TT_MB: usize = 256;
+
+/// Current time in milliseconds (the deadlines of every engine)
+pub fn now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
+/// The games, with the ids of the tournament protocol (`GameId`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GameKind {
+    GatosCaes,
+    Dominorio,
+    Quelhas,
+    Produto,
+    AtariGo,
+    Nex,
+}
+
+impl GameKind {
+    pub const ALL: [GameKind; 6] = [
+        GameKind::GatosCaes,
+        GameKind::Dominorio,
+        GameKind::Quelhas,
+        GameKind::Produto,
+        GameKind::AtariGo,
+        GameKind::Nex,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            GameKind::GatosCaes => "gatos-caes",
+            GameKind::Dominorio => "dominorio",
+            GameKind::Quelhas => "quelhas",
+            GameKind::Produto => "produto",
+            GameKind::AtariGo => "atari-go",
+            GameKind::Nex => "nex",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<GameKind> {
+        GameKind::ALL.into_iter().find(|kind| kind.id() == id)
+    }
+}
+
+/// Where a game stands
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Playing,
+    /// Side 0 or 1 won
+    Won(u8),
+    Drawn,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    UnknownGame(String),
+    InvalidPosition(String),
+    IllegalMove(String),
+    GameOver,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownGame(id) => write!(f, "unknown game '{id}'"),
+            Error::InvalidPosition(why) => write!(f, "invalid position: {why}"),
+            Error::IllegalMove(mv) => write!(f, "illegal move '{mv}'"),
+            Error::GameOver => write!(f, "the game is over"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Limits and randomization of one search
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SearchOptions {
+    pub time_ms: f64,
+    pub max_depth: u32,
+    /// Playout limit of Produto's tree search (the other games ignore it)
+    pub max_playouts: u64,
+    /// Node limit once the first iteration is complete (Dominório and
+    /// Quelhas; the other games ignore it)
+    pub max_nodes: u64,
+    /// Root moves given exact scores in [`SearchReport::lines`] (Dominório
+    /// and Quelhas)
+    pub multipv: usize,
+    /// Pick at random among the `top_n` best moves within `score_delta` of
+    /// the best (0 = always the best)
+    pub top_n: u32,
+    pub score_delta: i32,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self { time_ms: 1000.0, max_depth: 64, max_playouts: u64::MAX, max_nodes: u64::MAX, multipv: 1, top_n: 0, score_delta: 0 }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchReport {
+    /// `None` when the side to move has no move
+    pub best_move: Option<String>,
+    /// Principal variation, starting with `best_move`
+    pub pv: Vec<String>,
+    /// For the side to move, in the game's own scale
+    pub score: i32,
+    /// Turns of the side to move until a proven result, counting a turn
+    /// where it is stuck: at least 1 when it wins, at most -1 when it loses
+    /// (`None` for heuristic scores)
+    pub mate_in: Option<i32>,
+    /// The best `multipv` root moves with their scores, best first
+    pub lines: Vec<(String, i32)>,
+    /// Completed depth (0 for Produto's tree search)
+    pub depth: u32,
+    /// Search nodes (playouts for Produto's tree search)
+    pub nodes: u64,
+    pub elapsed_ms: f64,
+}
+
+/// One move of a game graded by the game's own post-game review; scores
+/// are for the side that played it, in the game's own scale
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveReview {
+    pub played: String,
+    /// The engine's choice in the position before the move
+    pub best_move: Option<String>,
+    pub best_score: i32,
+    pub best_mate_in: Option<i32>,
+    pub played_score: i32,
+    /// Points lost against the best move (never negative; proven results
+    /// count as the review's decided score)
+    pub loss: i32,
+    /// `best`, `good`, `inaccuracy`, `mistake` or `blunder`
+    pub class: &'static str,
+}
+
+/// A game in progress with its engine
+pub struct Engine {
+    kind: GameKind,
+    game: Box<dyn Game>,
+}
+
+impl Engine {
+    /// Engine at the start of `kind` with a transposition table of `tt_mb`
+    /// megabytes; `seed` drives every random choice
+    pub fn new(kind: GameKind, tt_mb: usize, seed: u64) -> Self {
+        Self { kind, game: games::new(kind, tt_mb.min(MAX_TT_MB), seed) }
+    }
+
+    pub fn kind(&self) -> GameKind {
+        self.kind
+    }
+
+    /// Back to the initial position (the tables are kept)
+    pub fn new_game(&mut self) {
+        self.game.reset();
+    }
+
+    pub fn position(&self) -> String {
+        self.game.position()
+    }
+
+    /// Replaces the position; on error the engine keeps the previous one
+    pub fn set_position(&mut self, notation: &str) -> Result<(), Error> {
+        self.game.set_position(notation.trim())
+    }
+
+    pub fn side_to_move(&self) -> u8 {
+        self.game.side_to_move()
+    }
+
+    /// Every legal move in notation (empty once the game is over)
+    pub fn legal_moves(&self) -> Vec<String> {
+        if self.status() != Status::Playing {
+            return Vec::new();
+        }
+        self.game.legal_moves()
+    }
+
+    pub fn apply_move(&mut self, mv: &str) -> Result<(), Error> {
+        if self.status() != Status::Playing {
+            return Err(Error::GameOver);
+        }
+        self.game.play(mv.trim())
+    }
+
+    pub fn status(&self) -> Status {
+        self.game.status()
+    }
+
+    pub fn search(&mut self, options: &SearchOptions) -> SearchReport {
+        self.search_with(options, &AtomicBool::new(false), &mut |_| {})
+    }
+
+    /// [`search`](Self::search) that stops early once `stop` is set (from
+    /// any thread) and passes the best line to `progress` after every
+    /// complete iteration; only Dominório and Quelhas report iterations
+    pub fn search_with(&mut self, options: &SearchOptions, stop: &AtomicBool, progress: &mut dyn FnMut(&SearchReport)) -> SearchReport {
+        if self.status() != Status::Playing {
+            return SearchReport::default();
+        }
+        let start = now();
+        let clock = || if stop.load(Ordering::Relaxed) { f64::INFINITY } else { now() };
+        let finish = |mut report: SearchReport| {
+            report.elapsed_ms = now() - start;
+            if report.lines.is_empty() {
+                report.lines.extend(report.best_move.clone().map(|mv| (mv, report.score)));
+            }
+            report
+        };
+        let mut report_iteration = |report: SearchReport| progress(&finish(report));
+        let hooks = Hooks { deadline: start + options.time_ms, clock: &clock, progress: &mut report_iteration };
+        let report = self.game.search(options, hooks);
+        finish(report)
+    }
+
+    /// Score of a win proven for the side to move, in the game's own scale
+    /// (a finished game scores this for the winner)
+    pub fn win_score(&self) -> i32 {
+        self.game.win_score()
+    }
+
+    /// Grades `moves`, played from the current position, with the game's
+    /// own post-game review, `options` limiting the search of each position;
+    /// the engine ends after the last move, or keeps its position if one
+    /// cannot be played. `None` for the games without a review (only
+    /// Dominório and Quelhas have one)
+    pub fn review(&mut self, moves: &[String], options: &SearchOptions) -> Option<Result<Vec<MoveReview>, Error>> {
+        self.game.review(moves, options)
+    }
+
+    /// Forgets everything the searches learned
+    pub fn clear_tables(&mut self) {
+        self.game.clear_tables();
+    }
+}
@@ -0,0 +1,132 @@
+//! Atari Go: `b` black (side 0, first to move) and `w` white stones; a move
+//! names the point of the new stone. The first capture wins, and a side
+//! without a legal move draws. A position in notation never holds a capture:
+//! the game ends when one is played.
+
+use atari_go_ai::engine::Searcher;
+use atari_go_ai::eval::{mate_in, MATE};
+use atari_go_ai::rng::Rng;
+use atari_go_ai::tt::TranspositionTable;
+use atari_go_ai::zobrist::ZobristKeys;
+use atari_go_core::{generate_moves, Board, BLACK, BOARD_SIZE, CELL_COUNT};
+
+use super::{names, Game, Hooks};
+use crate::engine::{Error, SearchOptions, SearchReport, Status};
+use crate::notation::{format_position, parse_position, parse_square, square_name};
+
+const ROWS: [usize; BOARD_SIZE] = [BOARD_SIZE; BOARD_SIZE];
+const LETTERS: [char; 2] = ['b', 'w'];
+
+pub(crate) struct AtariGo {
+    board: Board,
+    side: u8,
+    /// The side that captured (and won)
+    captured_by: Option<u8>,
+    tt: TranspositionTable,
+    zobrist: ZobristKeys,
+    rng: Rng,
+}
+
+fn name(mv: u8) -> String {
+    square_name(mv as usize, BOARD_SIZE)
+}
+
+impl AtariGo {
+    pub(crate) fn new(tt_mb: usize, seed: u64) -> Self {
+        Self {
+            board: Board::from_stones(0, 0),
+            side: BLACK,
+            captured_by: None,
+            tt: TranspositionTable::new(tt_mb),
+            zobrist: ZobristKeys::new(),
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl Game for AtariGo {
+    fn reset(&mut self) {
+        self.board = Board::from_stones(0, 0);
+        self.side = BLACK;
+        self.captured_by = None;
+    }
+
+    fn position(&self) -> String {
+        let cells = (0..CELL_COUNT).map(|sq| {
+            let bit = 1u128 << sq;
+            if self.board.stones(0) & bit != 0 {
+                'b'
+            } else if self.board.stones(1) & bit != 0 {
+                'w'
+            } else {
+                '.'
+            }
+        });
+        format_position(cells, &ROWS, LETTERS[self.side as usize])
+    }
+
+    fn set_position(&mut self, notation: &str) -> Result<(), Error> {
+        let (cells, side) = parse_position(notation, &ROWS, "bw", LETTERS)?;
+        let values: Vec<u8> = cells.iter().map(|&c| LETTERS.iter().position(|&l| l == c).map_or(0, |i| i as u8 + 1)).collect();
+        let board = Board::from_cells(&values).ok_or_else(|| Error::InvalidPosition("bad cells".into()))?;
+        if board.chains(0).chain(board.chains(1)).any(|chain| chain.liberties() == 0) {
+            return Err(Error::InvalidPosition("a chain without liberties should have been captured".into()));
+        }
+        self.board = board;
+        self.side = side;
+        self.captured_by = None;
+        Ok(())
+    }
+
+    fn side_to_move(&self) -> u8 {
+        self.side
+    }
+
+    fn legal_moves(&self) -> Vec<String> {
+        names(&generate_moves(&self.board, self.side), name)
+    }
+
+    fn play(&mut self, mv: &str) -> Result<(), Error> {
+        let sq = parse_square(mv, BOARD_SIZE, BOARD_SIZE)
+            .map(|sq| sq as u8)
+            .filter(|&sq| self.board.is_legal(sq, self.side))
+            .ok_or_else(|| Error::IllegalMove(mv.into()))?;
+        if self.board.play(sq, self.side) != 0 {
+            self.captured_by = Some(self.side);
+        }
+        self.side = 1 - self.side;
+        Ok(())
+    }
+
+    fn status(&self) -> Status {
+        match self.captured_by {
+            Some(side) => Status::Won(side),
+            None if generate_moves(&self.board, self.side).is_empty() => Status::Drawn,
+            None => Status::Playing,
+        }
+    }
+
+    fn search(&mut self, options: &SearchOptions, hooks: Hooks) -> SearchReport {
+        self.tt.new_search();
+        let result = Searcher::new(&mut self.tt, &self.zobrist, &mut self.rng, hooks.deadline, options.max_depth)
+            .iterative_deepening(&self.board, self.side, options.top_n, options.score_delta, hooks.clock);
+        SearchReport {
+            best_move: result.best_move.map(name),
+            pv: names(&result.pv, name),
+            score: result.score,
+            mate_in: mate_in(result.score),
+            lines: Vec::new(),
+            depth: result.depth_reached,
+            nodes: result.nodes_searched,
+            elapsed_ms: 0.0,
+        }
+    }
+
+    fn clear_tables(&mut self) {
+        self.tt.clear();
+    }
+
+    fn win_score(&self) -> i32 {
+        MATE
+    }
+}
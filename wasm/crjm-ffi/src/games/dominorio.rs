@@ -0,0 +1,176 @@
+//! Dominório: `v` and `h` cells are the halves of vertical and horizontal
+//! dominoes; a move names the domino's top (vertical) or left (horizontal)
+//! cell. The side left without a move loses.
+
+use dominorio_ai::bitboard::{generate_moves, get_second_cell, is_valid_move, Side};
+use crjm_search::Progress;
+use dominorio_ai::engine::Searcher;
+use dominorio_ai::eval::{mate_in, MATE_SCORE};
+use dominorio_ai::review::review_game_from;
+use dominorio_ai::rng::Rng;
+use dominorio_ai::tt::TranspositionTable;
+use dominorio_ai::zobrist::ZobristKeys;
+
+use super::{names, replay, Game, Hooks, CLASSES};
+use crate::engine::{now, Error, MoveReview, SearchOptions, SearchReport, Status};
+use crate::notation::{format_position, parse_position, parse_square, square_name};
+
+const SIZE: usize = 8;
+const ROWS: [usize; SIZE] = [SIZE; SIZE];
+const LETTERS: [char; 2] = ['v', 'h'];
+
+pub(crate) struct Dominorio {
+    /// 0 = empty, 1 = vertical, 2 = horizontal
+    cells: [u8; SIZE * SIZE],
+    side: Side,
+    tt: TranspositionTable,
+    zobrist: ZobristKeys,
+    rng: Rng,
+}
+
+fn side_of(index: u8) -> Side {
+    if index == 0 {
+        Side::Vertical
+    } else {
+        Side::Horizontal
+    }
+}
+
+impl Dominorio {
+    pub(crate) fn new(tt_mb: usize, seed: u64) -> Self {
+        Self {
+            cells: [0; SIZE * SIZE],
+            side: Side::Vertical,
+            tt: TranspositionTable::new(tt_mb),
+            zobrist: ZobristKeys::new(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    fn occupied(&self) -> u64 {
+        self.cells.iter().enumerate().filter(|(_, &c)| c != 0).fold(0, |acc, (i, _)| acc | 1 << i)
+    }
+}
+
+impl Game for Dominorio {
+    fn reset(&mut self) {
+        self.cells = [0; SIZE * SIZE];
+        self.side = Side::Vertical;
+    }
+
+    fn position(&self) -> String {
+        let cells = self.cells.iter().map(|&c| if c == 0 { '.' } else { LETTERS[c as usize - 1] });
+        format_position(cells, &ROWS, LETTERS[self.side as usize])
+    }
+
+    fn set_position(&mut self, notation: &str) -> Result<(), Error> {
+        let (cells, side) = parse_position(notation, &ROWS, "vh", LETTERS)?;
+        for (cell, c) in self.cells.iter_mut().zip(cells) {
+            *cell = LETTERS.iter().position(|&l| l == c).map_or(0, |i| i as u8 + 1);
+        }
+        self.side = side_of(side);
+        Ok(())
+    }
+
+    fn side_to_move(&self) -> u8 {
+        self.side as u8
+    }
+
+    fn legal_moves(&self) -> Vec<String> {
+        names(&generate_moves(self.occupied(), self.side), |a| square_name(a as usize, SIZE))
+    }
+
+    fn play(&mut self, mv: &str) -> Result<(), Error> {
+        let anchor = parse_square(mv, SIZE, SIZE)
+            .filter(|&a| is_valid_move(self.occupied(), a as u8, self.side))
+            .ok_or_else(|| Error::IllegalMove(mv.into()))? as u8;
+        for cell in [anchor, get_second_cell(anchor, self.side)] {
+            self.cells[cell as usize] = self.side as u8 + 1;
+        }
+        self.side = self.side.opposite();
+        Ok(())
+    }
+
+    fn status(&self) -> Status {
+        if generate_moves(self.occupied(), self.side).is_empty() {
+            Status::Won(self.side.opposite() as u8)
+        } else {
+            Status::Playing
+        }
+    }
+
+    fn search(&mut self, options: &SearchOptions, hooks: Hooks) -> SearchReport {
+        let name = |a: u8| square_name(a as usize, SIZE);
+        let progress = hooks.progress;
+        let mut lines = Vec::new();
+        let mut report_iteration = |p: &Progress<u8>| {
+            lines = p.root.iter().take(options.multipv).map(|&(m, score)| (name(m), score)).collect();
+            progress(SearchReport {
+                best_move: p.pv.first().map(|&a| name(a)),
+                pv: names(p.pv, name),
+                score: p.score,
+                mate_in: mate_in(p.score),
+                lines: lines.clone(),
+                depth: p.depth,
+                nodes: p.stats.nodes,
+                elapsed_ms: 0.0,
+            })
+        };
+        self.tt.new_search();
+        let occ = self.occupied();
+        let result = Searcher::new(&mut self.tt, &self.zobrist, &mut self.rng, hooks.deadline, options.max_depth)
+            .with_node_budget(options.max_nodes)
+            .with_multipv(options.multipv)
+            .with_progress(&mut report_iteration)
+            .iterative_deepening_with_clock(occ, self.side, options.top_n, options.score_delta, hooks.clock);
+        SearchReport {
+            best_move: result.best_move.map(name),
+            pv: names(&result.pv, name),
+            score: result.score,
+            mate_in: mate_in(result.score),
+            lines,
+            depth: result.depth_reached,
+            nodes: result.nodes_searched,
+            elapsed_ms: 0.0,
+        }
+    }
+
+    fn clear_tables(&mut self) {
+        self.tt.clear();
+    }
+
+    fn win_score(&self) -> i32 {
+        MATE_SCORE
+    }
+
+    fn review(&mut self, moves: &[String], options: &SearchOptions) -> Option<Result<Vec<MoveReview>, Error>> {
+        let (start, first) = (self.occupied(), self.side);
+        let anchors = match replay(self, moves, |mv| parse_square(mv, SIZE, SIZE).map(|a| a as u8)) {
+            Ok(anchors) => anchors,
+            Err(e) => return Some(Err(e)),
+        };
+        let (tt, zobrist, rng) = (&mut self.tt, &self.zobrist, &mut self.rng);
+        let review = review_game_from(start, first, &anchors, |occupied, side| {
+            tt.new_search();
+            let result = Searcher::new(tt, zobrist, rng, now() + options.time_ms, options.max_depth)
+                .with_node_budget(options.max_nodes)
+                .iterative_deepening_with_clock(occupied, side, 0, 0, now);
+            (result.best_move, result.score)
+        })
+        .expect("the moves were just played");
+        let name = |a: u8| square_name(a as usize, SIZE);
+        Some(Ok(review
+            .moves
+            .iter()
+            .map(|m| MoveReview {
+                played: name(m.played),
+                best_move: m.best_move.map(name),
+                best_score: m.best_score,
+                best_mate_in: mate_in(m.best_score),
+                played_score: m.played_score,
+                loss: m.eval_drop,
+                class: CLASSES[m.class as usize],
+            })
+            .collect()))
+    }
+}
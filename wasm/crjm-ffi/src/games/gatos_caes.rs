@@ -0,0 +1,123 @@
+//! Gatos & Cães: `c` cats (side 0, first to move) and `d` dogs; a move names
+//! the cell of the new piece. The side left without a move loses.
+
+use gatos_caes_ai::engine::Searcher;
+use gatos_caes_ai::eval::{mate_in, MATE};
+use gatos_caes_ai::rng::Rng;
+use gatos_caes_ai::tt::TranspositionTable;
+use gatos_caes_ai::zobrist::ZobristKeys;
+use gatos_caes_core::{apply_move, generate_moves, is_legal, neighbours, Board, BOARD_SIZE, CATS};
+
+use super::{names, Game, Hooks};
+use crate::engine::{Error, SearchOptions, SearchReport, Status};
+use crate::notation::{format_position, parse_position, parse_square, square_name};
+
+const ROWS: [usize; BOARD_SIZE] = [BOARD_SIZE; BOARD_SIZE];
+const LETTERS: [char; 2] = ['c', 'd'];
+
+pub(crate) struct GatosCaes {
+    board: Board,
+    side: u8,
+    tt: TranspositionTable,
+    zobrist: ZobristKeys,
+    rng: Rng,
+}
+
+fn name(mv: u8) -> String {
+    square_name(mv as usize, BOARD_SIZE)
+}
+
+impl GatosCaes {
+    pub(crate) fn new(tt_mb: usize, seed: u64) -> Self {
+        Self {
+            board: Board::default(),
+            side: CATS,
+            tt: TranspositionTable::new(tt_mb),
+            zobrist: ZobristKeys::new(),
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl Game for GatosCaes {
+    fn reset(&mut self) {
+        self.board = Board::default();
+        self.side = CATS;
+    }
+
+    fn position(&self) -> String {
+        let cells = (0..BOARD_SIZE * BOARD_SIZE).map(|sq| {
+            let bit = 1u64 << sq;
+            if self.board.cats & bit != 0 {
+                'c'
+            } else if self.board.dogs & bit != 0 {
+                'd'
+            } else {
+                '.'
+            }
+        });
+        format_position(cells, &ROWS, LETTERS[self.side as usize])
+    }
+
+    fn set_position(&mut self, notation: &str) -> Result<(), Error> {
+        let (cells, side) = parse_position(notation, &ROWS, "cd", LETTERS)?;
+        let mask = |letter: char| cells.iter().enumerate().filter(|(_, &c)| c == letter).fold(0u64, |acc, (i, _)| acc | 1 << i);
+        let board = Board { cats: mask('c'), dogs: mask('d') };
+        if neighbours(board.cats) & board.dogs != 0 {
+            return Err(Error::InvalidPosition("a cat is next to a dog".into()));
+        }
+        self.board = board;
+        self.side = side;
+        Ok(())
+    }
+
+    fn side_to_move(&self) -> u8 {
+        self.side
+    }
+
+    fn legal_moves(&self) -> Vec<String> {
+        names(&generate_moves(self.board, self.side), name)
+    }
+
+    fn play(&mut self, mv: &str) -> Result<(), Error> {
+        let sq = parse_square(mv, BOARD_SIZE, BOARD_SIZE)
+            .map(|sq| sq as u8)
+            .filter(|&sq| is_legal(self.board, sq, self.side))
+            .ok_or_else(|| Error::IllegalMove(mv.into()))?;
+        self.board = apply_move(self.board, sq, self.side);
+        self.side = 1 - self.side;
+        Ok(())
+    }
+
+    fn status(&self) -> Status {
+        if generate_moves(self.board, self.side).is_empty() {
+            Status::Won(1 - self.side)
+        } else {
+            Status::Playing
+        }
+    }
+
+    fn search(&mut self, options: &SearchOptions, hooks: Hooks) -> SearchReport {
+        self.tt.new_search();
+        let result = Searcher::new(&mut self.tt, &self.zobrist, &mut self.rng, hooks.deadline, options.max_depth)
+            .iterative_deepening(self.board, self.side, options.top_n, options.score_delta, hooks.clock);
+        SearchReport {
+            best_move: result.best_move.map(name),
+            pv: names(&result.pv, name),
+            score: result.score,
+            mate_in: mate_in(result.score),
+            lines: Vec::new(),
+            depth: result.depth_reached,
+            nodes: result.nodes_searched,
+            elapsed_ms: 0.0,
+        }
+    }
+
+    fn clear_tables(&mut self) {
+        self.tt.clear();
+    }
+
+    fn win_score(&self) -> i32 {
+        MATE
+    }
+}
@@ -0,0 +1,172 @@
+//! One adapter per game: position, notation and search on the game's own crates
+
+use crate::engine::{Error, GameKind, MoveReview, SearchOptions, SearchReport, Status};
+
+mod atari_go;
+mod dominorio;
+mod gatos_caes;
+mod nex;
+mod produto;
+mod quelhas;
+
+/// A game's position and engine tables, driven through notation
+pub(crate) trait Game: Send {
+    /// Back to the initial position
+    fn reset(&mut self);
+    fn position(&self) -> String;
+    fn set_position(&mut self, notation: &str) -> Result<(), Error>;
+    fn side_to_move(&self) -> u8;
+    fn legal_moves(&self) -> Vec<String>;
+    /// Plays `mv` if legal (the game is not over)
+    fn play(&mut self, mv: &str) -> Result<(), Error>;
+    fn status(&self) -> Status;
+    /// Searches the current position (not over)
+    fn search(&mut self, options: &SearchOptions, hooks: Hooks) -> SearchReport;
+    fn clear_tables(&mut self);
+    /// Score of a win proven for the side to move
+    fn win_score(&self) -> i32;
+    /// Grades `moves` with the game's own review and plays them (see
+    /// [`Engine::review`](crate::Engine::review))
+    fn review(&mut self, _moves: &[String], _options: &SearchOptions) -> Option<Result<Vec<MoveReview>, Error>> {
+        None
+    }
+}
+
+/// Clock and observer of one search
+pub(crate) struct Hooks<'h> {
+    /// On `clock`, in milliseconds
+    pub deadline: f64,
+    /// Jumps past the deadline once the search is stopped
+    pub clock: &'h dyn Fn() -> f64,
+    /// Best line after every complete iteration, where the engine has them
+    pub progress: &'h mut dyn FnMut(SearchReport),
+}
+
+pub(crate) fn new(kind: GameKind, tt_mb: usize, seed: u64) -> Box<dyn Game> {
+    match kind {
+        GameKind::GatosCaes => Box::new(gatos_caes::GatosCaes::new(tt_mb, seed)),
+        GameKind::Dominorio => Box::new(dominorio::Dominorio::new(tt_mb, seed)),
+        GameKind::Quelhas => Box::new(quelhas::Quelhas::new(tt_mb, seed)),
+        GameKind::Produto => Box::new(produto::Produto::new(seed)),
+        GameKind::AtariGo => Box::new(atari_go::AtariGo::new(tt_mb, seed)),
+        GameKind::Nex => Box::new(nex::Nex::new(tt_mb, seed)),
+    }
+}
+
+/// Names of the engines' review grades, by `MoveClass` discriminant
+const CLASSES: [&str; 5] = ["best", "good", "inaccuracy", "mistake", "blunder"];
+
+/// Plays `moves` in turn and returns them parsed by `parse`; at the first
+/// one that cannot be played the game is put back on its position
+fn replay<M>(game: &mut dyn Game, moves: &[String], parse: impl Fn(&str) -> Option<M>) -> Result<Vec<M>, Error> {
+    let position = game.position();
+    let mut parsed = Vec::with_capacity(moves.len());
+    for mv in moves.iter().map(|mv| mv.trim()) {
+        let played = if game.status() == Status::Playing { game.play(mv) } else { Err(Error::GameOver) };
+        if let Err(e) = played {
+            game.set_position(&position).expect("the game's own position");
+            return Err(e);
+        }
+        parsed.push(parse(mv).expect("a played move parses"));
+    }
+    Ok(parsed)
+}
+
+/// Moves of a principal variation in notation
+fn names<M: Copy>(moves: &[M], name: impl Fn(M) -> String) -> Vec<String> {
+    moves.iter().map(|&m| name(m)).collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::engine::Engine;
+
+    /// Plays a game of quick searches from the start, checking that every
+    /// chosen move is listed as legal and that notation round-trips
+    pub(crate) fn self_play(kind: GameKind, max_plies: usize) -> Engine {
+        let mut engine = Engine::new(kind, 1, 7);
+        let options = SearchOptions { max_depth: 2, max_playouts: 200, ..SearchOptions::default() };
+        for _ in 0..max_plies {
+            if engine.status() != Status::Playing {
+                break;
+            }
+            let position = engine.position();
+            let legal = engine.legal_moves();
+            let report = engine.search(&options);
+            let best = report.best_move.expect("a move while playing");
+            assert!(legal.contains(&best), "{best} not in {legal:?}");
+            assert_eq!(report.pv.first(), Some(&best));
+
+            let mut copy = Engine::new(kind, 1, 0);
+            copy.set_position(&position).unwrap();
+            assert_eq!(copy.position(), position);
+            assert_eq!(copy.legal_moves(), legal);
+            engine.apply_move(&best).unwrap();
+        }
+        engine
+    }
+
+    #[test]
+    fn every_game_plays_legal_moves() {
+        for kind in GameKind::ALL {
+            let engine = self_play(kind, 6);
+            assert!(engine.position().len() > 10, "{}", kind.id());
+        }
+    }
+
+    #[test]
+    fn illegal_moves_and_bad_positions_are_rejected() {
+        for kind in GameKind::ALL {
+            let mut engine = Engine::new(kind, 1, 1);
+            let start = engine.position();
+            assert!(matches!(engine.apply_move("z99"), Err(Error::IllegalMove(_))), "{}", kind.id());
+            assert!(matches!(engine.set_position("nonsense"), Err(Error::InvalidPosition(_))), "{}", kind.id());
+            assert_eq!(engine.position(), start);
+        }
+    }
+
+    #[test]
+    fn reviews_grade_the_moves_and_play_them() {
+        let options = SearchOptions { max_depth: 2, ..SearchOptions::default() };
+        for (kind, moves) in [(GameKind::Dominorio, ["d1", "a1"]), (GameKind::Quelhas, ["a1-a10", "b1-j1"])] {
+            let mut engine = Engine::new(kind, 1, 1);
+            let start = engine.position();
+            let repeated = vec![moves[0].to_string(); 2];
+            assert!(matches!(engine.review(&repeated, &options), Some(Err(Error::IllegalMove(_)))), "{}", kind.id());
+            assert_eq!(engine.position(), start);
+
+            let moves: Vec<String> = moves.map(String::from).into();
+            let reviews = engine.review(&moves, &options).expect("a review").unwrap();
+            assert_eq!(reviews.iter().map(|r| &r.played).collect::<Vec<_>>(), moves.iter().collect::<Vec<_>>());
+            assert!(reviews.iter().all(|r| r.loss >= 0 && CLASSES.contains(&r.class)), "{reviews:?}");
+            let mut played = Engine::new(kind, 1, 1);
+            moves.iter().for_each(|mv| played.apply_move(mv).unwrap());
+            assert_eq!(engine.position(), played.position());
+        }
+        assert!(Engine::new(GameKind::Nex, 1, 1).review(&[], &options).is_none());
+    }
+
+    #[test]
+    fn searches_report_progress_multipv_and_stop_on_request() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        for kind in [GameKind::Dominorio, GameKind::Quelhas] {
+            let mut engine = Engine::new(kind, 1, 1);
+            let stop = AtomicBool::new(false);
+            let mut lines = Vec::new();
+            let mut progress = |report: &SearchReport| {
+                lines.push(report.clone());
+                stop.store(report.depth == 3, Ordering::Relaxed);
+            };
+            let options = SearchOptions { time_ms: f64::MAX, multipv: 3, ..SearchOptions::default() };
+            let report = engine.search_with(&options, &stop, &mut progress);
+            assert_eq!(report.lines.len(), 3);
+            assert!(report.lines.windows(2).all(|w| w[0].1 >= w[1].1));
+            assert_eq!(report.lines[0], (report.pv[0].clone(), report.score));
+            assert_eq!(lines.iter().map(|l| l.depth).collect::<Vec<_>>(), [1, 2, 3], "{}", kind.id());
+            let last = lines.last().unwrap();
+            assert_eq!((report.depth, &report.pv, report.score), (3, &last.pv, last.score));
+        }
+    }
+}
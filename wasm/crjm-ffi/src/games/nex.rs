@@ -0,0 +1,144 @@
+//! Nex: `b` black (side 0, first to move), `w` white and `n` neutral pieces
+//! on the 11×11 rhombus, row `x` and column `y` as in `tabuleiro[x][y]`.
+//! Actions are written `c3+d5` (own piece on c3, neutral on d5), `c3,d5:e1`
+//! (neutrals c3 and d5 become own, own e1 becomes neutral) and `swap` (the
+//! pie rule, which keeps the turn). Connecting the two own edges wins.
+
+use nex_ai::engine::Searcher;
+use nex_ai::eval::{mate_in, MATE};
+use nex_ai::rng::Rng;
+use nex_ai::tt::TranspositionTable;
+use nex_ai::zobrist::ZobristKeys;
+use nex_core::{apply_action, generate_actions, has_actions, is_legal, Action, Board, BLACK, CELL_COUNT, SIDE};
+
+use super::{names, Game, Hooks};
+use crate::engine::{Error, SearchOptions, SearchReport, Status};
+use crate::notation::{format_position, parse_position, parse_square, square_name};
+
+const ROWS: [usize; SIDE] = [SIDE; SIDE];
+const LETTERS: [char; 2] = ['b', 'w'];
+
+pub(crate) struct Nex {
+    board: Board,
+    side: u8,
+    tt: TranspositionTable,
+    zobrist: ZobristKeys,
+    rng: Rng,
+}
+
+fn cell_name(c: u8) -> String {
+    square_name(c as usize, SIDE)
+}
+
+fn name(action: Action) -> String {
+    match action {
+        Action::Place { own, neutral } => format!("{}+{}", cell_name(own), cell_name(neutral)),
+        Action::Substitute { neutrals: [a, b], own } => format!("{},{}:{}", cell_name(a), cell_name(b), cell_name(own)),
+        Action::Swap => "swap".into(),
+    }
+}
+
+fn parse(text: &str) -> Option<Action> {
+    let cell = |s: &str| parse_square(s, SIDE, SIDE).map(|c| c as u8);
+    if text == "swap" {
+        Some(Action::Swap)
+    } else if let Some((own, neutral)) = text.split_once('+') {
+        Some(Action::Place { own: cell(own)?, neutral: cell(neutral)? })
+    } else {
+        let (neutrals, own) = text.split_once(':')?;
+        let (a, b) = neutrals.split_once(',')?;
+        let (a, b) = (cell(a)?, cell(b)?);
+        Some(Action::Substitute { neutrals: [a.min(b), a.max(b)], own: cell(own)? })
+    }
+}
+
+impl Nex {
+    pub(crate) fn new(tt_mb: usize, seed: u64) -> Self {
+        Self {
+            board: Board::default(),
+            side: BLACK,
+            tt: TranspositionTable::new(tt_mb),
+            zobrist: ZobristKeys::new(),
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl Game for Nex {
+    fn reset(&mut self) {
+        self.board = Board::default();
+        self.side = BLACK;
+    }
+
+    fn position(&self) -> String {
+        let cells = (0..CELL_COUNT).map(|c| {
+            let bit = 1u128 << c;
+            if self.board.stones(0) & bit != 0 {
+                'b'
+            } else if self.board.stones(1) & bit != 0 {
+                'w'
+            } else if self.board.neutral() & bit != 0 {
+                'n'
+            } else {
+                '.'
+            }
+        });
+        format_position(cells, &ROWS, LETTERS[self.side as usize])
+    }
+
+    fn set_position(&mut self, notation: &str) -> Result<(), Error> {
+        let (cells, side) = parse_position(notation, &ROWS, "bwn", LETTERS)?;
+        let values: Vec<u8> = cells.iter().map(|&c| "bwn".find(c).map_or(0, |i| i as u8 + 1)).collect();
+        self.board = Board::from_cells(&values).ok_or_else(|| Error::InvalidPosition("bad cells".into()))?;
+        self.side = side;
+        Ok(())
+    }
+
+    fn side_to_move(&self) -> u8 {
+        self.side
+    }
+
+    fn legal_moves(&self) -> Vec<String> {
+        names(&generate_actions(&self.board, self.side), name)
+    }
+
+    fn play(&mut self, mv: &str) -> Result<(), Error> {
+        let action = parse(mv)
+            .filter(|&a| is_legal(&self.board, self.side, a))
+            .ok_or_else(|| Error::IllegalMove(mv.into()))?;
+        (self.board, self.side) = apply_action(&self.board, self.side, action);
+        Ok(())
+    }
+
+    fn status(&self) -> Status {
+        match self.board.winner() {
+            Some(color) => Status::Won(color),
+            None if !has_actions(&self.board, self.side) => Status::Drawn,
+            None => Status::Playing,
+        }
+    }
+
+    fn search(&mut self, options: &SearchOptions, hooks: Hooks) -> SearchReport {
+        self.tt.new_search();
+        let result = Searcher::new(&mut self.tt, &self.zobrist, &mut self.rng, hooks.deadline, options.max_depth)
+            .iterative_deepening(&self.board, self.side, options.top_n, options.score_delta, hooks.clock);
+        SearchReport {
+            best_move: result.best_move.map(name),
+            pv: names(&result.pv, name),
+            score: result.score,
+            mate_in: mate_in(result.score),
+            lines: Vec::new(),
+            depth: result.depth_reached,
+            nodes: result.nodes_searched,
+            elapsed_ms: 0.0,
+        }
+    }
+
+    fn clear_tables(&mut self) {
+        self.tt.clear();
+    }
+
+    fn win_score(&self) -> i32 {
+        MATE
+    }
+}
@@ -0,0 +1,117 @@
+//! Produto: `b` black (side 0, first to move) and `w` white stones on the
+//! side-5 hexagon, one row per `q` from -4 to 4 with `r` ascending (the
+//! `gerarPosicoesValidas` order). A move is one stone of either colour,
+//! `w0,-3` for a white stone on q = 0, r = -3; after the opening stone each
+//! turn places two, so the side to move only changes every other move. The
+//! largest product of the two biggest groups wins once the board is full
+//! (then fewer stones).
+
+use produto_ai::engine::{Searcher, WIN};
+use produto_ai::rng::Rng;
+use produto_core::{apply_placement, axial, cell, generate_placements, is_legal, Board, Placement, BLACK, CELLS};
+
+use super::{names, Game, Hooks};
+use crate::engine::{Error, SearchOptions, SearchReport, Status};
+use crate::notation::{format_position, parse_position};
+
+const ROWS: [usize; 9] = [5, 6, 7, 8, 9, 8, 7, 6, 5];
+const LETTERS: [char; 2] = ['b', 'w'];
+
+pub(crate) struct Produto {
+    board: Board,
+    side: u8,
+    rng: Rng,
+}
+
+fn name(p: Placement) -> String {
+    let (q, r) = axial(p.cell);
+    format!("{}{q},{r}", LETTERS[p.color as usize])
+}
+
+fn parse(text: &str) -> Option<Placement> {
+    let color = LETTERS.iter().position(|&l| text.starts_with(l))? as u8;
+    let (q, r) = text[1..].split_once(',')?;
+    Some(Placement { cell: cell(q.parse().ok()?, r.parse().ok()?)?, color })
+}
+
+impl Produto {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { board: Board::default(), side: BLACK, rng: Rng::new(seed) }
+    }
+}
+
+impl Game for Produto {
+    fn reset(&mut self) {
+        self.board = Board::default();
+        self.side = BLACK;
+    }
+
+    fn position(&self) -> String {
+        let cells = CELLS.iter().map(|&c| {
+            let bit = 1u128 << c;
+            if self.board.stones(0) & bit != 0 {
+                'b'
+            } else if self.board.stones(1) & bit != 0 {
+                'w'
+            } else {
+                '.'
+            }
+        });
+        format_position(cells, &ROWS, LETTERS[self.side as usize])
+    }
+
+    fn set_position(&mut self, notation: &str) -> Result<(), Error> {
+        let (cells, side) = parse_position(notation, &ROWS, "bw", LETTERS)?;
+        let values: Vec<u8> = cells.iter().map(|&c| LETTERS.iter().position(|&l| l == c).map_or(0, |i| i as u8 + 1)).collect();
+        self.board = Board::from_cells(&values).ok_or_else(|| Error::InvalidPosition("bad cells".into()))?;
+        self.side = side;
+        Ok(())
+    }
+
+    fn side_to_move(&self) -> u8 {
+        self.side
+    }
+
+    fn legal_moves(&self) -> Vec<String> {
+        names(&generate_placements(&self.board), name)
+    }
+
+    fn play(&mut self, mv: &str) -> Result<(), Error> {
+        let p = parse(mv)
+            .filter(|&p| is_legal(&self.board, p))
+            .ok_or_else(|| Error::IllegalMove(mv.into()))?;
+        (self.board, self.side) = apply_placement(&self.board, self.side, p);
+        Ok(())
+    }
+
+    fn status(&self) -> Status {
+        if !self.board.is_full() {
+            Status::Playing
+        } else if let Some(color) = self.board.leader() {
+            Status::Won(color)
+        } else {
+            Status::Drawn
+        }
+    }
+
+    fn search(&mut self, options: &SearchOptions, hooks: Hooks) -> SearchReport {
+        let result = Searcher::new(&mut self.rng, hooks.deadline, options.max_playouts)
+            .search(&self.board, self.side, options.top_n, options.score_delta, hooks.clock);
+        SearchReport {
+            best_move: result.best_turn.first().map(|&p| name(p)),
+            pv: names(&result.pv, name),
+            score: result.score,
+            mate_in: None,
+            lines: Vec::new(),
+            depth: 0,
+            nodes: if result.proven { result.nodes } else { result.playouts },
+            elapsed_ms: 0.0,
+        }
+    }
+
+    fn clear_tables(&mut self) {}
+
+    fn win_score(&self) -> i32 {
+        WIN
+    }
+}
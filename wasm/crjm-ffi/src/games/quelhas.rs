@@ -0,0 +1,196 @@
+//! Quelhas: `v` and `h` cells belong to vertical and horizontal segments; a
+//! move names both ends of the segment (`c2-c5`). Misère: the side left
+//! without a move wins.
+
+use crjm_search::Progress;
+use quelhas_ai::engine::{mate_in, History, Searcher, MATE};
+use quelhas_ai::review::review_game_from;
+use quelhas_ai::rng::Rng;
+use quelhas_ai::tt::TranspositionTable;
+use quelhas_ai::zobrist::ZobristKeys;
+use quelhas_core::{decode_move, encode_move, generate_all_moves, EncMove, Occupancy, BOARD_SIZE, CELL_COUNT};
+
+use super::{names, replay, Game, Hooks, CLASSES};
+use crate::engine::{now, Error, MoveReview, SearchOptions, SearchReport, Status};
+use crate::notation::{format_position, parse_position, parse_square, square_name};
+
+const ROWS: [usize; BOARD_SIZE] = [BOARD_SIZE; BOARD_SIZE];
+const LETTERS: [char; 2] = ['v', 'h'];
+
+pub(crate) struct Quelhas {
+    /// 0 = empty, 1 = vertical, 2 = horizontal
+    cells: [u8; CELL_COUNT],
+    side: u8,
+    tt: TranspositionTable,
+    zobrist: ZobristKeys,
+    history: History,
+    rng: Rng,
+}
+
+/// `c2-c5`: first and last cell of the segment
+pub(crate) fn move_name(mv: EncMove) -> String {
+    let (start, len, orient) = decode_move(mv);
+    let step = if orient == 0 { BOARD_SIZE } else { 1 };
+    let end = start as usize + (len as usize - 1) * step;
+    format!("{}-{}", square_name(start as usize, BOARD_SIZE), square_name(end, BOARD_SIZE))
+}
+
+/// Segment between two cells of a row or column (in either order)
+pub(crate) fn parse_move(name: &str) -> Option<EncMove> {
+    let (a, b) = name.split_once('-')?;
+    let a = parse_square(a, BOARD_SIZE, BOARD_SIZE)?;
+    let b = parse_square(b, BOARD_SIZE, BOARD_SIZE)?;
+    let (start, end) = (a.min(b), a.max(b));
+    if start % BOARD_SIZE == end % BOARD_SIZE {
+        Some(encode_move(start as u8, ((end - start) / BOARD_SIZE + 1) as u8, 0))
+    } else if start / BOARD_SIZE == end / BOARD_SIZE {
+        Some(encode_move(start as u8, (end - start + 1) as u8, 1))
+    } else {
+        None
+    }
+}
+
+impl Quelhas {
+    pub(crate) fn new(tt_mb: usize, seed: u64) -> Self {
+        Self {
+            cells: [0; CELL_COUNT],
+            side: 0,
+            tt: TranspositionTable::new(tt_mb),
+            zobrist: ZobristKeys::new(),
+            history: History::new(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    fn occupancy(&self) -> Occupancy {
+        let mut occ = Occupancy::default();
+        self.cells.iter().enumerate().filter(|(_, &c)| c != 0).for_each(|(i, _)| occ.set(i));
+        occ
+    }
+}
+
+impl Game for Quelhas {
+    fn reset(&mut self) {
+        self.cells = [0; CELL_COUNT];
+        self.side = 0;
+    }
+
+    fn position(&self) -> String {
+        let cells = self.cells.iter().map(|&c| if c == 0 { '.' } else { LETTERS[c as usize - 1] });
+        format_position(cells, &ROWS, LETTERS[self.side as usize])
+    }
+
+    fn set_position(&mut self, notation: &str) -> Result<(), Error> {
+        let (cells, side) = parse_position(notation, &ROWS, "vh", LETTERS)?;
+        for (cell, c) in self.cells.iter_mut().zip(cells) {
+            *cell = LETTERS.iter().position(|&l| l == c).map_or(0, |i| i as u8 + 1);
+        }
+        self.side = side;
+        Ok(())
+    }
+
+    fn side_to_move(&self) -> u8 {
+        self.side
+    }
+
+    fn legal_moves(&self) -> Vec<String> {
+        names(&generate_all_moves(self.occupancy(), self.side), move_name)
+    }
+
+    fn play(&mut self, mv: &str) -> Result<(), Error> {
+        let encoded = parse_move(mv)
+            .filter(|&m| generate_all_moves(self.occupancy(), self.side).contains(&m))
+            .ok_or_else(|| Error::IllegalMove(mv.into()))?;
+        let (start, len, orient) = decode_move(encoded);
+        let step = if orient == 0 { BOARD_SIZE } else { 1 };
+        for i in 0..len as usize {
+            self.cells[start as usize + i * step] = self.side + 1;
+        }
+        self.side = 1 - self.side;
+        Ok(())
+    }
+
+    fn status(&self) -> Status {
+        if generate_all_moves(self.occupancy(), self.side).is_empty() {
+            Status::Won(self.side)
+        } else {
+            Status::Playing
+        }
+    }
+
+    fn search(&mut self, options: &SearchOptions, hooks: Hooks) -> SearchReport {
+        let progress = hooks.progress;
+        let mut lines = Vec::new();
+        let mut report_iteration = |p: &Progress<EncMove>| {
+            lines = p.root.iter().take(options.multipv).map(|&(m, score)| (move_name(m), score)).collect();
+            progress(SearchReport {
+                best_move: p.pv.first().map(|&m| move_name(m)),
+                pv: names(p.pv, move_name),
+                score: p.score,
+                mate_in: mate_in(p.score),
+                lines: lines.clone(),
+                depth: p.depth,
+                nodes: p.stats.nodes,
+                elapsed_ms: 0.0,
+            })
+        };
+        self.tt.new_search();
+        self.history.age();
+        let occ = self.occupancy();
+        let result = Searcher::new(&mut self.tt, &self.zobrist, &mut self.history, &mut self.rng, hooks.deadline, options.max_depth)
+            .with_node_budget(options.max_nodes)
+            .with_multipv(options.multipv)
+            .with_progress(&mut report_iteration)
+            .iterative_deepening(occ, self.side, options.top_n, options.score_delta, hooks.clock);
+        SearchReport {
+            best_move: result.best_move.map(move_name),
+            pv: names(&result.pv, move_name),
+            score: result.score,
+            mate_in: mate_in(result.score),
+            lines,
+            depth: result.depth_reached,
+            nodes: result.nodes_searched,
+            elapsed_ms: 0.0,
+        }
+    }
+
+    fn clear_tables(&mut self) {
+        self.tt.clear();
+        self.history.clear();
+    }
+
+    fn win_score(&self) -> i32 {
+        MATE
+    }
+
+    fn review(&mut self, moves: &[String], options: &SearchOptions) -> Option<Result<Vec<MoveReview>, Error>> {
+        let (start, first) = (self.occupancy(), self.side);
+        let encoded = match replay(self, moves, parse_move) {
+            Ok(encoded) => encoded,
+            Err(e) => return Some(Err(e)),
+        };
+        let (tt, zobrist, history, rng) = (&mut self.tt, &self.zobrist, &mut self.history, &mut self.rng);
+        let review = review_game_from(start, first, &encoded, |occ, side| {
+            tt.new_search();
+            history.age();
+            let result = Searcher::new(tt, zobrist, history, rng, now() + options.time_ms, options.max_depth)
+                .with_node_budget(options.max_nodes)
+                .iterative_deepening(occ, side, 0, 0, now);
+            (result.best_move, result.score)
+        })
+        .expect("the moves were just played");
+        Some(Ok(review
+            .moves
+            .iter()
+            .map(|m| MoveReview {
+                played: move_name(m.played),
+                best_move: m.best_move.map(move_name),
+                best_score: m.best_score,
+                best_mate_in: mate_in(m.best_score),
+                played_score: m.played_score,
+                loss: m.eval_drop,
+                class: CLASSES[m.class as usize],
+            })
+            .collect()))
+    }
+}
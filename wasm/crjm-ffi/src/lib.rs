@@ -0,0 +1,401 @@
+//! CRJM native engines behind a stable C ABI
+//!
+//! - Every game of the tournament protocol (`gatos-caes`, `dominorio`,
+//!   `quelhas`, `produto`, `atari-go`, `nex`) on its own search crate
+//! - Positions and moves as text (see [`notation`] and each game's module)
+//! - Every entry point returns a [`CrjmStatus`]; the message of the last
+//!   error on the calling thread is kept for `crjm_last_error`
+//! - Panics never cross the boundary: they become `CRJM_STATUS_PANIC` (the
+//!   engine that panicked should then be freed)
+//! - Strings handed out are owned by the caller and released with
+//!   `crjm_string_free`; strings passed in are NUL-terminated UTF-8
+//!
+//! `include/crjm_ffi.h` is checked in; after changing the C ABI, regenerate
+//! it from this file with `cargo build --features header`.
+//! Rust hosts can use [`Engine`] directly.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+pub mod engine;
+mod games;
+pub mod notation;
+
+pub use engine::{Engine, Error, GameKind, MoveReview, SearchOptions, SearchReport, Status};
+
+/// Result of every call
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrjmStatus {
+    Ok = 0,
+    /// A required pointer argument was NULL
+    NullArgument = 1,
+    /// A string argument was not UTF-8
+    InvalidUtf8 = 2,
+    UnknownGame = 3,
+    InvalidPosition = 4,
+    IllegalMove = 5,
+    GameOver = 6,
+    /// The engine panicked; free it and create another
+    Panic = 7,
+}
+
+/// Opaque engine: one game's position plus its search tables
+pub struct CrjmEngine(Engine);
+
+/// Search limits; zero `max_depth` or `max_playouts` means no limit
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CrjmSearchOptions {
+    pub time_ms: f64,
+    pub max_depth: u32,
+    /// Produto only
+    pub max_playouts: u64,
+    /// Random pick among the `top_n` best moves within `score_delta` (0 = best)
+    pub top_n: u32,
+    pub score_delta: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CrjmSearchInfo {
+    /// For the side to move
+    pub score: i32,
+    pub depth: u32,
+    pub nodes: u64,
+    pub elapsed_ms: f64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CrjmGameStatus {
+    pub side_to_move: u8,
+    pub finished: bool,
+    /// Winning side (0 or 1), or -1 while playing and on a draw
+    pub winner: i8,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+struct Failure(CrjmStatus, String);
+
+impl From<Error> for Failure {
+    fn from(e: Error) -> Self {
+        let status = match e {
+            Error::UnknownGame(_) => CrjmStatus::UnknownGame,
+            Error::InvalidPosition(_) => CrjmStatus::InvalidPosition,
+            Error::IllegalMove(_) => CrjmStatus::IllegalMove,
+            Error::GameOver => CrjmStatus::GameOver,
+        };
+        Failure(status, e.to_string())
+    }
+}
+
+/// Runs `body`, turning errors and panics into a status and the last error
+fn guard(body: impl FnOnce() -> Result<(), Failure>) -> CrjmStatus {
+    let failure = match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => return CrjmStatus::Ok,
+        Ok(Err(failure)) => failure,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".into());
+            Failure(CrjmStatus::Panic, format!("engine panicked: {message}"))
+        }
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(failure.1.replace('\0', " ")).ok());
+    failure.0
+}
+
+fn null(what: &str) -> Failure {
+    Failure(CrjmStatus::NullArgument, format!("{what} is NULL"))
+}
+
+unsafe fn text<'a>(s: *const c_char, what: &str) -> Result<&'a str, Failure> {
+    if s.is_null() {
+        return Err(null(what));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| Failure(CrjmStatus::InvalidUtf8, format!("{what} is not UTF-8")))
+}
+
+unsafe fn engine_ref<'a>(engine: *const CrjmEngine) -> Result<&'a Engine, Failure> {
+    engine.as_ref().map(|e| &e.0).ok_or_else(|| null("engine"))
+}
+
+unsafe fn engine_mut<'a>(engine: *mut CrjmEngine) -> Result<&'a mut Engine, Failure> {
+    engine.as_mut().map(|e| &mut e.0).ok_or_else(|| null("engine"))
+}
+
+/// Hands `s` to the caller (interior NULs cannot occur in notation)
+fn owned(s: String) -> *mut c_char {
+    CString::new(s).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+unsafe fn write_string(out: *mut *mut c_char, s: String, what: &str) -> Result<(), Failure> {
+    if out.is_null() {
+        return Err(null(what));
+    }
+    *out = owned(s);
+    Ok(())
+}
+
+/// Library version, a static string (do not free)
+#[no_mangle]
+pub extern "C" fn crjm_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Message of the last failed call on this thread (free it with
+/// `crjm_string_free`), or NULL
+#[no_mangle]
+pub extern "C" fn crjm_last_error() -> *mut c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null_mut(), |s| s.clone().into_raw()))
+}
+
+/// Releases a string returned by this library
+///
+/// # Safety
+/// `s` is NULL or a string from this library not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn crjm_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Creates an engine for `game` (a tournament `GameId`) at the initial
+/// position, with a transposition table of `tt_mb` megabytes (at most 256)
+///
+/// # Safety
+/// `game` is a NUL-terminated string and `out` is valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn crjm_engine_new(game: *const c_char, tt_mb: u32, seed: u64, out: *mut *mut CrjmEngine) -> CrjmStatus {
+    guard(|| {
+        let id = text(game, "game")?;
+        if out.is_null() {
+            return Err(null("out"));
+        }
+        let kind = GameKind::from_id(id).ok_or_else(|| Error::UnknownGame(id.into()))?;
+        *out = Box::into_raw(Box::new(CrjmEngine(Engine::new(kind, tt_mb as usize, seed))));
+        Ok(())
+    })
+}
+
+/// Destroys an engine (NULL is ignored)
+///
+/// # Safety
+/// `engine` is NULL or from `crjm_engine_new`, not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn crjm_engine_free(engine: *mut CrjmEngine) {
+    if !engine.is_null() {
+        // a panicking destructor has nothing left to clean up
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(engine))));
+    }
+}
+
+/// Back to the initial position, keeping the tables
+///
+/// # Safety
+/// `engine` is a live engine.
+#[no_mangle]
+pub unsafe extern "C" fn crjm_engine_new_game(engine: *mut CrjmEngine) -> CrjmStatus {
+    guard(|| {
+        engine_mut(engine)?.new_game();
+        Ok(())
+    })
+}
+
+/// Replaces the position (the engine keeps its position on error)
+///
+/// # Safety
+/// `engine` is a live engine and `notation` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn crjm_engine_set_position(engine: *mut CrjmEngine, notation: *const c_char) -> CrjmStatus {
+    guard(|| {
+        let notation = text(notation, "notation")?;
+        Ok(engine_mut(engine)?.set_position(notation)?)
+    })
+}
+
+/// Current position in notation, into `*out`
+///
+/// # Safety
+/// `engine` is a live engine and `out` is valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn crjm_engine_position(engine: *const CrjmEngine, out: *mut *mut c_char) -> CrjmStatus {
+    guard(|| write_string(out, engine_ref(engine)?.position(), "out"))
+}
+
+/// Space-separated legal moves into `*out` (empty once the game is over)
+///
+/// # Safety
+/// `engine` is a live engine and `out` is valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn crjm_engine_legal_moves(engine: *const CrjmEngine, out: *mut *mut c_char) -> CrjmStatus {
+    guard(|| write_string(out, engine_ref(engine)?.legal_moves().join(" "), "out"))
+}
+
+/// Plays `mv` for the side to move
+///
+/// # Safety
+/// `engine` is a live engine and `mv` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn crjm_engine_apply_move(engine: *mut CrjmEngine, mv: *const c_char) -> CrjmStatus {
+    guard(|| {
+        let mv = text(mv, "move")?;
+        Ok(engine_mut(engine)?.apply_move(mv)?)
+    })
+}
+
+/// Side to move and result so far
+///
+/// # Safety
+/// `engine` is a live engine and `out` is valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn crjm_engine_status(engine: *const CrjmEngine, out: *mut CrjmGameStatus) -> CrjmStatus {
+    guard(|| {
+        let engine = engine_ref(engine)?;
+        let out = out.as_mut().ok_or_else(|| null("out"))?;
+        let (finished, winner) = match engine.status() {
+            Status::Playing => (false, -1),
+            Status::Won(side) => (true, side as i8),
+            Status::Drawn => (true, -1),
+        };
+        *out = CrjmGameStatus { side_to_move: engine.side_to_move(), finished, winner };
+        Ok(())
+    })
+}
+
+/// Searches the current position. `options` may be NULL for the defaults
+/// (one second, no depth limit); `info`, `best_move` and `pv` may be NULL
+/// when not wanted. `*best_move` is NULL when there is no move, `*pv` holds
+/// space-separated moves.
+///
+/// # Safety
+/// `engine` is a live engine; the other pointers are NULL or valid.
+#[no_mangle]
+pub unsafe extern "C" fn crjm_engine_search(
+    engine: *mut CrjmEngine,
+    options: *const CrjmSearchOptions,
+    info: *mut CrjmSearchInfo,
+    best_move: *mut *mut c_char,
+    pv: *mut *mut c_char,
+) -> CrjmStatus {
+    guard(|| {
+        let engine = engine_mut(engine)?;
+        let defaults = SearchOptions::default();
+        let options = options.as_ref().map_or(defaults, |o| SearchOptions {
+            time_ms: o.time_ms,
+            max_depth: if o.max_depth == 0 { defaults.max_depth } else { o.max_depth },
+            max_playouts: if o.max_playouts == 0 { defaults.max_playouts } else { o.max_playouts },
+            top_n: o.top_n,
+            score_delta: o.score_delta,
+            ..defaults
+        });
+        let report = engine.search(&options);
+        if let Some(info) = info.as_mut() {
+            *info = CrjmSearchInfo {
+                score: report.score,
+                depth: report.depth,
+                nodes: report.nodes,
+                elapsed_ms: report.elapsed_ms,
+            };
+        }
+        if !best_move.is_null() {
+            *best_move = report.best_move.map_or(ptr::null_mut(), owned);
+        }
+        if !pv.is_null() {
+            *pv = owned(report.pv.join(" "));
+        }
+        Ok(())
+    })
+}
+
+/// Forgets what earlier searches stored
+///
+/// # Safety
+/// `engine` is a live engine.
+#[no_mangle]
+pub unsafe extern "C" fn crjm_engine_clear_tables(engine: *mut CrjmEngine) -> CrjmStatus {
+    guard(|| {
+        engine_mut(engine)?.clear_tables();
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn take(s: *mut c_char) -> String {
+        let text = CStr::from_ptr(s).to_str().unwrap().to_owned();
+        crjm_string_free(s);
+        text
+    }
+
+    #[test]
+    fn engines_are_driven_through_the_c_abi() {
+        unsafe {
+            let mut engine = ptr::null_mut();
+            assert_eq!(crjm_engine_new(c"quelhas".as_ptr(), 1, 3, &mut engine), CrjmStatus::Ok);
+
+            let mut out = ptr::null_mut();
+            assert_eq!(crjm_engine_legal_moves(engine, &mut out), CrjmStatus::Ok);
+            let moves = take(out);
+            assert!(moves.split(' ').any(|m| m == "a1-a10"));
+
+            assert_eq!(crjm_engine_apply_move(engine, c"a1-a10".as_ptr()), CrjmStatus::Ok);
+            let mut status = CrjmGameStatus::default();
+            assert_eq!(crjm_engine_status(engine, &mut status), CrjmStatus::Ok);
+            assert_eq!((status.side_to_move, status.finished, status.winner), (1, false, -1));
+
+            let options = CrjmSearchOptions { time_ms: 10_000.0, max_depth: 2, max_playouts: 0, top_n: 0, score_delta: 0 };
+            let (mut info, mut best, mut pv) = (CrjmSearchInfo::default(), ptr::null_mut(), ptr::null_mut());
+            assert_eq!(crjm_engine_search(engine, &options, &mut info, &mut best, &mut pv), CrjmStatus::Ok);
+            let best = take(best);
+            assert!(take(pv).starts_with(&best));
+            assert!(info.depth >= 1 && info.nodes > 0);
+            assert_eq!(crjm_engine_apply_move(engine, CString::new(best).unwrap().as_ptr()), CrjmStatus::Ok);
+
+            assert_eq!(crjm_engine_position(engine, &mut out), CrjmStatus::Ok);
+            let position = CString::new(take(out)).unwrap();
+            assert!(position.to_str().unwrap().ends_with(" v"));
+            assert_eq!(crjm_engine_new_game(engine), CrjmStatus::Ok);
+            assert_eq!(crjm_engine_set_position(engine, position.as_ptr()), CrjmStatus::Ok);
+            crjm_engine_free(engine);
+        }
+    }
+
+    #[test]
+    fn failures_report_a_status_and_a_message() {
+        unsafe {
+            let mut engine = ptr::null_mut();
+            assert_eq!(crjm_engine_new(c"xadrez".as_ptr(), 1, 0, &mut engine), CrjmStatus::UnknownGame);
+            assert!(engine.is_null());
+            assert_eq!(take(crjm_last_error()), "unknown game 'xadrez'");
+            assert_eq!(crjm_engine_new(ptr::null(), 1, 0, &mut engine), CrjmStatus::NullArgument);
+
+            assert_eq!(crjm_engine_new(c"dominorio".as_ptr(), 1, 0, &mut engine), CrjmStatus::Ok);
+            assert_eq!(crjm_engine_apply_move(engine, c"h8".as_ptr()), CrjmStatus::IllegalMove);
+            assert_eq!(crjm_engine_set_position(engine, c"../.. v".as_ptr()), CrjmStatus::InvalidPosition);
+            assert_eq!(crjm_engine_apply_move(engine, c"\xff".as_ptr()), CrjmStatus::InvalidUtf8);
+            assert_eq!(crjm_engine_position(engine, ptr::null_mut()), CrjmStatus::NullArgument);
+            assert_eq!(crjm_engine_apply_move(ptr::null_mut(), c"a1".as_ptr()), CrjmStatus::NullArgument);
+            crjm_engine_free(engine);
+        }
+    }
+
+    #[test]
+    fn panics_become_a_status() {
+        assert_eq!(guard(|| panic!("boom")), CrjmStatus::Panic);
+        assert_eq!(unsafe { take(crjm_last_error()) }, "engine panicked: boom");
+    }
+}
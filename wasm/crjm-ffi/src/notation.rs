@@ -0,0 +1,91 @@
+//! Text notation shared by the games
+//!
+//! A position is the board, one `/`-separated field per row starting from
+//! row 0, one character per cell (`.` empty, otherwise a game-specific
+//! letter), then a space and the letter of the side to move. An empty
+//! Gatos & Cães board with the cats to move is eight fields of `........`
+//! followed by ` c`.
+//!
+//! Cells of square boards (and of Nex's rhombus) are named by column letter
+//! and row number: `a1` is row 0, column 0. Produto names its hexagonal
+//! cells by axial coordinates, `q,r`.
+
+use crate::engine::Error;
+
+/// Cell name of `idx` on a board `width` cells wide
+pub fn square_name(idx: usize, width: usize) -> String {
+    format!("{}{}", (b'a' + (idx % width) as u8) as char, idx / width + 1)
+}
+
+/// Index of a cell name on a `width`×`height` board
+pub fn parse_square(name: &str, width: usize, height: usize) -> Option<usize> {
+    let mut chars = name.chars();
+    let col = chars.next()?.to_ascii_lowercase();
+    let col = (col as usize).checked_sub('a' as usize).filter(|&c| c < width)?;
+    let row: usize = chars.as_str().parse().ok()?;
+    (1..=height).contains(&row).then(|| (row - 1) * width + col)
+}
+
+/// Writes `cells` as `/`-separated rows of the given lengths, then the side
+pub fn format_position(cells: impl IntoIterator<Item = char>, rows: &[usize], side: char) -> String {
+    let mut cells = cells.into_iter();
+    let rows: Vec<String> = rows.iter().map(|&len| cells.by_ref().take(len).collect()).collect();
+    format!("{} {side}", rows.join("/"))
+}
+
+/// Cells (row by row) and side letter of a position with rows of the given
+/// lengths; cell letters outside `alphabet` (besides `.`) are rejected
+pub fn parse_position(notation: &str, rows: &[usize], alphabet: &str, sides: [char; 2]) -> Result<(Vec<char>, u8), Error> {
+    let invalid = |why: String| Error::InvalidPosition(why);
+    let (board, side) = notation
+        .split_once(' ')
+        .ok_or_else(|| invalid("expected '<rows> <side to move>'".into()))?;
+    let side = match side.trim() {
+        s if s.len() == 1 && s.starts_with(sides[0]) => 0,
+        s if s.len() == 1 && s.starts_with(sides[1]) => 1,
+        s => return Err(invalid(format!("side to move '{s}' is not '{}' or '{}'", sides[0], sides[1]))),
+    };
+    let fields: Vec<&str> = board.split('/').collect();
+    if fields.len() != rows.len() {
+        return Err(invalid(format!("expected {} rows, got {}", rows.len(), fields.len())));
+    }
+    let mut cells = Vec::new();
+    for (i, (field, &len)) in fields.iter().zip(rows).enumerate() {
+        if field.chars().count() != len {
+            return Err(invalid(format!("row {} should have {len} cells", i + 1)));
+        }
+        if let Some(c) = field.chars().find(|&c| c != '.' && !alphabet.contains(c)) {
+            return Err(invalid(format!("unexpected cell '{c}' in row {}", i + 1)));
+        }
+        cells.extend(field.chars());
+    }
+    Ok((cells, side))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn squares_round_trip() {
+        for idx in 0..100 {
+            assert_eq!(parse_square(&square_name(idx, 10), 10, 10), Some(idx));
+        }
+        assert_eq!(square_name(0, 8), "a1");
+        assert_eq!(square_name(63, 8), "h8");
+        assert_eq!(parse_square("i1", 8, 8), None);
+        assert_eq!(parse_square("a0", 8, 8), None);
+        assert_eq!(parse_square("a9", 8, 8), None);
+    }
+
+    #[test]
+    fn positions_round_trip_and_reject_garbage() {
+        let cells: Vec<char> = "..x.x....".chars().collect();
+        let text = format_position(cells.iter().copied(), &[3, 3, 3], 'b');
+        assert_eq!(text, "..x/.x./... b");
+        assert_eq!(parse_position(&text, &[3, 3, 3], "x", ['a', 'b']).unwrap(), (cells, 1));
+        assert!(parse_position("..x/.x./... c", &[3, 3, 3], "x", ['a', 'b']).is_err());
+        assert!(parse_position("..x/.y./... a", &[3, 3, 3], "x", ['a', 'b']).is_err());
+        assert!(parse_position("..x/.x. a", &[3, 3, 3], "x", ['a', 'b']).is_err());
+    }
+}
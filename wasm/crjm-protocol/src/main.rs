@@ -0,0 +1,55 @@
+//! UCI-style line protocol for the Dominório and Quelhas engines
+//!
+//! Reads commands from stdin and answers on stdout, one line each, so GUIs
+//! and match runners can drive the engines:
+//!
+//! ```text
+//! uci                                  id, options, uciok
+//! isready                              readyok
+//! setoption name Game value quelhas    Game (dominorio|quelhas), Hash (MB), Seed
+//! newgame                              start position, tables cleared
+//! position startpos|<board> <side> [moves <move>...]
+//! go [depth <n>] [nodes <n>] [movetime <ms>] [infinite]
+//! stop                                 ends the search: bestmove
+//! d                                    the current position
+//! quit
+//! ```
+//!
+//! Positions and moves use the notation of `crjm-ffi`. While searching, the
+//! engine prints `info depth <d> score cp <s>|mate <n> nodes <n> time <ms>
+//! nps <n> pv <moves>` after every complete iteration, then `bestmove`.
+//! Scores are for the side to move in the engine's own scale; `mate` counts
+//! the side to move's turns to a proven result, at least 1 when winning and
+//! at most -1 when losing.
+
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::channel;
+use std::thread;
+
+mod session;
+
+use session::{Flow, Session};
+
+fn main() {
+    let (tx, rx) = channel::<String>();
+    let printer = thread::spawn(move || {
+        let mut stdout = io::stdout().lock();
+        for line in rx {
+            if writeln!(stdout, "{line}").and_then(|()| stdout.flush()).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut session = Session::new(tx);
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        if session.handle(&line) == Flow::Quit {
+            break;
+        }
+    }
+    // quit or end of input: a running search stops and still prints bestmove
+    session.handle("quit");
+    drop(session);
+    let _ = printer.join();
+}
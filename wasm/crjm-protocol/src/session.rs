@@ -0,0 +1,353 @@
+//! Protocol state: the engine, the running search and the replies
+//!
+//! Commands are handled one line at a time. A search runs on its own thread
+//! with the engine moved into it; `stop` and `isready` are answered while it
+//! runs, any other command stops it first (the search still reports its
+//! `bestmove`).
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crjm_ffi::{Engine, GameKind, SearchOptions, SearchReport};
+
+/// The games the protocol plays
+const GAMES: [GameKind; 2] = [GameKind::Dominorio, GameKind::Quelhas];
+
+const DEFAULT_HASH_MB: usize = 16;
+
+/// What to do after a command
+#[derive(Debug, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    Quit,
+}
+
+struct Search {
+    handle: JoinHandle<Engine>,
+    stop: Arc<AtomicBool>,
+}
+
+pub struct Session {
+    /// Replies, one line each
+    out: Sender<String>,
+    /// `None` while a search owns it
+    engine: Option<Engine>,
+    search: Option<Search>,
+    hash_mb: usize,
+    seed: u64,
+}
+
+impl Session {
+    pub fn new(out: Sender<String>) -> Self {
+        Self {
+            out,
+            engine: Some(Engine::new(GameKind::Dominorio, DEFAULT_HASH_MB, 0)),
+            search: None,
+            hash_mb: DEFAULT_HASH_MB,
+            seed: 0,
+        }
+    }
+
+    fn send(&self, line: impl Into<String>) {
+        // the reader is gone only when the program is exiting
+        let _ = self.out.send(line.into());
+    }
+
+    /// The engine, once the running search (if any) has been stopped
+    fn engine(&mut self) -> &mut Engine {
+        if let Some(search) = self.search.take() {
+            search.stop.store(true, Ordering::Relaxed);
+            self.engine = Some(search.handle.join().expect("search thread panicked"));
+        }
+        self.engine.as_mut().expect("engine owned by the session")
+    }
+
+    pub fn handle(&mut self, line: &str) -> Flow {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Flow::Continue;
+        };
+        let args: Vec<&str> = words.collect();
+        match command {
+            "uci" => self.identify(),
+            "isready" => self.send("readyok"),
+            "setoption" => self.set_option(&args),
+            "newgame" | "ucinewgame" => {
+                let engine = self.engine();
+                engine.new_game();
+                engine.clear_tables();
+            }
+            "position" => self.position(&args),
+            "go" => self.go(&args),
+            "stop" => {
+                if let Some(search) = &self.search {
+                    search.stop.store(true, Ordering::Relaxed);
+                }
+            }
+            "d" => {
+                let position = self.engine().position();
+                self.send(format!("info string position {position}"));
+            }
+            "quit" => {
+                self.engine();
+                return Flow::Quit;
+            }
+            _ => self.send(format!("info string unknown command '{command}'")),
+        }
+        Flow::Continue
+    }
+
+    fn identify(&mut self) {
+        let game = self.engine().kind().id();
+        self.send(concat!("id name crjm-protocol ", env!("CARGO_PKG_VERSION")));
+        self.send("id author CRJM");
+        self.send(format!("option name Game type combo default {game} var dominorio var quelhas"));
+        self.send(format!("option name Hash type spin default {DEFAULT_HASH_MB} min 1 max {}", crjm_ffi::engine::MAX_TT_MB));
+        self.send("option name Seed type spin default 0 min 0 max 4294967295");
+        self.send("uciok");
+    }
+
+    /// `setoption name <name> value <value>`; a new game or table size
+    /// replaces the engine (and resets the position)
+    fn set_option(&mut self, args: &[&str]) {
+        let (name, value) = match args {
+            ["name", rest @ ..] => match rest.iter().position(|&w| w == "value") {
+                Some(i) => (rest[..i].join(" "), rest[i + 1..].join(" ")),
+                None => (rest.join(" "), String::new()),
+            },
+            _ => return self.send("info string expected 'setoption name <name> value <value>'"),
+        };
+        let kind = self.engine().kind();
+        match name.to_ascii_lowercase().as_str() {
+            "game" => match GameKind::from_id(&value).filter(|k| GAMES.contains(k)) {
+                Some(kind) => self.engine = Some(Engine::new(kind, self.hash_mb, self.seed)),
+                None => self.send(format!("info string unknown game '{value}'")),
+            },
+            "hash" => match value.parse::<usize>() {
+                Ok(mb) if mb > 0 => {
+                    self.hash_mb = mb;
+                    self.engine = Some(Engine::new(kind, mb, self.seed));
+                }
+                _ => self.send(format!("info string bad Hash '{value}'")),
+            },
+            "seed" => match value.parse() {
+                Ok(seed) => {
+                    self.seed = seed;
+                    self.engine = Some(Engine::new(kind, self.hash_mb, seed));
+                }
+                Err(_) => self.send(format!("info string bad Seed '{value}'")),
+            },
+            _ => self.send(format!("info string unknown option '{name}'")),
+        }
+    }
+
+    /// `position startpos|<board> <side> [moves <move>...]`; on an error the
+    /// previous position stays
+    fn position(&mut self, args: &[&str]) {
+        let split = args.iter().position(|&w| w == "moves").unwrap_or(args.len());
+        let (setup, moves) = (&args[..split], args.get(split + 1..).unwrap_or(&[]));
+        let engine = self.engine();
+        let previous = engine.position();
+        let result = match setup {
+            ["startpos"] => {
+                engine.new_game();
+                Ok(())
+            }
+            [] => Err("expected 'startpos' or a position".to_string()),
+            _ => engine.set_position(&setup.join(" ")).map_err(|e| e.to_string()),
+        }
+        .and_then(|()| moves.iter().try_for_each(|mv| engine.apply_move(mv)).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            // `previous` was written by the engine itself
+            let _ = engine.set_position(&previous);
+            self.send(format!("info string {e}"));
+        }
+    }
+
+    /// `go [depth <n>] [nodes <n>] [movetime <ms>] [infinite]`; without a
+    /// time limit the search runs until `stop` or the depth is reached
+    fn go(&mut self, args: &[&str]) {
+        let mut options = SearchOptions { time_ms: f64::INFINITY, ..SearchOptions::default() };
+        let mut words = args.iter();
+        while let Some(&word) = words.next() {
+            let value = match word {
+                "infinite" => continue,
+                "depth" | "nodes" | "movetime" => words.next().and_then(|v| v.parse::<u64>().ok()),
+                _ => return self.send(format!("info string unknown go parameter '{word}'")),
+            };
+            let Some(value) = value else {
+                return self.send(format!("info string expected a number after '{word}'"));
+            };
+            match word {
+                "depth" => options.max_depth = value.clamp(1, 64) as u32,
+                "nodes" => options.max_nodes = value,
+                _ => options.time_ms = value as f64,
+            }
+        }
+
+        self.engine();
+        let mut engine = self.engine.take().expect("engine owned by the session");
+        let stop = Arc::new(AtomicBool::new(false));
+        let out = self.out.clone();
+        let flag = stop.clone();
+        let handle = thread::spawn(move || {
+            let mut reported = false;
+            let mut progress = |report: &SearchReport| {
+                reported = true;
+                let _ = out.send(info_line(report));
+            };
+            let report = engine.search_with(&options, &flag, &mut progress);
+            if !reported && report.best_move.is_some() {
+                let _ = out.send(info_line(&report));
+            }
+            let _ = out.send(bestmove_line(&report));
+            engine
+        });
+        self.search = Some(Search { handle, stop });
+    }
+}
+
+/// `info depth .. score cp|mate .. nodes .. time .. nps .. pv ..`
+pub fn info_line(report: &SearchReport) -> String {
+    let score = match report.mate_in {
+        Some(moves) => format!("mate {moves}"),
+        None => format!("cp {}", report.score),
+    };
+    let time = report.elapsed_ms.max(0.0) as u64;
+    let nps = report.nodes * 1000 / time.max(1);
+    let mut line = format!("info depth {} score {score} nodes {} time {time} nps {nps}", report.depth, report.nodes);
+    if !report.pv.is_empty() {
+        line += &format!(" pv {}", report.pv.join(" "));
+    }
+    line
+}
+
+/// `bestmove <move> [ponder <reply>]`, `bestmove (none)` without a move
+pub fn bestmove_line(report: &SearchReport) -> String {
+    match (&report.best_move, report.pv.get(1)) {
+        (Some(best), Some(reply)) if report.pv[0] == *best => format!("bestmove {best} ponder {reply}"),
+        (Some(best), _) => format!("bestmove {best}"),
+        (None, _) => "bestmove (none)".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::{channel, Receiver};
+
+    fn session() -> (Session, Receiver<String>) {
+        let (tx, rx) = channel();
+        (Session::new(tx), rx)
+    }
+
+    /// Replies up to (and including) the first line starting with `last`
+    fn until(rx: &Receiver<String>, last: &str) -> Vec<String> {
+        let mut lines = Vec::new();
+        loop {
+            let line = rx.recv().expect("a reply");
+            let done = line.starts_with(last);
+            lines.push(line);
+            if done {
+                return lines;
+            }
+        }
+    }
+
+    #[test]
+    fn handshake_and_options() {
+        let (mut s, rx) = session();
+        s.handle("uci");
+        let lines = until(&rx, "uciok");
+        assert!(lines[0].starts_with("id name crjm-protocol"));
+        assert!(lines.iter().any(|l| l.starts_with("option name Game type combo default dominorio")));
+        s.handle("isready");
+        assert_eq!(rx.recv().unwrap(), "readyok");
+        s.handle("setoption name Game value quelhas");
+        s.handle("setoption name Game value xadrez");
+        assert_eq!(rx.recv().unwrap(), "info string unknown game 'xadrez'");
+        s.handle("frobnicate");
+        assert_eq!(rx.recv().unwrap(), "info string unknown command 'frobnicate'");
+        assert_eq!(s.handle("quit"), Flow::Quit);
+        assert_eq!(s.engine().kind(), GameKind::Quelhas);
+    }
+
+    #[test]
+    fn depth_limited_search_reports_every_iteration() {
+        let (mut s, rx) = session();
+        s.handle("position startpos moves a1 b1");
+        s.handle("go depth 3");
+        let lines = until(&rx, "bestmove");
+        let depths: Vec<&str> = lines.iter().filter_map(|l| l.split_whitespace().nth(2)).take(3).collect();
+        assert_eq!(depths, ["1", "2", "3"]);
+        assert!(lines[2].contains(" pv "));
+        let best = lines[3].split_whitespace().nth(1).unwrap().to_string();
+        let pv_first = lines[2].split(" pv ").nth(1).unwrap().split_whitespace().next().unwrap();
+        assert_eq!(best, pv_first);
+        // the move is playable from the searched position
+        s.handle(&format!("position startpos moves a1 b1 {best}"));
+        s.handle("d");
+        assert!(rx.recv().unwrap().ends_with(" h"));
+    }
+
+    #[test]
+    fn stop_ends_an_infinite_search() {
+        let (mut s, rx) = session();
+        s.handle("setoption name Game value quelhas");
+        s.handle("newgame");
+        s.handle("position startpos");
+        s.handle("go infinite");
+        until(&rx, "info depth 2");
+        s.handle("isready");
+        until(&rx, "readyok");
+        s.handle("stop");
+        let last = until(&rx, "bestmove").pop().unwrap();
+        assert!(last.split_whitespace().nth(1).is_some_and(|m| m.contains('-')), "{last}");
+    }
+
+    #[test]
+    fn commands_during_an_infinite_search_stop_it() {
+        let (mut s, rx) = session();
+        s.handle("position startpos");
+        s.handle("go infinite");
+        until(&rx, "info depth 2");
+        s.handle("position startpos moves a1");
+        until(&rx, "bestmove");
+        s.handle("d");
+        assert!(rx.recv().unwrap().ends_with(" h"));
+
+        s.handle("go infinite");
+        until(&rx, "info depth 2");
+        s.handle("go depth 1");
+        until(&rx, "bestmove");
+        let lines = until(&rx, "bestmove");
+        assert!(lines[0].starts_with("info depth 1 "), "{lines:?}");
+    }
+
+    #[test]
+    fn bad_positions_and_moves_keep_the_previous_position() {
+        let (mut s, rx) = session();
+        s.handle("position startpos moves a1");
+        s.handle("d");
+        let before = rx.recv().unwrap();
+        s.handle("position startpos moves b1 h8");
+        assert_eq!(rx.recv().unwrap(), "info string illegal move 'h8'");
+        s.handle("position ../.. v");
+        assert!(rx.recv().unwrap().starts_with("info string invalid position"));
+        s.handle("d");
+        assert_eq!(rx.recv().unwrap(), before);
+        s.handle("go depth x");
+        assert_eq!(rx.recv().unwrap(), "info string expected a number after 'depth'");
+    }
+
+    #[test]
+    fn finished_games_have_no_best_move() {
+        let (mut s, rx) = session();
+        let full = ["vvvvvvvv"; 8].join("/");
+        s.handle(&format!("position {full} h"));
+        s.handle("go depth 2");
+        assert_eq!(rx.recv().unwrap(), "bestmove (none)");
+    }
+}
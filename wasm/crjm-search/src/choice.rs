@@ -0,0 +1,46 @@
+//! Randomized root choice for the lower difficulties
+//!
+//! The engines take `top_n` and `score_delta` from their callers: with
+//! `top_n > 0` they play a random move among the `top_n` best root moves
+//! within `score_delta` of the best, instead of always the best one.
+
+use crate::rng::Rng;
+
+/// Index of the root move to play, from the root scores best first: a
+/// random one among the first `top_n` within `score_delta` of the first.
+///
+/// `None` when there is no choice to make (`top_n == 0` or a single
+/// candidate); `rng` is only drawn from when there is one.
+pub fn pick_root(scores: impl IntoIterator<Item = i32>, top_n: u32, score_delta: i32, rng: &mut Rng) -> Option<usize> {
+    let scores: Vec<i32> = scores.into_iter().take(top_n as usize).collect();
+    let best = *scores.first()?;
+    let candidates: Vec<usize> = (0..scores.len()).filter(|&i| best - scores[i] <= score_delta).collect();
+    (candidates.len() > 1).then(|| candidates[rng.below(candidates.len())])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_replay_with_the_same_seed_and_stay_within_the_limits() {
+        let scores = [50, 45, 44, 30, 49, 10];
+        let picks = |seed: u64, top_n: u32, delta: i32| -> Vec<Option<usize>> {
+            let mut rng = Rng::new(seed);
+            (0..32).map(|_| pick_root(scores, top_n, delta, &mut rng)).collect()
+        };
+        assert_eq!(picks(5, 6, 10), picks(5, 6, 10));
+        assert_ne!(picks(5, 6, 10), picks(6, 6, 10));
+        // only the first `top_n`, and only those close to the best
+        let seen: Vec<usize> = picks(5, 4, 10).into_iter().flatten().collect();
+        assert!(seen.iter().all(|&i| [0, 1, 2].contains(&i)), "{:?}", seen);
+        assert!([0, 1, 2].iter().all(|i| seen.contains(i)));
+
+        // no choice: the generator is left as it was
+        let mut rng = Rng::new(5);
+        assert_eq!(pick_root(scores, 0, 1000, &mut rng), None);
+        assert_eq!(pick_root(scores, 6, 0, &mut rng), None);
+        assert_eq!(pick_root([], 6, 1000, &mut rng), None);
+        assert_eq!(rng.next_u64(), Rng::new(5).next_u64());
+    }
+}
@@ -0,0 +1,362 @@
+//! Exact endgame solving over a game's own reduction
+//!
+//! Some endgames fall apart into parts no move can connect again: areas only
+//! one side can ever play in, and a small area both sides still share. A
+//! game that can see this implements [`Reduction`]: it maps a board to a
+//! small reduced position, keeps the score of the positions that need no
+//! search and lists the children of the rest. [`EndgameSolver`] does the
+//! rest: minimax with a memo over the reduced positions, node budgets,
+//! proven values for every root move and the proven line to the end.
+//!
+//! Values follow the search's convention for the side to move: `MATE -
+//! plies` for a win, `-(MATE - plies)` for a loss.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::choice::pick_root;
+use crate::rng::Rng;
+
+/// What a game provides to have its endgames solved
+pub trait Reduction {
+    /// Score of a win with no plies left, as in the engine
+    const MATE: i32;
+
+    /// Board without the side to move
+    type Board: Copy;
+    type Move: Copy;
+    /// Reduced position, side to move included
+    type Reduced: Clone + Eq + Hash;
+
+    /// Reduced position of `board`, if it decomposes and its shared part has
+    /// at most `max_shared_cells` cells
+    fn reduce(&self, board: Self::Board, side: u8, max_shared_cells: u32) -> Option<Self::Reduced>;
+
+    /// All legal moves of `board`
+    fn moves(&self, board: Self::Board, side: u8) -> Vec<Self::Move>;
+
+    fn apply(&self, board: Self::Board, mv: Self::Move, side: u8) -> Self::Board;
+
+    /// Value of a position known without search (e.g. no shared part left)
+    fn settled(&self, pos: &Self::Reduced) -> Option<i32>;
+
+    /// Reduced positions after each move of an unsettled position; moves
+    /// with the same result may be listed once
+    fn children(&self, pos: &Self::Reduced) -> Vec<Self::Reduced>;
+}
+
+/// Limits of one solve: size of the shared part and new positions visited
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    pub max_shared_cells: u32,
+    pub budget: u64,
+}
+
+/// Reduced positions kept before the memo is cleared
+const MAX_MEMO: usize = 1 << 20;
+
+/// Proven result of a position
+#[derive(Clone, Debug, PartialEq)]
+pub struct Solution<M> {
+    /// value for the side to move (`MATE - plies` or `-(MATE - plies)`)
+    pub score: i32,
+    /// every legal move with its proven value, best first
+    pub moves: Vec<(M, i32)>,
+}
+
+impl<M: Copy> Solution<M> {
+    pub fn best_move(&self) -> Option<M> {
+        self.moves.first().map(|&(mv, _)| mv)
+    }
+
+    pub fn wins(&self) -> bool {
+        self.score > 0
+    }
+}
+
+/// A root solved by [`EndgameSolver::play_root`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolvedRoot<M> {
+    pub solution: Solution<M>,
+    /// move to play
+    pub best_move: Option<M>,
+    /// proven line from `best_move`
+    pub pv: Vec<M>,
+    /// plies to the end of the game on the proven line
+    pub plies: u32,
+}
+
+/// Value of the parent from the value of a child: flip the sign and add a ply
+#[inline]
+pub fn parent_value(child: i32) -> i32 {
+    let v = -child;
+    v - v.signum()
+}
+
+/// Memo of the reduced positions already solved (valid across searches)
+#[derive(Default)]
+pub struct EndgameSolver<R: Reduction> {
+    reduction: R,
+    memo: HashMap<R::Reduced, i32>,
+    /// positions that ran out of a budget (not worth retrying with the same
+    /// budget or a smaller one)
+    too_big: HashMap<R::Reduced, u64>,
+    nodes: u64,
+    budget: u64,
+}
+
+impl<R: Reduction> EndgameSolver<R> {
+    pub fn new() -> Self
+    where
+        R: Default,
+    {
+        Self::with_reduction(R::default())
+    }
+
+    pub fn with_reduction(reduction: R) -> Self {
+        EndgameSolver {
+            reduction,
+            memo: HashMap::new(),
+            too_big: HashMap::new(),
+            nodes: 0,
+            budget: 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.memo.clear();
+        self.too_big.clear();
+    }
+
+    /// Exact value of `board` for `side`, if the position decomposes and the
+    /// solve fits the limits
+    pub fn value(&mut self, board: R::Board, side: u8, limits: Limits) -> Option<i32> {
+        let reduced = self.reduction.reduce(board, side, limits.max_shared_cells)?;
+        if !self.start(&reduced, limits.budget) {
+            return None;
+        }
+        let value = self.solve(&reduced);
+        self.finish(reduced, value.is_some());
+        value
+    }
+
+    /// Every move of `board` with its proven value, or `None` as in `value`
+    pub fn solve_root(&mut self, board: R::Board, side: u8, limits: Limits) -> Option<Solution<R::Move>> {
+        let reduced = self.reduction.reduce(board, side, limits.max_shared_cells)?;
+        let legal = self.reduction.moves(board, side);
+        if legal.is_empty() || !self.start(&reduced, limits.budget) {
+            return None;
+        }
+        let moves: Option<Vec<(R::Move, i32)>> = legal
+            .into_iter()
+            .map(|mv| {
+                // a move never grows the shared part
+                let child = self.reduction.reduce(self.reduction.apply(board, mv, side), 1 - side, u32::MAX)?;
+                Some((mv, parent_value(self.solve(&child)?)))
+            })
+            .collect();
+        self.finish(reduced, moves.is_some());
+        let mut moves = moves?;
+        // stable: equal moves keep the generator's order
+        moves.sort_by_key(|&(_, v)| std::cmp::Reverse(v));
+        Some(Solution { score: moves[0].1, moves })
+    }
+
+    /// Solves the root and picks the move to play, with the proven line up
+    /// to `max_pv` moves.
+    ///
+    /// With `top_n > 0` the move is random, but only among the first `top_n`
+    /// moves with the best proven value.
+    pub fn play_root(
+        &mut self,
+        board: R::Board,
+        side: u8,
+        limits: Limits,
+        top_n: u32,
+        rng: &mut Rng,
+        max_pv: usize,
+    ) -> Option<SolvedRoot<R::Move>> {
+        let solution = self.solve_root(board, side, limits)?;
+        let mut best_move = solution.best_move();
+        if let Some(idx) = pick_root(solution.moves.iter().map(|&(_, v)| v), top_n, 0, rng) {
+            best_move = Some(solution.moves[idx].0);
+        }
+        let mut pv: Vec<R::Move> = best_move.into_iter().collect();
+        self.extend_line(board, side, &mut pv, limits, max_pv);
+        Some(SolvedRoot {
+            best_move,
+            pv,
+            // the proven line goes to the end of the game
+            plies: (R::MATE - solution.score.abs()) as u32,
+            solution,
+        })
+    }
+
+    /// Completes `line`, played from `board`, with the solver's best moves
+    /// up to `max_len` moves or until a position is out of the limits
+    pub fn extend_line(&mut self, board: R::Board, side: u8, line: &mut Vec<R::Move>, limits: Limits, max_len: usize) {
+        let (mut board, mut side) = (board, side);
+        for &mv in line.iter() {
+            board = self.reduction.apply(board, mv, side);
+            side = 1 - side;
+        }
+        while line.len() < max_len {
+            let Some(mv) = self.solve_root(board, side, limits).and_then(|s| s.best_move()) else {
+                break;
+            };
+            line.push(mv);
+            board = self.reduction.apply(board, mv, side);
+            side = 1 - side;
+        }
+    }
+
+    /// Prepares a solve; `false` if it is already known not to fit the budget
+    fn start(&mut self, pos: &R::Reduced, budget: u64) -> bool {
+        if self.too_big.get(pos).is_some_and(|&failed| failed >= budget) {
+            return false;
+        }
+        if self.memo.len() + self.too_big.len() > MAX_MEMO {
+            self.clear();
+        }
+        self.nodes = 0;
+        self.budget = budget;
+        true
+    }
+
+    fn finish(&mut self, pos: R::Reduced, solved: bool) {
+        if !solved {
+            self.too_big.insert(pos, self.budget);
+        }
+    }
+
+    fn solve(&mut self, pos: &R::Reduced) -> Option<i32> {
+        if let Some(v) = self.reduction.settled(pos) {
+            return Some(v);
+        }
+        if let Some(&v) = self.memo.get(pos) {
+            return Some(v);
+        }
+        self.nodes += 1;
+        if self.nodes > self.budget {
+            return None;
+        }
+
+        let mut best = -R::MATE;
+        for child in self.reduction.children(pos) {
+            best = best.max(parent_value(self.solve(&child)?));
+        }
+
+        self.memo.insert(pos.clone(), best);
+        Some(best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MATE: i32 = 1000;
+    const EXACT: Limits = Limits { max_shared_cells: 64, budget: 1_000_000 };
+
+    /// Two piles; a move takes 1 or 2 stones from one pile and whoever
+    /// cannot move loses. Piles of one side's colour only shrink by that
+    /// side's moves, so once the shared pile is empty the game is a race.
+    #[derive(Default)]
+    struct Piles;
+
+    /// (shared pile, each side's own pile)
+    type Board = (u32, [u32; 2]);
+
+    impl Reduction for Piles {
+        const MATE: i32 = MATE;
+        type Board = Board;
+        type Move = (bool, u32);
+        type Reduced = (Board, u8);
+
+        fn reduce(&self, board: Board, side: u8, max_shared_cells: u32) -> Option<(Board, u8)> {
+            (board.0 <= max_shared_cells).then_some((board, side))
+        }
+
+        fn moves(&self, (shared, own): Board, side: u8) -> Vec<(bool, u32)> {
+            let own = own[side as usize];
+            (1..=2)
+                .filter(|&n| n <= shared)
+                .map(|n| (true, n))
+                .chain((1..=2).filter(|&n| n <= own).map(|n| (false, n)))
+                .collect()
+        }
+
+        fn apply(&self, (mut shared, mut own): Board, (from_shared, n): (bool, u32), side: u8) -> Board {
+            if from_shared {
+                shared -= n;
+            } else {
+                own[side as usize] -= n;
+            }
+            (shared, own)
+        }
+
+        fn settled(&self, &((shared, own), side): &(Board, u8)) -> Option<i32> {
+            // no shared pile: each side takes one stone at a time
+            let (a, b) = (own[side as usize] as i32, own[1 - side as usize] as i32);
+            (shared == 0).then_some(if a > b { MATE - (2 * b + 1) } else { -(MATE - 2 * a) })
+        }
+
+        fn children(&self, &(board, side): &(Board, u8)) -> Vec<(Board, u8)> {
+            self.moves(board, side)
+                .into_iter()
+                .map(|mv| (self.apply(board, mv, side), 1 - side))
+                .collect()
+        }
+    }
+
+    fn brute(board: Board, side: u8) -> i32 {
+        Piles
+            .moves(board, side)
+            .into_iter()
+            .map(|mv| parent_value(brute(Piles.apply(board, mv, side), 1 - side)))
+            .max()
+            .unwrap_or(-MATE)
+    }
+
+    #[test]
+    fn agrees_with_brute_force_and_plays_the_proven_line() {
+        let mut solver = EndgameSolver::<Piles>::new();
+        let mut rng = Rng::new(1);
+        for shared in 0..7 {
+            for own in [[0, 0], [1, 0], [0, 3], [2, 2], [4, 1]] {
+                for side in [0, 1] {
+                    let board = (shared, own);
+                    let exact = brute(board, side);
+                    assert_eq!(solver.value(board, side, EXACT), Some(exact));
+                    let Some(root) = solver.play_root(board, side, EXACT, 3, &mut rng, 64) else {
+                        assert!(Piles.moves(board, side).is_empty());
+                        continue;
+                    };
+                    assert_eq!(root.solution.score, exact);
+                    for &(mv, v) in &root.solution.moves {
+                        assert_eq!(v, parent_value(brute(Piles.apply(board, mv, side), 1 - side)));
+                    }
+                    // the line keeps the proven value and ends the game
+                    assert_eq!(root.pv.first().copied(), root.best_move);
+                    assert_eq!(root.pv.len() as u32, root.plies);
+                    let (mut board, mut side) = (board, side);
+                    for &mv in &root.pv {
+                        assert!(Piles.moves(board, side).contains(&mv));
+                        board = Piles.apply(board, mv, side);
+                        side = 1 - side;
+                    }
+                    assert!(Piles.moves(board, side).is_empty());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn limits_leave_positions_to_the_search() {
+        let mut solver = EndgameSolver::<Piles>::new();
+        let board = (12, [1, 1]);
+        assert_eq!(solver.value(board, 0, Limits { max_shared_cells: 10, ..EXACT }), None);
+        assert_eq!(solver.value(board, 0, Limits { budget: 5, ..EXACT }), None);
+        assert_eq!(solver.value(board, 0, EXACT), Some(brute(board, 0)));
+    }
+}
@@ -0,0 +1,75 @@
+//! What a game has to provide to be searched
+
+use crate::tt::{PackedMove, PackedScore};
+
+/// A two-player, perfect-information game seen from the side to move
+///
+/// Scores are always from the point of view of the side to move. Proven
+/// results are `±(MATE - plies)` with [`MATE`](Game::MATE) above
+/// [`MATE_BOUND`](Game::MATE_BOUND), so that the search prefers quick wins
+/// and slow losses and the transposition table can re-anchor them.
+pub trait Game {
+    /// Board plus side to move, and any incrementally updated hash state
+    type Position: Copy;
+    type Move: Copy + Eq;
+    /// Score as stored in the transposition table
+    type TableScore: PackedScore;
+    /// Move as stored in the transposition table
+    type TableMove: PackedMove;
+
+    /// Score of a win with no plies left
+    const MATE: i32;
+    /// Scores at least this large (in magnitude) are proven results
+    const MATE_BOUND: i32;
+
+    /// Whether horizon nodes probe the transposition table; games with a
+    /// cheap evaluation are faster without the memory access
+    const PROBE_LEAVES: bool = true;
+
+    /// Moves to search, in any order (may be a pruned candidate set)
+    fn moves(&mut self, pos: &Self::Position) -> Vec<Self::Move>;
+
+    /// Whether the side to move has a move at all
+    fn has_moves(&mut self, pos: &Self::Position) -> bool {
+        !self.moves(pos).is_empty()
+    }
+
+    /// Whether `mv` is a legal move here (including moves that
+    /// [`moves`](Game::moves) may have pruned)
+    fn is_legal(&mut self, pos: &Self::Position, mv: Self::Move) -> bool {
+        self.moves(pos).contains(&mv)
+    }
+
+    fn apply(&self, pos: &Self::Position, mv: Self::Move) -> Self::Position;
+
+    /// Score of a position where the side to move has no moves, `ply`
+    /// plies from the root (a loss in most games, but not necessarily)
+    fn stuck(&self, pos: &Self::Position, ply: u32) -> i32;
+
+    /// Static score at the horizon; `key` is [`key`](Game::key) of the
+    /// position, for games that key evaluation noise or caches on it
+    fn evaluate(&mut self, pos: &Self::Position, key: u64, ply: u32) -> i32;
+
+    /// Transposition table key
+    fn key(&self, pos: &Self::Position) -> u64;
+
+    /// Move as stored under [`key`](Game::key), e.g. mapped into the frame
+    /// of a symmetry-reduced key
+    fn pack_move(&self, pos: &Self::Position, mv: Self::Move) -> Self::TableMove;
+
+    /// Inverse of [`pack_move`](Game::pack_move); stored moves may come from
+    /// another position with the same key and are checked against the moves
+    /// of this one before use
+    fn unpack_move(&self, pos: &Self::Position, mv: Self::TableMove) -> Self::Move;
+
+    /// Ordering heuristic, higher first; TT moves, killers and history are
+    /// added by the search. `depth` is the remaining depth.
+    fn order_score(&mut self, _pos: &Self::Position, _mv: Self::Move, _depth: u32) -> i32 {
+        0
+    }
+
+    /// Slot of the move in the history table, `None` if the game keeps none
+    fn history_slot(&self, _pos: &Self::Position, _mv: Self::Move) -> Option<usize> {
+        None
+    }
+}
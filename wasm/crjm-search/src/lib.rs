@@ -0,0 +1,34 @@
+//! Game-agnostic search shared by the CRJM engines
+//!
+//! A game implements [`Game`] (moves, apply, hash, evaluation, terminal
+//! score) and gets:
+//! - Iterative deepening with time and node limits on a caller's clock
+//! - Negamax with alpha-beta and principal variation search (PVS)
+//! - Aspiration windows widened only on the side that failed
+//! - A bucketed transposition table with mate-distance re-anchoring
+//! - Move ordering by TT move, killers, history and a game heuristic
+//! - MultiPV: exact scores for the best `k` root moves
+//! - Randomized root choice among the best moves for lower difficulties
+//! - Progress reports after every complete iteration
+//! - Checksummed binary snapshots of the engines' state
+//! - A seedable RNG and the Zobrist key stream every engine draws from
+//! - Bradley-Terry ratings for calibrating skill levels from self-play
+//! - An exact endgame solver over a game's reduction of its endgames
+//!
+//! Engine policies on top of the search (skill levels, mistakes) and the
+//! games' endgame reductions stay in the engines.
+
+pub mod choice;
+pub mod endgame;
+pub mod game;
+pub mod rating;
+pub mod rng;
+pub mod search;
+pub mod snapshot;
+pub mod tt;
+pub mod zobrist;
+
+pub use choice::pick_root;
+pub use game::Game;
+pub use search::{Aspiration, Limits, Outcome, Progress, ProgressFn, SearchStats, Searcher, INF};
+pub use tt::{Bound, TranspositionTable};
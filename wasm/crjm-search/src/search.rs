@@ -0,0 +1,738 @@
+//! Iterative deepening negamax with alpha-beta, PVS and aspiration windows
+
+use std::cmp::Reverse;
+
+use crate::game::Game;
+use crate::tt::{score_from_tt, score_to_tt, Bound, TranspositionTable};
+
+/// Larger than any score a game may return
+pub const INF: i32 = 1_000_000;
+
+/// Longest principal variation returned
+const MAX_PV: usize = 64;
+
+/// The TT move is searched first, whatever the other heuristics say
+const TT_MOVE_PRIORITY: i64 = i64::MAX;
+/// Ordering bonuses on top of the game's own heuristic
+const KILLER_BONUS: [i64; 2] = [600_000, 450_000];
+
+/// When to stop searching
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub max_depth: u32,
+    /// Time on the search clock (milliseconds) at which the search stops
+    pub deadline: f64,
+    /// Nodes after which the search stops, once the first iteration is
+    /// complete (so there is always a searched move)
+    pub node_budget: u64,
+}
+
+impl Limits {
+    pub fn new(deadline: f64, max_depth: u32) -> Self {
+        Limits {
+            max_depth,
+            deadline,
+            node_budget: u64::MAX,
+        }
+    }
+}
+
+/// Aspiration window around the previous iteration's score
+///
+/// The window starts at `initial`, doubles (up to `max`) after an iteration
+/// that had to be re-searched and shrinks by a quarter (down to `min`)
+/// otherwise. Only the side that failed is widened on a re-search.
+#[derive(Debug, Clone, Copy)]
+pub struct Aspiration {
+    pub initial: i32,
+    pub min: i32,
+    pub max: i32,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchStats {
+    pub nodes: u64,
+    pub tt_hits: u64,
+    pub tt_probes: u64,
+}
+
+/// Result of [`Searcher::iterative_deepening`]
+#[derive(Debug, Clone)]
+pub struct Outcome<M> {
+    /// `None` only if the side to move has no moves
+    pub best_move: Option<M>,
+    /// Score of the deepest complete iteration (0 if none completed)
+    pub score: i32,
+    /// Principal variation, starting with `best_move`
+    pub pv: Vec<M>,
+    /// Deepest complete iteration
+    pub depth: u32,
+    /// Root moves of the deepest complete iteration with their scores, best
+    /// first; only the first `multipv` scores are exact, the others bounds
+    pub root: Vec<(M, i32)>,
+    pub stats: SearchStats,
+}
+
+/// A complete iteration, as passed to [`Searcher::with_progress`]
+#[derive(Debug)]
+pub struct Progress<'p, M> {
+    pub depth: u32,
+    pub score: i32,
+    /// Principal variation, starting with the best move
+    pub pv: &'p [M],
+    /// Root moves with their scores, best first (as in [`Outcome::root`])
+    pub root: &'p [(M, i32)],
+    pub stats: SearchStats,
+}
+
+/// Receiver of [`Progress`] reports
+pub type ProgressFn<'a, M> = dyn FnMut(&Progress<M>) + 'a;
+
+/// One root iteration searched to the end
+struct RootIteration<M> {
+    best_move: M,
+    score: i32,
+    /// Scores of the searched moves (bounds, except for the best)
+    scores: Vec<(M, i32)>,
+    pv: Vec<M>,
+}
+
+/// Searches positions of `G`; a searcher is meant for a single search
+pub struct Searcher<'a, G: Game> {
+    game: G,
+    tt: &'a mut TranspositionTable<G::TableScore, G::TableMove>,
+    /// Cutoff counts by [`Game::history_slot`], kept by the caller across searches
+    history: Option<&'a mut [i32]>,
+    limits: Limits,
+    multipv: usize,
+    aspiration: Option<Aspiration>,
+    stats: SearchStats,
+    /// Only applies once the first iteration is complete
+    node_limit: u64,
+    aborted: bool,
+    /// Two killer moves per ply
+    killers: Vec<[Option<G::Move>; 2]>,
+    /// Principal variations by ply (triangular table)
+    pv_table: Vec<Vec<G::Move>>,
+    progress: Option<&'a mut ProgressFn<'a, G::Move>>,
+}
+
+impl<'a, G: Game> Searcher<'a, G> {
+    pub fn new(game: G, tt: &'a mut TranspositionTable<G::TableScore, G::TableMove>, limits: Limits) -> Self {
+        Searcher {
+            game,
+            tt,
+            history: None,
+            limits,
+            multipv: 1,
+            aspiration: None,
+            stats: SearchStats::default(),
+            node_limit: u64::MAX,
+            aborted: false,
+            killers: Vec::new(),
+            pv_table: Vec::new(),
+            progress: None,
+        }
+    }
+
+    /// Order moves by (and update) a history table indexed by [`Game::history_slot`]
+    pub fn with_history(mut self, history: &'a mut [i32]) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Give the best `multipv` root moves exact scores; the others are only
+    /// proven to be no better than the `multipv`-th best. Disables aspiration.
+    pub fn with_multipv(mut self, multipv: usize) -> Self {
+        self.multipv = multipv.max(1);
+        self
+    }
+
+    pub fn with_aspiration(mut self, aspiration: Aspiration) -> Self {
+        self.aspiration = Some(aspiration);
+        self
+    }
+
+    /// Called after every complete iteration (for live analysis output)
+    pub fn with_progress(mut self, progress: &'a mut ProgressFn<'a, G::Move>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn game(&self) -> &G {
+        &self.game
+    }
+
+    pub fn game_mut(&mut self) -> &mut G {
+        &mut self.game
+    }
+
+    pub fn limits_mut(&mut self) -> &mut Limits {
+        &mut self.limits
+    }
+
+    pub fn stats(&self) -> SearchStats {
+        self.stats
+    }
+
+    /// Time is up or the node budget is spent
+    fn out_of_budget(&mut self, now: &impl Fn() -> f64) -> bool {
+        if self.stats.nodes >= self.node_limit || now() >= self.limits.deadline {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+
+    /// Same as [`out_of_budget`](Self::out_of_budget), reading the clock
+    /// only every 1024 nodes
+    #[inline]
+    fn check_abort(&mut self, now: &impl Fn() -> f64) -> bool {
+        if self.stats.nodes >= self.node_limit || (self.stats.nodes & 1023 == 0 && now() >= self.limits.deadline) {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+
+    /// Searches `root` one depth at a time until a limit is hit, a win is
+    /// proven or `max_depth` is reached, keeping the result of the deepest
+    /// complete iteration; `now` is the clock the deadline refers to
+    pub fn iterative_deepening(&mut self, root: G::Position, now: impl Fn() -> f64) -> Outcome<G::Move> {
+        let mut root_moves = self.game.moves(&root);
+        if root_moves.is_empty() {
+            return Outcome {
+                best_move: None,
+                score: self.game.stuck(&root, 0),
+                pv: Vec::new(),
+                depth: 0,
+                root: Vec::new(),
+                stats: self.stats,
+            };
+        }
+        let tt_move = self
+            .tt
+            .probe(self.game.key(&root))
+            .and_then(|e| e.best_move)
+            .map(|mv| self.game.unpack_move(&root, mv));
+        self.order_moves(&root, &mut root_moves, tt_move, 1, 0);
+
+        let mut best: Option<RootIteration<G::Move>> = None;
+        let mut depth_reached = 0;
+        let mut window = self.aspiration.map_or(0, |a| a.initial);
+
+        for depth in 1..=self.limits.max_depth {
+            if self.out_of_budget(&now) {
+                break;
+            }
+
+            let aspirate = self.aspiration.filter(|_| depth > 1 && self.multipv == 1);
+            let mut delta = window;
+            let (mut alpha, mut beta) = match (aspirate, &best) {
+                (Some(_), Some(prev)) => ((prev.score - delta).max(-INF), (prev.score + delta).min(INF)),
+                _ => (-INF, INF),
+            };
+            let mut failed = false;
+            let iteration = loop {
+                let Some(it) = self.search_root(&root, &root_moves, depth, alpha, beta, &now) else {
+                    break None;
+                };
+                if it.score <= alpha && alpha > -INF {
+                    alpha = (alpha - delta).max(-INF);
+                } else if it.score >= beta && beta < INF {
+                    beta = (beta + delta).min(INF);
+                } else {
+                    break Some(it);
+                }
+                delta = delta.saturating_mul(2);
+                failed = true;
+            };
+            // An interrupted iteration is discarded: the previous one stands
+            let Some(mut it) = iteration else {
+                break;
+            };
+            if let Some(a) = aspirate {
+                window = if failed { (window * 2).min(a.max) } else { (window * 3 / 4).max(a.min) };
+            }
+
+            depth_reached = depth;
+            self.node_limit = self.limits.node_budget;
+            it.scores.sort_by_key(|&(_, s)| Reverse(s));
+            root_moves = it.scores.iter().map(|&(mv, _)| mv).collect();
+            self.extend_pv_from_tt(&root, &mut it.pv);
+            if let Some(progress) = self.progress.as_mut() {
+                progress(&Progress {
+                    depth,
+                    score: it.score,
+                    pv: &it.pv,
+                    root: &it.scores,
+                    stats: self.stats,
+                });
+            }
+            // A win within the searched depth cannot get shorter (a longer
+            // one may come from the table or exact leaves); with MultiPV the
+            // other moves still need exact scores
+            let won = it.score >= G::MATE_BOUND && G::MATE - it.score <= depth as i32 && self.multipv == 1;
+            best = Some(it);
+            if won {
+                break;
+            }
+        }
+
+        let (best_move, score, pv, root_scores) = match best {
+            Some(it) => (it.best_move, it.score, it.pv, it.scores),
+            // Not even the first iteration finished: trust the ordering
+            None => (root_moves[0], 0, vec![root_moves[0]], Vec::new()),
+        };
+        Outcome {
+            best_move: Some(best_move),
+            score,
+            pv,
+            depth: depth_reached,
+            root: root_scores,
+            stats: self.stats,
+        }
+    }
+
+    /// Searches every root move with the window `(alpha, beta)` (PVS)
+    ///
+    /// `None` if the search was interrupted: scores of an incomplete
+    /// iteration cannot be trusted. If the best score falls outside the
+    /// window it is only a bound and the caller has to search again.
+    fn search_root(
+        &mut self,
+        root: &G::Position,
+        moves: &[G::Move],
+        depth: u32,
+        alpha: i32,
+        beta: i32,
+        now: &impl Fn() -> f64,
+    ) -> Option<RootIteration<G::Move>> {
+        let mut alpha = alpha;
+        let mut best: Option<RootIteration<G::Move>> = None;
+        let mut scores = Vec::with_capacity(moves.len());
+
+        for (i, &mv) in moves.iter().enumerate() {
+            if self.out_of_budget(now) {
+                return None;
+            }
+            let child = self.game.apply(root, mv);
+            let bound = if self.multipv > 1 { kth_best(&scores, self.multipv) } else { alpha };
+            let score = if i == 0 {
+                -self.negamax(&child, depth - 1, -beta, -bound, 1, now)
+            } else {
+                let narrow = -self.negamax(&child, depth - 1, -bound - 1, -bound, 1, now);
+                if narrow > bound && narrow < beta && !self.aborted {
+                    -self.negamax(&child, depth - 1, -beta, -bound, 1, now)
+                } else {
+                    narrow
+                }
+            };
+            if self.aborted {
+                return None;
+            }
+            scores.push((mv, score));
+
+            if best.as_ref().is_none_or(|b| score > b.score) {
+                let mut pv = vec![mv];
+                pv.extend_from_slice(self.pv_table.get(1).map_or(&[][..], |p| p.as_slice()));
+                best = Some(RootIteration {
+                    best_move: mv,
+                    score,
+                    scores: Vec::new(),
+                    pv,
+                });
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        let mut best = best?;
+        best.scores = scores;
+        Some(best)
+    }
+
+    fn negamax(
+        &mut self,
+        pos: &G::Position,
+        depth: u32,
+        mut alpha: i32,
+        beta: i32,
+        ply: u32,
+        now: &impl Fn() -> f64,
+    ) -> i32 {
+        self.stats.nodes += 1;
+        let p = ply as usize;
+        if self.pv_table.len() <= p + 1 {
+            self.pv_table.resize(p + 2, Vec::new());
+        }
+        self.pv_table[p].clear();
+        if self.check_abort(now) {
+            return 0;
+        }
+
+        let key = self.game.key(pos);
+        if depth == 0 && !G::PROBE_LEAVES {
+            if !self.game.has_moves(pos) {
+                return self.game.stuck(pos, ply);
+            }
+            return self.game.evaluate(pos, key, ply);
+        }
+        self.stats.tt_probes += 1;
+        let mut tt_move = None;
+        if let Some(entry) = self.tt.probe(key) {
+            self.stats.tt_hits += 1;
+            tt_move = entry.best_move.map(|mv| self.game.unpack_move(pos, mv));
+            if entry.depth as u32 >= depth {
+                let score = score_from_tt(entry.score, ply, G::MATE_BOUND);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+        }
+
+        if depth == 0 {
+            if !self.game.has_moves(pos) {
+                return self.game.stuck(pos, ply);
+            }
+            return self.game.evaluate(pos, key, ply);
+        }
+
+        let mut moves = self.game.moves(pos);
+        if moves.is_empty() {
+            return self.game.stuck(pos, ply);
+        }
+        self.order_moves(pos, &mut moves, tt_move, depth, p);
+
+        let alpha_orig = alpha;
+        let mut best_score = -INF;
+        let mut best_move = moves[0];
+
+        for (i, &mv) in moves.iter().enumerate() {
+            let child = self.game.apply(pos, mv);
+            let score = if i == 0 {
+                -self.negamax(&child, depth - 1, -beta, -alpha, ply + 1, now)
+            } else {
+                let narrow = -self.negamax(&child, depth - 1, -alpha - 1, -alpha, ply + 1, now);
+                if narrow > alpha && narrow < beta && !self.aborted {
+                    -self.negamax(&child, depth - 1, -beta, -alpha, ply + 1, now)
+                } else {
+                    narrow
+                }
+            };
+            if self.aborted {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = mv;
+            }
+            if score > alpha {
+                alpha = score;
+                let (head, tail) = self.pv_table.split_at_mut(p + 1);
+                head[p].clear();
+                head[p].push(mv);
+                head[p].extend_from_slice(&tail[0]);
+            }
+            if alpha >= beta {
+                self.record_cutoff(pos, mv, depth, p);
+                break;
+            }
+        }
+
+        let bound = if best_score <= alpha_orig {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        let packed = self.game.pack_move(pos, best_move);
+        self.tt.store(
+            key,
+            Some(packed),
+            depth.min(u8::MAX as u32) as u8,
+            bound,
+            score_to_tt(best_score, ply, G::MATE_BOUND),
+        );
+
+        best_score
+    }
+
+    /// Killers and history after a beta cutoff
+    fn record_cutoff(&mut self, pos: &G::Position, mv: G::Move, depth: u32, ply: usize) {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None, None]);
+        }
+        let k = &mut self.killers[ply];
+        if k[0] != Some(mv) {
+            *k = [Some(mv), k[0]];
+        }
+        let slot = self.game.history_slot(pos, mv);
+        if let (Some(history), Some(slot)) = (self.history.as_deref_mut(), slot) {
+            if let Some(h) = history.get_mut(slot) {
+                *h = h.saturating_add((depth * depth * 200) as i32);
+            }
+        }
+    }
+
+    /// TT move, then killers, then the game's heuristic plus history
+    fn order_moves(&mut self, pos: &G::Position, moves: &mut [G::Move], tt_move: Option<G::Move>, depth: u32, ply: usize) {
+        let killers = self.killers.get(ply).copied().unwrap_or([None, None]);
+        let mut scored: Vec<(G::Move, i64)> = Vec::with_capacity(moves.len());
+        for &mv in moves.iter() {
+            if tt_move == Some(mv) {
+                scored.push((mv, TT_MOVE_PRIORITY));
+                continue;
+            }
+            let mut priority = self.game.order_score(pos, mv, depth) as i64;
+            if killers[0] == Some(mv) {
+                priority += KILLER_BONUS[0];
+            } else if killers[1] == Some(mv) {
+                priority += KILLER_BONUS[1];
+            }
+            if let (Some(history), Some(slot)) = (self.history.as_deref(), self.game.history_slot(pos, mv)) {
+                priority += history.get(slot).copied().unwrap_or(0) as i64;
+            }
+            scored.push((mv, priority));
+        }
+        scored.sort_by_key(|&(_, priority)| Reverse(priority));
+        for (slot, (mv, _)) in moves.iter_mut().zip(scored) {
+            *slot = mv;
+        }
+    }
+
+    /// Completes `pv` with the best moves stored in the table (where the
+    /// variation was cut short by a table hit or the horizon)
+    fn extend_pv_from_tt(&mut self, root: &G::Position, pv: &mut Vec<G::Move>) {
+        let mut pos = *root;
+        for &mv in pv.iter() {
+            pos = self.game.apply(&pos, mv);
+        }
+        while pv.len() < MAX_PV {
+            let Some(mv) = self.tt.probe(self.game.key(&pos)).and_then(|e| e.best_move) else {
+                break;
+            };
+            let mv = self.game.unpack_move(&pos, mv);
+            if !self.game.is_legal(&pos, mv) {
+                break;
+            }
+            pv.push(mv);
+            pos = self.game.apply(&pos, mv);
+        }
+    }
+}
+
+/// Score of the `k`-th best move searched so far (`-INF` while fewer than `k`)
+fn kth_best<M>(scores: &[(M, i32)], k: usize) -> i32 {
+    if scores.len() < k {
+        return -INF;
+    }
+    let mut sorted: Vec<i32> = scores.iter().map(|&(_, s)| s).collect();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+    sorted[k - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const MATE: i32 = 30_000;
+
+    /// Two piles; a move takes 1 to 3 stones from one of them and the
+    /// player who cannot move loses
+    struct Piles;
+
+    impl Game for Piles {
+        type Position = (u32, u32);
+        type Move = (u8, u8);
+        type TableScore = i16;
+        type TableMove = u8;
+
+        const MATE: i32 = MATE;
+        const MATE_BOUND: i32 = MATE - 1000;
+
+        fn moves(&mut self, &(a, b): &(u32, u32)) -> Vec<(u8, u8)> {
+            let piles = [a, b];
+            (0..2u8)
+                .flat_map(|pile| (1..=3u8).map(move |take| (pile, take)))
+                .filter(|&(pile, take)| piles[pile as usize] >= take as u32)
+                .collect()
+        }
+
+        fn apply(&self, &(a, b): &(u32, u32), (pile, take): (u8, u8)) -> (u32, u32) {
+            if pile == 0 {
+                (a - take as u32, b)
+            } else {
+                (a, b - take as u32)
+            }
+        }
+
+        fn stuck(&self, _pos: &(u32, u32), ply: u32) -> i32 {
+            -MATE + ply as i32
+        }
+
+        fn evaluate(&mut self, &(a, b): &(u32, u32), _key: u64, _ply: u32) -> i32 {
+            // Deliberately poor, so that windows fail and re-searches happen
+            (a as i32 - b as i32) * 37 + (a % 4) as i32 * 50
+        }
+
+        fn key(&self, &(a, b): &(u32, u32)) -> u64 {
+            ((a as u64) << 32 | b as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        }
+
+        fn pack_move(&self, _pos: &(u32, u32), (pile, take): (u8, u8)) -> u8 {
+            pile << 2 | take
+        }
+
+        fn unpack_move(&self, _pos: &(u32, u32), mv: u8) -> (u8, u8) {
+            (mv >> 2, mv & 3)
+        }
+
+        fn order_score(&mut self, _pos: &(u32, u32), (_, take): (u8, u8), _depth: u32) -> i32 {
+            take as i32
+        }
+
+        fn history_slot(&self, pos: &(u32, u32), mv: (u8, u8)) -> Option<usize> {
+            Some(self.pack_move(pos, mv) as usize)
+        }
+    }
+
+    /// Plain minimax with distances
+    fn exact(pos: (u32, u32), memo: &mut HashMap<(u32, u32), i32>) -> i32 {
+        if let Some(&v) = memo.get(&pos) {
+            return v;
+        }
+        let moves = Piles.moves(&pos);
+        let v = if moves.is_empty() {
+            -MATE
+        } else {
+            moves
+                .into_iter()
+                .map(|mv| {
+                    let child = -exact(Piles.apply(&pos, mv), memo);
+                    child - child.signum()
+                })
+                .max()
+                .unwrap()
+        };
+        memo.insert(pos, v);
+        v
+    }
+
+    fn assert_legal_line(pos: (u32, u32), line: &[(u8, u8)]) {
+        let mut pos = pos;
+        for &mv in line {
+            assert!(Piles.moves(&pos).contains(&mv), "{:?}", line);
+            pos = Piles.apply(&pos, mv);
+        }
+    }
+
+    #[test]
+    fn scores_and_moves_are_exact() {
+        let mut memo = HashMap::new();
+        let mut history = vec![0; 16];
+        for a in 0..9 {
+            for b in 0..9 {
+                let mut tt = TranspositionTable::new(1);
+                let outcome = Searcher::new(Piles, &mut tt, Limits::new(f64::MAX, 24))
+                    .with_history(&mut history)
+                    .with_aspiration(Aspiration { initial: 20, min: 10, max: 200 })
+                    .iterative_deepening((a, b), || 0.0);
+                assert_eq!(outcome.score, exact((a, b), &mut memo), "{:?}", (a, b));
+                let Some(mv) = outcome.best_move else {
+                    assert_eq!((a, b), (0, 0));
+                    continue;
+                };
+                let child = -exact(Piles.apply(&(a, b), mv), &mut memo);
+                assert_eq!(child - child.signum(), outcome.score);
+                assert_eq!(outcome.pv.first(), Some(&mv));
+                assert_legal_line((a, b), &outcome.pv);
+            }
+        }
+    }
+
+    #[test]
+    fn scores_stay_exact_with_a_table_kept_across_searches() {
+        let mut memo = HashMap::new();
+        let mut tt = TranspositionTable::new(1);
+        // Larger positions first, so the table holds long wins of their
+        // children when those are searched
+        for a in (0..9).rev() {
+            for b in (0..9).rev() {
+                let outcome = Searcher::new(Piles, &mut tt, Limits::new(f64::MAX, 24)).iterative_deepening((a, b), || 0.0);
+                assert_eq!(outcome.score, exact((a, b), &mut memo), "{:?}", (a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn multipv_scores_are_exact() {
+        let mut memo = HashMap::new();
+        let mut tt = TranspositionTable::new(1);
+        let outcome = Searcher::new(Piles, &mut tt, Limits::new(f64::MAX, 24))
+            .with_multipv(3)
+            .iterative_deepening((6, 5), || 0.0);
+        assert_eq!(outcome.root.len(), 6);
+        for &(mv, score) in &outcome.root[..3] {
+            let child = -exact(Piles.apply(&(6, 5), mv), &mut memo);
+            assert_eq!(child - child.signum(), score);
+        }
+        assert!(outcome.root.windows(2).all(|w| w[0].1 >= w[1].1));
+    }
+
+    #[test]
+    fn interrupted_iterations_are_discarded() {
+        let search = |limits: Limits| {
+            let mut tt = TranspositionTable::new(1);
+            // Fake clock: every reading advances it by 1 ms
+            let clock = std::cell::Cell::new(0.0);
+            let now = || {
+                clock.set(clock.get() + 1.0);
+                clock.get()
+            };
+            Searcher::new(Piles, &mut tt, limits)
+                .with_aspiration(Aspiration { initial: 20, min: 10, max: 200 })
+                .iterative_deepening((300, 290), now)
+        };
+        for deadline in [50.0, 200.0, 600.0] {
+            let cut = search(Limits::new(deadline, 64));
+            assert!(cut.depth >= 1 && cut.depth < 64);
+            // Same result as a search that stopped after that iteration
+            let clean = search(Limits::new(f64::MAX, cut.depth));
+            assert_eq!((cut.best_move, cut.score, &cut.pv), (clean.best_move, clean.score, &clean.pv));
+        }
+    }
+
+    #[test]
+    fn progress_reports_every_complete_iteration() {
+        let mut tt = TranspositionTable::new(1);
+        let mut seen = Vec::new();
+        let mut progress = |p: &Progress<(u8, u8)>| seen.push((p.depth, p.score, p.pv.to_vec(), p.stats.nodes));
+        let outcome = Searcher::new(Piles, &mut tt, Limits::new(f64::MAX, 6))
+            .with_progress(&mut progress)
+            .iterative_deepening((7, 4), || 0.0);
+        let depths: Vec<u32> = seen.iter().map(|s| s.0).collect();
+        assert_eq!(depths, (1..=outcome.depth).collect::<Vec<_>>());
+        let last = seen.last().unwrap();
+        assert_eq!((last.1, &last.2), (outcome.score, &outcome.pv));
+        assert!(seen.windows(2).all(|w| w[0].3 <= w[1].3));
+    }
+
+    #[test]
+    fn node_budget_applies_after_the_first_iteration() {
+        let mut tt = TranspositionTable::new(1);
+        let limits = Limits {
+            node_budget: 500,
+            ..Limits::new(f64::MAX, 64)
+        };
+        let outcome = Searcher::new(Piles, &mut tt, limits).iterative_deepening((300, 290), || 0.0);
+        assert!(outcome.depth >= 1);
+        assert!(outcome.stats.nodes <= 500);
+        assert!(outcome.best_move.is_some());
+    }
+}
@@ -0,0 +1,217 @@
+//! Framing of the engines' binary snapshots
+//!
+//! Layout (little-endian):
+//! ```text
+//! magic "CRJM" | version u16 | variant u8 | cells u8
+//! body (engine-specific: Zobrist keys, history, table, ...)
+//! checksum u64 (FNV-1a of everything before it)
+//! ```
+//! An engine writes its body between [`begin`] and [`finish`] and reads it
+//! back with the [`Reader`] that [`open`] returns once the framing checks
+//! out.
+
+use std::fmt;
+
+use crate::tt::{PackedMove, PackedScore, TableError, TranspositionTable};
+
+/// Snapshot magic bytes
+pub const MAGIC: &[u8; 4] = b"CRJM";
+
+/// Current snapshot format version
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Magic, version, variant and cells
+const HEADER_LEN: usize = MAGIC.len() + 4;
+
+/// Why a snapshot was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateError {
+    /// The data ended before the snapshot was complete
+    Truncated,
+    /// The data does not start with the snapshot magic
+    BadMagic,
+    /// The snapshot was written by an unknown format version
+    UnsupportedVersion(u16),
+    /// The snapshot belongs to another game or board size
+    WrongVariant { variant: u8, cells: u8 },
+    /// The stored checksum does not match the contents
+    ChecksumMismatch,
+    /// The contents are structurally invalid
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::Truncated => write!(f, "snapshot is truncated"),
+            StateError::BadMagic => write!(f, "not an engine snapshot"),
+            StateError::UnsupportedVersion(v) => {
+                write!(f, "unsupported snapshot version {} (expected {})", v, FORMAT_VERSION)
+            }
+            StateError::WrongVariant { variant, cells } => {
+                write!(f, "snapshot is for variant {} with {} cells, not this game", variant, cells)
+            }
+            StateError::ChecksumMismatch => write!(f, "snapshot checksum mismatch (corrupted data)"),
+            StateError::Corrupt(what) => write!(f, "corrupted snapshot: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+impl From<TableError> for StateError {
+    fn from(e: TableError) -> Self {
+        match e {
+            TableError::Truncated => StateError::Truncated,
+            TableError::Corrupt(what) => StateError::Corrupt(what),
+        }
+    }
+}
+
+/// FNV-1a over a byte slice
+pub fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325u64, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Starts a snapshot of `variant` on a board of `cells` cells
+pub fn begin(variant: u8, cells: u8) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    out.push(variant);
+    out.push(cells);
+    out
+}
+
+/// Seals a snapshot started with [`begin`] with its checksum
+pub fn finish(mut out: Vec<u8>) -> Vec<u8> {
+    let sum = checksum(&out);
+    out.extend_from_slice(&sum.to_le_bytes());
+    out
+}
+
+/// Checks the framing of a snapshot of `variant`/`cells` and returns a
+/// reader over its body
+pub fn open(bytes: &[u8], variant: u8, cells: u8) -> Result<Reader<'_>, StateError> {
+    if bytes.len() < HEADER_LEN {
+        return Err(StateError::Truncated);
+    }
+    if &bytes[..MAGIC.len()] != MAGIC {
+        return Err(StateError::BadMagic);
+    }
+
+    let mut header = Reader::new(&bytes[MAGIC.len()..HEADER_LEN]);
+    let version = header.u16()?;
+    if version != FORMAT_VERSION {
+        return Err(StateError::UnsupportedVersion(version));
+    }
+    let (found_variant, found_cells) = (header.u8()?, header.u8()?);
+    if found_variant != variant || found_cells != cells {
+        return Err(StateError::WrongVariant { variant: found_variant, cells: found_cells });
+    }
+
+    if bytes.len() < HEADER_LEN + 8 {
+        return Err(StateError::Truncated);
+    }
+    let (body, sum) = bytes.split_at(bytes.len() - 8);
+    if checksum(body) != u64::from_le_bytes(sum.try_into().unwrap()) {
+        return Err(StateError::ChecksumMismatch);
+    }
+    Ok(Reader::new(&body[HEADER_LEN..]))
+}
+
+/// Cursor over snapshot bytes that reports truncation as an error
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes }
+    }
+
+    /// Bytes left to read
+    pub fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn take(&mut self, n: usize) -> Result<&'a [u8], StateError> {
+        if self.bytes.len() < n {
+            return Err(StateError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, StateError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, StateError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, StateError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn i16(&mut self) -> Result<i16, StateError> {
+        Ok(i16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    pub fn i32(&mut self) -> Result<i32, StateError> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, StateError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// A transposition table written with [`TranspositionTable::write_to`]
+    pub fn table<S: PackedScore, M: PackedMove>(&mut self) -> Result<TranspositionTable<S, M>, StateError> {
+        Ok(TranspositionTable::read_from(&mut self.bytes)?)
+    }
+
+    /// Fails unless the whole body was read
+    pub fn end(&self) -> Result<(), StateError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(StateError::Corrupt("trailing data"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn framing_round_trips_and_rejects_damage() {
+        let mut out = begin(7, 36);
+        out.extend_from_slice(&0xBEEFu16.to_le_bytes());
+        out.extend_from_slice(&(-5i32).to_le_bytes());
+        let bytes = finish(out);
+
+        let mut r = open(&bytes, 7, 36).unwrap();
+        assert_eq!((r.u16(), r.i32()), (Ok(0xBEEF), Ok(-5)));
+        assert_eq!(r.end(), Ok(()));
+        assert_eq!(r.u8(), Err(StateError::Truncated));
+
+        assert_eq!(open(&bytes, 7, 64).err(), Some(StateError::WrongVariant { variant: 7, cells: 36 }));
+        assert_eq!(open(&bytes[..bytes.len() - 1], 7, 36).err(), Some(StateError::ChecksumMismatch));
+        assert_eq!(open(&bytes[..HEADER_LEN + 3], 7, 36).err(), Some(StateError::Truncated));
+        assert_eq!(open(b"JSON{}{}{}{}", 7, 36).err(), Some(StateError::BadMagic));
+    }
+
+    #[test]
+    fn huge_bucket_counts_are_rejected_without_allocating() {
+        // generation 1, u32::MAX buckets and nothing behind them
+        let body = [1, 0xFF, 0xFF, 0xFF, 0xFF];
+        let table = Reader::new(&body).table::<i16, u8>();
+        assert_eq!(table.err(), Some(StateError::Truncated));
+    }
+}
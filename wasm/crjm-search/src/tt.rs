@@ -0,0 +1,562 @@
+//! Transposition table with 4-entry buckets
+//!
+//! Scores and moves are stored in the narrowest type a game needs (see
+//! [`PackedScore`] and [`PackedMove`]), so the bucket size, and with it the
+//! snapshot layout, depends on the game: 36 bytes for `i16` scores and `u8`
+//! moves, 48 bytes for `i32` scores and `u16` moves or `i16` scores and
+//! `u32` moves.
+
+/// Kind of bound a stored score is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Bound {
+    /// Exact score
+    Exact = 0,
+    /// Lower bound (score >= stored value)
+    Lower = 1,
+    /// Upper bound (score <= stored value)
+    Upper = 2,
+}
+
+/// Score representation inside a bucket
+pub trait PackedScore: Copy + Default {
+    const BYTES: usize;
+    /// Saturates scores that do not fit
+    fn pack(score: i32) -> Self;
+    fn unpack(self) -> i32;
+    fn write(self, out: &mut Vec<u8>);
+    fn read(bytes: &[u8]) -> Self;
+}
+
+/// Move representation inside a bucket
+pub trait PackedMove: Copy + Default + Eq {
+    const BYTES: usize;
+    /// Marks an entry without a best move
+    const NONE: Self;
+    fn write(self, out: &mut Vec<u8>);
+    fn read(bytes: &[u8]) -> Self;
+}
+
+impl PackedScore for i16 {
+    const BYTES: usize = 2;
+    fn pack(score: i32) -> Self {
+        score.clamp(i16::MIN as i32, i16::MAX as i32) as i16
+    }
+    fn unpack(self) -> i32 {
+        self as i32
+    }
+    fn write(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+    fn read(bytes: &[u8]) -> Self {
+        i16::from_le_bytes([bytes[0], bytes[1]])
+    }
+}
+
+impl PackedScore for i32 {
+    const BYTES: usize = 4;
+    fn pack(score: i32) -> Self {
+        score
+    }
+    fn unpack(self) -> i32 {
+        self
+    }
+    fn write(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+    fn read(bytes: &[u8]) -> Self {
+        i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+}
+
+impl PackedMove for u8 {
+    const BYTES: usize = 1;
+    const NONE: Self = u8::MAX;
+    fn write(self, out: &mut Vec<u8>) {
+        out.push(self);
+    }
+    fn read(bytes: &[u8]) -> Self {
+        bytes[0]
+    }
+}
+
+impl PackedMove for u16 {
+    const BYTES: usize = 2;
+    const NONE: Self = u16::MAX;
+    fn write(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+    fn read(bytes: &[u8]) -> Self {
+        u16::from_le_bytes([bytes[0], bytes[1]])
+    }
+}
+
+impl PackedMove for u32 {
+    const BYTES: usize = 4;
+    const NONE: Self = u32::MAX;
+    fn write(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+    fn read(bytes: &[u8]) -> Self {
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+}
+
+/// A decoded entry from the transposition table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TTEntry<M> {
+    /// Best move found, if any
+    pub best_move: Option<M>,
+    /// Search depth
+    pub depth: u8,
+    pub bound: Bound,
+    /// Generation of the search that stored the entry
+    pub generation: u8,
+    /// Score relative to the node (see [`score_to_tt`])
+    pub score: i32,
+}
+
+/// Entries per bucket
+const BUCKET_SIZE: usize = 4;
+
+/// Slots `0..DEPTH_SLOTS` keep the most valuable entries; the last slot is
+/// always overwritten when a new entry is not worth evicting any of them
+const DEPTH_SLOTS: usize = BUCKET_SIZE - 1;
+
+/// Generations wrap around in the upper 6 bits of `gen_bound`
+const GENERATION_MASK: u8 = 0x3F;
+
+/// Each generation of difference costs as much as this many plies of depth
+const AGE_WEIGHT: i32 = 4;
+
+/// A cluster of entries sharing one index, stored field by field so that
+/// no padding is needed between entries
+#[derive(Clone, Copy, Default)]
+#[repr(C)]
+struct Bucket<S, M> {
+    /// Upper 32 bits of the hash (the lower bits select the bucket)
+    keys: [u32; BUCKET_SIZE],
+    scores: [S; BUCKET_SIZE],
+    moves: [M; BUCKET_SIZE],
+    /// Search depth, 0 marks an empty slot
+    depths: [u8; BUCKET_SIZE],
+    /// `generation << 2 | bound`
+    gen_bound: [u8; BUCKET_SIZE],
+}
+
+impl<S: PackedScore, M: PackedMove> Bucket<S, M> {
+    /// Serialized size
+    const BYTES: usize = BUCKET_SIZE * (4 + S::BYTES + M::BYTES + 2);
+
+    #[inline]
+    fn generation(&self, slot: usize) -> u8 {
+        self.gen_bound[slot] >> 2
+    }
+
+    #[inline]
+    fn find(&self, key: u32) -> Option<usize> {
+        (0..BUCKET_SIZE).find(|&i| self.keys[i] == key && self.depths[i] > 0)
+    }
+
+    #[inline]
+    fn entry(&self, slot: usize) -> TTEntry<M> {
+        let bound = match self.gen_bound[slot] & 3 {
+            1 => Bound::Lower,
+            2 => Bound::Upper,
+            _ => Bound::Exact,
+        };
+        let mv = self.moves[slot];
+        TTEntry {
+            best_move: (mv != M::NONE).then_some(mv),
+            depth: self.depths[slot],
+            bound,
+            generation: self.generation(slot),
+            score: self.scores[slot].unpack(),
+        }
+    }
+
+    /// How much an entry is worth keeping: deep entries from recent searches win
+    #[inline]
+    fn keep_value(&self, slot: usize, generation: u8) -> i32 {
+        let age = generation.wrapping_sub(self.generation(slot)) & GENERATION_MASK;
+        self.depths[slot] as i32 - AGE_WEIGHT * age as i32
+    }
+
+    #[inline]
+    fn write(&mut self, slot: usize, key: u32, entry: TTEntry<M>) {
+        self.keys[slot] = key;
+        self.scores[slot] = S::pack(entry.score);
+        self.moves[slot] = entry.best_move.unwrap_or(M::NONE);
+        self.depths[slot] = entry.depth;
+        self.gen_bound[slot] = (entry.generation << 2) | entry.bound as u8;
+    }
+}
+
+/// Convert a score relative to the root into one relative to the node at `ply`
+///
+/// Proven results (beyond `mate_bound`) encode the distance from the root, so
+/// they are stored as the distance from the node itself and re-anchored on
+/// probe. This keeps forced win distances correct when the same position is
+/// reached at another ply.
+#[inline]
+pub fn score_to_tt(score: i32, ply: u32, mate_bound: i32) -> i32 {
+    if score >= mate_bound {
+        score + ply as i32
+    } else if score <= -mate_bound {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+/// Convert a stored score back into one relative to the root (see [`score_to_tt`])
+#[inline]
+pub fn score_from_tt(score: i32, ply: u32, mate_bound: i32) -> i32 {
+    if score >= mate_bound {
+        score - ply as i32
+    } else if score <= -mate_bound {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+/// Why a serialized table was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableError {
+    /// The data ended before the table was complete
+    Truncated,
+    /// The contents are structurally invalid
+    Corrupt(&'static str),
+}
+
+/// Transposition table sized in megabytes (any size, not just powers of two)
+pub struct TranspositionTable<S, M> {
+    buckets: Vec<Bucket<S, M>>,
+    generation: u8,
+}
+
+impl<S: PackedScore, M: PackedMove> TranspositionTable<S, M> {
+    /// Create a new transposition table using about `size_mb` megabytes
+    pub fn new(size_mb: usize) -> Self {
+        TranspositionTable {
+            buckets: vec![Bucket::default(); Self::bucket_count(size_mb)],
+            generation: 0,
+        }
+    }
+
+    fn bucket_count(size_mb: usize) -> usize {
+        (size_mb * 1024 * 1024 / std::mem::size_of::<Bucket<S, M>>()).max(1)
+    }
+
+    /// Number of buckets
+    pub fn buckets(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// An empty table with as many buckets as this one
+    pub fn empty_like(&self) -> Self {
+        TranspositionTable {
+            buckets: vec![Bucket::default(); self.buckets.len()],
+            generation: 0,
+        }
+    }
+
+    /// Reallocate the table with a new size, dropping all entries
+    pub fn resize(&mut self, size_mb: usize) {
+        self.buckets = vec![Bucket::default(); Self::bucket_count(size_mb)];
+        self.generation = 0;
+    }
+
+    /// Clear all entries
+    pub fn clear(&mut self) {
+        self.buckets.fill(Bucket::default());
+        self.generation = 0;
+    }
+
+    /// Start a new search: entries from older searches become replaceable
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1) & GENERATION_MASK;
+    }
+
+    /// Permille of sampled entries written by the current search
+    pub fn hashfull(&self) -> u32 {
+        let sample = &self.buckets[..self.buckets.len().min(1000)];
+        let used: usize = sample
+            .iter()
+            .map(|b| {
+                (0..BUCKET_SIZE)
+                    .filter(|&i| b.depths[i] > 0 && b.generation(i) == self.generation)
+                    .count()
+            })
+            .sum();
+        (used * 1000 / (sample.len() * BUCKET_SIZE)) as u32
+    }
+
+    /// Bucket index from the lower 32 bits, verification key from the upper 32
+    #[inline]
+    fn locate(&self, key: u64) -> (usize, u32) {
+        let index = ((key as u32 as u64 * self.buckets.len() as u64) >> 32) as usize;
+        (index, (key >> 32) as u32)
+    }
+
+    /// Probe the table for an entry
+    #[inline]
+    pub fn probe(&self, key: u64) -> Option<TTEntry<M>> {
+        let (index, check) = self.locate(key);
+        let bucket = &self.buckets[index];
+        bucket.find(check).map(|slot| bucket.entry(slot))
+    }
+
+    /// Store an entry in the table
+    ///
+    /// An existing entry for the same position is overwritten unless it is
+    /// deeper and from the current search (a new entry without a move keeps
+    /// the old move). Otherwise the new entry evicts the least valuable
+    /// depth-preferred slot if it is at least as valuable (depth weighed
+    /// against age), and falls back to the always-replace slot.
+    #[inline]
+    pub fn store(&mut self, key: u64, best_move: Option<M>, depth: u8, bound: Bound, score: i32) {
+        let (index, check) = self.locate(key);
+        let generation = self.generation;
+        let bucket = &mut self.buckets[index];
+        let mut entry = TTEntry {
+            best_move,
+            depth,
+            bound,
+            generation,
+            score,
+        };
+
+        if let Some(slot) = bucket.find(check) {
+            let keep = bucket.generation(slot) == generation && bucket.depths[slot] > depth && bound != Bound::Exact;
+            if !keep {
+                if entry.best_move.is_none() {
+                    entry.best_move = bucket.entry(slot).best_move;
+                }
+                bucket.write(slot, check, entry);
+            }
+            return;
+        }
+
+        let slot = (0..DEPTH_SLOTS)
+            .min_by_key(|&i| if bucket.depths[i] == 0 { i32::MIN } else { bucket.keep_value(i, generation) })
+            .unwrap_or(0);
+        let slot = if bucket.depths[slot] == 0 || depth as i32 >= bucket.keep_value(slot, generation) {
+            slot
+        } else {
+            DEPTH_SLOTS
+        };
+        bucket.write(slot, check, entry);
+    }
+
+    /// Append the generation and every bucket to a snapshot
+    pub fn write_to(&self, out: &mut Vec<u8>) {
+        out.push(self.generation);
+        out.extend_from_slice(&(self.buckets.len() as u32).to_le_bytes());
+        out.reserve(self.buckets.len() * Bucket::<S, M>::BYTES);
+        for bucket in &self.buckets {
+            for key in bucket.keys {
+                out.extend_from_slice(&key.to_le_bytes());
+            }
+            for score in bucket.scores {
+                score.write(out);
+            }
+            for mv in bucket.moves {
+                mv.write(out);
+            }
+            out.extend_from_slice(&bucket.depths);
+            out.extend_from_slice(&bucket.gen_bound);
+        }
+    }
+
+    /// Rebuild a table written by [`write_to`](Self::write_to), keeping its
+    /// size, and advance `data` past it
+    pub fn read_from(data: &mut &[u8]) -> Result<Self, TableError> {
+        let mut take = |n: usize| -> Result<&[u8], TableError> {
+            if data.len() < n {
+                return Err(TableError::Truncated);
+            }
+            let (head, rest) = data.split_at(n);
+            *data = rest;
+            Ok(head)
+        };
+        let generation = take(1)?[0];
+        if generation > GENERATION_MASK {
+            return Err(TableError::Corrupt("invalid table generation"));
+        }
+        let count = u32::from_le_bytes(take(4)?.try_into().unwrap()) as usize;
+        if count == 0 {
+            return Err(TableError::Corrupt("empty transposition table"));
+        }
+        // Take everything up front so a bogus count cannot trigger a huge allocation
+        let mut body = take(count.checked_mul(Bucket::<S, M>::BYTES).ok_or(TableError::Truncated)?)?;
+        let mut field = |n: usize| {
+            let (head, rest) = body.split_at(n);
+            body = rest;
+            head
+        };
+        let mut buckets = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut bucket = Bucket::<S, M>::default();
+            for key in bucket.keys.iter_mut() {
+                *key = u32::from_le_bytes(field(4).try_into().unwrap());
+            }
+            for score in bucket.scores.iter_mut() {
+                *score = S::read(field(S::BYTES));
+            }
+            for mv in bucket.moves.iter_mut() {
+                *mv = M::read(field(M::BYTES));
+            }
+            bucket.depths.copy_from_slice(field(BUCKET_SIZE));
+            bucket.gen_bound.copy_from_slice(field(BUCKET_SIZE));
+            buckets.push(bucket);
+        }
+        Ok(TranspositionTable { buckets, generation })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Table = TranspositionTable<i16, u8>;
+
+    const MATE: i32 = 29000;
+    const MATE_BOUND: i32 = MATE - 64;
+
+    #[test]
+    fn test_store_probe() {
+        let mut tt = Table::new(1);
+        let key = 0x123456789ABCDEF0;
+        tt.store(key, Some(27), 5, Bound::Exact, 100);
+
+        let entry = tt.probe(key).unwrap();
+        assert_eq!(entry.best_move, Some(27));
+        assert_eq!(entry.depth, 5);
+        assert_eq!(entry.score, 100);
+        assert_eq!(entry.bound, Bound::Exact);
+        assert!(tt.probe(0x0FEDCBA987654321).is_none());
+    }
+
+    #[test]
+    fn test_bucket_sizes() {
+        assert_eq!(std::mem::size_of::<Bucket<i16, u8>>(), Bucket::<i16, u8>::BYTES);
+        assert_eq!(std::mem::size_of::<Bucket<i32, u16>>(), Bucket::<i32, u16>::BYTES);
+        assert_eq!(std::mem::size_of::<Bucket<i16, u32>>(), Bucket::<i16, u32>::BYTES);
+        assert_eq!(Table::new(0).buckets(), 1);
+        assert_eq!(TranspositionTable::<i32, u16>::new(3).buckets(), 3 * 1024 * 1024 / 48);
+    }
+
+    #[test]
+    fn test_mate_score_normalization() {
+        // Win found 7 plies from the root, stored at ply 4 (3 plies from the node)
+        let stored = score_to_tt(MATE - 7, 4, MATE_BOUND);
+        assert_eq!(stored, MATE - 3);
+        // Same position reached at ply 2: the win is now 5 plies away
+        assert_eq!(score_from_tt(stored, 2, MATE_BOUND), MATE - 5);
+
+        let stored = score_to_tt(-MATE + 6, 6, MATE_BOUND);
+        assert_eq!(score_from_tt(stored, 1, MATE_BOUND), -MATE + 1);
+
+        // Heuristic scores pass through untouched
+        assert_eq!(score_from_tt(score_to_tt(-250, 9, MATE_BOUND), 3, MATE_BOUND), -250);
+    }
+
+    #[test]
+    fn test_scores_saturate() {
+        let mut tt = Table::new(0);
+        tt.store(1, None, 1, Bound::Lower, 1_000_000);
+        assert_eq!(tt.probe(1).unwrap().score, i16::MAX as i32);
+    }
+
+    #[test]
+    fn test_replacement_keeps_the_move() {
+        let mut tt = Table::new(1);
+        let key = 0x123456789ABCDEF0;
+        tt.store(key, Some(10), 3, Bound::Exact, 50);
+        tt.store(key, Some(20), 5, Bound::Exact, 100);
+        let entry = tt.probe(key).unwrap();
+        assert_eq!((entry.best_move, entry.depth), (Some(20), 5));
+
+        // A fail-low entry has no move of its own
+        tt.new_search();
+        tt.store(key, None, 6, Bound::Upper, 40);
+        let entry = tt.probe(key).unwrap();
+        assert_eq!((entry.best_move, entry.depth, entry.bound), (Some(20), 6, Bound::Upper));
+    }
+
+    /// Keys that land in the same bucket of a one-bucket table
+    fn colliding_keys(n: u64) -> Vec<u64> {
+        (1..=n).map(|i| (i << 32) | 7).collect()
+    }
+
+    #[test]
+    fn test_bucket_keeps_deep_entries() {
+        let mut tt = Table::new(0);
+        let keys = colliding_keys(6);
+
+        // Fill the depth-preferred slots with deep entries
+        for (i, &key) in keys[..3].iter().enumerate() {
+            tt.store(key, Some(i as u8), 10 + i as u8, Bound::Exact, 0);
+        }
+        // Shallow entries only cycle through the always-replace slot
+        tt.store(keys[3], Some(3), 1, Bound::Exact, 0);
+        tt.store(keys[4], Some(4), 1, Bound::Exact, 0);
+
+        assert!(keys[..3].iter().all(|&k| tt.probe(k).is_some()));
+        assert!(tt.probe(keys[3]).is_none());
+        assert_eq!(tt.probe(keys[4]).unwrap().best_move, Some(4));
+    }
+
+    #[test]
+    fn test_old_generations_are_replaced() {
+        let mut tt = Table::new(0);
+        let keys = colliding_keys(4);
+
+        for &key in &keys[..3] {
+            tt.store(key, None, 6, Bound::Lower, 0);
+        }
+        // Two searches later a depth-6 entry is worth less than a fresh depth-2 one
+        tt.new_search();
+        tt.new_search();
+        tt.store(keys[3], Some(9), 2, Bound::Upper, 0);
+
+        let entry = tt.probe(keys[3]).unwrap();
+        assert_eq!(entry.generation, 2);
+        assert_eq!(entry.bound, Bound::Upper);
+        assert_eq!((0..3).filter(|&i| tt.probe(keys[i]).is_some()).count(), 2);
+    }
+
+    #[test]
+    fn test_hashfull() {
+        let mut tt = Table::new(0);
+        assert_eq!(tt.hashfull(), 0);
+        for &key in &colliding_keys(2) {
+            tt.store(key, None, 3, Bound::Exact, 0);
+        }
+        assert_eq!(tt.hashfull(), 500);
+        tt.new_search();
+        assert_eq!(tt.hashfull(), 0);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut tt = TranspositionTable::<i32, u16>::new(0);
+        tt.new_search();
+        tt.store(0x0123_4567_89AB_CDEF, Some(0x0A2C), 9, Bound::Lower, -899_988);
+        let mut bytes = Vec::new();
+        tt.write_to(&mut bytes);
+        assert_eq!(bytes.len(), 5 + 48);
+        bytes.push(0xAA);
+
+        let mut data = bytes.as_slice();
+        let restored = TranspositionTable::<i32, u16>::read_from(&mut data).unwrap();
+        assert_eq!(data, [0xAA]);
+        assert_eq!(restored.probe(0x0123_4567_89AB_CDEF), tt.probe(0x0123_4567_89AB_CDEF));
+        assert_eq!(restored.hashfull(), tt.hashfull());
+
+        let mut short = &bytes[..20];
+        assert_eq!(TranspositionTable::<i32, u16>::read_from(&mut short).err(), Some(TableError::Truncated));
+    }
+}
@@ -0,0 +1,45 @@
+//! Zobrist key generation shared by the engines
+//!
+//! Every engine derives its keys from a fixed seed through [`KeyStream`], so
+//! hashes (and the snapshots keyed on them) are identical across runs. What
+//! gets hashed stays with each game.
+
+use crate::rng::splitmix64;
+
+/// Deterministic stream of Zobrist keys (SplitMix64 of a seed)
+#[derive(Debug, Clone)]
+pub struct KeyStream {
+    state: u64,
+}
+
+impl KeyStream {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// The next key
+    #[inline]
+    pub fn key(&mut self) -> u64 {
+        splitmix64(&mut self.state)
+    }
+
+    /// An array of the next `N` keys
+    pub fn keys<const N: usize>(&mut self) -> [u64; N] {
+        std::array::from_fn(|_| self.key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_follow_the_seed() {
+        let mut a = KeyStream::new(7);
+        let keys: [u64; 3] = a.keys();
+        let mut state = 7;
+        let expected: Vec<u64> = (0..4).map(|_| splitmix64(&mut state)).collect();
+        assert_eq!((keys.as_slice(), a.key()), (&expected[..3], expected[3]));
+        assert_ne!(KeyStream::new(8).key(), expected[0]);
+    }
+}
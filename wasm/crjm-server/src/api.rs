@@ -0,0 +1,336 @@
+//! Requests and answers of the service, independent of HTTP
+//!
+//! Every endpoint takes the same JSON body: an optional start `position`
+//! (the initial one when missing), the `moves` played from it and the search
+//! limits. Answers are JSON objects with camelCase keys, like the web app's
+//! AI types.
+
+use crjm_ffi::{Engine, Error, SearchOptions, SearchReport, Status};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// What the client asks for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Best line of the position at full strength
+    Analyse,
+    /// A move to play, randomized by `topN`/`scoreDelta`
+    BestMove,
+    /// Side to move, result so far and legal moves
+    Legal,
+    /// Every move of `moves` compared with the engine's choice
+    Review,
+    /// Search until the result is proven or a limit is hit
+    Solve,
+}
+
+impl Action {
+    pub fn from_path(name: &str) -> Option<Action> {
+        match name {
+            "analyse" => Some(Action::Analyse),
+            "bestmove" => Some(Action::BestMove),
+            "legal" => Some(Action::Legal),
+            "review" => Some(Action::Review),
+            "solve" => Some(Action::Solve),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Request {
+    /// Start position in notation (the initial position when missing)
+    pub position: Option<String>,
+    #[serde(default)]
+    pub moves: Vec<String>,
+    /// Per search (per position for a review); capped by the server
+    pub time_ms: Option<f64>,
+    pub max_depth: Option<u32>,
+    /// Dominório and Quelhas; capped by the server
+    pub max_nodes: Option<u64>,
+    /// Produto's playout limit
+    pub max_playouts: Option<u64>,
+    #[serde(default)]
+    pub top_n: u32,
+    #[serde(default)]
+    pub score_delta: i32,
+}
+
+/// Server-wide caps on what a request may ask for
+#[derive(Clone, Copy, Debug)]
+pub struct Caps {
+    pub max_time_ms: f64,
+    pub max_nodes: u64,
+    /// Longest game a review accepts
+    pub max_review_moves: usize,
+}
+
+impl Default for Caps {
+    fn default() -> Self {
+        Self { max_time_ms: 10_000.0, max_nodes: u64::MAX, max_review_moves: 200 }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ApiError {
+    BadRequest(String),
+    NotFound(String),
+    /// The queue is full
+    Busy,
+    Internal(String),
+}
+
+impl From<Error> for ApiError {
+    fn from(e: Error) -> Self {
+        ApiError::BadRequest(e.to_string())
+    }
+}
+
+/// Default search time of each action, in milliseconds
+fn default_time(action: Action) -> f64 {
+    match action {
+        Action::Review => 300.0,
+        Action::Solve => 5_000.0,
+        _ => 1_000.0,
+    }
+}
+
+fn options(action: Action, request: &Request, caps: &Caps) -> SearchOptions {
+    let defaults = SearchOptions::default();
+    let randomized = action == Action::BestMove;
+    SearchOptions {
+        time_ms: request.time_ms.unwrap_or(default_time(action)).clamp(0.0, caps.max_time_ms),
+        max_depth: request.max_depth.unwrap_or(defaults.max_depth).clamp(1, defaults.max_depth),
+        max_playouts: request.max_playouts.unwrap_or(defaults.max_playouts),
+        max_nodes: request.max_nodes.unwrap_or(u64::MAX).min(caps.max_nodes),
+        multipv: defaults.multipv,
+        top_n: if randomized { request.top_n } else { 0 },
+        score_delta: if randomized { request.score_delta } else { 0 },
+    }
+}
+
+fn status_json(engine: &Engine) -> Value {
+    let (status, winner) = match engine.status() {
+        Status::Playing => ("playing", None),
+        Status::Won(side) => ("won", Some(side)),
+        Status::Drawn => ("drawn", None),
+    };
+    json!({
+        "position": engine.position(),
+        "sideToMove": engine.side_to_move(),
+        "status": status,
+        "winner": winner,
+    })
+}
+
+fn report_json(report: &SearchReport) -> Value {
+    json!({
+        "bestMove": report.best_move,
+        "pv": report.pv,
+        "score": report.score,
+        "mateIn": report.mate_in,
+        "depth": report.depth,
+        "nodes": report.nodes,
+        "elapsedMs": report.elapsed_ms,
+    })
+}
+
+/// Adds the keys of `extra` to the object `base`
+fn merge(mut base: Value, extra: Value) -> Value {
+    if let (Some(base), Value::Object(extra)) = (base.as_object_mut(), extra) {
+        base.extend(extra);
+    }
+    base
+}
+
+/// Puts `engine` on the request's start position
+fn set_up(engine: &mut Engine, request: &Request) -> Result<(), ApiError> {
+    match &request.position {
+        Some(notation) => engine.set_position(notation)?,
+        None => engine.new_game(),
+    }
+    Ok(())
+}
+
+/// Answers `request` with `engine` (of the requested game)
+pub fn run(engine: &mut Engine, action: Action, request: &Request, caps: &Caps) -> Result<Value, ApiError> {
+    set_up(engine, request)?;
+    let options = options(action, request, caps);
+    if action == Action::Review {
+        return review(engine, request, &options, caps);
+    }
+    for mv in &request.moves {
+        engine.apply_move(mv)?;
+    }
+
+    Ok(match action {
+        Action::Legal => merge(status_json(engine), json!({ "moves": engine.legal_moves() })),
+        Action::Analyse | Action::BestMove => merge(status_json(engine), report_json(&engine.search(&options))),
+        Action::Solve => {
+            let status = engine.status();
+            let report = engine.search(&options);
+            let result = match status {
+                Status::Won(side) if side == engine.side_to_move() => "win",
+                Status::Won(_) => "loss",
+                Status::Drawn => "draw",
+                Status::Playing if report.mate_in.is_none() => "unknown",
+                Status::Playing if report.score > 0 => "win",
+                Status::Playing => "loss",
+            };
+            merge(merge(status_json(engine), report_json(&report)), json!({ "result": result }))
+        }
+        Action::Review => unreachable!("handled above"),
+    })
+}
+
+/// Grades every move against the engine's choice in the position before
+/// it, with `loss` how much worse than the best it was. Dominório and
+/// Quelhas use their engines' own review, which also gives each move a
+/// `class`; the other games search every position once and score the played
+/// move with [`played_score`]
+fn review(engine: &mut Engine, request: &Request, options: &SearchOptions, caps: &Caps) -> Result<Value, ApiError> {
+    if request.moves.len() > caps.max_review_moves {
+        return Err(ApiError::BadRequest(format!("a review takes at most {} moves", caps.max_review_moves)));
+    }
+    // Replay first, so an illegal record costs no search
+    for (ply, mv) in request.moves.iter().enumerate() {
+        engine
+            .apply_move(mv)
+            .map_err(|e| ApiError::BadRequest(format!("move {}: {e}", ply + 1)))?;
+    }
+    set_up(engine, request)?;
+    if let Some(reviews) = engine.review(&request.moves, options) {
+        let reviews: Vec<Value> = reviews?
+            .into_iter()
+            .enumerate()
+            .map(|(ply, m)| {
+                json!({
+                    "ply": ply,
+                    "played": m.played,
+                    "best": m.best_move,
+                    "bestScore": m.best_score,
+                    "bestMateIn": m.best_mate_in,
+                    "playedScore": m.played_score,
+                    "loss": m.loss,
+                    "class": m.class,
+                })
+            })
+            .collect();
+        return Ok(merge(status_json(engine), json!({ "moves": reviews })));
+    }
+
+    let mut reviews = Vec::with_capacity(request.moves.len());
+    let mut best = engine.search(options);
+    for (ply, mv) in request.moves.iter().enumerate() {
+        let mover = engine.side_to_move();
+        engine.apply_move(mv)?;
+        let next = engine.search(options);
+        let played_score = played_score(engine, mover, &next);
+        reviews.push(json!({
+            "ply": ply,
+            "played": mv,
+            "best": best.best_move,
+            "bestScore": best.score,
+            "bestMateIn": best.mate_in,
+            "playedScore": played_score,
+            "loss": (best.score - played_score).max(0),
+        }));
+        best = next;
+    }
+    Ok(merge(status_json(engine), json!({ "moves": reviews })))
+}
+
+/// Score for `mover` of the move just played on `engine`: what the next
+/// position (searched in `next`) scores for the mover, or the game's win
+/// score either way once the move ended the game
+fn played_score(engine: &Engine, mover: u8, next: &SearchReport) -> i32 {
+    match engine.status() {
+        // Produto's second stone and Nex's swap keep the turn
+        Status::Playing if engine.side_to_move() == mover => next.score,
+        Status::Playing => -next.score,
+        Status::Won(side) if side == mover => engine.win_score(),
+        Status::Won(_) => -engine.win_score(),
+        Status::Drawn => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crjm_ffi::GameKind;
+
+    fn request(body: Value) -> Request {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn legal_moves_follow_the_played_moves() {
+        let mut engine = Engine::new(GameKind::GatosCaes, 1, 0);
+        let answer = run(&mut engine, Action::Legal, &request(json!({ "moves": ["d4"] })), &Caps::default()).unwrap();
+        assert_eq!(answer["sideToMove"], 1);
+        assert_eq!(answer["status"], "playing");
+        let moves = answer["moves"].as_array().unwrap();
+        assert!(!moves.contains(&json!("d4")) && !moves.contains(&json!("e4")));
+
+        let bad = run(&mut engine, Action::Legal, &request(json!({ "moves": ["d4", "d4"] })), &Caps::default());
+        assert_eq!(bad, Err(ApiError::BadRequest("illegal move 'd4'".into())));
+        assert!(serde_json::from_value::<Request>(json!({ "depth": 3 })).is_err());
+    }
+
+    #[test]
+    fn searches_respect_the_caps() {
+        let mut engine = Engine::new(GameKind::Dominorio, 1, 0);
+        let caps = Caps { max_time_ms: 50.0, ..Caps::default() };
+        let body = request(json!({ "timeMs": 60_000, "maxDepth": 3 }));
+        let answer = run(&mut engine, Action::Analyse, &body, &caps).unwrap();
+        assert!(answer["elapsedMs"].as_f64().unwrap() < 5_000.0);
+        assert!(answer["depth"].as_u64().unwrap() <= 3);
+        assert_eq!(answer["pv"][0], answer["bestMove"]);
+    }
+
+    #[test]
+    fn solve_proves_small_endgames() {
+        // Dominório with a single vertical slot left: vertical wins at once
+        let mut rows = vec!["hhhhhhhh".to_string(); 8];
+        rows[0] = ".hhhhhhh".into();
+        rows[1] = ".hhhhhhh".into();
+        let body = request(json!({ "position": format!("{} v", rows.join("/")) }));
+        let mut engine = Engine::new(GameKind::Dominorio, 1, 0);
+        let answer = run(&mut engine, Action::Solve, &body, &Caps::default()).unwrap();
+        assert_eq!((answer["result"].as_str(), answer["bestMove"].as_str()), (Some("win"), Some("a1")));
+        assert_eq!(answer["mateIn"], 1);
+    }
+
+    #[test]
+    fn review_grades_every_move() {
+        let mut engine = Engine::new(GameKind::Quelhas, 1, 0);
+        let body = request(json!({ "moves": ["a1-a10", "b1-j1", "b2-b3"], "maxDepth": 2 }));
+        let answer = run(&mut engine, Action::Review, &body, &Caps::default()).unwrap();
+        let moves = answer["moves"].as_array().unwrap();
+        assert_eq!(moves.len(), 3);
+        assert_eq!(moves[1]["played"], "b1-j1");
+        assert!(moves.iter().all(|m| m["loss"].as_i64().is_some_and(|l| l >= 0)));
+        assert!(moves.iter().all(|m| m["class"].is_string()));
+        let bad = request(json!({ "moves": ["a1-a10", "a1-a10"] }));
+        assert!(matches!(run(&mut engine, Action::Review, &bad, &Caps::default()), Err(ApiError::BadRequest(e)) if e.starts_with("move 2:")));
+        let long = Caps { max_review_moves: 2, ..Caps::default() };
+        assert!(matches!(run(&mut engine, Action::Review, &body, &long), Err(ApiError::BadRequest(_))));
+    }
+
+    #[test]
+    fn a_move_that_ends_the_game_scores_as_proven() {
+        // The mover wins Dominório by leaving the opponent stuck and loses
+        // Quelhas (misère) that way
+        for (kind, sign) in [(GameKind::Dominorio, 1), (GameKind::Quelhas, -1)] {
+            let mut engine = Engine::new(kind, 1, 0);
+            let mut mover = 0;
+            while engine.status() == Status::Playing {
+                mover = engine.side_to_move();
+                let mv = engine.legal_moves()[0].clone();
+                engine.apply_move(&mv).unwrap();
+            }
+            assert_eq!(played_score(&engine, mover, &SearchReport::default()), sign * engine.win_score(), "{}", kind.id());
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Local HTTP/JSON analysis service on the native CRJM engines
+//!
+//! For labs where the browser engines are too slow: one machine runs the
+//! service and the web app sends its searches there instead of to wasm.
+//! Searches run natively but single-threaded, one request per worker
+//! thread (see [`pool`]).
+//!
+//! ```text
+//! crjm-server [--host 127.0.0.1] [--port 8787] [--workers <cores>]
+//!             [--queue 32] [--hash 32] [--max-time 10000] [--max-nodes <n>]
+//! ```
+//!
+//! `POST /{game}/{analyse|bestmove|legal|review|solve}` with a JSON body
+//! (see [`api::Request`]); `GET /health` tells the games and the load. The
+//! service listens on localhost unless `--host` says otherwise.
+
+use std::net::SocketAddr;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::thread;
+
+mod api;
+mod pool;
+mod routes;
+
+use api::Caps;
+use pool::{Config, Pool};
+
+const USAGE: &str = "usage: crjm-server [--host <ip>] [--port <n>] [--workers <n>] [--queue <n>] \
+                     [--hash <mb>] [--max-time <ms>] [--max-nodes <n>]";
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<(SocketAddr, Config), String> {
+    let workers = thread::available_parallelism().map_or(2, |n| n.get());
+    let mut config = Config { workers, queue: 32, hash_mb: 32, caps: Caps::default() };
+    let (mut host, mut port) = ("127.0.0.1".to_string(), 8787u16);
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{flag} needs a value"))?;
+        let number = || value.parse::<u64>().map_err(|_| format!("{flag}: '{value}' is not a number"));
+        match flag.as_str() {
+            "--host" => host = value.clone(),
+            "--port" => port = u16::try_from(number()?).map_err(|_| format!("bad port '{value}'"))?,
+            "--workers" => config.workers = number()?.max(1) as usize,
+            "--queue" => config.queue = number()? as usize,
+            "--hash" => config.hash_mb = number()?.clamp(1, 256) as usize,
+            "--max-time" => config.caps.max_time_ms = number()? as f64,
+            "--max-nodes" => config.caps.max_nodes = number()?,
+            _ => return Err(format!("unknown option {flag}")),
+        }
+    }
+    let addr = format!("{host}:{port}").parse().map_err(|_| format!("bad address '{host}:{port}'"))?;
+    Ok((addr, config))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let (addr, config) = match parse_args(std::env::args().skip(1)) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let listener = match tokio::net::TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("cannot listen on {addr}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!("crjm-server on http://{addr} ({} workers, queue {})", config.workers, config.queue);
+    let app = routes::router(Arc::new(Pool::new(config)));
+    if let Err(e) = axum::serve(listener, app).await {
+        eprintln!("server error: {e}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,115 @@
+//! Worker threads running the engines, fed by a bounded request queue
+//!
+//! Each worker keeps one engine per game, so its transposition tables carry
+//! over between requests. A request waits in the queue until a worker is
+//! free; once the queue is full new requests are turned away at once.
+//!
+//! The engines search on a single thread: a request runs on one worker, so
+//! more workers serve more requests at once but do not make one search any
+//! deeper.
+
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crjm_ffi::{Engine, GameKind};
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use crate::api::{self, Action, ApiError, Caps, Request};
+
+struct Job {
+    game: GameKind,
+    action: Action,
+    request: Request,
+    reply: oneshot::Sender<Result<Value, ApiError>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    pub workers: usize,
+    /// Requests that may wait for a worker
+    pub queue: usize,
+    /// Transposition table of each engine
+    pub hash_mb: usize,
+    pub caps: Caps,
+}
+
+pub struct Pool {
+    jobs: SyncSender<Job>,
+    /// Requests queued or running
+    pending: Arc<AtomicUsize>,
+    /// Most requests `pending` at once: one per worker plus the queue
+    capacity: usize,
+    config: Config,
+}
+
+impl Pool {
+    pub fn new(config: Config) -> Self {
+        let capacity = config.workers.max(1) + config.queue;
+        // Room for every admitted request, so only `capacity` turns them away
+        let (jobs, queue) = sync_channel::<Job>(capacity);
+        let queue = Arc::new(Mutex::new(queue));
+        let pending = Arc::new(AtomicUsize::new(0));
+        for index in 0..config.workers.max(1) {
+            let (queue, pending) = (queue.clone(), pending.clone());
+            thread::Builder::new()
+                .name(format!("engine-{index}"))
+                .spawn(move || work(index as u64, &queue, &pending, config))
+                .expect("spawn an engine worker");
+        }
+        Self { jobs, pending, capacity, config }
+    }
+
+    pub fn config(&self) -> Config {
+        self.config
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    /// Runs `request` on the next free worker
+    pub async fn submit(&self, game: GameKind, action: Action, request: Request) -> Result<Value, ApiError> {
+        let (reply, answer) = oneshot::channel();
+        // Counting admissions does not depend on a worker already waiting on
+        // the channel
+        let admitted = self.pending.fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| (n < self.capacity).then_some(n + 1));
+        if admitted.is_err() {
+            return Err(ApiError::Busy);
+        }
+        if let Err(e) = self.jobs.try_send(Job { game, action, request, reply }) {
+            self.pending.fetch_sub(1, Ordering::Relaxed);
+            return Err(match e {
+                TrySendError::Full(_) => ApiError::Busy,
+                TrySendError::Disconnected(_) => ApiError::Internal("no engine workers".into()),
+            });
+        }
+        answer.await.unwrap_or_else(|_| Err(ApiError::Internal("the engine worker stopped".into())))
+    }
+}
+
+fn work(index: u64, queue: &Mutex<Receiver<Job>>, pending: &AtomicUsize, config: Config) {
+    let mut engines: HashMap<GameKind, Engine> = HashMap::new();
+    loop {
+        // the lock is only held while waiting for the next job
+        let job = match queue.lock() {
+            Ok(queue) => queue.recv(),
+            Err(_) => return,
+        };
+        let Ok(job) = job else { return };
+        let engine = engines.entry(job.game).or_insert_with(|| Engine::new(job.game, config.hash_mb, index));
+        let answer = catch_unwind(AssertUnwindSafe(|| api::run(engine, job.action, &job.request, &config.caps)))
+            .unwrap_or_else(|_| {
+                // the engine may be inconsistent: start over with a fresh one
+                engines.remove(&job.game);
+                Err(ApiError::Internal("the engine panicked".into()))
+            });
+        pending.fetch_sub(1, Ordering::Relaxed);
+        // the client may have gone away
+        let _ = job.reply.send(answer);
+    }
+}
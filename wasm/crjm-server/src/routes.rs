@@ -0,0 +1,143 @@
+//! HTTP layer: `POST /{game}/{action}` with a JSON body, `GET /health`
+//!
+//! Errors are `{ "error": message }` with 400 (bad body, position or move),
+//! 404 (unknown game or action), 503 (queue full) or 500. Every answer
+//! allows any origin, so a web app served elsewhere can call the service.
+
+use std::sync::Arc;
+
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum::middleware::map_response;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use crjm_ffi::GameKind;
+use serde_json::{json, Value};
+
+use crate::api::{Action, ApiError, Request};
+use crate::pool::Pool;
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(m) => (StatusCode::BAD_REQUEST, m),
+            ApiError::NotFound(m) => (StatusCode::NOT_FOUND, m),
+            ApiError::Busy => (StatusCode::SERVICE_UNAVAILABLE, "too many requests queued, try again later".into()),
+            ApiError::Internal(m) => (StatusCode::INTERNAL_SERVER_ERROR, m),
+        };
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
+
+pub fn router(pool: Arc<Pool>) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/{game}/{action}", post(query).options(preflight))
+        .layer(map_response(allow_any_origin))
+        .with_state(pool)
+}
+
+async fn allow_any_origin(mut response: Response) -> Response {
+    let headers = response.headers_mut();
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+    headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, HeaderValue::from_static("content-type"));
+    let methods = HeaderValue::from_str(&format!("{}, {}", Method::POST, Method::OPTIONS)).expect("ASCII methods");
+    headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, methods);
+    response
+}
+
+async fn preflight() -> StatusCode {
+    StatusCode::NO_CONTENT
+}
+
+async fn health(State(pool): State<Arc<Pool>>) -> Json<Value> {
+    let config = pool.config();
+    Json(json!({
+        "games": GameKind::ALL.map(GameKind::id),
+        "workers": config.workers,
+        "queue": config.queue,
+        "pending": pool.pending(),
+        "maxTimeMs": config.caps.max_time_ms,
+    }))
+}
+
+async fn query(
+    State(pool): State<Arc<Pool>>,
+    Path((game, action)): Path<(String, String)>,
+    body: Result<Json<Request>, JsonRejection>,
+) -> Result<Json<Value>, ApiError> {
+    let kind = GameKind::from_id(&game).ok_or_else(|| ApiError::NotFound(format!("unknown game '{game}'")))?;
+    let action = Action::from_path(&action).ok_or_else(|| ApiError::NotFound(format!("unknown action '{action}'")))?;
+    let Json(request) = body.map_err(|e| ApiError::BadRequest(e.body_text()))?;
+    pool.submit(kind, action, request).await.map(Json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Caps;
+    use crate::pool::Config;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request as HttpRequest;
+    use tower::ServiceExt;
+
+    fn app(workers: usize, queue: usize) -> Router {
+        router(Arc::new(Pool::new(Config { workers, queue, hash_mb: 1, caps: Caps::default() })))
+    }
+
+    async fn post_json(app: Router, uri: &str, body: &str) -> (StatusCode, Value) {
+        let request = HttpRequest::post(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn every_game_answers_over_http() {
+        let app = app(2, 8);
+        for kind in GameKind::ALL {
+            let uri = format!("/{}/bestmove", kind.id());
+            let (status, answer) = post_json(app.clone(), &uri, r#"{"timeMs": 50, "maxDepth": 2, "maxPlayouts": 100}"#).await;
+            assert_eq!(status, StatusCode::OK, "{answer}");
+            let (_, legal) = post_json(app.clone(), &format!("/{}/legal", kind.id()), "{}").await;
+            assert!(legal["moves"].as_array().unwrap().contains(&answer["bestMove"]), "{}", kind.id());
+        }
+    }
+
+    #[tokio::test]
+    async fn errors_have_a_status_and_a_message() {
+        let app = app(1, 1);
+        let (status, answer) = post_json(app.clone(), "/xadrez/legal", "{}").await;
+        assert_eq!((status, answer["error"].as_str()), (StatusCode::NOT_FOUND, Some("unknown game 'xadrez'")));
+        let (status, _) = post_json(app.clone(), "/quelhas/ponder", "{}").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = post_json(app.clone(), "/quelhas/legal", "{\"moves\": 3}").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, answer) = post_json(app, "/quelhas/legal", r#"{"moves": ["a1-b2"]}"#).await;
+        assert_eq!((status, answer["error"].as_str()), (StatusCode::BAD_REQUEST, Some("illegal move 'a1-b2'")));
+    }
+
+    #[tokio::test]
+    async fn a_full_queue_turns_requests_away() {
+        // one worker busy with a long search, no room to wait
+        let pool = Arc::new(Pool::new(Config { workers: 1, queue: 0, hash_mb: 1, caps: Caps::default() }));
+        let busy = {
+            let pool = pool.clone();
+            let request = Request { time_ms: Some(1_000.0), ..Request::default() };
+            tokio::spawn(async move { pool.submit(GameKind::Quelhas, Action::Analyse, request).await })
+        };
+        tokio::task::yield_now().await;
+        assert_eq!(pool.pending(), 1);
+        let (status, _) = post_json(router(pool.clone()), "/quelhas/legal", "{}").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(busy.await.unwrap().is_ok());
+        assert_eq!(pool.pending(), 0);
+    }
+}
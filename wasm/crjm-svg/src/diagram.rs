@@ -0,0 +1,488 @@
+//! One board diagram
+//!
+//! Elements carry classes (`cell`, `blocked`, `piece vertical`, `last`,
+//! `arrow`, ...) so a stylesheet can restyle them.
+
+use std::fmt::{self, Write};
+
+use dominorio_ai::bitboard::Side;
+use quelhas_core::{decode_move, EncMove, Occupancy};
+
+/// Which board a diagram shows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Game {
+    Dominorio,
+    Quelhas,
+}
+
+impl Game {
+    /// Cells per side
+    pub fn size(self) -> usize {
+        match self {
+            Game::Dominorio => 8,
+            Game::Quelhas => quelhas_core::BOARD_SIZE,
+        }
+    }
+}
+
+/// A placed domino or segment; vertical pieces are the vertical player's
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Piece {
+    /// Top (vertical) or left (horizontal) cell
+    pub start: usize,
+    pub len: usize,
+    pub vertical: bool,
+}
+
+impl Piece {
+    /// A Dominório domino from its anchor
+    pub fn domino(anchor: u8, side: Side) -> Self {
+        Self { start: anchor as usize, len: 2, vertical: side == Side::Vertical }
+    }
+
+    /// A Quelhas segment from its encoded move
+    pub fn segment(m: EncMove) -> Self {
+        let (start, len, orient) = decode_move(m);
+        Self { start: start as usize, len: len as usize, vertical: orient == 0 }
+    }
+
+    fn step(self, size: usize) -> usize {
+        if self.vertical {
+            size
+        } else {
+            1
+        }
+    }
+
+    fn end(self, size: usize) -> usize {
+        self.start + (self.len - 1) * self.step(size)
+    }
+
+    /// Cells from the start, on a board `size` cells wide
+    pub fn cells(self, size: usize) -> impl Iterator<Item = usize> {
+        let step = self.step(size);
+        (0..self.len).map(move |i| self.start + i * step)
+    }
+
+    /// The move in the games' notation: the anchor of a domino, `c2-c5` for
+    /// a segment
+    pub fn name(self, game: Game) -> String {
+        let size = game.size();
+        match game {
+            Game::Dominorio => cell_name(self.start, size),
+            Game::Quelhas => format!("{}-{}", cell_name(self.start, size), cell_name(self.end(size), size)),
+        }
+    }
+}
+
+fn cell_name(idx: usize, size: usize) -> String {
+    format!("{}{}", (b'a' + (idx % size) as u8) as char, idx / size + 1)
+}
+
+/// Sizes and colours; the default colours are the web app's
+#[derive(Clone, Debug)]
+pub struct Style {
+    /// Side of a cell, in SVG user units
+    pub cell: f64,
+    pub vertical: String,
+    pub horizontal: String,
+    pub board: String,
+    pub empty: String,
+    /// Occupied cells without a piece in the history
+    pub blocked: String,
+    /// Outline of the last move
+    pub highlight: String,
+    /// Coordinates and captions
+    pub text: String,
+    pub coordinates: bool,
+    /// Prefix of the ids the document defines (the arrowheads): SVGs inlined
+    /// in the same page need different prefixes, or their arrows share the
+    /// first document's markers
+    pub id_prefix: String,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            cell: 40.0,
+            vertical: "#ec4899".into(),
+            horizontal: "#06b6d4".into(),
+            board: "#065f46".into(),
+            empty: "#f3f4f6".into(),
+            blocked: "#9ca3af".into(),
+            highlight: "#facc15".into(),
+            text: "#374151".into(),
+            coordinates: true,
+            id_prefix: "crjm".into(),
+        }
+    }
+}
+
+impl Style {
+    fn color(&self, vertical: bool) -> &str {
+        if vertical {
+            &self.vertical
+        } else {
+            &self.horizontal
+        }
+    }
+
+    fn arrow_id(&self, vertical: bool) -> String {
+        format!("{}-arrow-{}", self.id_prefix, if vertical { "v" } else { "h" })
+    }
+
+    fn margin(&self) -> f64 {
+        if self.coordinates {
+            self.cell * 0.6
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Numbers in SVG attributes: at most two decimals, no trailing zeros
+pub(crate) struct Num(pub f64);
+
+impl fmt::Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = format!("{:.2}", self.0);
+        let text = text.trim_end_matches('0').trim_end_matches('.');
+        f.write_str(if text == "-0" { "0" } else { text })
+    }
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Arrowheads of both players, referenced by the arrows
+pub(crate) fn arrow_defs(out: &mut String, style: &Style) {
+    out.push_str("<defs>");
+    for vertical in [true, false] {
+        let _ = write!(
+            out,
+            r#"<marker id="{}" viewBox="0 0 10 10" refX="5" refY="5" markerWidth="3" markerHeight="3" orient="auto-start-reverse"><path d="M0 0L10 5L0 10z" fill="{}"/></marker>"#,
+            escape(&style.arrow_id(vertical)),
+            style.color(vertical)
+        );
+    }
+    out.push_str("</defs>");
+}
+
+/// A board with its pieces, highlights, arrows and caption
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagram {
+    game: Game,
+    occupied: Vec<bool>,
+    /// In the order they were placed
+    pieces: Vec<Piece>,
+    highlight_last: bool,
+    /// History index of the first numbered piece and its number
+    numbered: Option<(usize, usize)>,
+    arrows: Vec<Piece>,
+    caption: Option<String>,
+}
+
+impl Diagram {
+    fn new(game: Game, occupied: impl Fn(usize) -> bool) -> Self {
+        let cells = game.size() * game.size();
+        Self {
+            game,
+            occupied: (0..cells).map(occupied).collect(),
+            pieces: Vec::new(),
+            highlight_last: true,
+            numbered: None,
+            arrows: Vec::new(),
+            caption: None,
+        }
+    }
+
+    /// A Dominório board (bit `row * 8 + col` set when occupied)
+    pub fn dominorio(occupied: u64) -> Self {
+        Self::new(Game::Dominorio, |i| occupied & (1 << i) != 0)
+    }
+
+    pub fn quelhas(occupied: Occupancy) -> Self {
+        Self::new(Game::Quelhas, |i| occupied.is_set(i))
+    }
+
+    pub fn game(&self) -> Game {
+        self.game
+    }
+
+    pub fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+
+    /// The pieces that fill the occupied cells, in the order they were
+    /// placed
+    pub fn with_history(mut self, pieces: impl IntoIterator<Item = Piece>) -> Self {
+        self.pieces.extend(pieces);
+        self
+    }
+
+    /// Places more pieces: fills their cells and adds them to the history
+    pub fn play(mut self, pieces: impl IntoIterator<Item = Piece>) -> Self {
+        let size = self.game.size();
+        for piece in pieces {
+            for cell in piece.cells(size) {
+                self.occupied[cell] = true;
+            }
+            self.pieces.push(piece);
+        }
+        self
+    }
+
+    /// Outlines the last piece of the history (on by default)
+    pub fn highlight_last(mut self, on: bool) -> Self {
+        self.highlight_last = on;
+        self
+    }
+
+    /// Writes move numbers on the pieces of the history, from `first`
+    pub fn with_move_numbers(mut self, first: usize) -> Self {
+        self.numbered = Some((0, first));
+        self
+    }
+
+    pub(crate) fn numbered_from(mut self, index: usize, first: usize) -> Self {
+        self.numbered = Some((index, first));
+        self
+    }
+
+    /// Suggested moves, drawn as arrows along the piece
+    pub fn with_arrows(mut self, moves: impl IntoIterator<Item = Piece>) -> Self {
+        self.arrows.extend(moves);
+        self
+    }
+
+    /// Text under the board
+    pub fn with_caption(mut self, caption: impl Into<String>) -> Self {
+        self.caption = Some(caption.into());
+        self
+    }
+
+    pub(crate) fn has_arrows(&self) -> bool {
+        !self.arrows.is_empty()
+    }
+
+    /// Width and height in SVG user units
+    pub fn size(&self, style: &Style) -> (f64, f64) {
+        let side = 0.2 * style.cell + style.margin() + self.board_side(style);
+        let caption = if self.caption.is_some() { 0.8 * style.cell } else { 0.0 };
+        (side, side + caption)
+    }
+
+    fn board_side(&self, style: &Style) -> f64 {
+        (self.game.size() as f64 + 0.2) * style.cell
+    }
+
+    /// A standalone SVG document
+    pub fn to_svg(&self, style: &Style) -> String {
+        let (width, height) = self.size(style);
+        let mut out = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif">"#,
+            w = Num(width),
+            h = Num(height)
+        );
+        if self.has_arrows() {
+            arrow_defs(&mut out, style);
+        }
+        self.render(&mut out, 0.0, 0.0, style);
+        out.push_str("</svg>\n");
+        out
+    }
+
+    /// Appends the diagram as a group with its top left corner at `(x, y)`
+    pub(crate) fn render(&self, out: &mut String, x: f64, y: f64, style: &Style) {
+        let size = self.game.size();
+        let c = style.cell;
+        let (pad, margin) = (0.1 * c, style.margin());
+        let (ox, oy) = (pad + margin, pad + margin);
+        let cell_x = |idx: usize| ox + 0.1 * c + (idx % size) as f64 * c;
+        let cell_y = |idx: usize| oy + 0.1 * c + (idx / size) as f64 * c;
+        let font = Num(0.35 * c);
+
+        let _ = write!(out, r#"<g transform="translate({} {})">"#, Num(x), Num(y));
+        let side = self.board_side(style);
+        let _ = write!(
+            out,
+            r#"<rect class="board" x="{}" y="{}" width="{s}" height="{s}" rx="{}" fill="{}"/>"#,
+            Num(ox),
+            Num(oy),
+            Num(0.15 * c),
+            style.board,
+            s = Num(side)
+        );
+
+        if style.coordinates {
+            for i in 0..size {
+                let _ = write!(
+                    out,
+                    r#"<text class="coord" x="{}" y="{}" font-size="{font}" text-anchor="middle" fill="{}">{}</text>"#,
+                    Num(cell_x(i) + c / 2.0),
+                    Num(pad + margin * 0.7),
+                    style.text,
+                    (b'a' + i as u8) as char
+                );
+                let _ = write!(
+                    out,
+                    r#"<text class="coord" x="{}" y="{}" font-size="{font}" text-anchor="middle" dominant-baseline="central" fill="{}">{}</text>"#,
+                    Num(pad + margin / 2.0),
+                    Num(cell_y(i * size) + c / 2.0),
+                    style.text,
+                    i + 1
+                );
+            }
+        }
+
+        let mut covered = vec![false; size * size];
+        for piece in &self.pieces {
+            for cell in piece.cells(size) {
+                covered[cell] = true;
+            }
+        }
+        let inset = 0.06 * c;
+        for idx in (0..size * size).filter(|&i| !covered[i]) {
+            let (class, fill) = if self.occupied[idx] { ("blocked", &style.blocked) } else { ("cell", &style.empty) };
+            let _ = write!(
+                out,
+                r#"<rect class="{class}" x="{}" y="{}" width="{s}" height="{s}" rx="{}" fill="{fill}"/>"#,
+                Num(cell_x(idx) + inset),
+                Num(cell_y(idx) + inset),
+                Num(0.1 * c),
+                s = Num(c - 2.0 * inset)
+            );
+        }
+
+        for (i, piece) in self.pieces.iter().enumerate() {
+            let end = piece.end(size);
+            let (x0, y0) = (cell_x(piece.start) + inset, cell_y(piece.start) + inset);
+            let (w, h) = (cell_x(end) + c - inset - x0, cell_y(end) + c - inset - y0);
+            let last = self.highlight_last && i + 1 == self.pieces.len();
+            let orient = if piece.vertical { "vertical" } else { "horizontal" };
+            let _ = write!(
+                out,
+                r#"<rect class="piece {orient}{}" x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}""#,
+                if last { " last" } else { "" },
+                Num(x0),
+                Num(y0),
+                Num(w),
+                Num(h),
+                Num(0.2 * c),
+                style.color(piece.vertical)
+            );
+            if last {
+                let _ = write!(out, r#" stroke="{}" stroke-width="{}""#, style.highlight, Num(0.1 * c));
+            }
+            out.push_str("/>");
+            if let Some(number) = self.numbered.and_then(|(from, first)| i.checked_sub(from).map(|k| first + k)) {
+                let _ = write!(
+                    out,
+                    r#"<text class="number" x="{}" y="{}" font-size="{font}" font-weight="bold" text-anchor="middle" dominant-baseline="central" fill="white">{number}</text>"#,
+                    Num(x0 + w / 2.0),
+                    Num(y0 + h / 2.0)
+                );
+            }
+        }
+
+        for arrow in &self.arrows {
+            let (sx, sy) = (cell_x(arrow.start) + c / 2.0, cell_y(arrow.start) + c / 2.0);
+            let end = arrow.end(size);
+            let (ex, ey) = (cell_x(end) + c / 2.0, cell_y(end) + c / 2.0);
+            // a little past the centres, so a domino's arrow is not a stub
+            let (dx, dy) = if arrow.vertical { (0.0, 0.25 * c) } else { (0.25 * c, 0.0) };
+            let _ = write!(
+                out,
+                r#"<line class="arrow" x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-opacity="0.85" marker-end="url(#{})"/>"#,
+                Num(sx - dx),
+                Num(sy - dy),
+                Num(ex + dx),
+                Num(ey + dy),
+                style.color(arrow.vertical),
+                Num(0.12 * c),
+                escape(&style.arrow_id(arrow.vertical))
+            );
+        }
+
+        if let Some(caption) = &self.caption {
+            let _ = write!(
+                out,
+                r#"<text class="caption" x="{}" y="{}" font-size="{}" text-anchor="middle" fill="{}">{}</text>"#,
+                Num(ox + side / 2.0),
+                Num(oy + side + 0.6 * c),
+                Num(0.4 * c),
+                style.text,
+                escape(caption)
+            );
+        }
+        out.push_str("</g>");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quelhas_core::encode_move;
+
+    #[test]
+    fn pieces_follow_the_notation() {
+        let domino = Piece::domino(9, Side::Vertical);
+        assert_eq!(domino.cells(8).collect::<Vec<_>>(), [9, 17]);
+        assert_eq!(domino.name(Game::Dominorio), "b2");
+        let segment = Piece::segment(encode_move(12, 4, 0));
+        assert_eq!(segment.cells(10).collect::<Vec<_>>(), [12, 22, 32, 42]);
+        assert_eq!(segment.name(Game::Quelhas), "c2-c5");
+        assert_eq!(Piece::segment(encode_move(0, 3, 1)).name(Game::Quelhas), "a1-c1");
+    }
+
+    #[test]
+    fn history_draws_pieces_and_the_last_move() {
+        let svg = Diagram::dominorio(0)
+            .play([Piece::domino(0, Side::Vertical), Piece::domino(2, Side::Horizontal)])
+            .to_svg(&Style::default());
+        assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches(r#"class="cell""#).count(), 60);
+        assert_eq!(svg.matches(r##"class="piece vertical" x="34.4" y="34.4" width="35.2" height="75.2" rx="8" fill="#ec4899"/>"##).count(), 1);
+        assert_eq!(svg.matches(r#"class="piece horizontal last""#).count(), 1);
+        assert_eq!(svg.matches(r#"class="coord""#).count(), 16);
+        assert!(!svg.contains("<defs>") && !svg.contains("class=\"number\""));
+    }
+
+    #[test]
+    fn occupancy_without_history_is_blocked() {
+        let mut occ = Occupancy::default();
+        for idx in [0, 55, 99] {
+            occ.set(idx);
+        }
+        let style = Style { coordinates: false, ..Style::default() };
+        let diagram = Diagram::quelhas(occ).with_caption("Puzzle <1> & 2");
+        let svg = diagram.to_svg(&style);
+        assert_eq!(svg.matches(r#"class="blocked""#).count(), 3);
+        assert_eq!(svg.matches(r#"class="cell""#).count(), 97);
+        assert!(svg.contains(">Puzzle &lt;1&gt; &amp; 2</text>"));
+        assert_eq!(diagram.size(&style), (416.0, 448.0));
+    }
+
+    #[test]
+    fn suggestions_are_arrows() {
+        let svg = Diagram::quelhas(Occupancy::default())
+            .with_arrows([Piece::segment(encode_move(0, 3, 1)), Piece::segment(encode_move(5, 2, 0))])
+            .to_svg(&Style::default());
+        assert_eq!(svg.matches("<marker ").count(), 2);
+        assert!(svg.contains(r##"stroke="#06b6d4" stroke-width="4.8" stroke-linecap="round" stroke-opacity="0.85" marker-end="url(#crjm-arrow-h)""##));
+        assert!(svg.contains("url(#crjm-arrow-v)"));
+
+        let style = Style { id_prefix: "game2".into(), ..Style::default() };
+        let svg = Diagram::dominorio(0).with_arrows([Piece::domino(9, Side::Vertical)]).to_svg(&style);
+        assert!(svg.contains(r#"<marker id="game2-arrow-v" "#) && svg.contains("url(#game2-arrow-v)"));
+        assert!(!svg.contains("crjm-arrow"));
+    }
+
+    #[test]
+    fn numbers_are_trimmed() {
+        assert_eq!(Num(2.4000000000000004).to_string(), "2.4");
+        assert_eq!(Num(40.0).to_string(), "40");
+        assert_eq!(Num(-0.001).to_string(), "0");
+    }
+}
@@ -0,0 +1,16 @@
+//! SVG board diagrams for Dominório and Quelhas worksheets and reports
+//!
+//! A [`Diagram`] draws an occupancy (Dominório's `u64`, Quelhas'
+//! `Occupancy`) with the coordinates of the notation, `a1` top left. With
+//! the history of placed pieces the dominoes and segments are drawn in their
+//! player's colour and the last one is highlighted; occupied cells without a
+//! piece are drawn as blocked. Suggested moves can be added as arrows.
+//!
+//! A [`GameSheet`] lays several diagrams out in a grid, for instance one
+//! every few moves of a game with the pieces numbered by move.
+
+mod diagram;
+mod sheet;
+
+pub use diagram::{Diagram, Game, Piece, Style};
+pub use sheet::GameSheet;
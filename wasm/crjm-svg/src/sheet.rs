@@ -0,0 +1,137 @@
+//! Game sheets: several diagrams in a grid
+
+use std::fmt::Write;
+
+use crate::diagram::{arrow_defs, escape, Diagram, Num, Piece, Style};
+
+pub struct GameSheet {
+    diagrams: Vec<Diagram>,
+    columns: usize,
+    title: Option<String>,
+}
+
+impl GameSheet {
+    /// An empty sheet `columns` diagrams wide
+    pub fn new(columns: usize) -> Self {
+        Self { diagrams: Vec::new(), columns: columns.max(1), title: None }
+    }
+
+    /// The game played from `start`: a diagram after every `every` moves
+    /// and after the last one, captioned with the move number and name, the
+    /// moves numbered on their pieces
+    pub fn from_moves(start: &Diagram, moves: &[Piece], every: usize, columns: usize) -> Self {
+        let mut sheet = Self::new(columns);
+        let every = every.max(1);
+        let mut stops: Vec<usize> = (every..=moves.len()).step_by(every).collect();
+        if stops.last() != Some(&moves.len()) {
+            stops.push(moves.len());
+        }
+        let game = start.game();
+        for ply in stops {
+            let caption = match ply.checked_sub(1).map(|i| moves[i]) {
+                Some(last) => format!("{ply}. {}", last.name(game)),
+                None => "start".to_string(),
+            };
+            let diagram = start
+                .clone()
+                .play(moves[..ply].iter().copied())
+                .numbered_from(start.pieces().len(), 1)
+                .with_caption(caption);
+            sheet.push(diagram);
+        }
+        sheet
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn push(&mut self, diagram: Diagram) {
+        self.diagrams.push(diagram);
+    }
+
+    pub fn diagrams(&self) -> &[Diagram] {
+        &self.diagrams
+    }
+
+    pub fn to_svg(&self, style: &Style) -> String {
+        let (cell_w, cell_h) = self
+            .diagrams
+            .iter()
+            .map(|d| d.size(style))
+            .fold((0.0_f64, 0.0_f64), |(w, h), (dw, dh)| (w.max(dw), h.max(dh)));
+        let gap = 0.5 * style.cell;
+        let top = if self.title.is_some() { 1.2 * style.cell } else { 0.0 };
+        let columns = self.columns.min(self.diagrams.len()).max(1);
+        let rows = self.diagrams.len().div_ceil(columns);
+        let width = columns as f64 * cell_w + (columns - 1) as f64 * gap;
+        let height = top + rows as f64 * cell_h + rows.saturating_sub(1) as f64 * gap;
+
+        let mut out = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif">"#,
+            w = Num(width),
+            h = Num(height)
+        );
+        if self.diagrams.iter().any(Diagram::has_arrows) {
+            arrow_defs(&mut out, style);
+        }
+        if let Some(title) = &self.title {
+            let _ = write!(
+                out,
+                r#"<text class="title" x="{}" y="{}" font-size="{}" font-weight="bold" text-anchor="middle" fill="{}">{}</text>"#,
+                Num(width / 2.0),
+                Num(0.8 * style.cell),
+                Num(0.5 * style.cell),
+                style.text,
+                escape(title)
+            );
+        }
+        for (i, diagram) in self.diagrams.iter().enumerate() {
+            let (row, col) = (i / columns, i % columns);
+            diagram.render(&mut out, col as f64 * (cell_w + gap), top + row as f64 * (cell_h + gap), style);
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dominorio_ai::bitboard::Side;
+
+    #[test]
+    fn a_diagram_every_few_moves_and_at_the_end() {
+        let moves = [
+            Piece::domino(0, Side::Vertical),
+            Piece::domino(2, Side::Horizontal),
+            Piece::domino(20, Side::Vertical),
+            Piece::domino(44, Side::Horizontal),
+            Piece::domino(7, Side::Vertical),
+        ];
+        let sheet = GameSheet::from_moves(&Diagram::dominorio(0), &moves, 2, 2).with_title("Final");
+        assert_eq!(sheet.diagrams().len(), 3);
+        assert_eq!(sheet.diagrams()[2].pieces().len(), 5);
+
+        let style = Style::default();
+        let svg = sheet.to_svg(&style);
+        let (w, h) = sheet.diagrams()[0].size(&style);
+        assert!(svg.starts_with(&format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" "#, Num(2.0 * w + 20.0))));
+        assert!(svg.contains(&format!(r#"<g transform="translate(0 {})">"#, Num(48.0 + h + 20.0))));
+        for caption in [">2. c1<", ">4. e6<", ">5. h1<"] {
+            assert!(svg.contains(caption), "{caption}");
+        }
+        assert_eq!(svg.matches(r#"class="number""#).count(), 2 + 4 + 5);
+        assert_eq!(svg.matches(" last\"").count(), 3);
+    }
+
+    #[test]
+    fn numbering_starts_after_the_start_position() {
+        let start = Diagram::dominorio(0).play([Piece::domino(0, Side::Vertical)]);
+        let sheet = GameSheet::from_moves(&start, &[], 4, 3);
+        assert_eq!(sheet.diagrams().len(), 1);
+        let svg = sheet.to_svg(&Style::default());
+        assert!(svg.contains(">start<") && !svg.contains(r#"class="number""#));
+    }
+}
@@ -0,0 +1,80 @@
+//! Live analysis: one engine searching the shown position on its own thread
+//!
+//! Every complete iteration is sent back as an [`Update`]. Starting a new
+//! analysis stops the previous one; updates of older analyses are dropped by
+//! their generation number.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crjm_ffi::{Engine, GameKind, SearchOptions, SearchReport};
+
+pub struct Update {
+    pub generation: u64,
+    pub report: SearchReport,
+    /// The search ended by itself (proven result or depth limit)
+    pub done: bool,
+}
+
+struct Running {
+    handle: JoinHandle<Engine>,
+    stop: Arc<AtomicBool>,
+}
+
+pub struct Analyser {
+    /// `None` while a search owns it
+    engine: Option<Engine>,
+    running: Option<Running>,
+    generation: u64,
+    tx: Sender<Update>,
+    rx: Receiver<Update>,
+}
+
+impl Analyser {
+    pub fn new(kind: GameKind, hash_mb: usize) -> Self {
+        let (tx, rx) = channel();
+        Self { engine: Some(Engine::new(kind, hash_mb, 0)), running: None, generation: 0, tx, rx }
+    }
+
+    /// Stops the running analysis (if any) and waits for it
+    pub fn stop(&mut self) {
+        if let Some(running) = self.running.take() {
+            running.stop.store(true, Ordering::Relaxed);
+            self.engine = Some(running.handle.join().expect("analysis thread panicked"));
+        }
+        self.generation += 1;
+    }
+
+    /// Analyses `position` until stopped or solved
+    pub fn start(&mut self, position: &str, multipv: usize) {
+        self.stop();
+        let mut engine = self.engine.take().expect("engine owned by the analyser");
+        engine.set_position(position).expect("positions come from the board engine");
+        let options = SearchOptions { time_ms: f64::INFINITY, multipv, ..SearchOptions::default() };
+        let stop = Arc::new(AtomicBool::new(false));
+        let (flag, tx, generation) = (stop.clone(), self.tx.clone(), self.generation);
+        let handle = thread::spawn(move || {
+            let mut progress = |report: &SearchReport| {
+                let _ = tx.send(Update { generation, report: report.clone(), done: false });
+            };
+            let report = engine.search_with(&options, &flag, &mut progress);
+            let done = !flag.load(Ordering::Relaxed);
+            let _ = tx.send(Update { generation, report, done });
+            engine
+        });
+        self.running = Some(Running { handle, stop });
+    }
+
+    /// Updates of the current analysis since the last call
+    pub fn poll(&self) -> Vec<Update> {
+        self.rx.try_iter().filter(|u| u.generation == self.generation).collect()
+    }
+}
+
+impl Drop for Analyser {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
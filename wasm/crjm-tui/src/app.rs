@@ -0,0 +1,317 @@
+//! State of the TUI and the commands typed into it
+//!
+//! The game is kept as a start position plus the moves played from it, so
+//! undo and redo just replay the moves on the board engine.
+
+use std::fs;
+
+use crjm_ffi::{Engine, GameKind, SearchReport, Status};
+
+use crate::analysis::Analyser;
+use crate::record::{self, Loaded, Record};
+
+pub const HELP: &str = "<move> | undo | redo | new | game dominorio|quelhas | position <notation> | \
+                        load <file> | save <file> | savepos <file> | multipv <n> | analyse | play | quit";
+
+const ANALYSIS_HASH_MB: usize = 64;
+
+pub struct App {
+    /// Plays the moves and checks them; never searches
+    board: Engine,
+    /// `None` for the initial position
+    start: Option<String>,
+    moves: Vec<String>,
+    /// Undone moves, the next one last
+    redo: Vec<String>,
+    analyser: Analyser,
+    pub analysing: bool,
+    pub multipv: usize,
+    /// Latest report of the shown position
+    pub analysis: Option<SearchReport>,
+    /// The analysis ended by itself
+    pub solved: bool,
+    /// Cells (row-major) that the last move filled
+    pub last_move: Vec<usize>,
+    pub input: String,
+    pub message: String,
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new(kind: GameKind) -> Self {
+        Self {
+            board: Engine::new(kind, 1, 0),
+            start: None,
+            moves: Vec::new(),
+            redo: Vec::new(),
+            analyser: Analyser::new(kind, ANALYSIS_HASH_MB),
+            analysing: false,
+            multipv: 1,
+            analysis: None,
+            solved: false,
+            last_move: Vec::new(),
+            input: String::new(),
+            message: HELP.to_string(),
+            quit: false,
+        }
+    }
+
+    pub fn kind(&self) -> GameKind {
+        self.board.kind()
+    }
+
+    pub fn position(&self) -> String {
+        self.board.position()
+    }
+
+    pub fn side_to_move(&self) -> u8 {
+        self.board.side_to_move()
+    }
+
+    pub fn status(&self) -> Status {
+        self.board.status()
+    }
+
+    pub fn moves(&self) -> &[String] {
+        &self.moves
+    }
+
+    pub fn record(&self) -> Record {
+        Record { game: self.kind(), start: self.start.clone(), moves: self.moves.clone() }
+    }
+
+    /// Runs the line typed into the input box
+    pub fn submit(&mut self) {
+        let line = std::mem::take(&mut self.input);
+        let line = line.trim();
+        if !line.is_empty() {
+            self.message = match self.command(line) {
+                Ok(message) => message,
+                Err(e) => format!("error: {e}"),
+            };
+        }
+    }
+
+    pub fn command(&mut self, line: &str) -> Result<String, String> {
+        let (word, rest) = line.split_once(' ').map_or((line, ""), |(w, r)| (w, r.trim()));
+        match word {
+            "undo" | "u" => self.undo(),
+            "redo" | "r" => self.redo(),
+            "new" => {
+                self.set_game(Record { game: self.kind(), start: None, moves: Vec::new() })?;
+                Ok("new game".into())
+            }
+            "game" => {
+                let game = GameKind::from_id(rest)
+                    .filter(|g| matches!(g, GameKind::Dominorio | GameKind::Quelhas))
+                    .ok_or_else(|| format!("unknown game '{rest}'"))?;
+                self.set_game(Record { game, start: None, moves: Vec::new() })?;
+                Ok(format!("new {} game", game.id()))
+            }
+            "position" => {
+                self.set_game(Record { game: self.kind(), start: Some(rest.to_string()), moves: Vec::new() })?;
+                Ok("position set".into())
+            }
+            "load" => self.load(rest),
+            "save" => {
+                fs::write(rest, self.record().to_text()).map_err(|e| format!("{rest}: {e}"))?;
+                Ok(format!("game saved to {rest}"))
+            }
+            "savepos" => {
+                fs::write(rest, self.position() + "\n").map_err(|e| format!("{rest}: {e}"))?;
+                Ok(format!("position saved to {rest}"))
+            }
+            "multipv" => {
+                self.multipv = rest.parse::<usize>().ok().filter(|n| (1..=10).contains(n)).ok_or("multipv takes 1 to 10")?;
+                self.position_changed();
+                Ok(format!("multipv {}", self.multipv))
+            }
+            "analyse" | "a" => Ok(self.toggle_analysis().into()),
+            "play" | "p" => {
+                let best = self.analysis.as_ref().and_then(|r| r.best_move.clone()).ok_or("no analysis yet")?;
+                self.play(&best)?;
+                Ok(format!("played {best}"))
+            }
+            "quit" | "q" => {
+                self.quit = true;
+                Ok(String::new())
+            }
+            "help" | "?" => Ok(HELP.into()),
+            _ if rest.is_empty() => {
+                self.play(line)?;
+                Ok(format!("played {line}"))
+            }
+            _ => Err(format!("unknown command '{word}'")),
+        }
+    }
+
+    pub fn play(&mut self, mv: &str) -> Result<(), String> {
+        self.board.apply_move(mv).map_err(|e| e.to_string())?;
+        self.moves.push(mv.to_string());
+        self.redo.clear();
+        self.replay()
+    }
+
+    pub fn undo(&mut self) -> Result<String, String> {
+        let mv = self.moves.pop().ok_or("nothing to undo")?;
+        self.redo.push(mv.clone());
+        self.replay()?;
+        Ok(format!("took back {mv}"))
+    }
+
+    pub fn redo(&mut self) -> Result<String, String> {
+        let mv = self.redo.pop().ok_or("nothing to redo")?;
+        self.moves.push(mv.clone());
+        self.replay()?;
+        Ok(format!("replayed {mv}"))
+    }
+
+    pub fn toggle_analysis(&mut self) -> &'static str {
+        self.analysing = !self.analysing;
+        self.position_changed();
+        if self.analysing {
+            "analysis on"
+        } else {
+            "analysis off"
+        }
+    }
+
+    /// Reads a record (which replaces the game) or a position file
+    fn load(&mut self, path: &str) -> Result<String, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        let record = match record::parse(&text)? {
+            Loaded::Record(record) => record,
+            Loaded::Position(position) => Record { game: self.kind(), start: Some(position), moves: Vec::new() },
+        };
+        self.set_game(record)?;
+        Ok(format!("loaded {path}"))
+    }
+
+    /// Switches to `record`, or leaves everything as it was if it does not
+    /// replay
+    fn set_game(&mut self, record: Record) -> Result<(), String> {
+        let mut board = Engine::new(record.game, 1, 0);
+        if let Some(start) = &record.start {
+            board.set_position(start).map_err(|e| e.to_string())?;
+        }
+        for (i, mv) in record.moves.iter().enumerate() {
+            board.apply_move(mv).map_err(|e| format!("move {}: {e}", i + 1))?;
+        }
+        if record.game != self.kind() {
+            self.analyser = Analyser::new(record.game, ANALYSIS_HASH_MB);
+        }
+        self.board = board;
+        self.start = record.start;
+        self.moves = record.moves;
+        self.redo.clear();
+        self.replay()
+    }
+
+    /// Puts the board on `start` + `moves`, noting the last move's cells
+    fn replay(&mut self) -> Result<(), String> {
+        match &self.start {
+            Some(start) => self.board.set_position(start).map_err(|e| e.to_string())?,
+            None => self.board.new_game(),
+        }
+        let mut before = self.board.position();
+        for mv in &self.moves {
+            before = self.board.position();
+            self.board.apply_move(mv).map_err(|e| e.to_string())?;
+        }
+        self.last_move = if self.moves.is_empty() { Vec::new() } else { changed_cells(&before, &self.board.position()) };
+        self.position_changed();
+        Ok(())
+    }
+
+    /// Restarts (or stops) the analysis for the shown position
+    fn position_changed(&mut self) {
+        self.analysis = None;
+        self.solved = false;
+        if self.analysing && self.status() == Status::Playing {
+            self.analyser.start(&self.position(), self.multipv);
+        } else {
+            self.analyser.stop();
+        }
+    }
+
+    /// Takes in what the analysis found since the last tick
+    pub fn tick(&mut self) {
+        for update in self.analyser.poll() {
+            self.solved = update.done;
+            self.analysis = Some(update.report);
+        }
+    }
+}
+
+/// Indices (row-major, separators skipped) of the cells that differ
+pub fn changed_cells(before: &str, after: &str) -> Vec<usize> {
+    let cells = |p: &str| p.split(' ').next().unwrap_or("").chars().filter(|&c| c != '/').collect::<Vec<_>>();
+    let (before, after) = (cells(before), cells(after));
+    (0..after.len()).filter(|&i| before.get(i) != after.get(i)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_undo_and_redo() {
+        let mut app = App::new(GameKind::Quelhas);
+        assert_eq!(app.command("a1-a3"), Ok("played a1-a3".into()));
+        assert_eq!(app.last_move, vec![0, 10, 20]);
+        assert!(app.command("a1-a3").is_err());
+        app.command("b1-d1").unwrap();
+        let after = app.position();
+        app.command("undo").unwrap();
+        app.command("undo").unwrap();
+        assert!(app.undo().is_err());
+        assert!(app.last_move.is_empty());
+        app.command("redo").unwrap();
+        app.command("redo").unwrap();
+        assert_eq!(app.position(), after);
+        assert_eq!(app.moves(), ["a1-a3", "b1-d1"]);
+    }
+
+    #[test]
+    fn records_and_positions_load_and_save() {
+        let dir = std::env::temp_dir().join(format!("crjm-tui-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (game, position) = (dir.join("game.txt"), dir.join("position.txt"));
+        let (game, position) = (game.to_str().unwrap(), position.to_str().unwrap());
+
+        let mut app = App::new(GameKind::Dominorio);
+        app.command("a1").unwrap();
+        app.command("c1").unwrap();
+        app.command(&format!("save {game}")).unwrap();
+        app.command(&format!("savepos {position}")).unwrap();
+        let shown = app.position();
+
+        let mut other = App::new(GameKind::Dominorio);
+        other.command("game quelhas").unwrap();
+        other.command(&format!("load {game}")).unwrap();
+        assert_eq!((other.kind(), other.position(), other.moves().len()), (GameKind::Dominorio, shown.clone(), 2));
+        other.command(&format!("load {position}")).unwrap();
+        assert_eq!((other.position(), other.moves().len()), (shown, 0));
+        assert!(other.command("load /nonexistent/file").is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn analysis_reports_multipv_lines() {
+        let mut app = App::new(GameKind::Dominorio);
+        app.command("multipv 3").unwrap();
+        app.command("analyse").unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(30);
+        while app.analysis.as_ref().is_none_or(|r| r.depth < 2) {
+            assert!(std::time::Instant::now() < deadline, "no analysis");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            app.tick();
+        }
+        let report = app.analysis.clone().unwrap();
+        assert_eq!(report.lines.len(), 3);
+        app.command("play").unwrap();
+        assert_eq!(app.moves(), [report.best_move.unwrap()]);
+        app.command("analyse").unwrap();
+        assert!(!app.analysing);
+    }
+}
@@ -0,0 +1,67 @@
+//! Terminal board and live analysis for Dominório and Quelhas
+//!
+//! `crjm-tui [dominorio|quelhas] [file]` opens the game (Dominório by
+//! default), optionally loading a game record or position file. Moves and
+//! commands are typed at the prompt; `help` lists them.
+//!
+//! Keys: Enter runs the command, Esc clears it, Tab toggles the analysis,
+//! Ctrl-Z / Ctrl-Y undo and redo, Ctrl-C quits.
+
+use std::io;
+use std::time::Duration;
+
+use crjm_ffi::GameKind;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+
+mod analysis;
+mod app;
+mod record;
+mod ui;
+
+use app::App;
+
+fn main() -> io::Result<()> {
+    let mut app = App::new(GameKind::Dominorio);
+    for arg in std::env::args().skip(1) {
+        let command = match GameKind::from_id(&arg) {
+            Some(_) => format!("game {arg}"),
+            None => format!("load {arg}"),
+        };
+        app.input = command;
+        app.submit();
+    }
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
+
+fn run(terminal: &mut ratatui::DefaultTerminal, app: &mut App) -> io::Result<()> {
+    while !app.quit {
+        app.tick();
+        terminal.draw(|frame| ui::draw(frame, app))?;
+        if !event::poll(Duration::from_millis(50))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else { continue };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl => app.quit = true,
+            KeyCode::Char('z') if ctrl => app.message = app.undo().unwrap_or_else(|e| format!("error: {e}")),
+            KeyCode::Char('y') if ctrl => app.message = app.redo().unwrap_or_else(|e| format!("error: {e}")),
+            KeyCode::Char(c) => app.input.push(c),
+            KeyCode::Backspace => {
+                app.input.pop();
+            }
+            KeyCode::Esc => app.input.clear(),
+            KeyCode::Tab => app.message = app.toggle_analysis().to_string(),
+            KeyCode::Enter => app.submit(),
+            _ => {}
+        }
+    }
+    Ok(())
+}
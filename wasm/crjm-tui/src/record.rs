@@ -0,0 +1,85 @@
+//! Game records and position files
+//!
+//! A record is a few `key value` lines; `start` is `startpos` or a position
+//! in notation, `moves` the moves played from it:
+//!
+//! ```text
+//! game quelhas
+//! start startpos
+//! moves a1-a10 b1-j1 b2-b3
+//! ```
+//!
+//! A position file holds a single position in notation. Blank lines and
+//! lines starting with `#` are ignored in both.
+
+use crjm_ffi::GameKind;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    pub game: GameKind,
+    /// `None` for the initial position
+    pub start: Option<String>,
+    pub moves: Vec<String>,
+}
+
+/// What a file turned out to hold
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Loaded {
+    Record(Record),
+    Position(String),
+}
+
+impl Record {
+    pub fn to_text(&self) -> String {
+        format!(
+            "game {}\nstart {}\nmoves {}\n",
+            self.game.id(),
+            self.start.as_deref().unwrap_or("startpos"),
+            self.moves.join(" ")
+        )
+    }
+}
+
+/// Reads a record or a position file (anything without a `game` line)
+pub fn parse(text: &str) -> Result<Loaded, String> {
+    let lines: Vec<&str> = text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')).collect();
+    if !lines.iter().any(|l| l.starts_with("game ")) {
+        return match lines.as_slice() {
+            [position] => Ok(Loaded::Position(position.to_string())),
+            _ => Err("expected a game record or a single position".into()),
+        };
+    }
+    let mut record = Record { game: GameKind::Dominorio, start: None, moves: Vec::new() };
+    for line in lines {
+        let (key, value) = line.split_once(' ').map_or((line, ""), |(k, v)| (k, v.trim()));
+        match key {
+            "game" => record.game = GameKind::from_id(value).ok_or_else(|| format!("unknown game '{value}'"))?,
+            "start" if value != "startpos" => record.start = Some(value.to_string()),
+            "start" => record.start = None,
+            "moves" => record.moves = value.split_whitespace().map(String::from).collect(),
+            _ => return Err(format!("unknown record line '{line}'")),
+        }
+    }
+    Ok(Loaded::Record(record))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_round_trip() {
+        let record = Record { game: GameKind::Quelhas, start: None, moves: vec!["a1-a10".into(), "b1-j1".into()] };
+        assert_eq!(parse(&record.to_text()), Ok(Loaded::Record(record)));
+        let from = Record { game: GameKind::Dominorio, start: Some("v......./v......./......../......../......../......../......../........ h".into()), moves: vec![] };
+        assert_eq!(parse(&from.to_text()), Ok(Loaded::Record(from)));
+    }
+
+    #[test]
+    fn positions_and_garbage() {
+        assert_eq!(parse("# saved\n\n.../... v\n"), Ok(Loaded::Position(".../... v".into())));
+        assert!(parse("a\nb\n").is_err());
+        assert_eq!(parse("game xadrez\n"), Err("unknown game 'xadrez'".into()));
+        assert!(parse("game quelhas\nclock 5\n").is_err());
+    }
+}
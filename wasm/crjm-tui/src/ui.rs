@@ -0,0 +1,126 @@
+//! Drawing: the board on the left, the analysis and moves on the right, the
+//! message and input line at the bottom
+
+use crjm_ffi::{SearchReport, Status};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::app::App;
+
+const SIDE_NAMES: [&str; 2] = ["vertical", "horizontal"];
+
+fn side_color(side: u8) -> Color {
+    if side == 0 {
+        Color::Blue
+    } else {
+        Color::Red
+    }
+}
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [main, message, input] =
+        Layout::vertical([Constraint::Min(12), Constraint::Length(3), Constraint::Length(3)]).areas(frame.area());
+    let [board, panel] = Layout::horizontal([Constraint::Length(26), Constraint::Min(30)]).areas(main);
+
+    draw_board(frame, app, board);
+    draw_panel(frame, app, panel);
+    frame.render_widget(
+        Paragraph::new(app.message.as_str()).wrap(Wrap { trim: true }).block(Block::bordered()),
+        message,
+    );
+    frame.render_widget(Paragraph::new(format!("> {}", app.input)).block(Block::bordered().title(" command ")), input);
+    frame.set_cursor_position((input.x + 3 + app.input.chars().count() as u16, input.y + 1));
+}
+
+/// Two terminal columns per cell; row 1 on top as in the notation
+fn draw_board(frame: &mut Frame, app: &App, area: Rect) {
+    let position = app.position();
+    let rows: Vec<&str> = position.split(' ').next().unwrap_or("").split('/').collect();
+    let width = rows.first().map_or(0, |r| r.len());
+    let columns: String = (0..width).map(|c| format!("{} ", (b'a' + c as u8) as char)).collect();
+
+    let mut lines = vec![Line::from(format!("    {columns}"))];
+    let mut index = 0;
+    for (r, row) in rows.iter().enumerate() {
+        let mut spans = vec![Span::raw(format!("{:>3} ", r + 1))];
+        for cell in row.chars() {
+            let last = app.last_move.contains(&index);
+            let span = match cell {
+                'v' | 'h' => {
+                    let style = Style::new().fg(side_color(u8::from(cell == 'h')));
+                    Span::styled("██", if last { style.add_modifier(Modifier::REVERSED) } else { style })
+                }
+                _ => Span::styled("· ", Style::new().fg(Color::DarkGray)),
+            };
+            spans.push(span);
+            index += 1;
+        }
+        lines.push(Line::from(spans));
+    }
+    let title = format!(" {} ", app.kind().id());
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(title)), area);
+}
+
+fn draw_panel(frame: &mut Frame, app: &App, area: Rect) {
+    let mut lines = vec![status_line(app), Line::raw("")];
+    if app.analysing {
+        match &app.analysis {
+            Some(report) => lines.extend(analysis_lines(report, app.solved)),
+            None => lines.push(Line::raw("thinking…")),
+        }
+    } else {
+        lines.push(Line::styled("analysis off (Tab)", Style::new().fg(Color::DarkGray)));
+    }
+
+    lines.push(Line::raw(""));
+    lines.push(Line::styled("moves", Style::new().add_modifier(Modifier::BOLD)));
+    let moves: Vec<String> = app.moves().iter().enumerate().map(|(i, mv)| format!("{}.{mv}", i + 1)).collect();
+    lines.push(Line::raw(moves.join(" ")));
+
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(Block::bordered()), area);
+}
+
+fn status_line(app: &App) -> Line<'static> {
+    match app.status() {
+        Status::Playing => {
+            let side = app.side_to_move();
+            Line::from(vec![
+                Span::styled(SIDE_NAMES[side as usize], Style::new().fg(side_color(side))),
+                Span::raw(" to move"),
+            ])
+        }
+        Status::Won(side) => Line::styled(format!("{} wins", SIDE_NAMES[side as usize]), Style::new().fg(side_color(side))),
+        Status::Drawn => Line::raw("draw"),
+    }
+}
+
+fn score_text(score: i32, mate_in: Option<i32>) -> String {
+    match mate_in {
+        Some(n) if n > 0 => format!("wins in {n}"),
+        Some(n) => format!("loses in {}", -n),
+        None => format!("{score:+}"),
+    }
+}
+
+fn analysis_lines(report: &SearchReport, solved: bool) -> Vec<Line<'static>> {
+    let nps = if report.elapsed_ms > 0.0 { (report.nodes as f64 * 1000.0 / report.elapsed_ms) as u64 } else { 0 };
+    let mut lines = vec![
+        Line::raw(format!(
+            "depth {}{}  score {}",
+            report.depth,
+            if solved { " (done)" } else { "" },
+            score_text(report.score, report.mate_in)
+        )),
+        Line::raw(format!("nodes {}  {} n/s  {:.1}s", report.nodes, nps, report.elapsed_ms / 1000.0)),
+        Line::raw(format!("pv {}", report.pv.join(" "))),
+    ];
+    if report.lines.len() > 1 {
+        for (i, (mv, score)) in report.lines.iter().enumerate() {
+            lines.push(Line::raw(format!("{:>2}. {mv:<8} {score:+}", i + 1)));
+        }
+    }
+    lines
+}
@@ -0,0 +1,70 @@
+//! Fixed-depth benchmark: best move, score and nodes on a fixed position set
+//!
+//! The positions come from random play with a fixed seed, so two builds of
+//! the engine can be compared move for move: a search change that keeps the
+//! results should only move the node counts.
+//!
+//! ```text
+//! cargo run --release --example bench -- [depth=9] [positions=24]
+//! ```
+
+use std::time::Instant;
+
+use dominorio_ai::DominorioEngine;
+
+fn legal_moves(occupied: u64, side: u8) -> Vec<u8> {
+    (0..64u8)
+        .filter(|&anchor| {
+            let second = if side == 0 { anchor + 8 } else { anchor + 1 };
+            let fits = if side == 0 { anchor < 56 } else { anchor % 8 != 7 };
+            fits && occupied & (1 << anchor) == 0 && occupied & (1 << second) == 0
+        })
+        .collect()
+}
+
+fn play(occupied: u64, anchor: u8, side: u8) -> u64 {
+    let second = if side == 0 { anchor + 8 } else { anchor + 1 };
+    occupied | (1 << anchor) | (1 << second)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let depth: u32 = args.get(1).and_then(|a| a.parse().ok()).unwrap_or(9);
+    let count: usize = args.get(2).and_then(|a| a.parse().ok()).unwrap_or(24);
+
+    let mut state = 0xBE7C_u64;
+    let mut next = || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 33) as usize
+    };
+    // 4 to 15 random moves from the empty board, the side to move not stuck
+    let mut positions = Vec::new();
+    while positions.len() < count {
+        let plies = 4 + next() % 12;
+        let (mut occupied, mut side) = (0u64, 0u8);
+        for _ in 0..plies {
+            let moves = legal_moves(occupied, side);
+            if moves.is_empty() {
+                break;
+            }
+            occupied = play(occupied, moves[next() % moves.len()], side);
+            side ^= 1;
+        }
+        if !legal_moves(occupied, side).is_empty() {
+            positions.push((occupied, side));
+        }
+    }
+
+    let (mut total_nodes, start) = (0u64, Instant::now());
+    for (i, &(occupied, side)) in positions.iter().enumerate() {
+        let mut engine = DominorioEngine::new(16, 1);
+        let result = engine.search(occupied as u32, (occupied >> 32) as u32, side, 1e9, depth, 0, 0);
+        total_nodes += result.nodes_searched;
+        println!(
+            "{i:3} {occupied:016x} {side}: best {:3} score {:6} nodes {:10}",
+            result.best_move, result.score, result.nodes_searched
+        );
+    }
+    let seconds = start.elapsed().as_secs_f64();
+    println!("depth {depth}: {total_nodes} nodes in {seconds:.1} s ({:.0} knps)", total_nodes as f64 / seconds / 1000.0);
+}
@@ -0,0 +1,18 @@
+//! Builds the small-region tablebase loaded by `DominorioEngine::load_tablebase`
+//!
+//! ```text
+//! cargo run --release --example build_tablebase -- [max_cells=10] [out=domineering.dtbl]
+//! ```
+
+use dominorio_ai::tablebase::{generate, Tablebase};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let max_cells: u8 = args.get(1).and_then(|a| a.parse().ok()).unwrap_or(10);
+    let out = args.get(2).map(String::as_str).unwrap_or("domineering.dtbl");
+
+    let bytes = generate(max_cells);
+    let tb = Tablebase::from_bytes(&bytes).expect("generated tablebase must load");
+    eprintln!("{} regions of up to {} cells, {} bytes", tb.len(), tb.max_cells(), bytes.len());
+    std::fs::write(out, &bytes).unwrap_or_else(|e| panic!("cannot write {}: {}", out, e));
+}
@@ -0,0 +1,124 @@
+//! Self-play calibration of the skill levels
+//!
+//! Plays every level against the anchor levels (0, 4, 8, 12, 16, 20) from
+//! random two-move openings, each opening once with each colour, and fits
+//! maximum-likelihood (Bradley-Terry) ratings to all the results. Those,
+//! unsmoothed, are the `ELO_GAP` table of `src/skill.rs`, and their standard
+//! errors the `ELO_ERROR` table. Run it alone on the machine: the engines
+//! search on the clock, so a busy core weakens whichever side is unlucky.
+//!
+//! ```text
+//! cargo run --release --example calibrate_skill -- [games_per_pair] [move_ms]
+//! ```
+
+use crjm_search::rating::{fit_ratings, rating_errors};
+use dominorio_ai::skill::MAX_SKILL_LEVEL;
+use dominorio_ai::DominorioEngine;
+
+const MAX_DEPTH: u32 = 64;
+
+fn legal_moves(occupied: u64, side: u8) -> Vec<u8> {
+    (0..64u8)
+        .filter(|&anchor| {
+            let (row, col) = (anchor / 8, anchor % 8);
+            let second = if side == 0 {
+                if row == 7 {
+                    return false;
+                }
+                anchor + 8
+            } else {
+                if col == 7 {
+                    return false;
+                }
+                anchor + 1
+            };
+            occupied & (1 << anchor) == 0 && occupied & (1 << second) == 0
+        })
+        .collect()
+}
+
+fn play(occupied: u64, anchor: u8, side: u8) -> u64 {
+    let second = if side == 0 { anchor + 8 } else { anchor + 1 };
+    occupied | (1 << anchor) | (1 << second)
+}
+
+/// Plays one game; returns true if `first` (moving first, as Vertical) wins
+fn game(first: &mut DominorioEngine, second: &mut DominorioEngine, opening: &[u8], move_ms: f64) -> bool {
+    let mut occupied = 0u64;
+    let mut side = 0u8;
+    for &mv in opening {
+        occupied = play(occupied, mv, side);
+        side ^= 1;
+    }
+    loop {
+        let engine = if side == 0 { &mut *first } else { &mut *second };
+        let result = engine.search(occupied as u32, (occupied >> 32) as u32, side, move_ms, MAX_DEPTH, 0, 0);
+        if result.best_move < 0 {
+            // Side to move is stuck and loses
+            return side == 1;
+        }
+        occupied = play(occupied, result.best_move as u8, side);
+        side ^= 1;
+    }
+}
+
+/// Levels every other level plays against
+const ANCHORS: [u8; 6] = [0, 4, 8, 12, 16, 20];
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let openings: usize = args.get(1).and_then(|a| a.parse().ok()).unwrap_or(40) / 2;
+    let move_ms: f64 = args.get(2).and_then(|a| a.parse().ok()).unwrap_or(300.0);
+
+    let mut opening_rng = 0x5EED_u64;
+    let mut next = || {
+        opening_rng = opening_rng.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (opening_rng >> 33) as usize
+    };
+    let mut book = Vec::new();
+    for _ in 0..openings {
+        let v = legal_moves(0, 0);
+        let first = v[next() % v.len()];
+        let h = legal_moves(play(0, first, 0), 1);
+        book.push([first, h[next() % h.len()]]);
+    }
+
+    let levels = MAX_SKILL_LEVEL as usize + 1;
+    // Half a win each way for every pairing played keeps perfect scores finite
+    let mut wins = vec![vec![0.0f64; levels]; levels];
+    for a in 0..levels as u8 {
+        for &b in ANCHORS.iter().filter(|&&b| b > a || (b < a && !ANCHORS.contains(&a))) {
+            let (a_idx, b_idx) = (a as usize, b as usize);
+            wins[a_idx][b_idx] += 0.5;
+            wins[b_idx][a_idx] += 0.5;
+            let mut a_points = 0;
+            for (i, opening) in book.iter().enumerate() {
+                for a_first in [true, false] {
+                    let seed = (a as u32) << 24 | (b as u32) << 16 | (i as u32) << 1 | a_first as u32;
+                    let mut pa = DominorioEngine::new(16, seed);
+                    let mut pb = DominorioEngine::new(16, seed ^ 0xFFFF);
+                    pa.set_skill_level(a);
+                    pb.set_skill_level(b);
+                    let a_won = if a_first {
+                        game(&mut pa, &mut pb, opening, move_ms)
+                    } else {
+                        !game(&mut pb, &mut pa, opening, move_ms)
+                    };
+                    if a_won {
+                        a_points += 1;
+                    }
+                }
+            }
+            let games = book.len() * 2;
+            wins[a_idx][b_idx] += a_points as f64;
+            wins[b_idx][a_idx] += (games - a_points) as f64;
+            println!("level {:2} vs {:2}: {}/{}", a, b, a_points, games);
+        }
+    }
+
+    let ratings = fit_ratings(&wins);
+    let errors = rating_errors(&wins, &ratings);
+    let round = |values: &[f64]| -> Vec<i32> { values.iter().map(|r| (r / 10.0).round() as i32 * 10).collect() };
+    println!("pub const ELO_GAP: [i32; 21] = {:?};", round(&ratings));
+    println!("pub const ELO_ERROR: [i32; 21] = {:?};", round(&errors));
+}
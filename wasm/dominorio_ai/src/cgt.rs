@@ -0,0 +1,244 @@
+//! Combinatorial game theory: canonical forms of short games
+//!
+//! Used to compute the exact values of Domineering regions. Games are
+//! interned in a [`GameTable`] and referred to by [`GameId`]; every game
+//! built through [`GameTable::canonical`] or [`GameTable::add`] is in
+//! canonical form (no dominated or reversible options), so two such games
+//! are equal exactly when they have the same id.
+//!
+//! Left is Vertical and Right is Horizontal, as usual for Domineering.
+
+use std::collections::HashMap;
+
+/// Index of a game in a [`GameTable`]
+pub type GameId = u32;
+
+/// The game `{ | }`, where the player to move loses
+pub const ZERO: GameId = 0;
+
+/// Numbers are stored as multiples of `2^-NUMBER_SHIFT`
+pub const NUMBER_SHIFT: u32 = 16;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Form {
+    left: Vec<GameId>,
+    right: Vec<GameId>,
+}
+
+/// Interned games with memoized comparison and addition
+pub struct GameTable {
+    forms: Vec<Form>,
+    ids: HashMap<Form, GameId>,
+    leq_memo: HashMap<(GameId, GameId), bool>,
+    sum_memo: HashMap<(GameId, GameId), GameId>,
+}
+
+impl GameTable {
+    pub fn new() -> Self {
+        let mut table = GameTable {
+            forms: Vec::new(),
+            ids: HashMap::new(),
+            leq_memo: HashMap::new(),
+            sum_memo: HashMap::new(),
+        };
+        table.intern(Vec::new(), Vec::new());
+        table
+    }
+
+    pub fn len(&self) -> usize {
+        self.forms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.forms.is_empty()
+    }
+
+    pub fn left(&self, g: GameId) -> &[GameId] {
+        &self.forms[g as usize].left
+    }
+
+    pub fn right(&self, g: GameId) -> &[GameId] {
+        &self.forms[g as usize].right
+    }
+
+    /// Id of `{ left | right }` as given (not simplified)
+    fn intern(&mut self, mut left: Vec<GameId>, mut right: Vec<GameId>) -> GameId {
+        left.sort_unstable();
+        left.dedup();
+        right.sort_unstable();
+        right.dedup();
+        let form = Form { left, right };
+        if let Some(&id) = self.ids.get(&form) {
+            return id;
+        }
+        let id = self.forms.len() as GameId;
+        self.forms.push(form.clone());
+        self.ids.insert(form, id);
+        id
+    }
+
+    /// `g <= h`: no left option of `g` is `>= h` and no right option of `h` is `<= g`
+    pub fn leq(&mut self, g: GameId, h: GameId) -> bool {
+        if g == h {
+            return true;
+        }
+        if let Some(&known) = self.leq_memo.get(&(g, h)) {
+            return known;
+        }
+        let result = !self.forms[g as usize].left.clone().into_iter().any(|gl| self.leq(h, gl))
+            && !self.forms[h as usize].right.clone().into_iter().any(|hr| self.leq(hr, g));
+        self.leq_memo.insert((g, h), result);
+        result
+    }
+
+    /// Canonical form of `{ left | right }`, whose options must be canonical
+    pub fn canonical(&mut self, mut left: Vec<GameId>, mut right: Vec<GameId>) -> GameId {
+        loop {
+            left.sort_unstable();
+            left.dedup();
+            right.sort_unstable();
+            right.dedup();
+            // Dominated options: Left keeps her largest, Right his smallest
+            let keep_left: Vec<GameId> = left
+                .iter()
+                .copied()
+                .filter(|&a| !left.iter().any(|&b| b != a && self.leq(a, b)))
+                .collect();
+            let keep_right: Vec<GameId> = right
+                .iter()
+                .copied()
+                .filter(|&a| !right.iter().any(|&b| b != a && self.leq(b, a)))
+                .collect();
+            left = keep_left;
+            right = keep_right;
+
+            // Reversible options are bypassed: a left option whose right
+            // option is <= g is replaced by that option's left options
+            let g = self.intern(left.clone(), right.clone());
+            if let Some((i, glr)) = self.find_reversible(g, &left, true) {
+                left.remove(i);
+                left.extend_from_slice(&self.forms[glr as usize].left.clone());
+                continue;
+            }
+            if let Some((i, grl)) = self.find_reversible(g, &right, false) {
+                right.remove(i);
+                right.extend_from_slice(&self.forms[grl as usize].right.clone());
+                continue;
+            }
+            return g;
+        }
+    }
+
+    fn find_reversible(&mut self, g: GameId, options: &[GameId], for_left: bool) -> Option<(usize, GameId)> {
+        for (i, &opt) in options.iter().enumerate() {
+            let replies = if for_left {
+                self.forms[opt as usize].right.clone()
+            } else {
+                self.forms[opt as usize].left.clone()
+            };
+            for reply in replies {
+                let reverses = if for_left { self.leq(reply, g) } else { self.leq(g, reply) };
+                if reverses {
+                    return Some((i, reply));
+                }
+            }
+        }
+        None
+    }
+
+    /// Canonical form of `g + h`
+    pub fn add(&mut self, g: GameId, h: GameId) -> GameId {
+        if g == ZERO {
+            return h;
+        }
+        if h == ZERO {
+            return g;
+        }
+        let key = (g.min(h), g.max(h));
+        if let Some(&sum) = self.sum_memo.get(&key) {
+            return sum;
+        }
+        let (gf, hf) = (self.forms[g as usize].clone(), self.forms[h as usize].clone());
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        for &gl in &gf.left {
+            left.push(self.add(gl, h));
+        }
+        for &hl in &hf.left {
+            left.push(self.add(g, hl));
+        }
+        for &gr in &gf.right {
+            right.push(self.add(gr, h));
+        }
+        for &hr in &hf.right {
+            right.push(self.add(g, hr));
+        }
+        let sum = self.canonical(left, right);
+        self.sum_memo.insert(key, sum);
+        sum
+    }
+
+    /// Value of `g` in units of `2^-NUMBER_SHIFT` if it is a number
+    ///
+    /// Only valid for canonical games: integers are `{n-1 | }` or `{ | n+1}`
+    /// and other numbers `{a | b}` with `a < b` the two nearest dyadics.
+    pub fn number(&self, g: GameId) -> Option<i32> {
+        let form = &self.forms[g as usize];
+        match (form.left.as_slice(), form.right.as_slice()) {
+            ([], []) => Some(0),
+            ([l], []) => self.number(*l).filter(|&n| n >= 0).map(|n| n + (1 << NUMBER_SHIFT)),
+            ([], [r]) => self.number(*r).filter(|&n| n <= 0).map(|n| n - (1 << NUMBER_SHIFT)),
+            ([l], [r]) => {
+                let (a, b) = (self.number(*l)?, self.number(*r)?);
+                (a < b && (a + b) % 2 == 0).then_some((a + b) / 2)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for GameTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_values() {
+        let mut t = GameTable::new();
+        let one = t.canonical(vec![ZERO], vec![]);
+        let minus_one = t.canonical(vec![], vec![ZERO]);
+        let star = t.canonical(vec![ZERO], vec![ZERO]);
+        let half = t.canonical(vec![ZERO], vec![one]);
+        assert_eq!(t.number(one), Some(1 << NUMBER_SHIFT));
+        assert_eq!(t.number(half), Some(1 << (NUMBER_SHIFT - 1)));
+        assert_eq!(t.number(star), None);
+
+        // 1/2 + 1/2 = 1, 1 + -1 = 0, * + * = 0
+        assert_eq!(t.add(half, half), one);
+        assert_eq!(t.add(one, minus_one), ZERO);
+        assert_eq!(t.add(star, star), ZERO);
+        // 0 < 1/2 < 1, and * is confused with 0
+        assert!(t.leq(ZERO, half) && !t.leq(half, ZERO));
+        assert!(t.leq(half, one) && !t.leq(one, half));
+        assert!(!t.leq(star, ZERO) && !t.leq(ZERO, star));
+    }
+
+    #[test]
+    fn test_dominated_and_reversible_options_are_removed() {
+        let mut t = GameTable::new();
+        let one = t.canonical(vec![ZERO], vec![]);
+        let two = t.canonical(vec![one], vec![]);
+        // { 0, 1 | } = { 1 | } = 2: the option 0 is dominated by 1
+        assert_eq!(t.canonical(vec![ZERO, one], vec![]), two);
+
+        // {{2|0} | }: Right answers Left's only option with 0, which is <= the
+        // whole game, so the option reverses out and nothing is left: 0
+        let two_zero = t.canonical(vec![two], vec![ZERO]);
+        assert_eq!(t.canonical(vec![two_zero], vec![]), ZERO);
+    }
+}
@@ -1,100 +1,198 @@
-//! Search engine: negamax with alpha-beta, iterative deepening, TT
+//! Search engine: Domineering on the shared alpha-beta search (`crjm_search`)
 
-use crate::bitboard::{apply_move, count_moves, generate_moves, Side};
-use crate::eval::{evaluate, score_move_for_ordering, INF, MATE_SCORE};
-use crate::tt::{TTFlag, TranspositionTable};
-use crate::zobrist::ZobristKeys;
+use crjm_search::{pick_root, Aspiration, Game, Limits, ProgressFn};
 
-/// External time function (from JS)
-extern "C" {
-    #[allow(improper_ctypes)]
-    fn now() -> f64;
+use crate::bitboard::{apply_move, count_moves, generate_moves, is_valid_move, Side};
+use crate::eval::{evaluate, score_move_for_ordering, MATE_SCORE, MAX_PLY};
+use crate::mistakes::MistakeModel;
+use crate::rng::Rng;
+use crate::skill::{self, SkillParams};
+use crate::tablebase::{Probe, Tablebase, TB_WIN};
+use crate::tt::TranspositionTable;
+use crate::zobrist::{SymmetricHash, ZobristKeys};
+
+/// Current time in milliseconds (JS clock in the browser, system clock natively)
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> f64 {
+    js_sys::Date::now()
+}
+
+/// Current time in milliseconds (JS clock in the browser, system clock natively)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
 }
 
 /// Search result
 pub struct SearchResult {
     pub best_move: Option<u8>,
+    /// Principal variation, starting with `best_move`
+    pub pv: Vec<u8>,
     pub score: i32,
     pub depth_reached: u32,
     pub nodes_searched: u64,
     pub tt_hits: u64,
     pub tt_probes: u64,
+    /// The mistake model replaced the best move
+    pub blunder: bool,
 }
 
-/// Killer moves storage (2 per ply)
-struct KillerMoves {
-    moves: [[Option<u8>; 2]; 64],
+/// A position as seen by the search: the mirror hashes travel with the board
+#[derive(Clone, Copy)]
+struct Position {
+    occupied: u64,
+    side: Side,
+    hash: SymmetricHash,
 }
 
-impl KillerMoves {
-    fn new() -> Self {
-        KillerMoves {
-            moves: [[None; 2]; 64],
-        }
+/// Domineering for the shared search
+///
+/// The table is keyed on the canonical (mirror-independent) hash, so stored
+/// moves are in the canonical frame and mapped back onto each board.
+struct Dominorio<'a> {
+    zobrist: &'a ZobristKeys,
+    tablebase: Option<Probe<'a>>,
+    /// Salt and amplitude of the skill level's evaluation noise
+    noise: Option<(u64, i32)>,
+}
+
+impl Game for Dominorio<'_> {
+    type Position = Position;
+    type Move = u8;
+    type TableScore = i16;
+    type TableMove = u8;
+    
+    const MATE: i32 = MATE_SCORE;
+    const MATE_BOUND: i32 = MATE_SCORE - MAX_PLY;
+    const PROBE_LEAVES: bool = false;
+    
+    fn moves(&mut self, pos: &Position) -> Vec<u8> {
+        generate_moves(pos.occupied, pos.side)
     }
     
-    fn add(&mut self, ply: usize, mv: u8) {
-        if ply < 64 {
-            if self.moves[ply][0] != Some(mv) {
-                self.moves[ply][1] = self.moves[ply][0];
-                self.moves[ply][0] = Some(mv);
-            }
+    fn has_moves(&mut self, pos: &Position) -> bool {
+        count_moves(pos.occupied, pos.side) > 0
+    }
+    
+    fn is_legal(&mut self, pos: &Position, mv: u8) -> bool {
+        is_valid_move(pos.occupied, mv, pos.side)
+    }
+    
+    fn apply(&self, pos: &Position, mv: u8) -> Position {
+        Position {
+            occupied: apply_move(pos.occupied, mv, pos.side),
+            side: pos.side.opposite(),
+            hash: self.zobrist.update_symmetric_hash(&pos.hash, mv, pos.side),
         }
     }
     
-    fn is_killer(&self, ply: usize, mv: u8) -> bool {
-        if ply < 64 {
-            self.moves[ply][0] == Some(mv) || self.moves[ply][1] == Some(mv)
-        } else {
-            false
+    fn stuck(&self, _pos: &Position, ply: u32) -> i32 {
+        -MATE_SCORE + ply as i32
+    }
+    
+    fn evaluate(&mut self, pos: &Position, key: u64, ply: u32) -> i32 {
+        // Opponent is already stuck: our next move wins
+        if count_moves(pos.occupied, pos.side.opposite()) == 0 {
+            return MATE_SCORE - (ply as i32 + 1);
         }
+        if let Some(wins) = self.tablebase.as_mut().and_then(|tb| tb.side_to_move_wins(pos.occupied, pos.side)) {
+            return if wins { TB_WIN } else { -TB_WIN };
+        }
+        let noise = match self.noise {
+            Some((salt, amplitude)) => skill::eval_noise(key, salt, amplitude),
+            None => 0,
+        };
+        evaluate(pos.occupied, pos.side) + noise
+    }
+    
+    fn key(&self, pos: &Position) -> u64 {
+        pos.hash.canonical().0
+    }
+    
+    fn pack_move(&self, pos: &Position, mv: u8) -> u8 {
+        pos.hash.canonical().1.map_move(mv, pos.side)
+    }
+    
+    fn unpack_move(&self, pos: &Position, mv: u8) -> u8 {
+        // Every symmetry is its own inverse
+        pos.hash.canonical().1.map_move(mv, pos.side)
+    }
+    
+    fn order_score(&mut self, pos: &Position, mv: u8, _depth: u32) -> i32 {
+        score_move_for_ordering(pos.occupied, mv, pos.side)
     }
 }
 
 /// Searcher state
 pub struct Searcher<'a> {
-    tt: &'a mut TranspositionTable,
-    zobrist: &'a ZobristKeys,
-    age: u8,
-    deadline: f64,
-    max_depth: u32,
-    nodes: u64,
-    killers: KillerMoves,
-    aborted: bool,
+    search: crjm_search::Searcher<'a, Dominorio<'a>>,
+    rng: &'a mut Rng,
+    skill: Option<SkillParams>,
+    mistakes: Option<MistakeModel>,
 }
 
 impl<'a> Searcher<'a> {
     pub fn new(
         tt: &'a mut TranspositionTable,
         zobrist: &'a ZobristKeys,
-        age: u8,
+        rng: &'a mut Rng,
         deadline: f64,
         max_depth: u32,
     ) -> Self {
-        tt.reset_stats();
-        Searcher {
-            tt,
+        let game = Dominorio {
             zobrist,
-            age,
-            deadline,
-            max_depth,
-            nodes: 0,
-            killers: KillerMoves::new(),
-            aborted: false,
+            tablebase: None,
+            noise: None,
+        };
+        Searcher {
+            search: crjm_search::Searcher::new(game, tt, Limits::new(deadline, max_depth))
+                .with_aspiration(Aspiration { initial: 150, min: 60, max: 1200 }),
+            rng,
+            skill: None,
+            mistakes: None,
         }
     }
     
-    /// Check if we should abort due to time
-    #[inline]
-    fn check_time(&mut self) -> bool {
-        if self.nodes & 1023 == 0 {
-            let current = unsafe { now() };
-            if current >= self.deadline {
-                self.aborted = true;
-                return true;
-            }
-        }
-        false
+    /// Play at a reduced skill level instead of picking among `top_n`
+    pub fn with_skill(mut self, params: SkillParams) -> Self {
+        self.search.game_mut().noise = Some((self.rng.next_u64(), params.eval_noise));
+        self.search.limits_mut().node_budget = params.node_budget;
+        self.search = self.search.with_multipv(params.multipv);
+        self.skill = Some(params);
+        self
+    }
+    
+    /// Sometimes play a human-like mistake instead of the chosen move
+    pub fn with_mistakes(mut self, model: MistakeModel) -> Self {
+        self.mistakes = Some(model);
+        self
+    }
+    
+    /// Score leaves exactly when all their regions are in the tablebase
+    pub fn with_tablebase(mut self, tb: &'a Tablebase) -> Self {
+        self.search.game_mut().tablebase = Some(Probe::new(tb));
+        self
+    }
+    
+    /// Stop after `nodes` nodes (once the first iteration is complete)
+    pub fn with_node_budget(mut self, nodes: u64) -> Self {
+        self.search.limits_mut().node_budget = nodes;
+        self
+    }
+    
+    /// Give the best `multipv` root moves exact scores (MultiPV analysis)
+    pub fn with_multipv(mut self, multipv: usize) -> Self {
+        self.search = self.search.with_multipv(multipv);
+        self
+    }
+    
+    /// Report every complete iteration (depth, score, PV) while searching
+    pub fn with_progress(mut self, progress: &'a mut ProgressFn<'a, u8>) -> Self {
+        self.search = self.search.with_progress(progress);
+        self
     }
     
     /// Iterative deepening search
@@ -105,250 +203,64 @@ impl<'a> Searcher<'a> {
         top_n: u32,
         score_delta: i32,
     ) -> SearchResult {
-        let mut best_move = None;
-        let mut best_score = -INF;
-        let mut depth_reached = 0;
-        
-        // Collect all root moves with scores for randomization
-        let mut root_moves: Vec<(u8, i32)> = Vec::new();
-        
-        for depth in 1..=self.max_depth {
-            self.aborted = false;
-            
-            let score = self.search_root(occupied, side, depth, &mut root_moves);
-            
-            if self.aborted {
-                break;
-            }
-            
-            depth_reached = depth;
-            best_score = score;
-            
-            // Get best move from root_moves
-            if let Some(&(mv, _)) = root_moves.first() {
-                best_move = Some(mv);
-            }
-        }
-        
-        // Apply randomization if requested
-        if top_n > 0 && !root_moves.is_empty() {
-            let candidates: Vec<_> = root_moves
-                .iter()
-                .take(top_n as usize)
-                .filter(|(_, s)| best_score - *s <= score_delta)
-                .collect();
-            
-            if candidates.len() > 1 {
-                // Simple random selection using node count as entropy
-                let idx = (self.nodes as usize) % candidates.len();
-                best_move = Some(candidates[idx].0);
-                best_score = candidates[idx].1;
-            }
-        }
-        
-        SearchResult {
-            best_move,
-            score: best_score,
-            depth_reached,
-            nodes_searched: self.nodes,
-            tt_hits: self.tt.hits(),
-            tt_probes: self.tt.probes(),
-        }
+        self.iterative_deepening_with_clock(occupied, side, top_n, score_delta, now)
     }
     
-    /// Search at root with move sorting
-    fn search_root(
+    /// [`iterative_deepening`](Self::iterative_deepening) against another
+    /// clock (the deadline is on it); a clock that jumps past the deadline
+    /// stops the search
+    pub fn iterative_deepening_with_clock(
         &mut self,
         occupied: u64,
         side: Side,
-        depth: u32,
-        root_moves: &mut Vec<(u8, i32)>,
-    ) -> i32 {
-        let moves = generate_moves(occupied, side);
-        
-        if moves.is_empty() {
-            return -MATE_SCORE;
-        }
-        
-        // Score and sort moves
-        let mut scored_moves: Vec<(u8, i32)> = moves
-            .iter()
-            .map(|&mv| (mv, score_move_for_ordering(occupied, mv, side)))
-            .collect();
+        top_n: u32,
+        score_delta: i32,
+        now: impl Fn() -> f64,
+    ) -> SearchResult {
+        let hash = self.search.game().zobrist.symmetric_hash(occupied, side);
+        let outcome = self.search.iterative_deepening(Position { occupied, side, hash }, now);
+        let mut best_move = outcome.best_move;
+        let mut best_score = outcome.score;
+        let completed = outcome.root;
         
-        // Use previous iteration ordering if available
-        if !root_moves.is_empty() {
-            for (i, (mv, _)) in root_moves.iter().enumerate() {
-                if let Some(pos) = scored_moves.iter().position(|(m, _)| m == mv) {
-                    scored_moves[pos].1 += 1000000 - (i as i32 * 1000);
-                }
+        if let Some(skill) = self.skill {
+            // Only the first `multipv` scores are exact; the rest are bounds
+            let candidates = &completed[..skill.multipv.min(completed.len())];
+            if candidates.len() > 1 {
+                let idx = skill::softmax_choice(candidates, skill.temperature, self.rng);
+                best_move = Some(candidates[idx].0);
+                best_score = candidates[idx].1;
             }
+        } else if let Some(idx) = pick_root(completed.iter().map(|&(_, s)| s), top_n, score_delta, self.rng) {
+            best_move = Some(completed[idx].0);
+            best_score = completed[idx].1;
         }
         
-        scored_moves.sort_by(|a, b| b.1.cmp(&a.1));
-        
-        let mut alpha = -INF;
-        let beta = INF;
-        let mut best_move = scored_moves[0].0;
-        
-        root_moves.clear();
-        
-        for (mv, _) in &scored_moves {
-            let new_occupied = apply_move(occupied, *mv, side);
-            let hash = self.zobrist.hash(new_occupied, side.opposite());
-            
-            let score = -self.negamax(new_occupied, side.opposite(), hash, depth - 1, -beta, -alpha, 1);
-            
-            if self.aborted {
-                return alpha;
-            }
-            
-            root_moves.push((*mv, score));
-            
-            if score > alpha {
-                alpha = score;
-                best_move = *mv;
+        let mut blunder = false;
+        if let Some(model) = self.mistakes {
+            let idx = model.choose(occupied, side, &completed, self.rng);
+            if idx > 0 {
+                best_move = Some(completed[idx].0);
+                best_score = completed[idx].1;
+                blunder = true;
             }
         }
         
-        // Sort root_moves by score for next iteration
-        root_moves.sort_by(|a, b| b.1.cmp(&a.1));
-        
-        alpha
-    }
-    
-    /// Negamax with alpha-beta pruning
-    fn negamax(
-        &mut self,
-        occupied: u64,
-        side: Side,
-        hash: u64,
-        depth: u32,
-        mut alpha: i32,
-        beta: i32,
-        ply: u32,
-    ) -> i32 {
-        self.nodes += 1;
-        
-        if self.check_time() {
-            return 0;
-        }
-        
-        // Terminal check
-        let my_moves = count_moves(occupied, side);
-        if my_moves == 0 {
-            return -MATE_SCORE + ply as i32;
+        let mut pv = outcome.pv;
+        if best_move != pv.first().copied() {
+            // Another move was chosen: the variation does not apply
+            pv = best_move.into_iter().collect();
         }
-        
-        // Depth 0: evaluate
-        if depth == 0 {
-            return evaluate(occupied, side);
-        }
-        
-        // TT probe
-        let tt_move = self.tt.get_tt_move(hash);
-        if let Some(entry) = self.tt.probe(hash) {
-            if entry.depth >= depth as u8 {
-                let score = entry.score as i32;
-                match entry.flag {
-                    TTFlag::Exact => return score,
-                    TTFlag::Lower => {
-                        if score >= beta {
-                            return score;
-                        }
-                    }
-                    TTFlag::Upper => {
-                        if score <= alpha {
-                            return score;
-                        }
-                    }
-                }
-            }
-        }
-        
-        // Generate and order moves
-        let moves = generate_moves(occupied, side);
-        let ordered_moves = self.order_moves(occupied, &moves, side, tt_move, ply as usize);
-        
-        let mut best_move = None;
-        let mut best_score = -INF;
-        let original_alpha = alpha;
-        
-        for mv in ordered_moves {
-            let new_occupied = apply_move(occupied, mv, side);
-            let new_hash = self.zobrist.update_hash(hash, mv, side);
-            
-            let score = -self.negamax(new_occupied, side.opposite(), new_hash, depth - 1, -beta, -alpha, ply + 1);
-            
-            if self.aborted {
-                return 0;
-            }
-            
-            if score > best_score {
-                best_score = score;
-                best_move = Some(mv);
-            }
-            
-            if score > alpha {
-                alpha = score;
-            }
-            
-            if alpha >= beta {
-                // Beta cutoff - update killers
-                self.killers.add(ply as usize, mv);
-                break;
-            }
+        SearchResult {
+            best_move,
+            pv,
+            score: best_score,
+            depth_reached: outcome.depth,
+            nodes_searched: outcome.stats.nodes,
+            tt_hits: outcome.stats.tt_hits,
+            tt_probes: outcome.stats.tt_probes,
+            blunder,
         }
-        
-        // TT store
-        let flag = if best_score <= original_alpha {
-            TTFlag::Upper
-        } else if best_score >= beta {
-            TTFlag::Lower
-        } else {
-            TTFlag::Exact
-        };
-        
-        self.tt.store(hash, best_move, depth as u8, flag, best_score as i16, self.age);
-        
-        best_score
-    }
-    
-    /// Order moves for better pruning
-    fn order_moves(
-        &self,
-        occupied: u64,
-        moves: &[u8],
-        side: Side,
-        tt_move: Option<u8>,
-        ply: usize,
-    ) -> Vec<u8> {
-        let mut scored: Vec<(u8, i32)> = moves
-            .iter()
-            .map(|&mv| {
-                let mut score = 0i32;
-                
-                // TT move gets highest priority
-                if tt_move == Some(mv) {
-                    score += 10_000_000;
-                }
-                
-                // Killer moves get high priority
-                if self.killers.is_killer(ply, mv) {
-                    score += 1_000_000;
-                }
-                
-                // Otherwise use heuristic
-                if score == 0 {
-                    score = score_move_for_ordering(occupied, mv, side);
-                }
-                
-                (mv, score)
-            })
-            .collect();
-        
-        scored.sort_by(|a, b| b.1.cmp(&a.1));
-        scored.into_iter().map(|(mv, _)| mv).collect()
     }
 }
 
@@ -365,16 +277,127 @@ mod tests {
     fn test_search_finds_winning_move() {
         // This is a basic sanity test
         // Real tests would need more sophisticated setups
-        let mut tt = TranspositionTable::new(1024);
+        let mut tt = TranspositionTable::new(1);
         let zobrist = ZobristKeys::new();
+        let mut rng = Rng::new(0);
         
         // Empty board search should not panic
-        let mut searcher = Searcher::new(&mut tt, &zobrist, 1, f64::MAX, 3);
+        let mut searcher = Searcher::new(&mut tt, &zobrist, &mut rng, f64::MAX, 3);
         let result = searcher.iterative_deepening(0, Side::Vertical, 0, 0);
         
         assert!(result.best_move.is_some());
         assert!(result.depth_reached >= 1);
     }
+    
+    #[test]
+    fn test_skill_level_stops_at_node_budget() {
+        let mut tt = TranspositionTable::new(1);
+        let zobrist = ZobristKeys::new();
+        let mut rng = Rng::new(4);
+        let params = skill::params(5);
+        
+        let result = Searcher::new(&mut tt, &zobrist, &mut rng, f64::MAX, 64)
+            .with_skill(params)
+            .iterative_deepening(0, Side::Vertical, 0, 0);
+        
+        assert!(result.best_move.is_some());
+        assert!(result.depth_reached >= 1);
+        assert!(result.nodes_searched <= params.node_budget);
+    }
+    
+    #[test]
+    fn test_progress_and_stopping_clock() {
+        let mut tt = TranspositionTable::new(1);
+        let zobrist = ZobristKeys::new();
+        let mut rng = Rng::new(0);
+        let stop = std::cell::Cell::new(false);
+        let mut depths = Vec::new();
+        let mut progress = |p: &crjm_search::Progress<u8>| {
+            depths.push(p.depth);
+            // stop the search from the outside after the third iteration
+            stop.set(p.depth == 3);
+        };
+        let clock = || if stop.get() { f64::INFINITY } else { 0.0 };
+        let result = Searcher::new(&mut tt, &zobrist, &mut rng, f64::MAX, 64)
+            .with_progress(&mut progress)
+            .iterative_deepening_with_clock(0, Side::Vertical, 0, 0, clock);
+        
+        assert_eq!(depths, vec![1, 2, 3]);
+        assert_eq!(result.depth_reached, 3);
+        assert!(result.best_move.is_some());
+    }
+    
+    #[test]
+    fn test_mate_distance_is_reported() {
+        let mut tt = TranspositionTable::new(1);
+        let zobrist = ZobristKeys::new();
+        let mut rng = Rng::new(0);
+        
+        // Only the vertical slot at squares 0/8 remains: Vertical plays it and
+        // Horizontal is stuck
+        let occupied = !((1u64 << 0) | (1u64 << 8));
+        let mut searcher = Searcher::new(&mut tt, &zobrist, &mut rng, f64::MAX, 4);
+        let result = searcher.iterative_deepening(occupied, Side::Vertical, 0, 0);
+        
+        assert_eq!(result.best_move, Some(0));
+        assert_eq!(result.score, MATE_SCORE - 1);
+        assert_eq!(crate::eval::mate_in(result.score), Some(1));
+    }
+    
+    #[test]
+    fn test_tablebase_score_is_the_same_at_every_ply() {
+        let tb = Tablebase::from_bytes(&crate::tablebase::generate(8)).unwrap();
+        let zobrist = ZobristKeys::new();
+        let mut rng = Rng::new(0);
+        
+        // Four separate 2x3 regions: every leaf of a shallow search is in the
+        // tablebase and nobody runs out of moves within four plies
+        let region = 0b111 | 0b111 << 8;
+        let occupied = ![0, 5, 32, 37].iter().fold(0u64, |acc, &sq| acc | region << sq);
+        let wins = Probe::new(&tb).side_to_move_wins(occupied, Side::Vertical).unwrap();
+        let expected = if wins { TB_WIN } else { -TB_WIN };
+        
+        // Leaves at ply 2 and at ply 4 score alike, with and without the TT
+        // entries of the shallower search
+        let mut tt = TranspositionTable::new(1);
+        for depth in [2, 4] {
+            let result = Searcher::new(&mut tt, &zobrist, &mut rng, f64::MAX, depth)
+                .with_tablebase(&tb)
+                .iterative_deepening(occupied, Side::Vertical, 0, 0);
+            assert_eq!((result.depth_reached, result.score), (depth, expected));
+        }
+        let mut fresh = TranspositionTable::new(1);
+        let result = Searcher::new(&mut fresh, &zobrist, &mut rng, f64::MAX, 4)
+            .with_tablebase(&tb)
+            .iterative_deepening(occupied, Side::Vertical, 0, 0);
+        assert_eq!(result.score, expected);
+    }
+    
+    #[test]
+    fn test_mirrored_position_reuses_tt() {
+        use crate::zobrist::Symmetry;
+        
+        let mut tt = TranspositionTable::new(1);
+        let zobrist = ZobristKeys::new();
+        let mut rng = Rng::new(0);
+        
+        // Empty 3x4 region in the top-left corner, the rest is filled
+        let region = (0..3).fold(0u64, |acc, row| acc | (0x0Fu64 << (row * 8)));
+        let occupied = !region;
+        let mirrored = (0..64u8)
+            .filter(|&sq| occupied & (1u64 << sq) != 0)
+            .fold(0u64, |acc, sq| acc | (1u64 << Symmetry::MirrorLeftRight.map_square(sq)));
+        
+        let first = Searcher::new(&mut tt, &zobrist, &mut rng, f64::MAX, 12)
+            .iterative_deepening(occupied, Side::Vertical, 0, 0);
+        tt.new_search();
+        let second = Searcher::new(&mut tt, &zobrist, &mut rng, f64::MAX, 12)
+            .iterative_deepening(mirrored, Side::Vertical, 0, 0);
+        
+        assert_eq!(first.score, second.score);
+        assert!(second.tt_hits > 0);
+        assert!(second.nodes_searched < first.nodes_searched);
+        let mv = second.best_move.unwrap();
+        assert!(crate::bitboard::is_valid_move(mirrored, mv, Side::Vertical));
+    }
 }
-
-
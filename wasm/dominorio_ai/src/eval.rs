@@ -2,12 +2,35 @@
 
 use crate::bitboard::{count_moves, Side};
 
-/// Infinity score for winning positions
-pub const INF: i32 = 30000;
-
-/// Mate score base (actual mate score is INF - ply)
+/// Mate score base (actual mate score is MATE_SCORE - ply)
 pub const MATE_SCORE: i32 = 29000;
 
+/// Longest possible game in plies (32 dominoes fill the 8x8 board)
+pub const MAX_PLY: i32 = 64;
+
+/// Whether a score encodes a forced win or loss rather than a heuristic value
+#[inline]
+pub fn is_mate_score(score: i32) -> bool {
+    score.abs() >= MATE_SCORE - MAX_PLY
+}
+
+/// Convert a search score into the side to move's turns until the game ends
+///
+/// Every turn left to the side to move counts, including one where it is
+/// stuck: at least 1 when it wins (its last move leaves the opponent
+/// stuck), at most -1 when it loses (-1: stuck now). Returns `None` for
+/// heuristic scores.
+pub fn mate_in(score: i32) -> Option<i32> {
+    if !is_mate_score(score) {
+        return None;
+    }
+    if score > 0 {
+        Some((MATE_SCORE - score + 1) / 2)
+    } else {
+        Some(-((MATE_SCORE + score) / 2 + 1))
+    }
+}
+
 /// Evaluate a position from the perspective of the side to move
 /// Positive = good for side to move
 pub fn evaluate(occupied: u64, side: Side) -> i32 {
@@ -38,7 +61,7 @@ pub fn evaluate(occupied: u64, side: Side) -> i32 {
 
 /// Count "safe" moves - runs of 2+ empty squares in orientation
 /// Each run of length N gives floor(N/2) guaranteed moves
-fn count_safe_moves(occupied: u64, side: Side) -> u32 {
+pub fn count_safe_moves(occupied: u64, side: Side) -> u32 {
     let mut safe = 0;
     
     match side {
@@ -152,7 +175,7 @@ pub fn score_move_for_ordering(occupied: u64, anchor: u8, side: Side) -> i32 {
     // Centrality bonus for early game
     let row = anchor / 8;
     let col = anchor % 8;
-    let center_dist = ((row as i32 - 3).abs() + (col as i32 - 4).abs()) as i32;
+    let center_dist = (row as i32 - 3).abs() + (col as i32 - 4).abs();
     score -= center_dist * 5;
     
     score
@@ -164,9 +187,11 @@ mod tests {
     
     #[test]
     fn test_empty_board_eval() {
+        // The weights favour the opponent's mobility, so the empty board is
+        // not even: it scores the same for both sides and is not a mate
         let score = evaluate(0, Side::Vertical);
-        // Should be roughly even (slight advantage to mover due to turn)
-        assert!(score.abs() < 100);
+        assert_eq!(score, evaluate(0, Side::Horizontal));
+        assert!(score < 0 && !is_mate_score(score));
     }
     
     #[test]
@@ -180,6 +205,18 @@ mod tests {
         assert!(score <= -MATE_SCORE + 100);
     }
     
+    #[test]
+    fn test_mate_in() {
+        // Side to move is already stuck
+        assert_eq!(mate_in(-MATE_SCORE), Some(-1));
+        // Opponent is stuck after our next move
+        assert_eq!(mate_in(MATE_SCORE - 1), Some(1));
+        // We make two more moves and are stuck on our third turn
+        assert_eq!(mate_in(-MATE_SCORE + 4), Some(-3));
+        assert_eq!(mate_in(MATE_SCORE - 5), Some(3));
+        assert_eq!(mate_in(150), None);
+    }
+    
     #[test]
     fn test_safe_moves() {
         // Empty board should have maximum safe moves
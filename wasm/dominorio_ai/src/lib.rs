@@ -2,16 +2,25 @@
 //!
 //! High-performance AI for 8x8 Domineering using:
 //! - Bitboard representation (u64)
-//! - Negamax with alpha-beta pruning
+//! - Negamax with alpha-beta, PVS and aspiration windows, shared with the
+//!   other engines through `crjm_search`
 //! - Iterative deepening with time control
 //! - Zobrist hashing and transposition table
 //! - Move ordering heuristics
 
-mod bitboard;
-mod engine;
-mod eval;
-mod tt;
-mod zobrist;
+pub mod bitboard;
+pub mod cgt;
+pub mod engine;
+pub mod eval;
+mod mistakes;
+pub mod puzzle;
+pub mod review;
+pub use crjm_search::rng;
+pub mod skill;
+pub mod state;
+pub mod tablebase;
+pub mod tt;
+pub mod zobrist;
 
 use wasm_bindgen::prelude::*;
 
@@ -19,11 +28,10 @@ use wasm_bindgen::prelude::*;
 extern "C" {
     #[wasm_bindgen(js_namespace = console)]
     fn log(s: &str);
-    
-    #[wasm_bindgen(js_namespace = Date)]
-    fn now() -> f64;
 }
 
+use engine::now;
+
 /// Initialize panic hook for better error messages
 #[wasm_bindgen(start)]
 pub fn init() {
@@ -41,13 +49,21 @@ pub struct SearchResult {
     pub tt_hits: u64,
     pub tt_probes: u64,
     pub score: i32,
+    /// Permille of the transposition table filled by this search
+    pub hashfull: u32,
+    /// Turns of the side to move until a proven win (≥ 1) or loss (≤ -1)
+    pub mate_in: Option<i32>,
+    /// The move is a deliberate mistake (see `set_blunder_rate`)
+    pub blunder: bool,
+    pv: Vec<i32>,
 }
 
 #[wasm_bindgen]
 impl SearchResult {
+    /// Principal variation, starting with `best_move` (empty if there is none)
     #[wasm_bindgen(getter)]
     pub fn pv(&self) -> Vec<i32> {
-        vec![self.best_move]
+        self.pv.clone()
     }
     
     #[wasm_bindgen(getter)]
@@ -60,31 +76,204 @@ impl SearchResult {
     }
 }
 
+/// Post-game review returned to JavaScript, one entry per move in each array
+#[wasm_bindgen]
+pub struct GameReview {
+    inner: review::GameReview,
+}
+
+#[wasm_bindgen]
+impl GameReview {
+    /// Engine's preferred move before each played move (-1 if none)
+    #[wasm_bindgen(getter)]
+    pub fn best_moves(&self) -> Vec<i32> {
+        self.inner.moves.iter().map(|m| m.best_move.map_or(-1, |mv| mv as i32)).collect()
+    }
+    
+    /// Score of the best move, for the side that played
+    #[wasm_bindgen(getter)]
+    pub fn best_scores(&self) -> Vec<i32> {
+        self.inner.moves.iter().map(|m| m.best_score).collect()
+    }
+    
+    /// Score of the played move, for the side that played
+    #[wasm_bindgen(getter)]
+    pub fn played_scores(&self) -> Vec<i32> {
+        self.inner.moves.iter().map(|m| m.played_score).collect()
+    }
+    
+    /// Points each move lost against the best move
+    #[wasm_bindgen(getter)]
+    pub fn eval_drops(&self) -> Vec<i32> {
+        self.inner.moves.iter().map(|m| m.eval_drop).collect()
+    }
+    
+    /// 0 = best, 1 = good, 2 = inaccuracy, 3 = mistake, 4 = blunder
+    #[wasm_bindgen(getter)]
+    pub fn classes(&self) -> Vec<u8> {
+        self.inner.moves.iter().map(|m| m.class as u8).collect()
+    }
+    
+    /// Proven outcome for the mover before each move: 1 win, -1 loss, 0 unknown
+    #[wasm_bindgen(getter)]
+    pub fn outcomes_before(&self) -> Vec<i8> {
+        self.inner.moves.iter().map(|m| m.outcome_before.sign()).collect()
+    }
+    
+    /// Proven outcome for the mover after each move: 1 win, -1 loss, 0 unknown
+    #[wasm_bindgen(getter)]
+    pub fn outcomes_after(&self) -> Vec<i8> {
+        self.inner.moves.iter().map(|m| m.outcome_after.sign()).collect()
+    }
+    
+    /// Index of the move that decided the game, if the search proved one
+    #[wasm_bindgen(getter)]
+    pub fn critical_ply(&self) -> Option<u32> {
+        self.inner.critical_ply.map(|p| p as u32)
+    }
+}
+
+/// Largest transposition table the engine will allocate
+const MAX_TT_MB: u32 = 256;
+
 /// The AI engine instance (persistent across calls)
 #[wasm_bindgen]
 pub struct DominorioEngine {
     tt: tt::TranspositionTable,
     zobrist: zobrist::ZobristKeys,
-    search_age: u8,
+    rng: rng::Rng,
+    seed: u32,
+    skill_level: Option<u8>,
+    mistakes: Option<mistakes::MistakeModel>,
+    tablebase: Option<tablebase::Tablebase>,
 }
 
 #[wasm_bindgen]
 impl DominorioEngine {
-    /// Create a new engine with specified TT size (in entries, power of 2)
+    /// Create a new engine with a transposition table of `tt_mb` megabytes
+    ///
+    /// `seed` drives every random choice the engine makes: two engines with
+    /// the same seed given the same sequence of requests (with depth rather
+    /// than time limits) play the same moves.
     #[wasm_bindgen(constructor)]
-    pub fn new(tt_size_bits: u32) -> DominorioEngine {
-        let tt_size = 1usize << tt_size_bits.min(20); // Max 1M entries
+    pub fn new(tt_mb: u32, seed: u32) -> DominorioEngine {
         DominorioEngine {
-            tt: tt::TranspositionTable::new(tt_size),
+            tt: tt::TranspositionTable::new(tt_mb.min(MAX_TT_MB) as usize),
             zobrist: zobrist::ZobristKeys::new(),
-            search_age: 0,
+            rng: rng::Rng::new(seed as u64),
+            seed,
+            skill_level: None,
+            mistakes: None,
+            tablebase: None,
         }
     }
     
+    /// Seed the engine was created (or last reseeded) with
+    #[wasm_bindgen(getter)]
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+    
+    /// Restart the random stream from `seed`, e.g. to replay a reported game
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed;
+        self.rng = rng::Rng::new(seed as u64);
+    }
+    
+    /// Play at skill level `level` (0 = beginner, 20 = full strength)
+    ///
+    /// While a level is set, `search` ignores `top_n` and `score_delta`.
+    /// The transposition table is cleared, since weakened searches store
+    /// noisy scores.
+    pub fn set_skill_level(&mut self, level: u8) {
+        self.skill_level = Some(level.min(skill::MAX_SKILL_LEVEL));
+        self.tt.clear();
+    }
+    
+    /// Go back to the `top_n` / `score_delta` randomization of `search`
+    pub fn clear_skill_level(&mut self) {
+        if self.skill_level.take().is_some() {
+            self.tt.clear();
+        }
+    }
+    
+    /// Current skill level, if one is set
+    #[wasm_bindgen(getter)]
+    pub fn skill_level(&self) -> Option<u8> {
+        self.skill_level
+    }
+    
+    /// Measured rating of `level` relative to level 20 (from self-play)
+    pub fn skill_elo_gap(level: u8) -> i32 {
+        skill::elo_gap(level)
+    }
+    
+    /// Standard error of `skill_elo_gap(level)`
+    pub fn skill_elo_error(level: u8) -> i32 {
+        skill::elo_error(level)
+    }
+    
+    /// Make human-like mistakes in about `rate` of the moves (0 = never)
+    ///
+    /// Mistakes are more likely in complex positions and favour moves that
+    /// waste or give away safe moves; moves that lose on the spot are never
+    /// chosen.
+    pub fn set_blunder_rate(&mut self, rate: f64) {
+        self.mistakes = (rate > 0.0).then(|| mistakes::MistakeModel::new(rate));
+    }
+    
+    /// Target mistake rate (0 when the mistake model is off)
+    #[wasm_bindgen(getter)]
+    pub fn blunder_rate(&self) -> f64 {
+        self.mistakes.map_or(0.0, |m| m.blunder_rate)
+    }
+    
     /// Clear the transposition table
     pub fn clear_tt(&mut self) {
         self.tt.clear();
-        self.search_age = 0;
+    }
+    
+    /// Reallocate the transposition table with `tt_mb` megabytes (clears it)
+    pub fn resize_tt(&mut self, tt_mb: u32) {
+        self.tt.resize(tt_mb.min(MAX_TT_MB) as usize);
+    }
+    
+    /// Permille of the transposition table used by the current search generation
+    pub fn hashfull(&self) -> u32 {
+        self.tt.hashfull()
+    }
+    
+    /// Serialize the transposition table (and its hashing keys) into a
+    /// versioned, checksummed snapshot
+    pub fn export_state(&self) -> Vec<u8> {
+        state::export(&self.tt, &self.zobrist)
+    }
+    
+    /// Restore a snapshot produced by `export_state`
+    ///
+    /// Corrupted, truncated or non-Dominório snapshots are rejected and leave
+    /// the engine untouched.
+    pub fn import_state(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        self.try_import_state(bytes).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+    
+    /// Load a small-region tablebase (see `examples/build_tablebase.rs`)
+    ///
+    /// Once loaded, search leaves whose regions are all in the table are
+    /// scored exactly. Invalid files are rejected and keep the current table.
+    pub fn load_tablebase(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        self.try_load_tablebase(bytes).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+    
+    /// Stop using the loaded tablebase
+    pub fn unload_tablebase(&mut self) {
+        self.tablebase = None;
+    }
+    
+    /// Largest region size in the loaded tablebase (0 when none is loaded)
+    #[wasm_bindgen(getter)]
+    pub fn tablebase_max_cells(&self) -> u8 {
+        self.tablebase.as_ref().map_or(0, |tb| tb.max_cells())
     }
     
     /// Search for the best move
@@ -97,6 +286,7 @@ impl DominorioEngine {
     /// * `max_depth` - Maximum search depth
     /// * `top_n` - Number of top moves for randomization (0 = best only)
     /// * `score_delta` - Score window for move randomization
+    #[allow(clippy::too_many_arguments)]
     pub fn search(
         &mut self,
         occupied_low: u32,
@@ -110,17 +300,26 @@ impl DominorioEngine {
         let occupied = ((occupied_high as u64) << 32) | (occupied_low as u64);
         let side = if side == 0 { bitboard::Side::Vertical } else { bitboard::Side::Horizontal };
         
-        self.search_age = self.search_age.wrapping_add(1);
+        self.tt.new_search();
         
         let deadline = now() + time_budget_ms;
         
         let mut searcher = engine::Searcher::new(
             &mut self.tt,
             &self.zobrist,
-            self.search_age,
+            &mut self.rng,
             deadline,
             max_depth,
         );
+        if let Some(level) = self.skill_level {
+            searcher = searcher.with_skill(skill::params(level));
+        }
+        if let Some(model) = self.mistakes {
+            searcher = searcher.with_mistakes(model);
+        }
+        if let Some(tb) = &self.tablebase {
+            searcher = searcher.with_tablebase(tb);
+        }
         
         let result = searcher.iterative_deepening(occupied, side, top_n, score_delta);
         
@@ -132,9 +331,31 @@ impl DominorioEngine {
             tt_hits: result.tt_hits,
             tt_probes: result.tt_probes,
             score: result.score,
+            hashfull: self.tt.hashfull(),
+            mate_in: eval::mate_in(result.score),
+            blunder: result.blunder,
+            pv: result.pv.iter().map(|&m| m as i32).collect(),
         }
     }
     
+    /// Review a game played from the empty board: every position is searched
+    /// for up to `time_per_position_ms` and `max_depth`, and every move graded
+    ///
+    /// `moves` are anchors, `first_side` the side that moved first. Reviews
+    /// always use full strength, whatever the skill level, with a table and
+    /// random stream of their own: they leave the game's untouched.
+    pub fn review_game(
+        &self,
+        moves: &[u8],
+        first_side: u8,
+        time_per_position_ms: f64,
+        max_depth: u32,
+    ) -> Result<GameReview, JsValue> {
+        self.try_review_game(moves, first_side, time_per_position_ms, max_depth)
+            .map(|inner| GameReview { inner })
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+    
     /// Get the number of legal moves for a position
     pub fn count_moves(&self, occupied_low: u32, occupied_high: u32, side: u8) -> u32 {
         let occupied = ((occupied_high as u64) << 32) | (occupied_low as u64);
@@ -155,24 +376,172 @@ impl DominorioEngine {
     }
 }
 
+impl DominorioEngine {
+    /// Native counterpart of `import_state` for tools that persist sessions to disk
+    pub fn try_import_state(&mut self, bytes: &[u8]) -> Result<(), state::StateError> {
+        let (tt, zobrist) = state::import(bytes)?;
+        self.tt = tt;
+        self.zobrist = zobrist;
+        Ok(())
+    }
+    
+    /// Native counterpart of `load_tablebase`
+    pub fn try_load_tablebase(&mut self, bytes: &[u8]) -> Result<(), tablebase::TablebaseError> {
+        self.tablebase = Some(tablebase::Tablebase::from_bytes(bytes)?);
+        Ok(())
+    }
+    
+    /// Native counterpart of `review_game`
+    pub fn try_review_game(
+        &self,
+        moves: &[u8],
+        first_side: u8,
+        time_per_position_ms: f64,
+        max_depth: u32,
+    ) -> Result<review::GameReview, review::ReviewError> {
+        let first = if first_side == 0 { bitboard::Side::Vertical } else { bitboard::Side::Horizontal };
+        // The game's table may hold scores searched with skill-level noise and
+        // the game's random stream must not move: the review gets its own
+        let mut tt = self.tt.empty_like();
+        let mut rng = rng::Rng::new(self.seed as u64);
+        review::review_game(moves, first, |occupied, side| {
+            tt.new_search();
+            let deadline = now() + time_per_position_ms;
+            let mut searcher = engine::Searcher::new(&mut tt, &self.zobrist, &mut rng, deadline, max_depth);
+            if let Some(tb) = &self.tablebase {
+                searcher = searcher.with_tablebase(tb);
+            }
+            let result = searcher.iterative_deepening(occupied, side, 0, 0);
+            (result.best_move, result.score)
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     
     #[test]
     fn test_engine_creation() {
-        let engine = DominorioEngine::new(16);
+        let engine = DominorioEngine::new(1, 0);
         assert_eq!(engine.count_moves(0, 0, 0), 56); // Vertical has 56 moves on empty board
         assert_eq!(engine.count_moves(0, 0, 1), 56); // Horizontal has 56 moves on empty board
     }
     
     #[test]
     fn test_game_over() {
-        let engine = DominorioEngine::new(16);
+        let engine = DominorioEngine::new(1, 0);
         // Empty board is not game over
         assert!(!engine.is_game_over(0, 0, 0));
         assert!(!engine.is_game_over(0, 0, 1));
     }
+    
+    #[test]
+    fn test_same_seed_replays_randomized_choices() {
+        let play = |seed: u32| -> Vec<i32> {
+            let mut engine = DominorioEngine::new(1, seed);
+            (0..6)
+                .map(|_| engine.search(0, 0, 0, f64::MAX, 2, 8, 1000).best_move)
+                .collect()
+        };
+        
+        assert_eq!(play(11), play(11));
+        assert_ne!(play(11), play(12));
+    }
+    
+    #[test]
+    fn test_review_finds_the_losing_move() {
+        // A full-strength engine against a random mover, then reviewed
+        let mut engine = DominorioEngine::new(4, 5);
+        let mut rng = rng::Rng::new(17);
+        let (mut occupied, mut side) = (0u64, 0u8);
+        let mut moves = Vec::new();
+        loop {
+            let best = if side == 0 {
+                engine.search(occupied as u32, (occupied >> 32) as u32, 0, f64::MAX, 4, 0, 0).best_move
+            } else {
+                let legal = bitboard::generate_moves(occupied, bitboard::Side::Horizontal);
+                if legal.is_empty() { -1 } else { legal[rng.below(legal.len())] as i32 }
+            };
+            if best < 0 {
+                break;
+            }
+            let s = if side == 0 { bitboard::Side::Vertical } else { bitboard::Side::Horizontal };
+            occupied = bitboard::apply_move(occupied, best as u8, s);
+            moves.push(best as u8);
+            side ^= 1;
+        }
+        let loser = side;
+        
+        let review = engine.try_review_game(&moves, 0, f64::MAX, 6).unwrap();
+        assert_eq!(review.moves.len(), moves.len());
+        for m in &review.moves {
+            assert!(m.eval_drop >= 0);
+            if m.best_move == Some(m.played) {
+                assert_eq!(m.class, review::MoveClass::Best);
+            }
+        }
+        // Proven results are exact, so only the loser can walk into a proven loss
+        let critical = review.critical_ply.expect("the end of the game is solved");
+        assert_eq!(review.moves[critical].side, loser);
+        assert_eq!(review.moves[critical].outcome_after, review::Outcome::Loss);
+        
+        assert!(engine.try_review_game(&[60], 0, f64::MAX, 1).is_err());
+    }
+    
+    #[test]
+    fn test_review_ignores_the_games_table_and_random_stream() {
+        let mut rng = rng::Rng::new(2);
+        let (mut occupied, mut side) = (0u64, bitboard::Side::Vertical);
+        let mut moves = Vec::new();
+        for _ in 0..16 {
+            let legal = bitboard::generate_moves(occupied, side);
+            let mv = legal[rng.below(legal.len())];
+            moves.push(mv);
+            occupied = bitboard::apply_move(occupied, mv, side);
+            side = side.opposite();
+        }
+        let clean = DominorioEngine::new(4, 3).try_review_game(&moves, 0, f64::MAX, 3).unwrap();
+        
+        // Level 0 searches along the game fill the table with noisy scores
+        // and draw from the random stream
+        let mut played = DominorioEngine::new(4, 3);
+        let mut twin = DominorioEngine::new(4, 3);
+        for engine in [&mut played, &mut twin] {
+            engine.set_skill_level(0);
+            let (mut occupied, mut side) = (0u64, bitboard::Side::Vertical);
+            for &mv in &moves {
+                engine.search(occupied as u32, (occupied >> 32) as u32, side as u8, f64::MAX, 8, 0, 0);
+                occupied = bitboard::apply_move(occupied, mv, side);
+                side = side.opposite();
+            }
+        }
+        
+        assert_eq!(played.try_review_game(&moves, 0, f64::MAX, 3).unwrap(), clean);
+        let next = |e: &mut DominorioEngine| e.search(0, 0, 0, f64::MAX, 6, 0, 0).best_move;
+        assert_eq!(next(&mut played), next(&mut twin));
+    }
+    
+    #[test]
+    fn test_tablebase_keeps_endgame_results() {
+        let mut plain = DominorioEngine::new(4, 1);
+        let mut with_tb = DominorioEngine::new(4, 1);
+        with_tb.try_load_tablebase(&tablebase::generate(8)).unwrap();
+        assert_eq!(with_tb.tablebase_max_cells(), 8);
+        assert!(with_tb.try_load_tablebase(b"DTBL").is_err());
+        assert_eq!(with_tb.tablebase_max_cells(), 8);
+        
+        let mut rng = rng::Rng::new(9);
+        for _ in 0..20 {
+            let mut occupied = !0u64;
+            for _ in 0..22 {
+                occupied &= !(1u64 << rng.below(64));
+            }
+            let (lo, hi) = (occupied as u32, (occupied >> 32) as u32);
+            let exact = plain.search(lo, hi, 0, f64::MAX, 12, 0, 0);
+            let probed = with_tb.search(lo, hi, 0, f64::MAX, 12, 0, 0);
+            assert!(eval::is_mate_score(exact.score));
+            assert_eq!(exact.score > 0, probed.score > 0, "{:#x}", occupied);
+        }
+    }
 }
-
-
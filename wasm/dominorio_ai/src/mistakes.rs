@@ -0,0 +1,181 @@
+//! Human-like mistakes for beginner difficulty
+//!
+//! Rather than a uniformly random near-best move, a beginner blunders
+//! where humans do: more often in complex positions (many moves, several
+//! that look equally good) and preferably with moves that waste their own
+//! safe moves or hand safe moves to the opponent. Moves that lose on the
+//! spot are never chosen, so the mistakes look plausible instead of absurd.
+
+use crate::bitboard::{apply_move, count_moves, generate_moves_bb, Side};
+use crate::eval::count_safe_moves;
+use crate::rng::Rng;
+
+/// Root moves within this many points of the best look equally good
+const CLOSE_SCORE: i32 = 40;
+
+/// Mistake settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MistakeModel {
+    /// Target fraction of moves that are not the engine's best move
+    pub blunder_rate: f64,
+}
+
+impl MistakeModel {
+    pub fn new(blunder_rate: f64) -> Self {
+        MistakeModel {
+            blunder_rate: blunder_rate.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Probability of blundering in a position of the given complexity
+    ///
+    /// Complexity averages about one half over a game, so the overall rate
+    /// stays close to `blunder_rate`.
+    pub fn blunder_probability(&self, complexity: f64) -> f64 {
+        (self.blunder_rate * (0.5 + complexity)).min(1.0)
+    }
+
+    /// Replace the best move (first in `root_moves`, sorted best first) by a
+    /// plausible mistake, or keep it; returns the index of the move to play
+    pub fn choose(&self, occupied: u64, side: Side, root_moves: &[(u8, i32)], rng: &mut Rng) -> usize {
+        if root_moves.len() < 2 || self.blunder_rate <= 0.0 {
+            return 0;
+        }
+        if rng.next_f64() >= self.blunder_probability(complexity(root_moves)) {
+            return 0;
+        }
+
+        let weights: Vec<(usize, u32)> = root_moves
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|&(_, &(mv, _))| !is_suicidal(occupied, mv, side))
+            .map(|(i, &(mv, _))| (i, 1 + 2 * give_away(occupied, mv, side)))
+            .collect();
+        let total: u32 = weights.iter().map(|&(_, w)| w).sum();
+        if total == 0 {
+            return 0;
+        }
+        let mut pick = rng.below(total as usize) as u32;
+        for (i, w) in weights {
+            if pick < w {
+                return i;
+            }
+            pick -= w;
+        }
+        0
+    }
+}
+
+/// How hard the position is for a beginner, from 0 to 1: half from the
+/// number of legal moves, half from how many moves score close to the best
+pub fn complexity(root_moves: &[(u8, i32)]) -> f64 {
+    let Some(&(_, best)) = root_moves.first() else {
+        return 0.0;
+    };
+    let breadth = (root_moves.len() as f64 / 40.0).min(1.0);
+    let close = root_moves.iter().filter(|&&(_, s)| best - s <= CLOSE_SCORE).count();
+    let ambiguity = ((close - 1) as f64 / 4.0).min(1.0);
+    0.5 * breadth + 0.5 * ambiguity
+}
+
+/// Cells the opponent of `side` could still cover with a domino
+fn reachable_by(occupied: u64, side: Side) -> u64 {
+    let anchors = generate_moves_bb(occupied, side);
+    match side {
+        Side::Vertical => anchors | (anchors << 8),
+        Side::Horizontal => anchors | (anchors << 1),
+    }
+}
+
+/// Moves `side` keeps no matter what the opponent does: runs of cells the
+/// opponent can no longer reach
+pub fn safe_moves(occupied: u64, side: Side) -> u32 {
+    count_safe_moves(occupied | reachable_by(occupied, side.opposite()), side)
+}
+
+/// Safe moves the move spends for `side` plus those it creates for the opponent
+pub fn give_away(occupied: u64, mv: u8, side: Side) -> u32 {
+    let after = apply_move(occupied, mv, side);
+    let opp = side.opposite();
+    let spent = safe_moves(occupied, side).saturating_sub(safe_moves(after, side));
+    let gifted = safe_moves(after, opp).saturating_sub(safe_moves(occupied, opp));
+    spent + gifted
+}
+
+/// A move that loses on the spot: `side` is left without a move of its own
+/// while the opponent can still play
+pub fn is_suicidal(occupied: u64, mv: u8, side: Side) -> bool {
+    let after = apply_move(occupied, mv, side);
+    count_moves(after, side) == 0 && count_moves(after, side.opposite()) > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::generate_moves;
+
+    /// Random position reached by `plies` random moves (None if the game ended)
+    fn random_position(rng: &mut Rng, plies: usize) -> Option<(u64, Side)> {
+        let mut occupied = 0u64;
+        let mut side = Side::Vertical;
+        for _ in 0..plies {
+            let moves = generate_moves(occupied, side);
+            if moves.is_empty() {
+                return None;
+            }
+            occupied = apply_move(occupied, moves[rng.below(moves.len())], side);
+            side = side.opposite();
+        }
+        (count_moves(occupied, side) > 1).then_some((occupied, side))
+    }
+
+    #[test]
+    fn test_blunder_rate_is_close_to_target() {
+        let mut rng = Rng::new(21);
+        let model = MistakeModel::new(0.3);
+        let (mut blunders, mut positions) = (0, 0);
+        for i in 0..2_000 {
+            let Some((occupied, side)) = random_position(&mut rng, i % 24) else {
+                continue;
+            };
+            let root: Vec<(u8, i32)> = generate_moves(occupied, side)
+                .into_iter()
+                .enumerate()
+                .map(|(k, mv)| (mv, -(k as i32) * 15))
+                .collect();
+            positions += 1;
+            if model.choose(occupied, side, &root, &mut rng) != 0 {
+                blunders += 1;
+            }
+        }
+        let rate = blunders as f64 / positions as f64;
+        assert!((0.2..0.4).contains(&rate), "{}", rate);
+    }
+
+    #[test]
+    fn test_never_plays_a_suicidal_move() {
+        // Free cells: column 0 rows 0-3 (Vertical only) and 62/63 (Horizontal
+        // only). Vertical anchor 8 splits its column into singles and leaves it
+        // stuck while Horizontal can still move
+        let occupied = !(1u64 | 1 << 8 | 1 << 16 | 1 << 24 | 1 << 62 | 1 << 63);
+        assert!(is_suicidal(occupied, 8, Side::Vertical));
+        assert!(!is_suicidal(occupied, 16, Side::Vertical));
+
+        let root = [(0u8, 0), (8u8, -10), (16u8, -20)];
+        let model = MistakeModel::new(1.0);
+        let mut rng = Rng::new(1);
+        for _ in 0..200 {
+            assert_ne!(model.choose(occupied, Side::Vertical, &root, &mut rng), 1);
+        }
+    }
+
+    #[test]
+    fn test_wasting_safe_moves_counts_as_giving_them_away() {
+        // Column 0 is Vertical's alone (column 1 is full): playing there spends
+        // a safe move, playing in the open board does not
+        let column_1 = 0x0202_0202_0202_0202u64;
+        assert_eq!(safe_moves(column_1, Side::Vertical), 4);
+        assert!(give_away(column_1, 0, Side::Vertical) >= 1);
+    }
+}
@@ -0,0 +1,336 @@
+//! Puzzle generator: endgame positions with exactly one winning move
+//!
+//! Positions are sampled from randomized self-play games once few cells are
+//! left in play. Every legal move is then solved exactly (searching to the end
+//! of the game), and the position is kept only if a single move wins. The
+//! difficulty is the length of the forced win, i.e. how deep one has to look
+//! to see it. Losing moves that look best to a static evaluation come with the
+//! reply that refutes them.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::bitboard::{apply_move, generate_moves, generate_moves_bb, Side};
+use crate::engine::Searcher;
+use crate::eval::{evaluate, is_mate_score};
+use crate::rng::Rng;
+use crate::tt::TranspositionTable;
+use crate::zobrist::ZobristKeys;
+
+/// Tempting wrong moves listed per puzzle
+const MAX_REFUTATIONS: usize = 3;
+
+/// Puzzle grade from the length of the forced win
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    /// Wins in up to 3 plies are easy, up to 7 medium, longer ones hard
+    pub fn from_depth(depth: u32) -> Difficulty {
+        match depth {
+            0..=3 => Difficulty::Easy,
+            4..=7 => Difficulty::Medium,
+            _ => Difficulty::Hard,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+}
+
+/// A losing move and the opponent's reply that proves it loses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Refutation {
+    pub mv: u8,
+    pub reply: u8,
+}
+
+/// A position where exactly one move wins for the side to move
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub occupied: u64,
+    /// Side to move (0 = Vertical, 1 = Horizontal)
+    pub side: u8,
+    /// Winning line: the key move, then best defence and winning replies
+    /// until the loser is out of moves
+    pub solution: Vec<u8>,
+    /// Plies of the forced win (the length of `solution`)
+    pub depth: u32,
+    /// Most tempting wrong moves, best-looking first
+    pub refutations: Vec<Refutation>,
+}
+
+impl Puzzle {
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty::from_depth(self.depth)
+    }
+}
+
+/// Cells some domino of either side could still cover
+pub fn live_cells(occupied: u64) -> u64 {
+    let v = generate_moves_bb(occupied, Side::Vertical);
+    let h = generate_moves_bb(occupied, Side::Horizontal);
+    v | (v << 8) | h | (h << 1)
+}
+
+fn side_from(side: u8) -> Side {
+    if side == 0 {
+        Side::Vertical
+    } else {
+        Side::Horizontal
+    }
+}
+
+/// Samples and solves positions; owns its own transposition table
+pub struct PuzzleGenerator {
+    tt: TranspositionTable,
+    zobrist: ZobristKeys,
+    rng: Rng,
+    /// Positions are analysed once at most this many cells are still in play
+    pub max_live_cells: u32,
+    /// Shortest forced win (in plies) worth keeping
+    pub min_depth: u32,
+}
+
+impl PuzzleGenerator {
+    pub fn new(seed: u64, max_live_cells: u32) -> Self {
+        PuzzleGenerator {
+            tt: TranspositionTable::new(32),
+            zobrist: ZobristKeys::new(),
+            rng: Rng::new(seed),
+            max_live_cells,
+            min_depth: 3,
+        }
+    }
+
+    /// Exact best move and score for the side to move
+    fn solve(&mut self, occupied: u64, side: Side) -> (Option<u8>, i32) {
+        // Every move covers two live cells, so this depth reaches the end
+        let depth = live_cells(occupied).count_ones() / 2 + 1;
+        self.tt.new_search();
+        let result = Searcher::new(&mut self.tt, &self.zobrist, &mut self.rng, f64::MAX, depth)
+            .iterative_deepening(occupied, side, 0, 0);
+        (result.best_move, result.score)
+    }
+
+    /// The puzzle at this position, if exactly one move wins
+    pub fn analyse(&mut self, occupied: u64, side: u8) -> Option<Puzzle> {
+        let side = side_from(side);
+        let moves = generate_moves(occupied, side);
+        if moves.len() < 2 {
+            return None;
+        }
+
+        let mut winner = None;
+        let mut losers = Vec::new();
+        for &mv in &moves {
+            let child = apply_move(occupied, mv, side);
+            let (reply, score) = self.solve(child, side.opposite());
+            debug_assert!(is_mate_score(score), "solve must reach the end of the game");
+            if score < 0 {
+                if winner.is_some() {
+                    return None;
+                }
+                winner = Some(mv);
+            } else if let Some(reply) = reply {
+                // Tempting = looks good for the mover without search
+                losers.push((-evaluate(child, side.opposite()), Refutation { mv, reply }));
+            }
+        }
+        let winner = winner?;
+
+        let solution = self.winning_line(occupied, side, winner);
+        let depth = solution.len() as u32;
+        if depth < self.min_depth {
+            return None;
+        }
+        losers.sort_by_key(|&(looks, _)| std::cmp::Reverse(looks));
+        Some(Puzzle {
+            occupied,
+            side: side as u8,
+            solution,
+            depth,
+            refutations: losers.into_iter().take(MAX_REFUTATIONS).map(|(_, r)| r).collect(),
+        })
+    }
+
+    /// `first`, then the engine's moves for both sides until the game ends
+    fn winning_line(&mut self, occupied: u64, side: Side, first: u8) -> Vec<u8> {
+        let mut line = vec![first];
+        let mut occupied = apply_move(occupied, first, side);
+        let mut side = side.opposite();
+        while let (Some(mv), _) = self.solve(occupied, side) {
+            line.push(mv);
+            occupied = apply_move(occupied, mv, side);
+            side = side.opposite();
+        }
+        line
+    }
+
+    /// Plays one randomized self-play game and returns the puzzles found
+    /// along its endgame
+    pub fn sample_game(&mut self) -> Vec<Puzzle> {
+        let mut occupied = 0u64;
+        let mut side = Side::Vertical;
+        let mut found = Vec::new();
+        loop {
+            let moves = generate_moves(occupied, side);
+            if moves.is_empty() {
+                return found;
+            }
+            if live_cells(occupied).count_ones() <= self.max_live_cells {
+                found.extend(self.analyse(occupied, side as u8));
+            }
+            // Random openings, then a shallow search with the usual root
+            // randomization so games differ
+            let mv = if occupied.count_ones() < 8 {
+                moves[self.rng.below(moves.len())]
+            } else {
+                self.tt.new_search();
+                Searcher::new(&mut self.tt, &self.zobrist, &mut self.rng, f64::MAX, 3)
+                    .iterative_deepening(occupied, side, 4, 60)
+                    .best_move
+                    .unwrap_or(moves[0])
+            };
+            occupied = apply_move(occupied, mv, side);
+            side = side.opposite();
+        }
+    }
+
+    /// Up to `count` distinct puzzles (mirror images count as the same) from
+    /// at most `max_games` games, easiest first
+    pub fn generate(&mut self, count: usize, max_games: usize) -> Vec<Puzzle> {
+        let mut seen = HashSet::new();
+        let mut puzzles = Vec::new();
+        for _ in 0..max_games {
+            for puzzle in self.sample_game() {
+                let key = self
+                    .zobrist
+                    .symmetric_hash(puzzle.occupied, side_from(puzzle.side))
+                    .canonical()
+                    .0;
+                if seen.insert(key) {
+                    puzzles.push(puzzle);
+                }
+            }
+            if puzzles.len() >= count {
+                break;
+            }
+        }
+        puzzles.truncate(count);
+        puzzles.sort_by_key(|p| p.depth);
+        puzzles
+    }
+}
+
+/// Puzzle set as JSON; boards use the `(occupied_low, occupied_high)` pair
+/// that `DominorioEngine::search` takes
+pub fn to_json(puzzles: &[Puzzle]) -> String {
+    let list = |moves: &mut dyn Iterator<Item = u8>| {
+        moves.map(|m| m.to_string()).collect::<Vec<_>>().join(",")
+    };
+    let mut out = String::from("{\"game\":\"dominorio\",\"puzzles\":[");
+    for (i, p) in puzzles.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let refutations: Vec<String> = p
+            .refutations
+            .iter()
+            .map(|r| format!("{{\"move\":{},\"reply\":{}}}", r.mv, r.reply))
+            .collect();
+        let _ = write!(
+            out,
+            "{{\"occupied\":[{},{}],\"side\":{},\"solution\":[{}],\"depth\":{},\"difficulty\":\"{}\",\"refutations\":[{}]}}",
+            p.occupied as u32,
+            (p.occupied >> 32) as u32,
+            p.side,
+            list(&mut p.solution.iter().copied()),
+            p.depth,
+            p.difficulty().name(),
+            refutations.join(",")
+        );
+    }
+    out.push_str("]}");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::count_moves;
+
+    /// Plain minimax: does the side to move win?
+    fn wins(occupied: u64, side: Side) -> bool {
+        generate_moves(occupied, side)
+            .into_iter()
+            .any(|mv| !wins(apply_move(occupied, mv, side), side.opposite()))
+    }
+
+    #[test]
+    fn test_agrees_with_brute_force() {
+        // Random endgames from a nearly full board
+        let mut rng = Rng::new(3);
+        let mut generator = PuzzleGenerator::new(1, 64);
+        generator.min_depth = 1;
+        let mut puzzles = 0;
+        for _ in 0..60 {
+            let mut occupied = !0u64;
+            for _ in 0..14 {
+                occupied &= !(1u64 << rng.below(64));
+            }
+            for side in [Side::Vertical, Side::Horizontal] {
+                let winning = generate_moves(occupied, side)
+                    .into_iter()
+                    .filter(|&mv| !wins(apply_move(occupied, mv, side), side.opposite()))
+                    .count();
+                let unique = winning == 1 && count_moves(occupied, side) > 1;
+                let puzzle = generator.analyse(occupied, side as u8);
+                assert_eq!(puzzle.is_some(), unique);
+                if let Some(p) = puzzle {
+                    assert!(!wins(apply_move(occupied, p.solution[0], side), side.opposite()));
+                    puzzles += 1;
+                }
+            }
+        }
+        assert!(puzzles > 0);
+    }
+
+    #[test]
+    fn test_sampled_puzzles_are_sound() {
+        let mut generator = PuzzleGenerator::new(6, 16);
+        let puzzles = generator.generate(3, 6);
+        assert!(!puzzles.is_empty());
+        for p in &puzzles {
+            let side = side_from(p.side);
+            assert!(p.depth >= generator.min_depth);
+            assert_eq!(p.depth as usize, p.solution.len());
+            // The line is legal and ends with the loser to move and stuck
+            let (mut occupied, mut s) = (p.occupied, side);
+            for &mv in &p.solution {
+                assert!(generate_moves(occupied, s).contains(&mv));
+                occupied = apply_move(occupied, mv, s);
+                s = s.opposite();
+            }
+            assert_eq!(count_moves(occupied, s), 0);
+            assert_eq!(s, side.opposite());
+            for r in &p.refutations {
+                assert_ne!(r.mv, p.solution[0]);
+                let after = apply_move(p.occupied, r.mv, side);
+                assert!(generate_moves(after, side.opposite()).contains(&r.reply));
+            }
+        }
+        let json = to_json(&puzzles);
+        assert!(json.starts_with("{\"game\":\"dominorio\",\"puzzles\":[{\"occupied\":["));
+        assert_eq!(json.matches("\"difficulty\"").count(), puzzles.len());
+    }
+}
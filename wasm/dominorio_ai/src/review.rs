@@ -0,0 +1,292 @@
+//! Post-game review: every move of a game graded against the engine
+//!
+//! Each position of the game is searched once. The best score of a position
+//! is compared with the score of the move actually played, which is minus the
+//! score of the next position, so a game of `n` moves costs `n + 1` searches.
+//! Scores the search proves (mate scores) also tell whether the game-theoretic
+//! outcome changed with the move.
+
+use std::fmt;
+
+use crate::bitboard::{apply_move, count_moves, is_valid_move, Side};
+use crate::eval::{is_mate_score, MATE_SCORE};
+
+/// Proven scores count as this many points when measuring how much a move lost
+pub const DECIDED_SCORE: i32 = 1000;
+
+/// Largest loss (in evaluation points) for each grade below `Best`
+pub const GOOD_DROP: i32 = 40;
+pub const INACCURACY_DROP: i32 = 100;
+pub const MISTAKE_DROP: i32 = 250;
+
+/// Grade of a played move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MoveClass {
+    Best = 0,
+    Good = 1,
+    Inaccuracy = 2,
+    Mistake = 3,
+    Blunder = 4,
+}
+
+/// Game-theoretic outcome for the side that moves, when the search proved it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Unknown,
+}
+
+impl Outcome {
+    fn of(score: i32) -> Outcome {
+        if !is_mate_score(score) {
+            Outcome::Unknown
+        } else if score > 0 {
+            Outcome::Win
+        } else {
+            Outcome::Loss
+        }
+    }
+
+    /// 1 for a win, -1 for a loss, 0 if not proven
+    pub fn sign(self) -> i8 {
+        match self {
+            Outcome::Win => 1,
+            Outcome::Loss => -1,
+            Outcome::Unknown => 0,
+        }
+    }
+}
+
+/// Review of one move; scores are from the point of view of the mover
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveReview {
+    /// Side that played (0 = Vertical, 1 = Horizontal)
+    pub side: u8,
+    pub played: u8,
+    /// The engine's choice in the position before the move
+    pub best_move: Option<u8>,
+    pub best_score: i32,
+    pub played_score: i32,
+    /// Points lost against the best move (never negative)
+    pub eval_drop: i32,
+    /// Outcome before the move (with best play) and after it
+    pub outcome_before: Outcome,
+    pub outcome_after: Outcome,
+    pub class: MoveClass,
+}
+
+impl MoveReview {
+    /// The move turned a proven win into a proven loss
+    pub fn throws_win(&self) -> bool {
+        self.outcome_before == Outcome::Win && self.outcome_after == Outcome::Loss
+    }
+}
+
+/// Review of a whole game
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameReview {
+    pub moves: Vec<MoveReview>,
+    /// Index in `moves` of the move that decided the game (see `critical_move`)
+    pub critical_ply: Option<usize>,
+}
+
+/// Reasons a game record cannot be reviewed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewError {
+    /// The move at `ply` is not legal in the position reached
+    IllegalMove { ply: usize, anchor: u8 },
+}
+
+impl fmt::Display for ReviewError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReviewError::IllegalMove { ply, anchor } => {
+                write!(f, "move {} (anchor {}) is illegal", ply + 1, anchor)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReviewError {}
+
+/// Review `moves` played from the empty board, `first` moving first
+///
+/// `search(occupied, side)` returns the engine's best move and score for the
+/// side to move; it is not called on finished positions.
+pub fn review_game<F>(moves: &[u8], first: Side, search: F) -> Result<GameReview, ReviewError>
+where
+    F: FnMut(u64, Side) -> (Option<u8>, i32),
+{
+    review_game_from(0, first, moves, search)
+}
+
+/// [`review_game`] for moves played from the position `start`
+pub fn review_game_from<F>(start: u64, first: Side, moves: &[u8], mut search: F) -> Result<GameReview, ReviewError>
+where
+    F: FnMut(u64, Side) -> (Option<u8>, i32),
+{
+    // Replay first, so an illegal record costs no search
+    let mut positions = Vec::with_capacity(moves.len() + 1);
+    let (mut occupied, mut side) = (start, first);
+    for (ply, &anchor) in moves.iter().enumerate() {
+        if anchor >= 64 || !is_valid_move(occupied, anchor, side) {
+            return Err(ReviewError::IllegalMove { ply, anchor });
+        }
+        positions.push((occupied, side));
+        occupied = apply_move(occupied, anchor, side);
+        side = side.opposite();
+    }
+    positions.push((occupied, side));
+
+    let analysed: Vec<(Option<u8>, i32)> = positions
+        .iter()
+        .map(|&(occupied, side)| {
+            if count_moves(occupied, side) == 0 {
+                (None, -MATE_SCORE)
+            } else {
+                search(occupied, side)
+            }
+        })
+        .collect();
+
+    let reviews: Vec<MoveReview> = moves
+        .iter()
+        .enumerate()
+        .map(|(ply, &played)| {
+            let (best_move, best_score) = analysed[ply];
+            let played_score = -analysed[ply + 1].1;
+            let eval_drop = if best_move == Some(played) {
+                0
+            } else {
+                (decided(best_score) - decided(played_score)).max(0)
+            };
+            let outcome_before = Outcome::of(best_score);
+            let outcome_after = Outcome::of(played_score);
+            let class = if best_move == Some(played) || eval_drop == 0 {
+                MoveClass::Best
+            } else if outcome_before != Outcome::Loss && outcome_after == Outcome::Loss {
+                MoveClass::Blunder
+            } else {
+                classify(eval_drop)
+            };
+            MoveReview {
+                side: positions[ply].1 as u8,
+                played,
+                best_move,
+                best_score,
+                played_score,
+                eval_drop,
+                outcome_before,
+                outcome_after,
+                class,
+            }
+        })
+        .collect();
+
+    Ok(GameReview {
+        critical_ply: critical_move(&reviews),
+        moves: reviews,
+    })
+}
+
+/// Grade for a move that lost `eval_drop` points
+pub fn classify(eval_drop: i32) -> MoveClass {
+    match eval_drop {
+        d if d <= 0 => MoveClass::Best,
+        d if d <= GOOD_DROP => MoveClass::Good,
+        d if d <= INACCURACY_DROP => MoveClass::Inaccuracy,
+        d if d <= MISTAKE_DROP => MoveClass::Mistake,
+        _ => MoveClass::Blunder,
+    }
+}
+
+/// The move after which the game-theoretic outcome never changed again
+///
+/// That is the last move that threw away a proven win; when the search never
+/// proved the position won before the losing move, the last move that walked
+/// into a proven loss from an unproven position stands in for it.
+pub fn critical_move(moves: &[MoveReview]) -> Option<usize> {
+    moves.iter().rposition(MoveReview::throws_win).or_else(|| {
+        moves
+            .iter()
+            .rposition(|m| m.outcome_before == Outcome::Unknown && m.outcome_after == Outcome::Loss)
+    })
+}
+
+/// Score with proven results capped, so a lost win weighs like a big blunder
+fn decided(score: i32) -> i32 {
+    if is_mate_score(score) {
+        score.signum() * DECIDED_SCORE
+    } else {
+        score.clamp(-DECIDED_SCORE + 1, DECIDED_SCORE - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::generate_moves;
+
+    /// Search stub returning the first legal move and scripted scores in turn
+    fn scripted(scores: &[i32]) -> impl FnMut(u64, Side) -> (Option<u8>, i32) + '_ {
+        let mut next = scores.iter();
+        move |occupied, side| (generate_moves(occupied, side).first().copied(), *next.next().unwrap())
+    }
+
+    /// The last legal move at every turn (never the stub's choice)
+    fn last_moves(plies: usize) -> Vec<u8> {
+        let (mut occupied, mut side) = (0u64, Side::Vertical);
+        (0..plies)
+            .map(|_| {
+                let mv = *generate_moves(occupied, side).last().unwrap();
+                occupied = apply_move(occupied, mv, side);
+                side = side.opposite();
+                mv
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_illegal_move_is_reported() {
+        // Vertical cannot anchor on the bottom row
+        let err = review_game(&[3, 60, 57], Side::Horizontal, scripted(&[])).unwrap_err();
+        assert_eq!(err, ReviewError::IllegalMove { ply: 1, anchor: 60 });
+    }
+
+    #[test]
+    fn test_moves_are_graded_by_eval_drop() {
+        let first = generate_moves(0, Side::Vertical)[0];
+        let review = review_game(&[first], Side::Vertical, scripted(&[60, 500])).unwrap();
+        assert_eq!(review.moves[0].class, MoveClass::Best);
+        assert_eq!(review.moves[0].eval_drop, 0);
+
+        // The opponent scores +60 after a move the mover scored +60 before
+        let review = review_game(&last_moves(1), Side::Vertical, scripted(&[60, 60])).unwrap();
+        assert_eq!(review.moves[0].played_score, -60);
+        assert_eq!(review.moves[0].eval_drop, 120);
+        assert_eq!(review.moves[0].class, MoveClass::Mistake);
+        assert_eq!(review.moves[0].outcome_after, Outcome::Unknown);
+    }
+
+    #[test]
+    fn test_last_thrown_win_is_the_critical_move() {
+        // Both sides throw a proven win away; the second one decided the game
+        let scores = [MATE_SCORE - 5, MATE_SCORE - 4, MATE_SCORE - 7, 10];
+        let review = review_game(&last_moves(3), Side::Vertical, scripted(&scores)).unwrap();
+        assert!(review.moves[0].throws_win());
+        assert!(review.moves[1].throws_win());
+        assert_eq!(review.moves[1].class, MoveClass::Blunder);
+        assert_eq!(review.moves[2].outcome_after, Outcome::Unknown);
+        assert_eq!(review.critical_ply, Some(1));
+    }
+
+    #[test]
+    fn test_unproven_position_walking_into_a_loss() {
+        let scores = [30, -40, MATE_SCORE - 9];
+        let review = review_game(&last_moves(2), Side::Vertical, scripted(&scores)).unwrap();
+        assert_eq!(review.moves[1].outcome_before, Outcome::Unknown);
+        assert_eq!(review.moves[1].class, MoveClass::Blunder);
+        assert_eq!(review.critical_ply, Some(1));
+    }
+}
@@ -0,0 +1,165 @@
+//! Skill levels: weakening the engine in a controlled, measurable way
+//!
+//! A level from 0 (beginner) to 20 (full strength) sets three knobs:
+//! - a node budget that caps how far the search can see,
+//! - noise added to leaf evaluations, so weak levels misjudge positions,
+//! - a softmax temperature used to pick among the MultiPV root moves.
+//!
+//! `ELO_GAP` maps each level to its measured rating relative to level 20 and
+//! `ELO_ERROR` to that rating's standard error. The numbers come from
+//! self-play: `cargo run --release --example calibrate_skill`.
+
+use crate::rng::Rng;
+
+/// Strongest level (no weakening at all)
+pub const MAX_SKILL_LEVEL: u8 = 20;
+
+/// Search settings for one skill level
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkillParams {
+    /// Nodes the search may visit (`u64::MAX` = limited by time and depth only)
+    pub node_budget: u64,
+    /// Leaf evaluations are shifted by up to this many points either way
+    pub eval_noise: i32,
+    /// Softmax temperature over root scores, in evaluation points (0 = best move)
+    pub temperature: f64,
+    /// Root moves that get an exact score for the softmax choice
+    pub multipv: usize,
+}
+
+/// Rating of each level relative to level 20, measured by self-play: 40
+/// games per pairing against six anchor levels, 300 ms per move, run alone on
+/// one core, unsmoothed. Only differences well beyond `ELO_ERROR` mean
+/// anything: levels 14 to 19 are not told apart, nor are 4 and 5 or 6 and 7,
+/// and the fit even puts 7 below 6.
+pub const ELO_GAP: [i32; 21] = [
+    -980, -870, -830, -750, -640, -630, -510, -540, -430, -310,
+    -280, -240, -160, -130, -70, -30, -80, -40, -70, -40, 0,
+];
+
+/// Standard error of each `ELO_GAP` entry (level 20 is the fixed anchor)
+pub const ELO_ERROR: [i32; 21] = [
+    40, 50, 50, 40, 30, 40, 40, 40, 20, 30,
+    30, 30, 20, 30, 30, 30, 20, 30, 30, 30, 0,
+];
+
+/// Settings for `level` (values above `MAX_SKILL_LEVEL` are clamped)
+pub fn params(level: u8) -> SkillParams {
+    let level = level.min(MAX_SKILL_LEVEL);
+    if level == MAX_SKILL_LEVEL {
+        return SkillParams {
+            node_budget: u64::MAX,
+            eval_noise: 0,
+            temperature: 0.0,
+            multipv: 1,
+        };
+    }
+
+    let l = level as i32;
+    let temperature = (14 - l).max(0) as f64 * 12.0;
+    SkillParams {
+        // 200 nodes at level 0 up to 200k at level 19 (~1.44x per level):
+        // even level 19 runs out of nodes well before a 150 ms move time
+        node_budget: (200.0 * 1000f64.powf(l as f64 / 19.0)) as u64,
+        eval_noise: (16 - l).max(0) * 20,
+        temperature,
+        multipv: if temperature > 0.0 { 4 } else { 1 },
+    }
+}
+
+/// Measured rating of `level` relative to full strength
+pub fn elo_gap(level: u8) -> i32 {
+    ELO_GAP[level.min(MAX_SKILL_LEVEL) as usize]
+}
+
+/// Standard error of `elo_gap(level)`
+pub fn elo_error(level: u8) -> i32 {
+    ELO_ERROR[level.min(MAX_SKILL_LEVEL) as usize]
+}
+
+/// Pick an index into `scored` (best first) with probability proportional to
+/// `exp((score - best) / temperature)`
+pub fn softmax_choice<M>(scored: &[(M, i32)], temperature: f64, rng: &mut Rng) -> usize {
+    if scored.len() < 2 || temperature <= 0.0 {
+        return 0;
+    }
+    let best = scored.iter().map(|&(_, s)| s).max().unwrap_or(0);
+    let weights: Vec<f64> = scored
+        .iter()
+        .map(|&(_, s)| ((s - best) as f64 / temperature).exp())
+        .collect();
+    let mut pick = rng.next_f64() * weights.iter().sum::<f64>();
+    for (i, w) in weights.iter().enumerate() {
+        if pick < *w {
+            return i;
+        }
+        pick -= w;
+    }
+    scored.len() - 1
+}
+
+/// Deterministic noise in `-amplitude..=amplitude` for a position key
+///
+/// The same position always gets the same offset within a search, so the
+/// transposition table stays consistent.
+#[inline]
+pub fn eval_noise(key: u64, salt: u64, amplitude: i32) -> i32 {
+    if amplitude == 0 {
+        return 0;
+    }
+    let mut state = key ^ salt;
+    let r = crate::rng::splitmix64(&mut state);
+    (r % (2 * amplitude as u64 + 1)) as i32 - amplitude
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_get_stronger() {
+        for level in 1..=MAX_SKILL_LEVEL {
+            let (weak, strong) = (params(level - 1), params(level));
+            assert!(strong.node_budget > weak.node_budget);
+            assert!(strong.eval_noise <= weak.eval_noise);
+            assert!(strong.temperature <= weak.temperature);
+        }
+        assert_eq!(params(MAX_SKILL_LEVEL), params(99));
+    }
+
+    #[test]
+    fn test_ratings_match_their_error_bars() {
+        assert_eq!((elo_gap(MAX_SKILL_LEVEL), elo_error(MAX_SKILL_LEVEL)), (0, 0));
+        let apart = |a: u8, b: u8| 2.0 * ((elo_error(a).pow(2) + elo_error(b).pow(2)) as f64).sqrt();
+        for level in 1..=MAX_SKILL_LEVEL {
+            // a weaker measured level must be within noise of the one below
+            let drop = elo_gap(level - 1) - elo_gap(level);
+            assert!((drop as f64) < apart(level - 1, level), "level {level}");
+            assert!(elo_error(level - 1) > 0);
+        }
+        assert!(((elo_gap(MAX_SKILL_LEVEL) - elo_gap(0)) as f64) > apart(0, MAX_SKILL_LEVEL));
+    }
+
+    #[test]
+    fn test_softmax_prefers_better_moves() {
+        let scored = [(0u8, 100), (1, 60), (2, -400)];
+        let mut rng = Rng::new(3);
+        let mut counts = [0u32; 3];
+        for _ in 0..10_000 {
+            counts[softmax_choice(&scored, 40.0, &mut rng)] += 1;
+        }
+        assert!(counts[0] > counts[1] && counts[1] > counts[2], "{:?}", counts);
+        assert!(counts[1] > 1_500);
+        assert_eq!(softmax_choice(&scored, 0.0, &mut rng), 0);
+    }
+
+    #[test]
+    fn test_noise_is_bounded_and_stable() {
+        for key in 0..1000u64 {
+            let n = eval_noise(key, 99, 50);
+            assert!((-50..=50).contains(&n));
+            assert_eq!(n, eval_noise(key, 99, 50));
+        }
+        assert_eq!(eval_noise(7, 99, 0), 0);
+    }
+}
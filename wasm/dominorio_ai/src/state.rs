@@ -0,0 +1,123 @@
+//! Binary snapshots of the engine state
+//!
+//! Layout (little-endian):
+//! ```text
+//! magic "CRJM" | version u16 | variant u8 | cells u8
+//! zobrist: key count u32, keys u64 × count
+//! tt: generation u8, bucket count u32, buckets
+//! checksum u64 (FNV-1a of everything before it)
+//! ```
+//! The Zobrist keys travel with the table so that a snapshot stays valid
+//! even if it was taken by an engine using different keys.
+
+use crjm_search::snapshot;
+
+use crate::tt::TranspositionTable;
+use crate::zobrist::ZobristKeys;
+
+pub use crjm_search::snapshot::StateError;
+
+/// Variant tag for Dominório snapshots (Quelhas snapshots use 2)
+pub const VARIANT_DOMINORIO: u8 = 1;
+
+/// Board cells of the Dominório variant
+const CELLS: u8 = 64;
+
+/// Serialize the engine state into a snapshot
+pub fn export(tt: &TranspositionTable, zobrist: &ZobristKeys) -> Vec<u8> {
+    let mut out = snapshot::begin(VARIANT_DOMINORIO, CELLS);
+    let keys: Vec<u64> = zobrist
+        .square_keys
+        .iter()
+        .copied()
+        .chain(std::iter::once(zobrist.side_key))
+        .collect();
+    out.extend_from_slice(&(keys.len() as u32).to_le_bytes());
+    for key in keys {
+        out.extend_from_slice(&key.to_le_bytes());
+    }
+
+    tt.write_to(&mut out);
+    snapshot::finish(out)
+}
+
+/// Validate a snapshot and rebuild the engine state from it
+pub fn import(bytes: &[u8]) -> Result<(TranspositionTable, ZobristKeys), StateError> {
+    let mut r = snapshot::open(bytes, VARIANT_DOMINORIO, CELLS)?;
+    let key_count = r.u32()? as usize;
+    if key_count != 65 {
+        return Err(StateError::Corrupt("wrong number of Zobrist keys"));
+    }
+    let mut zobrist = ZobristKeys {
+        square_keys: [0; 64],
+        side_key: 0,
+    };
+    for key in zobrist.square_keys.iter_mut() {
+        *key = r.u64()?;
+    }
+    zobrist.side_key = r.u64()?;
+
+    let tt = r.table()?;
+    r.end()?;
+    Ok((tt, zobrist))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crjm_search::Bound;
+
+    fn sample() -> (TranspositionTable, ZobristKeys) {
+        let mut tt = TranspositionTable::new(1);
+        tt.new_search();
+        tt.store(0xDEAD_BEEF_0000_1234, Some(27), 7, Bound::Lower, -321);
+        (tt, ZobristKeys::new())
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let (tt, zobrist) = sample();
+        let bytes = export(&tt, &zobrist);
+        let (restored, keys) = import(&bytes).unwrap();
+
+        assert_eq!(keys.square_keys, zobrist.square_keys);
+        assert_eq!(keys.side_key, zobrist.side_key);
+        assert_eq!(restored.hashfull(), tt.hashfull());
+        let entry = restored.probe(0xDEAD_BEEF_0000_1234).unwrap();
+        assert_eq!(entry.best_move, Some(27));
+        assert_eq!(entry.depth, 7);
+        assert_eq!(entry.bound, Bound::Lower);
+        assert_eq!(entry.score, -321);
+        assert_eq!(entry.generation, 1);
+    }
+
+    #[test]
+    fn test_rejects_corruption() {
+        let (tt, zobrist) = sample();
+        let mut bytes = export(&tt, &zobrist);
+        let mid = bytes.len() / 2;
+        bytes[mid] ^= 0x40;
+        assert_eq!(import(&bytes).err(), Some(StateError::ChecksumMismatch));
+
+        let bytes = export(&tt, &zobrist);
+        assert_eq!(import(&bytes[..bytes.len() - 3]).err(), Some(StateError::ChecksumMismatch));
+        assert_eq!(import(&bytes[..6]).err(), Some(StateError::Truncated));
+        assert_eq!(import(b"JSON{}{}{}{}").err(), Some(StateError::BadMagic));
+    }
+
+    #[test]
+    fn test_rejects_other_variants_and_versions() {
+        let (tt, zobrist) = sample();
+        let mut bytes = export(&tt, &zobrist);
+        bytes[6] = 2;
+        bytes[7] = 100;
+        assert_eq!(
+            import(&bytes).err(),
+            Some(StateError::WrongVariant { variant: 2, cells: 100 })
+        );
+
+        let mut bytes = export(&tt, &zobrist);
+        bytes[4] = 9;
+        assert_eq!(import(&bytes).err(), Some(StateError::UnsupportedVersion(9)));
+    }
+}
@@ -0,0 +1,500 @@
+//! Small-region tablebase: exact values of Domineering regions
+//!
+//! The empty cells of a Domineering position split into connected regions
+//! that are played independently, so the position is the sum of its regions.
+//! The tablebase stores the canonical game value (see [`crate::cgt`]) of every
+//! connected region of up to `max_cells` cells that fits on the board, up to
+//! translation and left-right / top-bottom reflection (mirroring keeps every
+//! domino in its orientation, so mirrored regions have the same value).
+//!
+//! When every region of a position is in the table, the winner is known
+//! without search: numbers are added up directly and the rest of the sum is
+//! compared with zero through the stored options.
+//!
+//! File layout (little-endian):
+//! ```text
+//! magic "DTBL" | version u16 | max_cells u8
+//! games: count u32, then per game: number i32 (i32::MIN if none),
+//!        left count u16, right count u16, option ids u32 × (left + right)
+//! regions: count u32, then (shape u64, game id u32) sorted by shape
+//! checksum u64 (FNV-1a of everything before it)
+//! ```
+//! Options always refer to earlier games, so the table is loaded in one pass.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::bitboard::Side;
+use crate::cgt::{GameId, GameTable, ZERO};
+use crjm_search::snapshot::{checksum, Reader, StateError};
+
+const MAGIC: &[u8; 4] = b"DTBL";
+
+/// Current tablebase format version
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Regions of more cells than this are never generated (the table grows
+/// about sevenfold per extra cell)
+pub const MAX_REGION_CELLS: u8 = 14;
+
+/// Stored in place of the value of games that are not numbers
+const NOT_A_NUMBER: i32 = i32::MIN;
+
+/// Score of a position the tablebase proves won; below the mate range
+/// because the distance to the end is unknown
+///
+/// The same at every ply: with no distance in the score the transposition
+/// table can store it as it is.
+pub const TB_WIN: i32 = 20_000;
+
+const COLUMN_A: u64 = 0x0101_0101_0101_0101;
+const COLUMN_H: u64 = 0x8080_8080_8080_8080;
+
+/// Why a tablebase file was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TablebaseError {
+    /// The data ended before the file was complete
+    Truncated,
+    /// The data does not start with the tablebase magic
+    BadMagic,
+    /// The file was written by an unknown format version
+    UnsupportedVersion(u16),
+    /// The stored checksum does not match the contents
+    ChecksumMismatch,
+    /// The contents are structurally invalid
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for TablebaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TablebaseError::Truncated => write!(f, "tablebase is truncated"),
+            TablebaseError::BadMagic => write!(f, "not a Domineering tablebase"),
+            TablebaseError::UnsupportedVersion(v) => {
+                write!(f, "unsupported tablebase version {} (expected {})", v, FORMAT_VERSION)
+            }
+            TablebaseError::ChecksumMismatch => write!(f, "tablebase checksum mismatch (corrupted data)"),
+            TablebaseError::Corrupt(what) => write!(f, "corrupted tablebase: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for TablebaseError {}
+
+impl From<StateError> for TablebaseError {
+    fn from(e: StateError) -> Self {
+        match e {
+            StateError::Truncated => TablebaseError::Truncated,
+            _ => TablebaseError::Corrupt("unreadable data"),
+        }
+    }
+}
+
+/// Cells orthogonally adjacent to `bb`
+#[inline]
+fn neighbours(bb: u64) -> u64 {
+    (bb << 8) | (bb >> 8) | ((bb << 1) & !COLUMN_A) | ((bb >> 1) & !COLUMN_H)
+}
+
+/// Connected region of `cells` containing the lowest set bit of `seed`,
+/// or `None` once it grows beyond `limit` cells
+#[inline]
+fn region(cells: u64, seed: u64, limit: u32) -> Option<u64> {
+    let mut region = seed & seed.wrapping_neg();
+    loop {
+        let grown = (region | neighbours(region)) & cells;
+        if grown.count_ones() > limit {
+            return None;
+        }
+        if grown == region {
+            return Some(region);
+        }
+        region = grown;
+    }
+}
+
+/// Move a shape to the top-left corner
+fn translate(shape: u64) -> u64 {
+    let rows = shape.trailing_zeros() / 8;
+    let shape = shape >> (rows * 8);
+    let columns = shape.to_le_bytes().iter().fold(0u8, |acc, &b| acc | b);
+    shape >> columns.trailing_zeros()
+}
+
+fn mirror_left_right(shape: u64) -> u64 {
+    let bytes = shape.to_le_bytes().map(|b| b.reverse_bits());
+    u64::from_le_bytes(bytes)
+}
+
+/// Key shared by a shape and its mirror images
+pub fn canonical_shape(shape: u64) -> u64 {
+    let lr = mirror_left_right(shape);
+    [shape, lr, shape.swap_bytes(), lr.swap_bytes()]
+        .into_iter()
+        .map(translate)
+        .min()
+        .unwrap()
+}
+
+/// Anchors of the dominoes `side` can place inside `shape`
+fn moves_in(shape: u64, side: Side) -> u64 {
+    match side {
+        Side::Vertical => shape & (shape >> 8),
+        Side::Horizontal => shape & (shape >> 1) & !COLUMN_H,
+    }
+}
+
+/// Every connected shape of up to `max_cells` cells that fits on the board,
+/// one per mirror class, smallest first
+pub fn enumerate_shapes(max_cells: u8) -> Vec<u64> {
+    let mut all = Vec::new();
+    let mut layer = vec![1u64];
+    for _ in 1..max_cells {
+        all.extend_from_slice(&layer);
+        let mut next = HashSet::new();
+        for &shape in &layer {
+            // The shape sits in the top-left corner: shifted copies (where
+            // they fit) also let it grow upwards and to the left
+            let mut placements = vec![shape];
+            if shape >> 56 == 0 {
+                placements.push(shape << 8);
+            }
+            if shape & COLUMN_H == 0 {
+                placements.extend(placements.clone().iter().map(|p| p << 1));
+            }
+            for placed in placements {
+                let mut frontier = neighbours(placed) & !placed;
+                while frontier != 0 {
+                    let cell = frontier & frontier.wrapping_neg();
+                    next.insert(canonical_shape(placed | cell));
+                    frontier &= frontier - 1;
+                }
+            }
+        }
+        layer = next.into_iter().collect();
+        layer.sort_unstable();
+    }
+    all.extend_from_slice(&layer);
+    all
+}
+
+/// Build the tablebase file for all regions of up to `max_cells` cells
+pub fn generate(max_cells: u8) -> Vec<u8> {
+    let max_cells = max_cells.clamp(1, MAX_REGION_CELLS);
+    let mut table = GameTable::new();
+    let mut values: HashMap<u64, GameId> = HashMap::new();
+    for shape in enumerate_shapes(max_cells) {
+        let mut options = [Vec::new(), Vec::new()];
+        for (side, dir) in [(Side::Vertical, 8), (Side::Horizontal, 1)] {
+            let mut anchors = moves_in(shape, side);
+            while anchors != 0 {
+                let anchor = anchors & anchors.wrapping_neg();
+                let mut rest = shape & !(anchor | anchor << dir);
+                let mut value = ZERO;
+                while rest != 0 {
+                    let part = region(rest, rest, 64).unwrap();
+                    rest &= !part;
+                    value = table.add(value, values[&canonical_shape(part)]);
+                }
+                options[side as usize].push(value);
+                anchors &= anchors - 1;
+            }
+        }
+        let [left, right] = options;
+        let value = table.canonical(left, right);
+        values.insert(shape, value);
+    }
+    write(&table, &values, max_cells)
+}
+
+/// Serialize the games reachable from `values` (renumbered densely)
+fn write(table: &GameTable, values: &HashMap<u64, GameId>, max_cells: u8) -> Vec<u8> {
+    // Depth-first numbering puts every option before the games using it
+    fn visit(table: &GameTable, g: GameId, ids: &mut HashMap<GameId, u32>, order: &mut Vec<GameId>) {
+        if ids.contains_key(&g) {
+            return;
+        }
+        for &o in table.left(g).iter().chain(table.right(g)) {
+            visit(table, o, ids, order);
+        }
+        ids.insert(g, order.len() as u32);
+        order.push(g);
+    }
+    let mut ids = HashMap::new();
+    let mut order = Vec::new();
+    visit(table, ZERO, &mut ids, &mut order);
+    let mut regions: Vec<(u64, GameId)> = values.iter().map(|(&s, &g)| (s, g)).collect();
+    regions.sort_unstable();
+    for &(_, g) in &regions {
+        visit(table, g, &mut ids, &mut order);
+    }
+
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    out.push(max_cells);
+    out.extend_from_slice(&(order.len() as u32).to_le_bytes());
+    for &g in &order {
+        out.extend_from_slice(&table.number(g).unwrap_or(NOT_A_NUMBER).to_le_bytes());
+        out.extend_from_slice(&(table.left(g).len() as u16).to_le_bytes());
+        out.extend_from_slice(&(table.right(g).len() as u16).to_le_bytes());
+        for o in table.left(g).iter().chain(table.right(g)) {
+            out.extend_from_slice(&ids[o].to_le_bytes());
+        }
+    }
+    out.extend_from_slice(&(regions.len() as u32).to_le_bytes());
+    for (shape, g) in regions {
+        out.extend_from_slice(&shape.to_le_bytes());
+        out.extend_from_slice(&ids[&g].to_le_bytes());
+    }
+    let sum = checksum(&out);
+    out.extend_from_slice(&sum.to_le_bytes());
+    out
+}
+
+/// A stored game: its value if it is a number, and its options
+struct Game {
+    number: Option<i32>,
+    left: Vec<u32>,
+    right: Vec<u32>,
+}
+
+/// A loaded tablebase
+pub struct Tablebase {
+    max_cells: u8,
+    games: Vec<Game>,
+    /// (canonical shape, game index), sorted by shape
+    regions: Vec<(u64, u32)>,
+}
+
+impl Tablebase {
+    /// Validate and load a file produced by [`generate`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Tablebase, TablebaseError> {
+        if bytes.len() < MAGIC.len() + 3 + 8 {
+            return Err(TablebaseError::Truncated);
+        }
+        if &bytes[..MAGIC.len()] != MAGIC {
+            return Err(TablebaseError::BadMagic);
+        }
+        let (body, sum) = bytes.split_at(bytes.len() - 8);
+        let mut r = Reader::new(&body[MAGIC.len()..]);
+        let version = r.u16()?;
+        if version != FORMAT_VERSION {
+            return Err(TablebaseError::UnsupportedVersion(version));
+        }
+        if checksum(body) != u64::from_le_bytes(sum.try_into().unwrap()) {
+            return Err(TablebaseError::ChecksumMismatch);
+        }
+        let max_cells = r.u8()?;
+        if max_cells == 0 || max_cells > MAX_REGION_CELLS {
+            return Err(TablebaseError::Corrupt("region size out of range"));
+        }
+
+        let game_count = r.u32()? as usize;
+        let mut games = Vec::with_capacity(game_count.min(r.remaining() / 8));
+        for index in 0..game_count {
+            let number = r.u32()? as i32;
+            let (left, right) = (r.u16()? as usize, r.u16()? as usize);
+            let mut options = Vec::with_capacity(left + right);
+            for _ in 0..left + right {
+                let o = r.u32()?;
+                if o as usize >= index {
+                    return Err(TablebaseError::Corrupt("option refers to a later game"));
+                }
+                options.push(o);
+            }
+            let right = options.split_off(left);
+            games.push(Game {
+                number: (number != NOT_A_NUMBER).then_some(number),
+                left: options,
+                right,
+            });
+        }
+
+        let region_count = r.u32()? as usize;
+        let mut regions = Vec::with_capacity(region_count.min(r.remaining() / 12));
+        for _ in 0..region_count {
+            let shape = r.u64()?;
+            let game = r.u32()?;
+            if game as usize >= games.len() {
+                return Err(TablebaseError::Corrupt("region refers to a missing game"));
+            }
+            if regions.last().is_some_and(|&(prev, _)| prev >= shape) {
+                return Err(TablebaseError::Corrupt("regions out of order"));
+            }
+            regions.push((shape, game));
+        }
+        if r.remaining() != 0 {
+            return Err(TablebaseError::Corrupt("trailing data"));
+        }
+        Ok(Tablebase { max_cells, games, regions })
+    }
+
+    /// Largest region stored
+    pub fn max_cells(&self) -> u8 {
+        self.max_cells
+    }
+
+    /// Number of regions stored
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    fn lookup(&self, shape: u64) -> Option<u32> {
+        let key = canonical_shape(shape);
+        self.regions
+            .binary_search_by_key(&key, |&(s, _)| s)
+            .ok()
+            .map(|i| self.regions[i].1)
+    }
+}
+
+/// Tablebase lookups during one search, with a cache for sums of regions
+pub struct Probe<'a> {
+    tb: &'a Tablebase,
+    /// (sorted games of the sum, asking ">= 0") -> answer
+    memo: HashMap<(Vec<u32>, bool), bool>,
+}
+
+impl<'a> Probe<'a> {
+    pub fn new(tb: &'a Tablebase) -> Self {
+        Probe { tb, memo: HashMap::new() }
+    }
+
+    /// Whether the side to move wins, if every region of the position is
+    /// in the tablebase
+    pub fn side_to_move_wins(&mut self, occupied: u64, side: Side) -> Option<bool> {
+        let limit = self.tb.max_cells as u32;
+        let mut empty = !occupied;
+        let mut games = Vec::new();
+        while empty != 0 {
+            let part = region(empty, empty, limit)?;
+            empty &= !part;
+            if part.count_ones() >= 2 {
+                games.push(self.tb.lookup(part)?);
+            }
+        }
+        games.retain(|&g| g != ZERO);
+        games.sort_unstable();
+        // Left (Vertical) moving first wins unless the sum is <= 0; Right
+        // (Horizontal) moving first wins unless it is >= 0
+        Some(match side {
+            Side::Vertical => !self.compare(games, false),
+            Side::Horizontal => !self.compare(games, true),
+        })
+    }
+
+    /// Whether the sum of `games` is `>= 0` (if `geq`) or `<= 0`
+    fn compare(&mut self, games: Vec<u32>, geq: bool) -> bool {
+        // A sum of numbers is a number: its sign decides
+        let numbers: Option<i64> = games
+            .iter()
+            .map(|&g| self.tb.games[g as usize].number.map(i64::from))
+            .sum();
+        if let Some(total) = numbers {
+            return if geq { total >= 0 } else { total <= 0 };
+        }
+        let key = (games, geq);
+        if let Some(&known) = self.memo.get(&key) {
+            return known;
+        }
+        let games = &key.0;
+        // S >= 0 iff no right option of S is <= 0 (and symmetrically)
+        let mut result = true;
+        'outer: for (i, &g) in games.iter().enumerate() {
+            if i > 0 && games[i - 1] == g {
+                continue;
+            }
+            let game = &self.tb.games[g as usize];
+            for &o in if geq { &game.right } else { &game.left } {
+                let mut next = games.clone();
+                next.remove(i);
+                if o != ZERO {
+                    let pos = next.partition_point(|&x| x < o);
+                    next.insert(pos, o);
+                }
+                if self.compare(next, !geq) {
+                    result = false;
+                    break 'outer;
+                }
+            }
+        }
+        self.memo.insert(key, result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::{apply_move, generate_moves};
+    use crate::rng::Rng;
+
+    fn wins(occupied: u64, side: Side) -> bool {
+        generate_moves(occupied, side)
+            .into_iter()
+            .any(|mv| !wins(apply_move(occupied, mv, side), side.opposite()))
+    }
+
+    #[test]
+    fn test_shapes_up_to_reflection() {
+        // 1 monomino, 2 dominoes (vertical, horizontal), and for trominoes
+        // 2 straight + 1 L (its four orientations are mirror images)
+        let shapes = enumerate_shapes(3);
+        assert_eq!(shapes.len(), 1 + 2 + 3);
+        assert_eq!(canonical_shape(0b11 << 20), 0b11);
+        assert_eq!(canonical_shape(0b01 | 0b11 << 8), canonical_shape(0b10 | 0b11 << 8));
+    }
+
+    #[test]
+    fn test_known_region_values() {
+        let tb = Tablebase::from_bytes(&generate(4)).unwrap();
+        let one = 1 << crate::cgt::NUMBER_SHIFT;
+        let value = |shape: u64| tb.games[tb.lookup(shape).unwrap() as usize].number;
+        // A vertical domino slot is 1, a horizontal one -1, 2x2 is ±1
+        assert_eq!(value(1 | 1 << 8), Some(one));
+        assert_eq!(value(0b11), Some(-one));
+        assert_eq!(value(0b11 | 0b11 << 8), None);
+        // Straight tromino: still one move
+        assert_eq!(value(1 | 1 << 8 | 1 << 16), Some(one));
+    }
+
+    #[test]
+    fn test_probe_agrees_with_search() {
+        let tb = Tablebase::from_bytes(&generate(8)).unwrap();
+        let mut probe = Probe::new(&tb);
+        let mut rng = Rng::new(11);
+        let mut probed = 0;
+        for holes in 0..300 {
+            let mut occupied = !0u64;
+            for _ in 0..14 + holes % 12 {
+                occupied &= !(1u64 << rng.below(64));
+            }
+            for side in [Side::Vertical, Side::Horizontal] {
+                if let Some(result) = probe.side_to_move_wins(occupied, side) {
+                    assert_eq!(result, wins(occupied, side), "{:#x} {:?}", occupied, side);
+                    probed += 1;
+                }
+            }
+        }
+        assert!(probed > 100);
+    }
+
+    #[test]
+    fn test_corrupted_files_are_rejected() {
+        let bytes = generate(4);
+        assert!(Tablebase::from_bytes(&bytes).is_ok());
+        let mut flipped = bytes.clone();
+        flipped[20] ^= 1;
+        assert_eq!(Tablebase::from_bytes(&flipped).err(), Some(TablebaseError::ChecksumMismatch));
+        assert_eq!(Tablebase::from_bytes(&bytes[..bytes.len() - 3]).err(), Some(TablebaseError::ChecksumMismatch));
+        assert_eq!(Tablebase::from_bytes(b"CRJM....").err(), Some(TablebaseError::Truncated));
+        let mut wrong = bytes;
+        wrong[0] = b'X';
+        assert_eq!(Tablebase::from_bytes(&wrong).err(), Some(TablebaseError::BadMagic));
+    }
+}
@@ -1,8 +1,18 @@
 //! Zobrist hashing for transposition table
+//!
+//! Mirroring the board left-right or top-bottom keeps every domino in its
+//! orientation, so mirrored positions have the same value for both players.
+//! [`SymmetricHash`] tracks the hash of all four mirror images incrementally
+//! and picks the smallest one as the canonical transposition key.
 
-use getrandom::getrandom;
+use crjm_search::zobrist::KeyStream;
+
+/// Seed of the default Zobrist keys, fixed so hashes (and snapshots) are
+/// identical across runs
+pub const DEFAULT_ZOBRIST_SEED: u64 = 0xD04B_1B0A;
 
 /// Zobrist keys for hashing board positions
+#[derive(Clone)]
 pub struct ZobristKeys {
     /// Key for each square being occupied
     pub square_keys: [u64; 64],
@@ -11,41 +21,18 @@ pub struct ZobristKeys {
 }
 
 impl ZobristKeys {
-    /// Create new random Zobrist keys
+    /// Create the default (fixed) Zobrist keys
     pub fn new() -> Self {
-        let mut keys = ZobristKeys {
-            square_keys: [0; 64],
-            side_key: 0,
-        };
-        
-        // Generate random keys
-        let mut bytes = [0u8; 8];
-        
-        for i in 0..64 {
-            if getrandom(&mut bytes).is_ok() {
-                keys.square_keys[i] = u64::from_le_bytes(bytes);
-            } else {
-                // Fallback to deterministic pseudo-random
-                keys.square_keys[i] = Self::deterministic_key(i as u64);
-            }
-        }
-        
-        if getrandom(&mut bytes).is_ok() {
-            keys.side_key = u64::from_le_bytes(bytes);
-        } else {
-            keys.side_key = Self::deterministic_key(64);
-        }
-        
-        keys
+        Self::with_seed(DEFAULT_ZOBRIST_SEED)
     }
     
-    /// Deterministic fallback key generation
-    fn deterministic_key(seed: u64) -> u64 {
-        // Simple xorshift-based PRNG
-        let mut x = seed.wrapping_add(0x9E3779B97F4A7C15);
-        x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
-        x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
-        x ^ (x >> 31)
+    /// Create Zobrist keys derived from `seed`
+    pub fn with_seed(seed: u64) -> Self {
+        let mut keys = KeyStream::new(seed);
+        ZobristKeys {
+            square_keys: keys.keys(),
+            side_key: keys.key(),
+        }
     }
     
     /// Compute hash for a board position
@@ -80,6 +67,119 @@ impl ZobristKeys {
     }
 }
 
+/// Board symmetries that preserve each player's orientation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    Identity = 0,
+    /// Reflect columns (col -> 7 - col)
+    MirrorLeftRight = 1,
+    /// Reflect rows (row -> 7 - row)
+    MirrorTopBottom = 2,
+    /// Both reflections (rotation by 180 degrees)
+    Rotate180 = 3,
+}
+
+/// All orientation-preserving symmetries, indexed by discriminant
+pub const SYMMETRIES: [Symmetry; 4] = [
+    Symmetry::Identity,
+    Symmetry::MirrorLeftRight,
+    Symmetry::MirrorTopBottom,
+    Symmetry::Rotate180,
+];
+
+impl Symmetry {
+    /// XOR mask that maps a square index to its image
+    #[inline]
+    fn square_mask(self) -> u8 {
+        match self {
+            Symmetry::Identity => 0,
+            Symmetry::MirrorLeftRight => 7,
+            Symmetry::MirrorTopBottom => 56,
+            Symmetry::Rotate180 => 63,
+        }
+    }
+    
+    /// Image of a square under this symmetry
+    #[inline]
+    pub fn map_square(self, sq: u8) -> u8 {
+        sq ^ self.square_mask()
+    }
+    
+    /// Image of a move: the anchor of the mirrored domino
+    ///
+    /// A reflection along the domino's own axis swaps its two cells, so the
+    /// new anchor is the image of the second cell. Every symmetry is its own
+    /// inverse, so the same call maps moves back.
+    #[inline]
+    pub fn map_move(self, anchor: u8, side: crate::bitboard::Side) -> u8 {
+        let second = crate::bitboard::get_second_cell(anchor, side);
+        self.map_square(anchor).min(self.map_square(second))
+    }
+}
+
+/// Hashes of a position under each symmetry, updated incrementally
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymmetricHash {
+    hashes: [u64; 4],
+}
+
+impl SymmetricHash {
+    /// Canonical key (smallest hash over the mirror images) and the symmetry
+    /// mapping the position onto its canonical form
+    #[inline]
+    pub fn canonical(&self) -> (u64, Symmetry) {
+        let mut best = 0;
+        for i in 1..4 {
+            if self.hashes[i] < self.hashes[best] {
+                best = i;
+            }
+        }
+        (self.hashes[best], SYMMETRIES[best])
+    }
+}
+
+impl ZobristKeys {
+    /// Hash every mirror image of a position from scratch
+    pub fn symmetric_hash(&self, occupied: u64, side: crate::bitboard::Side) -> SymmetricHash {
+        let mut hashes = [0u64; 4];
+        let mut bb = occupied;
+        
+        while bb != 0 {
+            let sq = bb.trailing_zeros() as u8;
+            for (h, sym) in hashes.iter_mut().zip(SYMMETRIES) {
+                *h ^= self.square_keys[sym.map_square(sq) as usize];
+            }
+            bb &= bb - 1;
+        }
+        
+        if side == crate::bitboard::Side::Horizontal {
+            for h in hashes.iter_mut() {
+                *h ^= self.side_key;
+            }
+        }
+        
+        SymmetricHash { hashes }
+    }
+    
+    /// Incrementally update all mirror hashes after a move
+    #[inline]
+    pub fn update_symmetric_hash(
+        &self,
+        hash: &SymmetricHash,
+        anchor: u8,
+        side: crate::bitboard::Side,
+    ) -> SymmetricHash {
+        let second = crate::bitboard::get_second_cell(anchor, side);
+        let mut hashes = hash.hashes;
+        for (h, sym) in hashes.iter_mut().zip(SYMMETRIES) {
+            *h ^= self.square_keys[sym.map_square(anchor) as usize]
+                ^ self.square_keys[sym.map_square(second) as usize]
+                ^ self.side_key;
+        }
+        SymmetricHash { hashes }
+    }
+}
+
 impl Default for ZobristKeys {
     fn default() -> Self {
         Self::new()
@@ -91,6 +191,15 @@ mod tests {
     use super::*;
     use crate::bitboard::Side;
     
+    #[test]
+    fn test_default_keys_are_fixed() {
+        let a = ZobristKeys::new();
+        let b = ZobristKeys::new();
+        assert_eq!(a.square_keys, b.square_keys);
+        assert_eq!(a.side_key, b.side_key);
+        assert_ne!(ZobristKeys::with_seed(1).square_keys, a.square_keys);
+    }
+    
     #[test]
     fn test_empty_board_hash() {
         let keys = ZobristKeys::new();
@@ -118,6 +227,50 @@ mod tests {
         
         assert_eq!(h1, h2);
     }
+    
+    /// Mirror a whole occupancy bitboard square by square
+    fn mirror(occupied: u64, sym: Symmetry) -> u64 {
+        (0..64u8)
+            .filter(|&sq| occupied & (1u64 << sq) != 0)
+            .fold(0, |acc, sq| acc | (1u64 << sym.map_square(sq)))
+    }
+    
+    #[test]
+    fn test_mirrored_positions_share_canonical_hash() {
+        let keys = ZobristKeys::new();
+        // Vertical domino at (1,2) plus horizontal at (5,0)
+        let occupied = (1u64 << 10) | (1u64 << 18) | (1u64 << 40) | (1u64 << 41);
+        let (canonical, _) = keys.symmetric_hash(occupied, Side::Vertical).canonical();
+        
+        for sym in SYMMETRIES {
+            let image = mirror(occupied, sym);
+            assert_eq!(keys.symmetric_hash(image, Side::Vertical).canonical().0, canonical);
+            assert_ne!(keys.symmetric_hash(image, Side::Horizontal).canonical().0, canonical);
+        }
+    }
+    
+    #[test]
+    fn test_map_move() {
+        for side in [Side::Vertical, Side::Horizontal] {
+            for anchor in crate::bitboard::generate_moves(0, side) {
+                let placed = crate::bitboard::apply_move(0, anchor, side);
+                for sym in SYMMETRIES {
+                    let mapped = sym.map_move(anchor, side);
+                    assert_eq!(crate::bitboard::apply_move(0, mapped, side), mirror(placed, sym));
+                    assert_eq!(sym.map_move(mapped, side), anchor);
+                }
+            }
+        }
+    }
+    
+    #[test]
+    fn test_incremental_symmetric_hash() {
+        let keys = ZobristKeys::new();
+        let h0 = keys.symmetric_hash(0, Side::Vertical);
+        let h1 = keys.update_symmetric_hash(&h0, 19, Side::Vertical);
+        let h2 = keys.update_symmetric_hash(&h1, 44, Side::Horizontal);
+        
+        let occupied = (1u64 << 19) | (1u64 << 27) | (1u64 << 44) | (1u64 << 45);
+        assert_eq!(h2, keys.symmetric_hash(occupied, Side::Vertical));
+    }
 }
-
-
@@ -0,0 +1,206 @@
+//! Solver exato de finais por decomposição em regiões.
+//!
+//! Uma casa vazia ao lado de um gato e de um cão está morta: ninguém lá
+//! pode jogar, até ao fim do jogo. As casas vivas formam regiões (componentes
+//! ortogonais) que não se influenciam, porque uma peça só afeta as casas
+//! vizinhas. Uma região onde só um lado pode jogar é exclusiva: nada do que o
+//! adversário faça a muda, e o dono pode ocupar todas as casas, por qualquer
+//! ordem. Cada região exclusiva vale então apenas pelo seu tamanho, e a
+//! posição reduz-se a:
+//!
+//! - a parte partilhada (regiões onde os dois lados podem jogar), com as
+//!   casas onde cada lado pode jogar;
+//! - o número de lances livres (casas exclusivas) de cada lado.
+//!
+//! Sem parte partilhada o resultado sai de uma conta: quem joga com `a`
+//! lances livres contra `b` ganha sse `a > b`. Com uma parte partilhada
+//! pequena, a posição reduzida é resolvida por minimax com memo, com
+//! distâncias exatas até ao fim.
+//!
+//! Os valores seguem a convenção do motor: `MATE - plies` se quem joga ganha,
+//! `-(MATE - plies)` se perde.
+
+use gatos_caes_core::{apply_move, flood, generate_moves, neighbours, playable, Board, Move};
+
+pub use crjm_search::endgame::Limits;
+
+use crate::eval::MATE;
+
+/// Máximo de casas na parte partilhada para tentar resolver a posição
+pub const MAX_SHARED_CELLS: u32 = 20;
+
+/// Solver de finais de Gatos & Cães (memo válido entre pesquisas)
+pub type EndgameSolver = crjm_search::endgame::EndgameSolver<GatosCaesEndgame>;
+
+/// Posição reduzida: a parte partilhada, onde cada lado pode jogar nela, e
+/// os lances livres de cada lado
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Reduced {
+    shared: u64,
+    /// casas da parte partilhada onde cada lado (gatos, cães) pode jogar
+    ok: [u64; 2],
+    free: [u8; 2],
+    side: u8,
+}
+
+/// Separa as casas vivas de `ok` por regiões: as exclusivas passam para
+/// `free`, as partilhadas ficam na posição devolvida
+fn split_regions(ok: [u64; 2], free: &mut [u8; 2], side: u8) -> Reduced {
+    let mut live = ok[0] | ok[1];
+    let mut shared = 0u64;
+    while live != 0 {
+        let region = flood(live & live.wrapping_neg(), live);
+        live &= !region;
+        if region & ok[1] == 0 {
+            free[0] += region.count_ones() as u8;
+        } else if region & ok[0] == 0 {
+            free[1] += region.count_ones() as u8;
+        } else {
+            shared |= region;
+        }
+    }
+    Reduced {
+        shared,
+        ok: [ok[0] & shared, ok[1] & shared],
+        free: *free,
+        side,
+    }
+}
+
+/// Redução dos finais de Gatos & Cães para o solver partilhado (`crjm_search`)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GatosCaesEndgame;
+
+impl crjm_search::endgame::Reduction for GatosCaesEndgame {
+    const MATE: i32 = MATE;
+    type Board = Board;
+    type Move = Move;
+    type Reduced = Reduced;
+
+    /// Posição reduzida de `board`, se a abertura já acabou e a parte
+    /// partilhada é pequena
+    fn reduce(&self, board: Board, side: u8, max_shared_cells: u32) -> Option<Reduced> {
+        if board.cats == 0 || board.dogs == 0 {
+            return None;
+        }
+        let ok = [playable(board, 0), playable(board, 1)];
+        let reduced = split_regions(ok, &mut [0, 0], side);
+        (reduced.shared.count_ones() <= max_shared_cells).then_some(reduced)
+    }
+
+    fn moves(&self, board: Board, side: u8) -> Vec<Move> {
+        generate_moves(board, side)
+    }
+
+    fn apply(&self, board: Board, mv: Move, side: u8) -> Board {
+        apply_move(board, mv, side)
+    }
+
+    fn settled(&self, pos: &Reduced) -> Option<i32> {
+        // só regiões exclusivas: cada lado gasta um lance livre por vez
+        let side = pos.side as usize;
+        let (a, b) = (pos.free[side] as i32, pos.free[1 - side] as i32);
+        (pos.shared == 0).then_some(if a > b { MATE - (2 * b + 1) } else { -(MATE - 2 * a) })
+    }
+
+    fn children(&self, pos: &Reduced) -> Vec<Reduced> {
+        // há sempre lances: a parte partilhada tem casas de cada lado
+        let side = pos.side as usize;
+        let mut children = Vec::new();
+        if pos.free[side] > 0 {
+            // qualquer casa exclusiva serve: nenhuma afeta o resto
+            let mut child = *pos;
+            child.free[side] -= 1;
+            child.side = 1 - pos.side;
+            children.push(child);
+        }
+        for mv in gatos_caes_core::squares(pos.ok[side]) {
+            let bit = 1u64 << mv;
+            let mut ok = pos.ok;
+            ok[side] &= !bit;
+            ok[1 - side] &= !(bit | neighbours(bit));
+            children.push(split_regions(ok, &mut pos.free.clone(), 1 - pos.side));
+        }
+        children
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use crjm_search::endgame::parent_value;
+    use std::collections::HashMap;
+    use gatos_caes_core::{count_moves, CATS, DOGS};
+
+    const EXACT: Limits = Limits { max_shared_cells: MAX_SHARED_CELLS, budget: 1_000_000 };
+
+    /// Minimax exato com distâncias sobre o tabuleiro inteiro
+    pub(crate) fn brute(board: Board, side: u8, memo: &mut HashMap<(Board, u8), i32>) -> i32 {
+        if let Some(&v) = memo.get(&(board, side)) {
+            return v;
+        }
+        let v = generate_moves(board, side)
+            .into_iter()
+            .map(|mv| parent_value(brute(apply_move(board, mv, side), 1 - side, memo)))
+            .max()
+            .unwrap_or(-MATE);
+        memo.insert((board, side), v);
+        v
+    }
+
+    /// Jogo aleatório até restarem no máximo `live` casas vivas
+    pub(crate) fn random_endgame(rng: &mut Rng, live: u32) -> (Board, u8) {
+        let mut board = Board::default();
+        let mut side = CATS;
+        while (playable(board, 0) | playable(board, 1)).count_ones() > live || board.dogs == 0 {
+            let moves = generate_moves(board, side);
+            if moves.is_empty() {
+                break;
+            }
+            board = apply_move(board, moves[rng.below(moves.len())], side);
+            side = 1 - side;
+        }
+        (board, side)
+    }
+
+    #[test]
+    fn exclusive_regions_are_counted() {
+        // gatos na coluna 0 e cães nas colunas 2, 5 e 7: a coluna 1 está
+        // morta e as restantes são só dos cães
+        let column = 0x0101_0101_0101_0101u64;
+        let board = Board { cats: column, dogs: column << 2 | column << 5 | column << 7 };
+        let mut solver = EndgameSolver::new();
+        assert_eq!(count_moves(board, CATS), 0);
+        assert_eq!(solver.value(board, CATS, EXACT), Some(-MATE));
+        // os cães têm 24 lances livres e os gatos nenhum
+        assert_eq!(solver.value(board, DOGS, EXACT), Some(MATE - 1));
+    }
+
+    #[test]
+    fn agrees_with_brute_force() {
+        let mut rng = Rng::new(3);
+        let mut solver = EndgameSolver::new();
+        let mut memo = HashMap::new();
+        for _ in 0..40 {
+            let (board, side) = random_endgame(&mut rng, 14);
+            let expected = brute(board, side, &mut memo);
+            assert_eq!(solver.value(board, side, EXACT), Some(expected));
+            if let Some(solution) = solver.solve_root(board, side, EXACT) {
+                assert_eq!(solution.score, expected);
+                for (mv, v) in solution.moves {
+                    assert_eq!(v, parent_value(brute(apply_move(board, mv, side), 1 - side, &mut memo)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn openings_and_large_shared_areas_are_left_to_the_search() {
+        let mut solver = EndgameSolver::new();
+        let board = apply_move(Board::default(), 27, CATS);
+        assert_eq!(solver.value(board, DOGS, EXACT), None);
+        let board = apply_move(board, 0, DOGS);
+        assert_eq!(solver.value(board, CATS, EXACT), None);
+    }
+}
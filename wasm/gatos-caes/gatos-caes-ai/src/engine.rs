@@ -0,0 +1,261 @@
+//! Motor de pesquisa: Gatos & Cães sobre a pesquisa alfa-beta partilhada (`crjm_search`)
+
+use crjm_search::endgame::SolvedRoot;
+use crjm_search::{pick_root, Aspiration, Game};
+
+use gatos_caes_core::{apply_move, count_moves, generate_moves, is_legal, playable, Board, Move};
+
+use crate::endgame::{EndgameSolver, Limits, MAX_SHARED_CELLS};
+use crate::eval::{self, MATE, MATE_BOUND};
+use crate::rng::Rng;
+use crate::tt::TranspositionTable;
+use crate::zobrist::ZobristKeys;
+
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    /// variante principal, a começar em `best_move`
+    pub pv: Vec<Move>,
+    pub depth_reached: u32,
+    pub nodes_searched: u64,
+    pub tt_hits: u64,
+    pub tt_probes: u64,
+    pub score: i32,
+}
+
+/// Comprimento máximo da variante principal devolvida
+const MAX_PV: usize = 64;
+
+/// Limites do solver de finais na raiz e nas folhas (onde é chamado muitas
+/// vezes, por isso só para finais quase decompostos)
+const ROOT_ENDGAME: Limits = Limits { max_shared_cells: MAX_SHARED_CELLS, budget: 250_000 };
+const LEAF_ENDGAME: Limits = Limits { max_shared_cells: 8, budget: 300 };
+/// Nas folhas, o solver só é tentado com poucas casas vivas
+const LEAF_ENDGAME_MAX_LIVE: u32 = 24;
+
+/// Janela de aspiration (alargada só do lado que falhou)
+const ASPIRATION: Aspiration = Aspiration { initial: 40, min: 20, max: 400 };
+
+/// Posição vista pela pesquisa: o hash viaja com o tabuleiro
+#[derive(Clone, Copy)]
+struct Position {
+    board: Board,
+    side: u8,
+    hash: u64,
+}
+
+/// Gatos & Cães para a pesquisa partilhada
+struct GatosCaes<'a> {
+    zobrist: &'a ZobristKeys,
+    endgame: Option<EndgameSolver>,
+}
+
+impl Game for GatosCaes<'_> {
+    type Position = Position;
+    type Move = Move;
+    type TableScore = i16;
+    type TableMove = u8;
+
+    const MATE: i32 = MATE;
+    const MATE_BOUND: i32 = MATE_BOUND;
+    // a avaliação é barata: as folhas não vão à tabela
+    const PROBE_LEAVES: bool = false;
+
+    fn moves(&mut self, pos: &Position) -> Vec<Move> {
+        generate_moves(pos.board, pos.side)
+    }
+
+    fn has_moves(&mut self, pos: &Position) -> bool {
+        count_moves(pos.board, pos.side) > 0
+    }
+
+    fn is_legal(&mut self, pos: &Position, mv: Move) -> bool {
+        is_legal(pos.board, mv, pos.side)
+    }
+
+    fn apply(&self, pos: &Position, mv: Move) -> Position {
+        Position {
+            board: apply_move(pos.board, mv, pos.side),
+            side: 1 - pos.side,
+            hash: self.zobrist.update(pos.hash, mv, pos.side),
+        }
+    }
+
+    // jogo normal: quem fica sem lances perde
+    fn stuck(&self, _pos: &Position, ply: u32) -> i32 {
+        -MATE + ply as i32
+    }
+
+    fn evaluate(&mut self, pos: &Position, _key: u64, ply: u32) -> i32 {
+        // o adversário já não tem lances: o nosso próximo lance ganha
+        if count_moves(pos.board, 1 - pos.side) == 0 {
+            return MATE - (ply as i32 + 1);
+        }
+        let live = (playable(pos.board, 0) | playable(pos.board, 1)).count_ones();
+        let endgame = self.endgame.as_mut().filter(|_| live <= LEAF_ENDGAME_MAX_LIVE);
+        if let Some(v) = endgame.and_then(|e| e.value(pos.board, pos.side, LEAF_ENDGAME)) {
+            // valor relativo a este nó, distância contada desde a raiz
+            let ply = ply as i32;
+            return if v > 0 { v - ply } else { v + ply };
+        }
+        eval::evaluate(pos.board, pos.side)
+    }
+
+    fn key(&self, pos: &Position) -> u64 {
+        pos.hash
+    }
+
+    fn pack_move(&self, _pos: &Position, mv: Move) -> u8 {
+        mv
+    }
+
+    fn unpack_move(&self, _pos: &Position, mv: u8) -> Move {
+        mv
+    }
+
+    fn order_score(&mut self, pos: &Position, mv: Move, _depth: u32) -> i32 {
+        eval::move_score(pos.board, mv, pos.side)
+    }
+}
+
+pub struct Searcher<'a> {
+    search: crjm_search::Searcher<'a, GatosCaes<'a>>,
+    rng: &'a mut Rng,
+}
+
+impl<'a> Searcher<'a> {
+    pub fn new(
+        tt: &'a mut TranspositionTable,
+        zobrist: &'a ZobristKeys,
+        rng: &'a mut Rng,
+        deadline_ms: f64,
+        max_depth: u32,
+    ) -> Self {
+        let game = GatosCaes {
+            zobrist,
+            endgame: Some(EndgameSolver::new()),
+        };
+        let limits = crjm_search::Limits::new(deadline_ms, max_depth);
+        Self {
+            search: crjm_search::Searcher::new(game, tt, limits).with_aspiration(ASPIRATION),
+            rng,
+        }
+    }
+
+    /// Pesquisa com aprofundamento iterativo; com `top_n > 0` escolhe ao acaso
+    /// entre os `top_n` melhores lances a menos de `score_delta` do melhor
+    pub fn iterative_deepening(
+        &mut self,
+        board: Board,
+        side: u8,
+        top_n: u32,
+        score_delta: i32,
+        now: impl Fn() -> f64,
+    ) -> SearchResult {
+        // final decomposto: resolvido de forma exata, sem pesquisa
+        let root = self.search.game_mut().endgame.as_mut().and_then(|e| e.play_root(board, side, ROOT_ENDGAME, top_n, self.rng, MAX_PV));
+        if let Some(root) = root {
+            return solved_result(root);
+        }
+
+        let hash = self.search.game().zobrist.hash(board, side);
+        let outcome = self.search.iterative_deepening(Position { board, side, hash }, now);
+        let mut best_move = outcome.best_move;
+        let mut best_score = outcome.score;
+
+        if let Some(idx) = pick_root(outcome.root.iter().map(|&(_, s)| s), top_n, score_delta, self.rng) {
+            (best_move, best_score) = (Some(outcome.root[idx].0), outcome.root[idx].1);
+        }
+
+        let mut pv = outcome.pv;
+        if best_move != pv.first().copied() {
+            // a escolha não foi o lance principal: a variante não se aplica
+            pv = best_move.into_iter().collect();
+        }
+        SearchResult {
+            best_move,
+            pv,
+            depth_reached: outcome.depth,
+            nodes_searched: outcome.stats.nodes,
+            tt_hits: outcome.stats.tt_hits,
+            tt_probes: outcome.stats.tt_probes,
+            score: best_score,
+        }
+    }
+}
+
+/// Resultado de uma raiz resolvida pelo solver de finais
+fn solved_result(root: SolvedRoot<Move>) -> SearchResult {
+    SearchResult {
+        best_move: root.best_move,
+        pv: root.pv,
+        depth_reached: root.plies,
+        nodes_searched: 0,
+        tt_hits: 0,
+        tt_probes: 0,
+        score: root.solution.score,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endgame::tests::{brute, random_endgame};
+    use crjm_search::endgame::parent_value;
+    use gatos_caes_core::{CATS, CENTER};
+    use std::collections::HashMap;
+
+    fn assert_legal_line(board: Board, side: u8, line: &[Move]) {
+        let (mut board, mut side) = (board, side);
+        for &mv in line {
+            assert!(is_legal(board, mv, side), "{:?}", line);
+            board = apply_move(board, mv, side);
+            side = 1 - side;
+        }
+    }
+
+    #[test]
+    fn root_score_and_move_agree_with_exact_values() {
+        let zobrist = ZobristKeys::new();
+        let mut rng = Rng::new(4);
+        let mut memo = HashMap::new();
+        for round in 0..12 {
+            let (board, side) = random_endgame(&mut rng, 12);
+            let mut tt = TranspositionTable::new(1);
+            let mut search_rng = Rng::new(1);
+            let mut searcher = Searcher::new(&mut tt, &zobrist, &mut search_rng, f64::MAX, 16);
+            if round % 2 == 0 {
+                // metade das vezes sem o solver, só pela pesquisa
+                searcher.search.game_mut().endgame = None;
+            }
+            let result = searcher.iterative_deepening(board, side, 0, 0, || 0.0);
+            let value = brute(board, side, &mut memo);
+            assert_eq!(result.score, value);
+            let Some(best) = result.best_move else {
+                assert_eq!(value, -MATE);
+                continue;
+            };
+            assert_eq!(parent_value(brute(apply_move(board, best, side), 1 - side, &mut memo)), value);
+            assert_eq!(result.pv.first(), Some(&best));
+            assert_legal_line(board, side, &result.pv);
+        }
+    }
+
+    #[test]
+    fn openings_follow_the_centre_rules() {
+        let zobrist = ZobristKeys::new();
+        let mut tt = TranspositionTable::new(1);
+        let mut rng = Rng::new(5);
+        let first = Searcher::new(&mut tt, &zobrist, &mut rng, f64::MAX, 3)
+            .iterative_deepening(Board::default(), CATS, 4, 1000, || 0.0)
+            .best_move
+            .unwrap();
+        assert_ne!(CENTER & (1u64 << first), 0);
+        let board = apply_move(Board::default(), first, CATS);
+        let reply = Searcher::new(&mut tt, &zobrist, &mut rng, f64::MAX, 3)
+            .iterative_deepening(board, 1 - CATS, 0, 0, || 0.0)
+            .best_move
+            .unwrap();
+        assert_eq!(CENTER & (1u64 << reply), 0);
+        assert!(is_legal(board, reply, 1 - CATS));
+    }
+}
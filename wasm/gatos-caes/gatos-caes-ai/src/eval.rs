@@ -0,0 +1,82 @@
+//! Avaliação heurística (jogo normal: quem fica sem lances perde)
+
+use gatos_caes_core::{neighbours, playable, Board, Move};
+
+pub const MATE: i32 = 30_000;
+/// Um jogo tem no máximo 64 lances: scores a partir daqui são provados
+pub const MATE_BOUND: i32 = MATE - 100;
+
+/// Peso de uma casa segura (só nossa e sem casas do adversário ao lado)
+const SAFE_WEIGHT: i32 = 24;
+/// Peso de uma casa onde podemos jogar
+const MOBILITY_WEIGHT: i32 = 8;
+/// Vantagem de ter a vez
+const TEMPO: i32 = 6;
+
+/// Vezes de jogar de quem tem a vez até o jogo acabar, para um score
+/// provado; `None` para scores heurísticos
+///
+/// Conta também a vez em que o jogo já acabou: pelo menos 1 quando ganha
+/// (o último lance deixa o adversário sem lances), no máximo -1 quando perde (-1: já sem lances).
+pub fn mate_in(score: i32) -> Option<i32> {
+    if score >= MATE_BOUND {
+        Some((MATE - score + 1) / 2)
+    } else if score <= -MATE_BOUND {
+        Some(-((MATE + score) / 2 + 1))
+    } else {
+        None
+    }
+}
+
+/// Casas onde só `side` pode jogar e que o adversário já não consegue tirar
+#[inline]
+pub fn safe_cells(mine: u64, theirs: u64) -> u64 {
+    mine & !theirs & !neighbours(theirs)
+}
+
+/// Score para quem joga (positivo = bom para `side`)
+///
+/// As restrições de abertura são ignoradas: nas folhas já há peças dos dois lados.
+pub fn evaluate(board: Board, side: u8) -> i32 {
+    let mine = playable(board, side);
+    let theirs = playable(board, 1 - side);
+    let safe = safe_cells(mine, theirs).count_ones() as i32 - safe_cells(theirs, mine).count_ones() as i32;
+    let mobility = mine.count_ones() as i32 - theirs.count_ones() as i32;
+    SAFE_WEIGHT * safe + MOBILITY_WEIGHT * mobility + TEMPO
+}
+
+/// Ordenação barata: lances que tiram casas ao adversário primeiro, lances
+/// em casas que o adversário nunca poderia usar no fim
+#[inline]
+pub fn move_score(board: Board, mv: Move, side: u8) -> i32 {
+    let theirs = playable(board, 1 - side);
+    let bit = 1u64 << mv;
+    let taken = ((neighbours(bit) | bit) & theirs).count_ones() as i32;
+    if theirs & bit == 0 {
+        taken * 16 - 32
+    } else {
+        taken * 16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mate_in_counts_moves_of_each_side() {
+        assert_eq!(mate_in(MATE - 1), Some(1));
+        assert_eq!(mate_in(MATE - 5), Some(3));
+        assert_eq!(mate_in(-MATE), Some(-1));
+        assert_eq!(mate_in(-(MATE - 2)), Some(-2));
+        assert_eq!(mate_in(-(MATE - 4)), Some(-3));
+        assert_eq!(mate_in(250), None);
+    }
+
+    #[test]
+    fn symmetric_positions_only_count_the_tempo() {
+        let board = Board { cats: 1, dogs: 1u64 << 63 };
+        assert_eq!(evaluate(board, 0), TEMPO);
+        assert_eq!(evaluate(board, 1), TEMPO);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod endgame;
+pub mod engine;
+pub mod eval;
+pub use crjm_search::rng;
+pub mod tt;
+pub mod zobrist;
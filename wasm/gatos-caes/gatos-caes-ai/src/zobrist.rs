@@ -0,0 +1,49 @@
+use gatos_caes_core::{Board, Move, CATS, CELL_COUNT};
+
+use crjm_search::zobrist::KeyStream;
+
+#[derive(Clone)]
+pub struct ZobristKeys {
+    /// chaves por lado (gatos, cães) e casa
+    pub pieces: [[u64; CELL_COUNT]; 2],
+    pub side: u64,
+}
+
+pub const DEFAULT_ZOBRIST_SEED: u64 = 0x6A70_C4E5;
+
+impl ZobristKeys {
+    /// Chaves fixas (iguais em todas as execuções).
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_ZOBRIST_SEED)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        let mut keys = KeyStream::new(seed);
+        Self { pieces: [keys.keys(), keys.keys()], side: keys.key() }
+    }
+
+    pub fn hash(&self, board: Board, side_to_move: u8) -> u64 {
+        let mut h = 0u64;
+        for (keys, mask) in self.pieces.iter().zip([board.cats, board.dogs]) {
+            for sq in gatos_caes_core::squares(mask) {
+                h ^= keys[sq as usize];
+            }
+        }
+        if side_to_move != CATS {
+            h ^= self.side;
+        }
+        h
+    }
+
+    /// Hash depois de `side` jogar `mv` (passa também a vez)
+    #[inline]
+    pub fn update(&self, hash: u64, mv: Move, side: u8) -> u64 {
+        hash ^ self.pieces[side as usize][mv as usize] ^ self.side
+    }
+}
+
+impl Default for ZobristKeys {
+    fn default() -> Self {
+        Self::new()
+    }
+}
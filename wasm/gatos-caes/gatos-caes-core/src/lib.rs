@@ -0,0 +1,178 @@
+//! Regras de Gatos & Cães sobre bitboards 8×8.
+//!
+//! Os gatos (lado 0) começam; cada jogador coloca uma peça por lance e nunca
+//! pode haver um gato ortogonalmente adjacente a um cão. O primeiro gato vai
+//! para uma das quatro casas centrais e o primeiro cão para fora delas. Jogo
+//! normal: quem não tem lance legal perde.
+//!
+//! Casa `linha * 8 + coluna`, com a linha 0 em cima.
+
+pub const BOARD_SIZE: usize = 8;
+pub const CELL_COUNT: usize = BOARD_SIZE * BOARD_SIZE;
+
+pub const CATS: u8 = 0;
+pub const DOGS: u8 = 1;
+
+/// As casas (3,3), (3,4), (4,3) e (4,4)
+pub const CENTER: u64 = (0b11 << 27) | (0b11 << 35);
+
+const NOT_FIRST_COL: u64 = 0xFEFE_FEFE_FEFE_FEFE;
+const NOT_LAST_COL: u64 = 0x7F7F_7F7F_7F7F_7F7F;
+
+/// Lance = índice da casa (0..64)
+pub type Move = u8;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Board {
+    pub cats: u64,
+    pub dogs: u64,
+}
+
+impl Board {
+    #[inline]
+    pub fn from_u32_parts(cats_lo: u32, cats_hi: u32, dogs_lo: u32, dogs_hi: u32) -> Self {
+        Self {
+            cats: (cats_hi as u64) << 32 | cats_lo as u64,
+            dogs: (dogs_hi as u64) << 32 | dogs_lo as u64,
+        }
+    }
+
+    #[inline]
+    pub fn occupied(&self) -> u64 {
+        self.cats | self.dogs
+    }
+
+    #[inline]
+    pub fn pieces(&self, side: u8) -> u64 {
+        if side == CATS {
+            self.cats
+        } else {
+            self.dogs
+        }
+    }
+}
+
+/// Casas ortogonalmente adjacentes a alguma casa de `mask` (sem `mask`)
+#[inline]
+pub fn neighbours(mask: u64) -> u64 {
+    let spread = (mask << 8) | (mask >> 8) | ((mask << 1) & NOT_FIRST_COL) | ((mask >> 1) & NOT_LAST_COL);
+    spread & !mask
+}
+
+/// Casas vazias onde `side` pode jogar sem ficar ao lado de uma peça
+/// adversária, ignorando as restrições de abertura
+#[inline]
+pub fn playable(board: Board, side: u8) -> u64 {
+    let blocked = neighbours(board.pieces(1 - side)) | board.pieces(1 - side);
+    !(board.occupied() | blocked)
+}
+
+/// Casas onde `side` pode jogar, com as restrições de abertura (primeiro gato
+/// no centro, primeiro cão fora dele)
+#[inline]
+pub fn legal_mask(board: Board, side: u8) -> u64 {
+    let mask = playable(board, side);
+    match side {
+        CATS if board.cats == 0 => mask & CENTER,
+        DOGS if board.dogs == 0 => mask & !CENTER,
+        _ => mask,
+    }
+}
+
+pub fn generate_moves(board: Board, side: u8) -> Vec<Move> {
+    squares(legal_mask(board, side)).collect()
+}
+
+#[inline]
+pub fn count_moves(board: Board, side: u8) -> u32 {
+    legal_mask(board, side).count_ones()
+}
+
+#[inline]
+pub fn is_legal(board: Board, mv: Move, side: u8) -> bool {
+    (mv as usize) < CELL_COUNT && legal_mask(board, side) & (1u64 << mv) != 0
+}
+
+#[inline]
+pub fn apply_move(mut board: Board, mv: Move, side: u8) -> Board {
+    if side == CATS {
+        board.cats |= 1u64 << mv;
+    } else {
+        board.dogs |= 1u64 << mv;
+    }
+    board
+}
+
+/// Casas de `mask`, da mais baixa para a mais alta
+#[inline]
+pub fn squares(mut mask: u64) -> impl Iterator<Item = Move> {
+    std::iter::from_fn(move || {
+        (mask != 0).then(|| {
+            let sq = mask.trailing_zeros() as Move;
+            mask &= mask - 1;
+            sq
+        })
+    })
+}
+
+/// Componente conexa (ortogonal) de `mask` que contém as casas de `seed`
+pub fn flood(seed: u64, mask: u64) -> u64 {
+    let mut region = seed & mask;
+    loop {
+        let grown = (region | neighbours(region)) & mask;
+        if grown == region {
+            return region;
+        }
+        region = grown;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sq(linha: usize, coluna: usize) -> Move {
+        (linha * BOARD_SIZE + coluna) as Move
+    }
+
+    #[test]
+    fn first_cat_goes_to_the_center() {
+        let board = Board::default();
+        assert_eq!(generate_moves(board, CATS), vec![sq(3, 3), sq(3, 4), sq(4, 3), sq(4, 4)]);
+        assert!(!is_legal(board, sq(0, 0), CATS));
+    }
+
+    #[test]
+    fn first_dog_goes_outside_the_center_and_away_from_cats() {
+        let board = apply_move(Board::default(), sq(3, 3), CATS);
+        let moves = generate_moves(board, DOGS);
+        // 64 casas, menos o gato, as outras 3 centrais e os 2 vizinhos de fora do centro
+        assert_eq!(moves.len(), 64 - 1 - 3 - 2);
+        for bad in [sq(3, 4), sq(4, 4), sq(2, 3), sq(3, 2)] {
+            assert!(!moves.contains(&bad));
+        }
+        // depois do primeiro cão o centro fica livre para os cães
+        let board = apply_move(board, sq(0, 0), DOGS);
+        assert!(is_legal(board, sq(4, 4), DOGS));
+    }
+
+    #[test]
+    fn no_cat_next_to_a_dog() {
+        let board = Board { cats: 1u64 << sq(3, 3), dogs: 1u64 << sq(0, 7) };
+        assert!(!is_legal(board, sq(0, 6), CATS));
+        assert!(!is_legal(board, sq(1, 7), CATS));
+        assert!(is_legal(board, sq(1, 6), CATS));
+        // as colunas não dão a volta ao tabuleiro
+        assert!(is_legal(board, sq(1, 0), CATS));
+        assert!(!is_legal(board, sq(3, 4), DOGS));
+        assert!(is_legal(board, sq(2, 4), DOGS));
+    }
+
+    #[test]
+    fn flood_stays_inside_the_mask() {
+        let column = 0x0101_0101_0101_0101u64;
+        assert_eq!(flood(1, column), column);
+        assert_eq!(flood(1, column & !(1 << 24)), 0x0001_0101);
+        assert_eq!(neighbours(1u64 << 7), (1u64 << 6) | (1u64 << 15));
+    }
+}
@@ -0,0 +1,147 @@
+//! Gatos & Cães WASM Engine
+//!
+//! - 8x8 board, cats and dogs as two u64 bitboards
+//! - Negamax + alpha-beta + PVS with aspiration windows, TT, killers (`crjm_search`)
+//! - Root randomization for easier difficulties (top_n + score_delta)
+//! - Exact play once the live cells split into small regions (`gatos_caes_ai::endgame`)
+
+use wasm_bindgen::prelude::*;
+
+use gatos_caes_ai::engine as ai_engine;
+use gatos_caes_ai::eval;
+use gatos_caes_ai::rng::Rng;
+use gatos_caes_ai::tt::TranspositionTable;
+use gatos_caes_ai::zobrist::ZobristKeys;
+use gatos_caes_core::{Board, Move};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = Date)]
+    fn now() -> f64;
+}
+
+#[wasm_bindgen(start)]
+pub fn init() {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}
+
+#[wasm_bindgen]
+pub struct SearchResult {
+    /// Square `row * 8 + col`, or -1 if the side to move has no move
+    pub best_move: i32,
+    pub depth_reached: u32,
+    pub nodes_searched: u64,
+    pub elapsed_ms: f64,
+    pub tt_hits: u64,
+    pub tt_probes: u64,
+    pub score: i32,
+    /// Permille of the transposition table filled by this search
+    pub hashfull: u32,
+    /// Moves until the game is decided, in turns of the side to move: a proven win (≥ 1) or loss (≤ -1)
+    pub mate_in: Option<i32>,
+    pv: Vec<Move>,
+}
+
+#[wasm_bindgen]
+impl SearchResult {
+    /// Principal variation, starting with `best_move` (empty if there is none)
+    #[wasm_bindgen(getter)]
+    pub fn pv(&self) -> Vec<Move> {
+        self.pv.clone()
+    }
+}
+
+/// Legal squares for `side` (0 = cats, 1 = dogs), opening rules included
+#[wasm_bindgen]
+pub fn legal_moves(cats_lo: u32, cats_hi: u32, dogs_lo: u32, dogs_hi: u32, side: u8) -> Vec<Move> {
+    gatos_caes_core::generate_moves(Board::from_u32_parts(cats_lo, cats_hi, dogs_lo, dogs_hi), side)
+}
+
+const MAX_TT_MB: u32 = 256;
+
+#[wasm_bindgen]
+pub struct GatosCaesEngine {
+    tt: TranspositionTable,
+    zobrist: ZobristKeys,
+    rng: Rng,
+    seed: u32,
+}
+
+#[wasm_bindgen]
+impl GatosCaesEngine {
+    /// Engine with a transposition table of `tt_mb` megabytes; `seed` drives
+    /// every random choice, so the same seed and the same requests (with
+    /// depth rather than time limits) replay the same moves
+    #[wasm_bindgen(constructor)]
+    pub fn new(tt_mb: u32, seed: u32) -> GatosCaesEngine {
+        GatosCaesEngine {
+            tt: TranspositionTable::new(tt_mb.min(MAX_TT_MB) as usize),
+            zobrist: ZobristKeys::new(),
+            rng: Rng::new(seed as u64),
+            seed,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// Restarts the random stream from `seed` (e.g. to replay a reported game)
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed;
+        self.rng = Rng::new(seed as u64);
+    }
+
+    pub fn clear_tt(&mut self) {
+        self.tt.clear();
+    }
+
+    /// Reallocates the transposition table with `tt_mb` megabytes (clears it)
+    pub fn resize_tt(&mut self, tt_mb: u32) {
+        self.tt.resize(tt_mb.min(MAX_TT_MB) as usize);
+    }
+
+    /// Permille of the transposition table used by the current search
+    pub fn hashfull(&self) -> u32 {
+        self.tt.hashfull()
+    }
+
+    /// Best move for `side` (0 = cats, 1 = dogs); with `top_n > 0` picks at
+    /// random among the `top_n` best moves within `score_delta` of the best
+    #[allow(clippy::too_many_arguments)]
+    pub fn search(
+        &mut self,
+        cats_lo: u32,
+        cats_hi: u32,
+        dogs_lo: u32,
+        dogs_hi: u32,
+        side: u8,
+        time_budget_ms: f64,
+        max_depth: u32,
+        top_n: u32,
+        score_delta: i32,
+    ) -> SearchResult {
+        let board = Board::from_u32_parts(cats_lo, cats_hi, dogs_lo, dogs_hi);
+
+        self.tt.new_search();
+        let deadline = now() + time_budget_ms;
+
+        let result = ai_engine::Searcher::new(&mut self.tt, &self.zobrist, &mut self.rng, deadline, max_depth)
+            .iterative_deepening(board, side, top_n, score_delta, now);
+
+        SearchResult {
+            best_move: result.best_move.map(|m| m as i32).unwrap_or(-1),
+            depth_reached: result.depth_reached,
+            nodes_searched: result.nodes_searched,
+            elapsed_ms: now() - (deadline - time_budget_ms),
+            tt_hits: result.tt_hits,
+            tt_probes: result.tt_probes,
+            score: result.score,
+            hashfull: self.tt.hashfull(),
+            mate_in: eval::mate_in(result.score),
+            pv: result.pv,
+        }
+    }
+}
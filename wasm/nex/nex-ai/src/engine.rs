@@ -0,0 +1,354 @@
+//! Motor de pesquisa: Nex sobre a pesquisa alfa-beta partilhada (`crjm_search`)
+//!
+//! Só as colocações dão mais de 14 000 ações, por isso a pesquisa vê um
+//! conjunto de candidatas escolhidas pelos mapas de distâncias: peças
+//! próprias nas casas dos caminhos mais curtos (nossos ou do adversário),
+//! neutras nos caminhos do adversário, as melhores substituições e a troca.
+//! Com uma ligação imediata só se pesquisa essa ação; com uma ameaça do
+//! adversário, só as colocações que a tapam.
+//!
+//! A troca deixa as brancas a jogar outra vez; para a pesquisa, que alterna
+//! os lados, isso é a troca seguida de uma passagem forçada das pretas.
+
+use crjm_search::{pick_root, Aspiration, Game};
+
+use nex_core::{apply_action, cells, has_actions, is_legal, swap_available, Action, Board, Cell, BLACK};
+
+use crate::eval::{self, DistanceMap, MATE, MATE_BOUND};
+use crate::rng::Rng;
+use crate::tt::TranspositionTable;
+use crate::zobrist::ZobristKeys;
+
+/// Ação na pesquisa; `None` é a passagem das pretas depois da troca
+pub type Move = Option<Action>;
+
+pub struct SearchResult {
+    pub best_move: Option<Action>,
+    /// variante principal, a começar em `best_move` (sem as passagens)
+    pub pv: Vec<Action>,
+    pub depth_reached: u32,
+    pub nodes_searched: u64,
+    pub tt_hits: u64,
+    pub tt_probes: u64,
+    pub score: i32,
+}
+
+/// Casas candidatas para a peça própria e para a neutra de uma colocação
+const OWN_CANDIDATES: usize = 8;
+const NEUTRAL_CANDIDATES: usize = 6;
+/// Neutras a converter e peças próprias a largar nas substituições
+const SUBSTITUTE_NEUTRALS: usize = 3;
+const SUBSTITUTE_OWN: usize = 2;
+
+/// Código da passagem na tabela (fora dos códigos de `Action::encode`)
+const PASS_CODE: u32 = 3 << 30;
+
+/// Janela de aspiration (alargada só do lado que falhou)
+const ASPIRATION: Aspiration = Aspiration { initial: 80, min: 40, max: 800 };
+
+/// Posição vista pela pesquisa
+#[derive(Clone, Copy)]
+struct Position {
+    board: Board,
+    side: u8,
+    hash: u64,
+    /// as pretas só podem passar (as brancas acabaram de trocar)
+    pass: bool,
+}
+
+/// Distância de uma casa ao centro, para desempatar
+fn center_distance(c: Cell) -> u16 {
+    let (x, y) = (c as i32 / 11 - 5, c as i32 % 11 - 5);
+    // distância hexagonal com os vizinhos (x+1, y-1) e (x-1, y+1)
+    (x.abs().max(y.abs()).max((x + y).abs())) as u16
+}
+
+/// As `count` casas de `mask` com menor `key` (o centro desempata)
+fn best_cells(mask: u128, count: usize, key: impl Fn(Cell) -> u16) -> Vec<Cell> {
+    let mut list: Vec<Cell> = cells(mask).collect();
+    list.sort_by_key(|&c| (key(c), center_distance(c)));
+    list.truncate(count);
+    list
+}
+
+/// Ações candidatas, pela ordem em que vale a pena pesquisá-las
+fn candidates(board: &Board, side: u8) -> Vec<Action> {
+    let empty = board.empty();
+    let empties = empty.count_ones();
+
+    let wins = board.winning_cells(side);
+    if wins != 0 && empties >= 2 {
+        let own = wins.trailing_zeros() as Cell;
+        let neutral = (empty & !(1 << own)).trailing_zeros() as Cell;
+        return vec![Action::Place { own, neutral }];
+    }
+
+    let mine = DistanceMap::new(board, side);
+    let theirs = DistanceMap::new(board, 1 - side);
+    let mut actions = Vec::new();
+
+    if empties >= 2 {
+        let own_cells = best_cells(empty, OWN_CANDIDATES, |c| {
+            let (a, b) = (mine.slack(board, c, side), theirs.slack(board, c, 1 - side));
+            a.min(b) * 4 + a.max(b).min(100)
+        });
+        let neutral_cells = best_cells(empty, NEUTRAL_CANDIDATES, |c| {
+            // tapar o adversário sem nos tapar a nós
+            theirs.slack(board, c, 1 - side).min(100) * 2 + if mine.slack(board, c, side) == 0 { 3 } else { 0 }
+        });
+
+        let threats = board.winning_cells(1 - side);
+        match threats.count_ones() {
+            0 => {
+                for &own in &own_cells {
+                    for &neutral in neutral_cells.iter().filter(|&&n| n != own) {
+                        actions.push(Action::Place { own, neutral });
+                    }
+                }
+            }
+            // uma casa: ocupá-la com a peça própria ou com a neutra
+            1 => {
+                let t = threats.trailing_zeros() as Cell;
+                for &neutral in neutral_cells.iter().filter(|&&n| n != t) {
+                    actions.push(Action::Place { own: t, neutral });
+                }
+                for &own in own_cells.iter().filter(|&&o| o != t) {
+                    actions.push(Action::Place { own, neutral: t });
+                }
+            }
+            // duas casas: as duas peças da colocação
+            2 => {
+                let a = threats.trailing_zeros() as Cell;
+                let b = (threats & (threats - 1)).trailing_zeros() as Cell;
+                actions.push(Action::Place { own: a, neutral: b });
+                actions.push(Action::Place { own: b, neutral: a });
+            }
+            // mais do que isso não se tapa: perde-se de qualquer maneira
+            _ => actions.push(Action::Place { own: own_cells[0], neutral: neutral_cells[0] }),
+        }
+        if threats != 0 {
+            return actions;
+        }
+    }
+
+    let own = board.stones(side);
+    if board.neutral().count_ones() >= 2 && own != 0 {
+        let neutrals = best_cells(board.neutral(), SUBSTITUTE_NEUTRALS, |c| mine.slack(board, c, side));
+        // largar as peças que menos servem os nossos caminhos
+        let mut spare: Vec<Cell> = cells(own).collect();
+        spare.sort_by_key(|&c| std::cmp::Reverse(mine.slack(board, c, side)));
+        spare.truncate(SUBSTITUTE_OWN);
+        for (i, &a) in neutrals.iter().enumerate() {
+            for &b in &neutrals[i + 1..] {
+                for &o in &spare {
+                    actions.push(Action::Substitute { neutrals: [a, b], own: o });
+                }
+            }
+        }
+    }
+    if swap_available(board, side) {
+        actions.insert(0, Action::Swap);
+    }
+    actions
+}
+
+/// Nex para a pesquisa partilhada
+struct Nex<'a> {
+    zobrist: &'a ZobristKeys,
+}
+
+impl Nex<'_> {
+    /// Score de `board` com `side` a jogar, a `ply` meios-lances da raiz
+    fn score(&self, board: &Board, side: u8, ply: u32) -> i32 {
+        if board.winning_cells(side) != 0 && board.empty().count_ones() >= 2 {
+            return MATE - (ply as i32 + 1);
+        }
+        eval::evaluate(board, side)
+    }
+}
+
+impl Game for Nex<'_> {
+    type Position = Position;
+    type Move = Move;
+    type TableScore = i16;
+    type TableMove = u32;
+
+    const MATE: i32 = MATE;
+    const MATE_BOUND: i32 = MATE_BOUND;
+
+    fn moves(&mut self, pos: &Position) -> Vec<Move> {
+        if pos.pass {
+            return vec![None];
+        }
+        if pos.board.connected(1 - pos.side) {
+            return Vec::new();
+        }
+        candidates(&pos.board, pos.side).into_iter().map(Some).collect()
+    }
+
+    fn has_moves(&mut self, pos: &Position) -> bool {
+        pos.pass || has_actions(&pos.board, pos.side)
+    }
+
+    fn is_legal(&mut self, pos: &Position, mv: Move) -> bool {
+        match mv {
+            None => pos.pass,
+            Some(action) => !pos.pass && is_legal(&pos.board, pos.side, action),
+        }
+    }
+
+    fn apply(&self, pos: &Position, mv: Move) -> Position {
+        let (board, side, pass) = match mv {
+            None => (pos.board, 1 - pos.side, false),
+            // as brancas voltam a jogar depois da passagem das pretas
+            Some(Action::Swap) => (apply_action(&pos.board, pos.side, Action::Swap).0, BLACK, true),
+            Some(action) => {
+                let (board, side) = apply_action(&pos.board, pos.side, action);
+                (board, side, false)
+            }
+        };
+        Position { board, side, hash: self.zobrist.hash(&board, side, pass), pass }
+    }
+
+    // o adversário ligou as margens; sem ações e sem ligação é empate
+    fn stuck(&self, pos: &Position, ply: u32) -> i32 {
+        if pos.board.connected(1 - pos.side) {
+            -MATE + ply as i32
+        } else {
+            0
+        }
+    }
+
+    fn evaluate(&mut self, pos: &Position, _key: u64, ply: u32) -> i32 {
+        if pos.pass {
+            return -self.score(&pos.board, 1 - pos.side, ply + 1);
+        }
+        self.score(&pos.board, pos.side, ply)
+    }
+
+    fn key(&self, pos: &Position) -> u64 {
+        pos.hash
+    }
+
+    fn pack_move(&self, _pos: &Position, mv: Move) -> u32 {
+        mv.map_or(PASS_CODE, Action::encode)
+    }
+
+    fn unpack_move(&self, _pos: &Position, code: u32) -> Move {
+        Action::decode(code)
+    }
+}
+
+pub struct Searcher<'a> {
+    search: crjm_search::Searcher<'a, Nex<'a>>,
+    rng: &'a mut Rng,
+}
+
+impl<'a> Searcher<'a> {
+    pub fn new(
+        tt: &'a mut TranspositionTable,
+        zobrist: &'a ZobristKeys,
+        rng: &'a mut Rng,
+        deadline_ms: f64,
+        max_depth: u32,
+    ) -> Self {
+        let limits = crjm_search::Limits::new(deadline_ms, max_depth);
+        Self {
+            search: crjm_search::Searcher::new(Nex { zobrist }, tt, limits).with_aspiration(ASPIRATION),
+            rng,
+        }
+    }
+
+    /// Pesquisa com aprofundamento iterativo; com `top_n > 0` escolhe ao acaso
+    /// entre as `top_n` melhores ações a menos de `score_delta` da melhor
+    pub fn iterative_deepening(
+        &mut self,
+        board: &Board,
+        side: u8,
+        top_n: u32,
+        score_delta: i32,
+        now: impl Fn() -> f64,
+    ) -> SearchResult {
+        let hash = self.search.game().zobrist.hash(board, side, false);
+        let outcome = self.search.iterative_deepening(Position { board: *board, side, hash, pass: false }, now);
+        let mut best_move = outcome.best_move.flatten();
+        let mut best_score = outcome.score;
+
+        if let Some(idx) = pick_root(outcome.root.iter().map(|&(_, s)| s), top_n, score_delta, self.rng) {
+            (best_move, best_score) = outcome.root[idx];
+        }
+
+        let mut pv: Vec<Action> = outcome.pv.into_iter().flatten().collect();
+        if best_move != pv.first().copied() {
+            // a escolha não foi a ação principal: a variante não se aplica
+            pv = best_move.into_iter().collect();
+        }
+        SearchResult {
+            best_move,
+            pv,
+            depth_reached: outcome.depth,
+            nodes_searched: outcome.stats.nodes,
+            tt_hits: outcome.stats.tt_hits,
+            tt_probes: outcome.stats.tt_probes,
+            score: best_score,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nex_core::{cell, SIDE, WHITE};
+
+    fn search(board: &Board, side: u8, depth: u32) -> SearchResult {
+        let zobrist = ZobristKeys::new();
+        let mut tt = TranspositionTable::new(1);
+        let mut rng = Rng::new(1);
+        Searcher::new(&mut tt, &zobrist, &mut rng, f64::MAX, depth).iterative_deepening(board, side, 0, 0, || 0.0)
+    }
+
+    fn column(x: usize, ys: impl Iterator<Item = usize>) -> u128 {
+        ys.fold(0, |acc, y| acc | 1 << cell(x, y))
+    }
+
+    #[test]
+    fn connects_when_one_cell_is_missing() {
+        let board = Board::from_stones(column(5, (0..SIDE).filter(|&y| y != 4)), 0, 0);
+        let result = search(&board, BLACK, 3);
+        assert!(matches!(result.best_move, Some(Action::Place { own, .. }) if own == cell(5, 4)));
+        assert_eq!(eval::mate_in(result.score), Some(1));
+    }
+
+    #[test]
+    fn blocks_a_single_threat() {
+        // pretas a uma casa de ligar, brancas a jogar longe disso
+        let board = Board::from_stones(column(5, (0..SIDE).filter(|&y| y != 4)), column(0, 0..3), 0);
+        let result = search(&board, WHITE, 2);
+        let Some(Action::Place { own, neutral }) = result.best_move else {
+            panic!("{:?}", result.best_move);
+        };
+        assert!(own == cell(5, 4) || neutral == cell(5, 4));
+        // duas ameaças: a peça própria numa e a neutra na outra
+        let board = Board::from_stones(
+            column(2, (0..SIDE).filter(|&y| y != 4)) | column(8, (0..SIDE).filter(|&y| y != 7)),
+            column(0, 0..3),
+            0,
+        );
+        assert_eq!(board.winning_cells(BLACK).count_ones(), 2);
+        let Some(Action::Place { own, neutral }) = search(&board, WHITE, 2).best_move else {
+            panic!();
+        };
+        assert_eq!(1u128 << own | 1 << neutral, board.winning_cells(BLACK));
+    }
+
+    #[test]
+    fn white_swaps_the_first_stone_in_the_centre() {
+        let (board, side) = apply_action(&Board::default(), BLACK, Action::Place { own: cell(5, 5), neutral: cell(0, 0) });
+        let result = search(&board, side, 3);
+        assert_eq!(result.best_move, Some(Action::Swap));
+        // a variante segue com as brancas a jogar outra vez
+        assert!(result.pv.len() >= 2);
+        let (after, side) = apply_action(&board, WHITE, Action::Swap);
+        assert!(is_legal(&after, side, result.pv[1]));
+    }
+}
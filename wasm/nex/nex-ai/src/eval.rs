@@ -0,0 +1,177 @@
+//! Avaliação por caminho mais curto
+//!
+//! A distância de uma cor é o custo do caminho mais barato entre as suas
+//! margens: peças próprias custam 0, casas vazias 2 e neutras 3 (só entram
+//! por substituição, duas de cada vez e a troco de uma peça própria). As
+//! peças adversárias não se atravessam. Os custos estão em meios-passos:
+//! uma colocação tira 2 à distância.
+
+use nex_core::{adjacent, flood, Board, Cell, CELL_COUNT, EDGES};
+
+pub const MATE: i32 = 30_000;
+/// Scores a partir daqui são provados (um jogo não chega a 200 lances)
+pub const MATE_BOUND: i32 = MATE - 200;
+
+pub const EMPTY_COST: u16 = 2;
+pub const NEUTRAL_COST: u16 = 3;
+/// Distância de uma cor sem caminho possível
+pub const BLOCKED: u16 = 400;
+
+/// Peso de cada meio-passo de diferença entre as distâncias
+const DISTANCE_WEIGHT: i32 = 40;
+/// Vantagem de ter a vez
+const TEMPO: i32 = 20;
+
+/// Vezes de jogar de quem tem a vez até o jogo acabar, para um score
+/// provado; `None` para scores heurísticos
+///
+/// Conta também a vez em que o jogo já acabou: pelo menos 1 quando ganha
+/// (o último lance liga as margens), no máximo -1 quando perde (-1: o
+/// adversário já ligou).
+pub fn mate_in(score: i32) -> Option<i32> {
+    if score >= MATE_BOUND {
+        Some((MATE - score + 1) / 2)
+    } else if score <= -MATE_BOUND {
+        Some(-((MATE + score) / 2 + 1))
+    } else {
+        None
+    }
+}
+
+/// Expande as casas alcançadas a partir de `edge` custo a custo; `visit`
+/// recebe cada custo e as casas novas e pode parar a expansão
+fn expand(board: &Board, color: u8, edge: u128, mut visit: impl FnMut(u16, u128) -> bool) {
+    let own = board.stones(color);
+    let (empty, neutral) = (board.empty(), board.neutral());
+    // casas alcançadas até aos últimos custos: reached[d % 4] = custo <= d
+    let mut reached = [0u128; 4];
+    let mut idle = 0;
+    for d in 0u16.. {
+        let before = if d == 0 { 0 } else { reached[(d as usize - 1) % 4] };
+        let from = |cost: u16| match d.checked_sub(cost) {
+            Some(prev) => adjacent(reached[prev as usize % 4]) | edge,
+            None => 0,
+        };
+        let mut new = (empty & from(EMPTY_COST)) | (neutral & from(NEUTRAL_COST));
+        if d == 0 {
+            new |= own & edge;
+        }
+        new = flood(new | (own & adjacent(new)), new | own) & !before;
+        reached[d as usize % 4] = before | new;
+        if new == 0 {
+            idle += 1;
+            // nenhum custo passa de 3: quatro custos seguidos sem casas
+            // novas é o fim
+            if idle > NEUTRAL_COST {
+                return;
+            }
+            continue;
+        }
+        idle = 0;
+        if visit(d, new) {
+            return;
+        }
+    }
+}
+
+/// Distância entre as margens de `color` ([`BLOCKED`] sem caminho)
+pub fn distance(board: &Board, color: u8) -> u16 {
+    let (start, end) = EDGES[color as usize];
+    let mut found = BLOCKED;
+    expand(board, color, start, |d, new| {
+        if new & end != 0 {
+            found = d;
+            return true;
+        }
+        false
+    });
+    found
+}
+
+/// Custo do caminho mais barato de cada margem até cada casa, com o custo
+/// da casa incluído ([`BLOCKED`] se não houver)
+pub struct DistanceMap {
+    pub from_start: [u16; CELL_COUNT],
+    pub from_end: [u16; CELL_COUNT],
+    /// distância entre as margens
+    pub total: u16,
+}
+
+impl DistanceMap {
+    pub fn new(board: &Board, color: u8) -> Self {
+        let (start, end) = EDGES[color as usize];
+        let fill = |edge: u128| {
+            let mut map = [BLOCKED; CELL_COUNT];
+            expand(board, color, edge, |d, new| {
+                for c in nex_core::cells(new) {
+                    map[c as usize] = d;
+                }
+                false
+            });
+            map
+        };
+        let from_start = fill(start);
+        let total = nex_core::cells(end).map(|c| from_start[c as usize]).min().unwrap_or(BLOCKED);
+        Self { from_start, from_end: fill(end), total }
+    }
+
+    /// Quanto mais longo que o mais curto é o melhor caminho por `c`
+    /// (0 = `c` está num caminho mais curto)
+    pub fn slack(&self, board: &Board, c: Cell, color: u8) -> u16 {
+        let bit = 1u128 << c;
+        let own = if board.stones(color) & bit != 0 {
+            0
+        } else if board.neutral() & bit != 0 {
+            NEUTRAL_COST
+        } else {
+            EMPTY_COST
+        };
+        let (s, e) = (self.from_start[c as usize], self.from_end[c as usize]);
+        if s >= BLOCKED || e >= BLOCKED {
+            return BLOCKED;
+        }
+        (s + e - own).saturating_sub(self.total)
+    }
+}
+
+/// Score para quem joga (positivo = bom para `side`)
+pub fn evaluate(board: &Board, side: u8) -> i32 {
+    let mine = distance(board, side) as i32;
+    let theirs = distance(board, 1 - side) as i32;
+    DISTANCE_WEIGHT * (theirs - mine) + TEMPO
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nex_core::{cell, BLACK, SIDE, WHITE};
+
+    #[test]
+    fn mate_in_counts_moves_of_each_side() {
+        assert_eq!(mate_in(MATE - 1), Some(1));
+        assert_eq!(mate_in(MATE - 5), Some(3));
+        assert_eq!(mate_in(-MATE), Some(-1));
+        assert_eq!(mate_in(-(MATE - 2)), Some(-2));
+        assert_eq!(mate_in(-(MATE - 4)), Some(-3));
+        assert_eq!(mate_in(250), None);
+    }
+
+    #[test]
+    fn distances_count_empty_and_neutral_cells() {
+        let board = Board::default();
+        assert_eq!(distance(&board, BLACK), EMPTY_COST * SIDE as u16);
+        assert_eq!(evaluate(&board, BLACK), TEMPO);
+
+        // coluna x=5: pretas em y=1..9, neutra em y=0, vazia em y=10
+        let stones = (1..SIDE - 1).fold(0u128, |acc, y| acc | 1 << cell(5, y));
+        let board = Board::from_stones(stones, 0, 1 << cell(5, 0));
+        // (6,0) vazia ao lado de (5,1) é mais barata do que a neutra
+        assert_eq!(distance(&board, BLACK), 2 * EMPTY_COST);
+        let map = DistanceMap::new(&board, BLACK);
+        assert_eq!(map.total, 2 * EMPTY_COST);
+        assert_eq!(map.slack(&board, cell(5, 5), BLACK), 0);
+        assert_eq!(map.slack(&board, cell(5, 0), BLACK), NEUTRAL_COST - EMPTY_COST);
+        // as brancas atravessam a coluna preta pela casa vazia (5,10)
+        assert_eq!(distance(&board, WHITE), EMPTY_COST * SIDE as u16);
+    }
+}
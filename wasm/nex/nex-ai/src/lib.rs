@@ -0,0 +1,5 @@
+pub mod engine;
+pub mod eval;
+pub use crjm_search::rng;
+pub mod tt;
+pub mod zobrist;
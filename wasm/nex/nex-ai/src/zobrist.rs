@@ -0,0 +1,48 @@
+use nex_core::{cells, Board, BLACK, CELL_COUNT, WHITE};
+
+use crjm_search::zobrist::KeyStream;
+
+#[derive(Clone)]
+pub struct ZobristKeys {
+    /// chaves por tipo de peça (pretas, brancas, neutras) e casa
+    pub pieces: [[u64; CELL_COUNT]; 3],
+    pub side: u64,
+    /// as pretas passam a seguir à troca
+    pub pass: u64,
+}
+
+pub const DEFAULT_ZOBRIST_SEED: u64 = 0x4E58_0011;
+
+impl ZobristKeys {
+    /// Chaves fixas (iguais em todas as execuções).
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_ZOBRIST_SEED)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        let mut keys = KeyStream::new(seed);
+        Self { pieces: [keys.keys(), keys.keys(), keys.keys()], side: keys.key(), pass: keys.key() }
+    }
+
+    pub fn hash(&self, board: &Board, side_to_move: u8, pass: bool) -> u64 {
+        let mut h = 0u64;
+        for (keys, mask) in self.pieces.iter().zip([board.stones(BLACK), board.stones(WHITE), board.neutral()]) {
+            for c in cells(mask) {
+                h ^= keys[c as usize];
+            }
+        }
+        if side_to_move != BLACK {
+            h ^= self.side;
+        }
+        if pass {
+            h ^= self.pass;
+        }
+        h
+    }
+}
+
+impl Default for ZobristKeys {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,540 @@
+//! Regras de Nex no losango hexagonal 11×11.
+//!
+//! Casa `x * 11 + y` (como `tabuleiro[x][y]` em `logic.ts`); cada casa tem
+//! seis vizinhas: (x±1, y), (x, y±1), (x+1, y-1) e (x-1, y+1). As pretas
+//! (lado 0) ligam y=0 a y=10 e as brancas (lado 1) ligam x=0 a x=10.
+//!
+//! Em cada vez joga-se uma ação:
+//! - colocação: uma peça própria e uma neutra em duas casas vazias;
+//! - substituição: duas neutras passam a próprias e uma própria (que já
+//!   estava no tabuleiro) passa a neutra.
+//!
+//! Regra da torta: depois do primeiro lance das pretas, as brancas podem
+//! trocar as cores de todas as peças e continuam a jogar (como em
+//! `executarSwap`). As ligações são seguidas com union-find: colocar só junta
+//! conjuntos, tirar uma peça (substituição ou troca) reconstrói os da cor.
+
+pub const SIDE: usize = 11;
+pub const CELL_COUNT: usize = SIDE * SIDE;
+
+pub const BLACK: u8 = 0;
+pub const WHITE: u8 = 1;
+
+/// As 121 casas
+pub const BOARD_MASK: u128 = (1 << CELL_COUNT) - 1;
+
+const fn line_mask(on_x: bool, value: usize) -> u128 {
+    let mut mask = 0u128;
+    let mut sq = 0;
+    while sq < CELL_COUNT {
+        let coord = if on_x { sq / SIDE } else { sq % SIDE };
+        if coord == value {
+            mask |= 1 << sq;
+        }
+        sq += 1;
+    }
+    mask
+}
+
+const Y_FIRST: u128 = line_mask(false, 0);
+const Y_LAST: u128 = line_mask(false, SIDE - 1);
+const X_FIRST: u128 = line_mask(true, 0);
+const X_LAST: u128 = line_mask(true, SIDE - 1);
+
+/// Margens de partida e de chegada de cada cor
+pub const EDGES: [(u128, u128); 2] = [(Y_FIRST, Y_LAST), (X_FIRST, X_LAST)];
+
+/// Casa = `x * 11 + y` (0..121)
+pub type Cell = u8;
+
+#[inline]
+pub fn cell(x: usize, y: usize) -> Cell {
+    (x * SIDE + y) as Cell
+}
+
+/// Casas vizinhas de alguma casa de `mask` (podem estar em `mask`)
+#[inline]
+pub fn adjacent(mask: u128) -> u128 {
+    ((mask << SIDE)
+        | (mask >> SIDE)
+        | ((mask << 1) & !Y_FIRST)
+        | ((mask >> 1) & !Y_LAST)
+        | ((mask << (SIDE - 1)) & !Y_LAST)
+        | ((mask >> (SIDE - 1)) & !Y_FIRST))
+        & BOARD_MASK
+}
+
+/// Casas vizinhas de alguma casa de `mask` (sem `mask`)
+#[inline]
+pub fn neighbours(mask: u128) -> u128 {
+    adjacent(mask) & !mask
+}
+
+/// Casas de `mask`, da mais baixa para a mais alta
+#[inline]
+pub fn cells(mut mask: u128) -> impl Iterator<Item = Cell> {
+    std::iter::from_fn(move || {
+        (mask != 0).then(|| {
+            let c = mask.trailing_zeros() as Cell;
+            mask &= mask - 1;
+            c
+        })
+    })
+}
+
+/// Componente conexa de `mask` que contém as casas de `seed`
+pub fn flood(seed: u128, mask: u128) -> u128 {
+    let mut region = seed & mask;
+    loop {
+        let grown = (region | adjacent(region)) & mask;
+        if grown == region {
+            return region;
+        }
+        region = grown;
+    }
+}
+
+/// Nó virtual da margem de partida
+const START: usize = CELL_COUNT;
+/// Nó virtual da margem de chegada
+const END: usize = CELL_COUNT + 1;
+
+/// Conjuntos disjuntos das peças de uma cor mais as duas margens
+#[derive(Clone, Copy, Debug)]
+pub struct UnionFind {
+    parent: [u8; CELL_COUNT + 2],
+}
+
+impl Default for UnionFind {
+    fn default() -> Self {
+        let mut parent = [0u8; CELL_COUNT + 2];
+        for (i, p) in parent.iter_mut().enumerate() {
+            *p = i as u8;
+        }
+        Self { parent }
+    }
+}
+
+impl UnionFind {
+    #[inline]
+    pub fn find(&self, mut i: usize) -> usize {
+        while self.parent[i] as usize != i {
+            i = self.parent[i] as usize;
+        }
+        i
+    }
+
+    /// Com compressão de caminhos (a meio)
+    #[inline]
+    fn find_mut(&mut self, mut i: usize) -> usize {
+        while self.parent[i] as usize != i {
+            let grand = self.parent[self.parent[i] as usize];
+            self.parent[i] = grand;
+            i = grand as usize;
+        }
+        i
+    }
+
+    #[inline]
+    pub fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find_mut(a), self.find_mut(b));
+        if ra != rb {
+            self.parent[ra.min(rb)] = ra.max(rb) as u8;
+        }
+    }
+
+    /// As duas margens estão no mesmo conjunto
+    #[inline]
+    pub fn connected(&self) -> bool {
+        self.find(START) == self.find(END)
+    }
+}
+
+/// Ação de um jogador
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Place { own: Cell, neutral: Cell },
+    Substitute { neutrals: [Cell; 2], own: Cell },
+    /// Regra da torta (só as brancas, logo a seguir ao primeiro lance)
+    Swap,
+}
+
+impl Action {
+    const KIND_SHIFT: u32 = 30;
+
+    /// Código de 32 bits: casas de 7 bits e o tipo nos bits 30-31
+    pub fn encode(self) -> u32 {
+        match self {
+            Action::Place { own, neutral } => own as u32 | (neutral as u32) << 7,
+            Action::Substitute { neutrals: [a, b], own } => {
+                a as u32 | (b as u32) << 7 | (own as u32) << 14 | 1 << Self::KIND_SHIFT
+            }
+            Action::Swap => 2 << Self::KIND_SHIFT,
+        }
+    }
+
+    /// Inverso de [`encode`](Action::encode); `None` para códigos inválidos
+    pub fn decode(code: u32) -> Option<Action> {
+        let cell_at = |shift: u32| {
+            let c = (code >> shift) & 0x7F;
+            ((c as usize) < CELL_COUNT).then_some(c as Cell)
+        };
+        match code >> Self::KIND_SHIFT {
+            0 if code >> 14 == 0 => Some(Action::Place { own: cell_at(0)?, neutral: cell_at(7)? }),
+            1 if (code >> 21) & 0x1FF == 0 => Some(Action::Substitute {
+                neutrals: [cell_at(0)?, cell_at(7)?],
+                own: cell_at(14)?,
+            }),
+            2 if code == 2 << Self::KIND_SHIFT => Some(Action::Swap),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Board {
+    stones: [u128; 2],
+    neutral: u128,
+    links: [UnionFind; 2],
+}
+
+/// Dois tabuleiros são iguais se tiverem as mesmas peças
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.stones == other.stones && self.neutral == other.neutral
+    }
+}
+
+impl Eq for Board {}
+
+impl Board {
+    pub fn from_stones(black: u128, white: u128, neutral: u128) -> Self {
+        let black = black & BOARD_MASK;
+        let white = white & BOARD_MASK & !black;
+        let mut board = Self {
+            stones: [black, white],
+            neutral: neutral & BOARD_MASK & !(black | white),
+            links: Default::default(),
+        };
+        board.relink(BLACK);
+        board.relink(WHITE);
+        board
+    }
+
+    /// Tabuleiro a partir de 121 casas pela ordem de `cell` (0 = vazia,
+    /// 1 = preta, 2 = branca, 3 = neutra); `None` com outro tamanho ou
+    /// outros valores
+    pub fn from_cells(values: &[u8]) -> Option<Self> {
+        if values.len() != CELL_COUNT {
+            return None;
+        }
+        let mut masks = [0u128; 3];
+        for (c, &v) in values.iter().enumerate() {
+            match v {
+                0 => {}
+                1..=3 => masks[v as usize - 1] |= 1 << c,
+                _ => return None,
+            }
+        }
+        Some(Self::from_stones(masks[0], masks[1], masks[2]))
+    }
+
+    #[inline]
+    pub fn stones(&self, color: u8) -> u128 {
+        self.stones[color as usize]
+    }
+
+    #[inline]
+    pub fn neutral(&self) -> u128 {
+        self.neutral
+    }
+
+    #[inline]
+    pub fn empty(&self) -> u128 {
+        !(self.stones[0] | self.stones[1] | self.neutral) & BOARD_MASK
+    }
+
+    /// `color` já liga as suas margens
+    #[inline]
+    pub fn connected(&self, color: u8) -> bool {
+        self.links[color as usize].connected()
+    }
+
+    pub fn winner(&self) -> Option<u8> {
+        [BLACK, WHITE].into_iter().find(|&c| self.connected(c))
+    }
+
+    /// Casas vazias onde uma peça de `color` liga as margens
+    pub fn winning_cells(&self, color: u8) -> u128 {
+        let links = &self.links[color as usize];
+        let (start, end) = EDGES[color as usize];
+        let (rs, re) = (links.find(START), links.find(END));
+        let (mut touching_start, mut touching_end) = (0u128, 0u128);
+        for c in cells(self.stones[color as usize]) {
+            let root = links.find(c as usize);
+            if root == rs {
+                touching_start |= 1 << c;
+            }
+            if root == re {
+                touching_end |= 1 << c;
+            }
+        }
+        self.empty() & (adjacent(touching_start) | start) & (adjacent(touching_end) | end)
+    }
+
+    /// Põe uma peça de `color` numa casa vazia ou neutra
+    pub fn place(&mut self, c: Cell, color: u8) {
+        let bit = 1u128 << c;
+        let me = color as usize;
+        self.neutral &= !bit;
+        self.stones[me] |= bit;
+        let links = &mut self.links[me];
+        for n in cells(neighbours(bit) & self.stones[me]) {
+            links.union(c as usize, n as usize);
+        }
+        let (start, end) = EDGES[me];
+        if start & bit != 0 {
+            links.union(c as usize, START);
+        }
+        if end & bit != 0 {
+            links.union(c as usize, END);
+        }
+    }
+
+    /// Recalcula os conjuntos de `color`
+    fn relink(&mut self, color: u8) {
+        let me = color as usize;
+        self.links[me] = UnionFind::default();
+        let stones = self.stones[me];
+        self.stones[me] = 0;
+        for c in cells(stones) {
+            self.place(c, color);
+        }
+    }
+
+    /// Passa uma peça de `color` a neutra
+    pub fn neutralize(&mut self, c: Cell, color: u8) {
+        let bit = 1u128 << c;
+        self.stones[color as usize] &= !bit;
+        self.neutral |= bit;
+        self.relink(color);
+    }
+
+    /// Troca as cores de todas as peças
+    pub fn swap_colors(&mut self) {
+        self.stones.swap(0, 1);
+        self.relink(BLACK);
+        self.relink(WHITE);
+    }
+}
+
+/// As brancas podem trocar: só há a peça própria e a neutra do primeiro
+/// lance das pretas
+pub fn swap_available(board: &Board, side: u8) -> bool {
+    side == WHITE
+        && board.stones(BLACK).count_ones() == 1
+        && board.stones(WHITE) == 0
+        && board.neutral().count_ones() == 1
+}
+
+pub fn is_legal(board: &Board, side: u8, action: Action) -> bool {
+    let bit = |c: Cell| if (c as usize) < CELL_COUNT { 1u128 << c } else { 0 };
+    if board.winner().is_some() {
+        return false;
+    }
+    match action {
+        Action::Place { own, neutral } => {
+            let empty = board.empty();
+            own != neutral && empty & bit(own) != 0 && empty & bit(neutral) != 0
+        }
+        Action::Substitute { neutrals: [a, b], own } => {
+            a != b && board.neutral() & bit(a) != 0 && board.neutral() & bit(b) != 0 && board.stones(side) & bit(own) != 0
+        }
+        Action::Swap => swap_available(board, side),
+    }
+}
+
+/// Todas as ações legais (a colocação sozinha dá até 121 × 120)
+pub fn generate_actions(board: &Board, side: u8) -> Vec<Action> {
+    let mut actions = Vec::new();
+    if board.winner().is_some() {
+        return actions;
+    }
+    let empty = board.empty();
+    for own in cells(empty) {
+        for neutral in cells(empty & !(1 << own)) {
+            actions.push(Action::Place { own, neutral });
+        }
+    }
+    let neutral = board.neutral();
+    for a in cells(neutral) {
+        for b in cells(neutral & !((2u128 << a) - 1)) {
+            for own in cells(board.stones(side)) {
+                actions.push(Action::Substitute { neutrals: [a, b], own });
+            }
+        }
+    }
+    if swap_available(board, side) {
+        actions.push(Action::Swap);
+    }
+    actions
+}
+
+/// Há alguma ação legal?
+pub fn has_actions(board: &Board, side: u8) -> bool {
+    board.winner().is_none()
+        && (board.empty().count_ones() >= 2 || (board.neutral().count_ones() >= 2 && board.stones(side) != 0))
+}
+
+/// Tabuleiro e lado a jogar depois da ação (a troca não passa a vez)
+pub fn apply_action(board: &Board, side: u8, action: Action) -> (Board, u8) {
+    let mut next = *board;
+    match action {
+        Action::Place { own, neutral } => {
+            next.neutral |= 1 << neutral;
+            next.place(own, side);
+        }
+        Action::Substitute { neutrals: [a, b], own } => {
+            next.place(a, side);
+            next.place(b, side);
+            next.neutralize(own, side);
+        }
+        Action::Swap => {
+            next.swap_colors();
+            return (next, side);
+        }
+    }
+    (next, 1 - side)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits(list: &[(usize, usize)]) -> u128 {
+        list.iter().fold(0, |acc, &(x, y)| acc | 1 << cell(x, y))
+    }
+
+    /// Gerador pequeno só para os testes
+    fn next(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    /// Ligação por flood fill, sem union-find
+    fn flood_connected(board: &Board, color: u8) -> bool {
+        let (start, end) = EDGES[color as usize];
+        flood(board.stones(color) & start, board.stones(color)) & end != 0
+    }
+
+    #[test]
+    fn hex_neighbours_follow_logic_ts() {
+        assert_eq!(neighbours(bits(&[(5, 5)])), bits(&[(6, 5), (4, 5), (5, 6), (5, 4), (6, 4), (4, 6)]));
+        assert_eq!(neighbours(bits(&[(0, 0)])), bits(&[(1, 0), (0, 1)]));
+        assert_eq!(neighbours(bits(&[(0, 10)])), bits(&[(1, 10), (0, 9), (1, 9)]));
+        assert_eq!(neighbours(bits(&[(10, 0)])), bits(&[(9, 0), (10, 1), (9, 1)]));
+    }
+
+    #[test]
+    fn black_joins_y_edges_and_white_x_edges() {
+        let column: Vec<_> = (0..SIDE).map(|y| (5, y)).collect();
+        let board = Board::from_stones(bits(&column), 0, 0);
+        assert_eq!(board.winner(), Some(BLACK));
+        let board = Board::from_stones(0, bits(&column), 0);
+        assert!(!board.connected(WHITE));
+
+        // diagonal (x+1, y-1) liga as brancas de x=0 a x=10
+        let diagonal: Vec<_> = (0..SIDE).map(|x| (x, 10 - x)).collect();
+        let board = Board::from_stones(0, bits(&diagonal), 0);
+        assert_eq!(board.winner(), Some(WHITE));
+
+        // falta uma casa: é a única casa vencedora
+        let mut gap = Board::from_stones(0, bits(&diagonal) & !bits(&[(4, 6)]), 0);
+        assert_eq!(gap.winning_cells(WHITE), bits(&[(4, 6)]));
+        assert_eq!(gap.winning_cells(BLACK), 0);
+        gap.place(cell(4, 6), WHITE);
+        assert!(gap.connected(WHITE));
+    }
+
+    #[test]
+    fn winning_cells_next_to_an_edge() {
+        // pretas de y=1 a y=10: falta y=0 por cima ou por (6,0)
+        let column: Vec<_> = (1..SIDE).map(|y| (5, y)).collect();
+        let board = Board::from_stones(bits(&column), 0, bits(&[(5, 0)]));
+        assert_eq!(board.winning_cells(BLACK), bits(&[(6, 0)]));
+    }
+
+    #[test]
+    fn actions_and_swap_follow_logic_ts() {
+        let board = Board::default();
+        assert!(!swap_available(&board, WHITE));
+        assert_eq!(generate_actions(&board, BLACK).len(), CELL_COUNT * (CELL_COUNT - 1));
+
+        let first = Action::Place { own: cell(5, 5), neutral: cell(0, 0) };
+        let (board, side) = apply_action(&board, BLACK, first);
+        assert_eq!(side, WHITE);
+        assert!(swap_available(&board, WHITE));
+        assert!(generate_actions(&board, WHITE).contains(&Action::Swap));
+
+        // a troca muda as cores e as brancas continuam a jogar
+        let (swapped, side) = apply_action(&board, WHITE, Action::Swap);
+        assert_eq!(side, WHITE);
+        assert_eq!(swapped.stones(WHITE), bits(&[(5, 5)]));
+        assert_eq!(swapped.neutral(), bits(&[(0, 0)]));
+        assert!(!swap_available(&swapped, WHITE));
+
+        // substituição: duas neutras passam a pretas, uma preta a neutra
+        let board = Board::from_stones(bits(&[(5, 5)]), 0, bits(&[(1, 1), (2, 2)]));
+        let sub = Action::Substitute { neutrals: [cell(1, 1), cell(2, 2)], own: cell(5, 5) };
+        assert!(is_legal(&board, BLACK, sub));
+        assert!(!is_legal(&board, WHITE, sub));
+        let (after, _) = apply_action(&board, BLACK, sub);
+        assert_eq!(after.stones(BLACK), bits(&[(1, 1), (2, 2)]));
+        assert_eq!(after.neutral(), bits(&[(5, 5)]));
+        assert!(!is_legal(&board, BLACK, Action::Place { own: cell(1, 1), neutral: cell(3, 3) }));
+    }
+
+    #[test]
+    fn actions_round_trip_through_their_code() {
+        for action in [
+            Action::Place { own: 0, neutral: 120 },
+            Action::Substitute { neutrals: [3, 77], own: 120 },
+            Action::Swap,
+        ] {
+            assert_eq!(Action::decode(action.encode()), Some(action));
+        }
+        assert_eq!(Action::decode(121), None);
+        assert_eq!(Action::decode(3 << 30), None);
+    }
+
+    #[test]
+    fn union_find_agrees_with_flood_fill() {
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        for _ in 0..20 {
+            let mut board = Board::default();
+            let mut side = BLACK;
+            while has_actions(&board, side) {
+                // poucas colocações por jogo, para haver substituições
+                let actions = generate_actions(&board, side);
+                let subs: Vec<_> = actions.iter().filter(|a| !matches!(a, Action::Place { .. })).collect();
+                let action = if !subs.is_empty() && next(&mut state).is_multiple_of(3) {
+                    *subs[next(&mut state) as usize % subs.len()]
+                } else {
+                    actions[next(&mut state) as usize % actions.len()]
+                };
+                (board, side) = apply_action(&board, side, action);
+                for color in [BLACK, WHITE] {
+                    assert_eq!(board.connected(color), flood_connected(&board, color));
+                    // uma casa vencedora liga mesmo as margens
+                    for c in cells(board.winning_cells(color)) {
+                        let mut with = board;
+                        with.place(c, color);
+                        assert!(flood_connected(&with, color));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Nex WASM Engine
+//!
+//! - 11x11 hex board, black, white and neutral stones as u128 bitboards with
+//!   incremental edge-to-edge union-find
+//! - Negamax + alpha-beta + PVS with aspiration windows, TT, killers (`crjm_search`)
+//!   over candidate actions picked from two-distance maps
+//! - Shortest-path (empty 2, neutral 3) evaluation
+//! - Root randomization for easier difficulties (top_n + score_delta)
+//!
+//! Actions cross the boundary as `u32` codes: cells `x * 11 + y` in 7-bit
+//! fields and the kind in bits 30-31 (0 = place own + neutral,
+//! 1 = substitute two neutrals + own, 2 = swap).
+
+use wasm_bindgen::prelude::*;
+
+use nex_ai::engine as ai_engine;
+use nex_ai::eval;
+use nex_ai::rng::Rng;
+use nex_ai::tt::TranspositionTable;
+use nex_ai::zobrist::ZobristKeys;
+use nex_core::{Action, Board};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = Date)]
+    fn now() -> f64;
+}
+
+#[wasm_bindgen(start)]
+pub fn init() {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}
+
+#[wasm_bindgen]
+pub struct SearchResult {
+    /// Action code, or `undefined` if the side to move has no action
+    pub best_move: Option<u32>,
+    pub depth_reached: u32,
+    pub nodes_searched: u64,
+    pub elapsed_ms: f64,
+    pub tt_hits: u64,
+    pub tt_probes: u64,
+    pub score: i32,
+    /// Permille of the transposition table filled by this search
+    pub hashfull: u32,
+    /// Moves until a connection, in turns of the side to move: a proven win (≥ 1) or loss (≤ -1)
+    pub mate_in: Option<i32>,
+    pv: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl SearchResult {
+    /// Principal variation as action codes, starting with `best_move` (empty
+    /// if there is none); after a swap White simply moves again
+    #[wasm_bindgen(getter)]
+    pub fn pv(&self) -> Vec<u32> {
+        self.pv.clone()
+    }
+}
+
+fn board_from(cells: &[u8]) -> Result<Board, JsValue> {
+    Board::from_cells(cells).ok_or_else(|| {
+        JsValue::from_str("expected 121 cells with values 0 (empty), 1 (black), 2 (white) or 3 (neutral)")
+    })
+}
+
+/// Legal action codes for `side` (0 = black, 1 = white); `cells` holds the
+/// 121 cells by `x * 11 + y` (0 = empty, 1 = black, 2 = white, 3 = neutral)
+#[wasm_bindgen]
+pub fn legal_actions(cells: &[u8], side: u8) -> Result<Vec<u32>, JsValue> {
+    Ok(nex_core::generate_actions(&board_from(cells)?, side).into_iter().map(Action::encode).collect())
+}
+
+/// Whether the action `code` is legal for `side` on `cells`
+#[wasm_bindgen]
+pub fn is_legal_action(cells: &[u8], side: u8, code: u32) -> Result<bool, JsValue> {
+    let board = board_from(cells)?;
+    Ok(Action::decode(code).is_some_and(|action| nex_core::is_legal(&board, side, action)))
+}
+
+const MAX_TT_MB: u32 = 256;
+
+#[wasm_bindgen]
+pub struct NexEngine {
+    tt: TranspositionTable,
+    zobrist: ZobristKeys,
+    rng: Rng,
+    seed: u32,
+}
+
+#[wasm_bindgen]
+impl NexEngine {
+    /// Engine with a transposition table of `tt_mb` megabytes; `seed` drives
+    /// every random choice, so the same seed and the same requests (with
+    /// depth rather than time limits) replay the same actions
+    #[wasm_bindgen(constructor)]
+    pub fn new(tt_mb: u32, seed: u32) -> NexEngine {
+        NexEngine {
+            tt: TranspositionTable::new(tt_mb.min(MAX_TT_MB) as usize),
+            zobrist: ZobristKeys::new(),
+            rng: Rng::new(seed as u64),
+            seed,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// Restarts the random stream from `seed` (e.g. to replay a reported game)
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed;
+        self.rng = Rng::new(seed as u64);
+    }
+
+    pub fn clear_tt(&mut self) {
+        self.tt.clear();
+    }
+
+    /// Reallocates the transposition table with `tt_mb` megabytes (clears it)
+    pub fn resize_tt(&mut self, tt_mb: u32) {
+        self.tt.resize(tt_mb.min(MAX_TT_MB) as usize);
+    }
+
+    /// Permille of the transposition table used by the current search
+    pub fn hashfull(&self) -> u32 {
+        self.tt.hashfull()
+    }
+
+    /// Best action for `side` (0 = black, 1 = white) on `cells` (see
+    /// `legal_actions`); with `top_n > 0` picks at random among the `top_n`
+    /// best actions within `score_delta` of the best. An immediate
+    /// connection is always played.
+    pub fn search(
+        &mut self,
+        cells: &[u8],
+        side: u8,
+        time_budget_ms: f64,
+        max_depth: u32,
+        top_n: u32,
+        score_delta: i32,
+    ) -> Result<SearchResult, JsValue> {
+        let board = board_from(cells)?;
+
+        self.tt.new_search();
+        let deadline = now() + time_budget_ms;
+
+        let result = ai_engine::Searcher::new(&mut self.tt, &self.zobrist, &mut self.rng, deadline, max_depth)
+            .iterative_deepening(&board, side, top_n, score_delta, now);
+
+        Ok(SearchResult {
+            best_move: result.best_move.map(Action::encode),
+            depth_reached: result.depth_reached,
+            nodes_searched: result.nodes_searched,
+            elapsed_ms: now() - (deadline - time_budget_ms),
+            tt_hits: result.tt_hits,
+            tt_probes: result.tt_probes,
+            score: result.score,
+            hashfull: self.tt.hashfull(),
+            mate_in: eval::mate_in(result.score),
+            pv: result.pv.into_iter().map(Action::encode).collect(),
+        })
+    }
+}
@@ -0,0 +1,172 @@
+//! Solver exato dos finais.
+//!
+//! Com poucas casas vazias joga-se o resto do jogo todo: minimax por peças,
+//! em que a segunda peça de uma vez é do mesmo jogador (o valor não muda de
+//! sinal) e só se lê depois da primeira pela ordem das casas, para cada par
+//! aparecer uma vez. O valor é o resultado final para quem joga: 1 ganha,
+//! 0 empata, -1 perde.
+
+use produto_core::{apply_placement, cells, Board, Cell, Placement, BLACK, WHITE};
+
+/// Casas vazias a partir das quais se resolve a posição na raiz
+pub const ENDGAME_EMPTIES: u32 = 10;
+
+/// Resultado de uma posição cheia para `side`
+pub fn final_value(board: &Board, side: u8) -> i32 {
+    match board.leader() {
+        None => 0,
+        Some(c) if c == side => 1,
+        Some(_) => -1,
+    }
+}
+
+/// Resultado provado de uma posição
+#[derive(Clone, Debug, PartialEq)]
+pub struct Solution {
+    /// valor para quem joga (1, 0 ou -1)
+    pub value: i32,
+    /// as vezes possíveis (uma ou duas peças) com o valor, da melhor para a pior
+    pub turns: Vec<(Vec<Placement>, i32)>,
+}
+
+impl Solution {
+    pub fn best_turn(&self) -> Option<&[Placement]> {
+        self.turns.first().map(|(turn, _)| turn.as_slice())
+    }
+}
+
+/// Minimax com contagem de nós
+pub struct EndgameSolver {
+    nodes: u64,
+    budget: u64,
+}
+
+impl EndgameSolver {
+    pub fn new(budget: u64) -> Self {
+        Self { nodes: 0, budget }
+    }
+
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Todas as vezes de `side` com o valor exato; `None` se o orçamento
+    /// acabar antes
+    pub fn solve(&mut self, board: &Board, side: u8) -> Option<Solution> {
+        let mut turns = Vec::new();
+        for first in placements(board, None) {
+            let (child, next) = apply_placement(board, side, first);
+            if next != side {
+                turns.push((vec![first], -self.value(&child, next, None)?));
+                continue;
+            }
+            for second in placements(&child, Some(first.cell)) {
+                let (grand, next) = apply_placement(&child, side, second);
+                turns.push((vec![first, second], -self.value(&grand, next, None)?));
+            }
+        }
+        // estável: com valores iguais fica a ordem das casas
+        turns.sort_by_key(|&(_, v)| -v);
+        let value = turns.first().map_or_else(|| final_value(board, side), |&(_, v)| v);
+        Some(Solution { value, turns })
+    }
+
+    /// Valor para `side`; `after` é a primeira peça desta vez (a segunda
+    /// vem numa casa mais alta)
+    fn value(&mut self, board: &Board, side: u8, after: Option<Cell>) -> Option<i32> {
+        self.nodes += 1;
+        if self.nodes > self.budget {
+            return None;
+        }
+        if board.is_full() {
+            return Some(final_value(board, side));
+        }
+        let mut best = -1;
+        for p in placements(board, after) {
+            let (child, next) = apply_placement(board, side, p);
+            let v = if next == side { self.value(&child, side, Some(p.cell))? } else { -self.value(&child, next, None)? };
+            if v > best {
+                best = v;
+                if best == 1 {
+                    break;
+                }
+            }
+        }
+        Some(best)
+    }
+}
+
+/// Peças possíveis, só em casas acima de `after`
+fn placements(board: &Board, after: Option<Cell>) -> impl Iterator<Item = Placement> {
+    let above = after.map_or(!0, |c| !0u128 << c << 1);
+    cells(board.empty() & above).flat_map(|cell| [BLACK, WHITE].map(|color| Placement { cell, color }))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use produto_core::{generate_placements, CELLS};
+
+    /// Minimax por vezes sem cortes nem pares repetidos
+    fn naive(board: &Board, side: u8) -> i32 {
+        if board.is_full() {
+            return final_value(board, side);
+        }
+        generate_placements(board)
+            .into_iter()
+            .map(|p| {
+                let (child, next) = apply_placement(board, side, p);
+                if next == side {
+                    naive(&child, side)
+                } else {
+                    -naive(&child, next)
+                }
+            })
+            .max()
+            .unwrap()
+    }
+
+    /// Tabuleiro ao acaso com `empties` casas vazias e quem joga
+    pub(crate) fn random_position(rng: &mut Rng, empties: usize) -> (Board, u8) {
+        let mut board = Board::default();
+        let mut side = BLACK;
+        let mut order = CELLS;
+        for i in (1..order.len()).rev() {
+            order.swap(i, rng.below(i + 1));
+        }
+        for &cell in &order[..CELLS.len() - empties] {
+            (board, side) = apply_placement(&board, side, Placement { cell, color: rng.below(2) as u8 });
+        }
+        (board, side)
+    }
+
+    #[test]
+    fn solver_agrees_with_naive_minimax() {
+        let mut rng = Rng::new(3);
+        for empties in [1, 2, 3, 4, 5] {
+            for _ in 0..6 {
+                let (board, side) = random_position(&mut rng, empties);
+                let solution = EndgameSolver::new(u64::MAX).solve(&board, side).unwrap();
+                assert_eq!(solution.value, naive(&board, side));
+                // a melhor vez vale mesmo isso
+                let turn = solution.best_turn().unwrap();
+                assert_eq!(turn.len(), board.stones_to_place() as usize);
+                let (mut after, mut next) = (board, side);
+                for &p in turn {
+                    (after, next) = apply_placement(&after, next, p);
+                }
+                assert_ne!(next, side);
+                assert_eq!(-naive(&after, next), solution.value);
+            }
+        }
+    }
+
+    #[test]
+    fn budget_stops_the_solver() {
+        let (board, side) = random_position(&mut Rng::new(5), 12);
+        let mut solver = EndgameSolver::new(1000);
+        assert_eq!(solver.solve(&board, side), None);
+        assert!(solver.nodes() > 1000);
+    }
+}
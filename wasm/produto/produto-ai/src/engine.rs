@@ -0,0 +1,147 @@
+//! Motor de Produto: solver exato nos finais, MCTS por peças no resto
+//!
+//! A escolha é sempre uma vez inteira (uma peça na abertura ou a meio de uma
+//! vez, duas no início de uma vez). O score vai de -1000 (derrota certa) a
+//! 1000 (vitória certa): o valor exato quando o final é resolvido, senão a
+//! recompensa média da MCTS na mesma escala.
+
+use crjm_search::pick_root;
+use produto_core::{apply_placement, Board, Placement};
+
+use crate::endgame::{EndgameSolver, ENDGAME_EMPTIES};
+use crate::mcts::{greedy_placement, Limits, Mcts};
+use crate::rng::Rng;
+
+/// Nós do solver na raiz antes de passar à MCTS
+const ROOT_ENDGAME_BUDGET: u64 = 2_000_000;
+
+/// Escala do score
+pub const WIN: i32 = 1000;
+
+pub struct SearchResult {
+    /// as peças a pôr nesta vez (vazio com o tabuleiro cheio)
+    pub best_turn: Vec<Placement>,
+    /// variante principal, a começar em `best_turn`
+    pub pv: Vec<Placement>,
+    pub playouts: u64,
+    pub nodes: u64,
+    pub score: i32,
+    /// o score é o resultado exato
+    pub proven: bool,
+}
+
+pub struct Searcher<'a> {
+    rng: &'a mut Rng,
+    limits: Limits,
+}
+
+impl<'a> Searcher<'a> {
+    pub fn new(rng: &'a mut Rng, deadline_ms: f64, max_playouts: u64) -> Self {
+        Self { rng, limits: Limits { deadline_ms, max_playouts } }
+    }
+
+    /// Melhor vez para `side`; com `top_n > 0` escolhe ao acaso entre as
+    /// `top_n` melhores a menos de `score_delta` da melhor (na MCTS, só a
+    /// primeira peça; a segunda é a melhor depois dela)
+    pub fn search(
+        &mut self,
+        board: &Board,
+        side: u8,
+        top_n: u32,
+        score_delta: i32,
+        now: impl Fn() -> f64,
+    ) -> SearchResult {
+        if board.is_full() {
+            return SearchResult { best_turn: Vec::new(), pv: Vec::new(), playouts: 0, nodes: 0, score: 0, proven: true };
+        }
+        if board.empty().count_ones() <= ENDGAME_EMPTIES {
+            let mut solver = EndgameSolver::new(ROOT_ENDGAME_BUDGET);
+            if let Some(solution) = solver.solve(board, side) {
+                let values = solution.turns.iter().map(|(_, v)| v * WIN);
+                let pick = pick_root(values, top_n, score_delta, self.rng).unwrap_or(0);
+                let (turn, value) = solution.turns[pick].clone();
+                return SearchResult {
+                    pv: turn.clone(),
+                    best_turn: turn,
+                    playouts: 0,
+                    nodes: solver.nodes(),
+                    score: value * WIN,
+                    proven: true,
+                };
+            }
+        }
+
+        let limits = self.limits;
+        let mut mcts = Mcts::new(self.rng);
+        let report = mcts.search(board, side, limits, now);
+        let scale = |mean: f32| ((2.0 * mean - 1.0) * WIN as f32).round() as i32;
+        let scores = report.root.iter().map(|s| scale(s.mean));
+        let pick = pick_root(scores, top_n, score_delta, mcts.rng()).unwrap_or(0);
+        let first = report.root[pick];
+
+        let mut best_turn = vec![first.placement];
+        let (after, next) = apply_placement(board, side, first.placement);
+        if next == side {
+            // a segunda peça: a mais visitada depois da primeira
+            let child = mcts.root_child(first.placement).expect("root statistics come from root children");
+            let second = mcts.principal_variation(child).first().copied();
+            best_turn.extend(second.or_else(|| greedy_placement(&after, side)));
+        }
+        let pv = if report.pv.starts_with(&best_turn) { report.pv } else { best_turn.clone() };
+        SearchResult {
+            best_turn,
+            pv,
+            playouts: report.playouts,
+            nodes: report.nodes as u64,
+            score: scale(first.mean),
+            proven: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endgame::tests::random_position;
+    use produto_core::{is_legal, BLACK, WHITE};
+
+    fn search(board: &Board, side: u8, seed: u64, top_n: u32) -> SearchResult {
+        let mut rng = Rng::new(seed);
+        Searcher::new(&mut rng, f64::MAX, 2_000).search(board, side, top_n, 300, || 0.0)
+    }
+
+    /// Joga a vez e devolve quem joga a seguir
+    fn play(board: &Board, side: u8, turn: &[Placement]) -> (Board, u8) {
+        let (mut board, mut next) = (*board, side);
+        for &p in turn {
+            assert!(is_legal(&board, p));
+            (board, next) = apply_placement(&board, next, p);
+        }
+        (board, next)
+    }
+
+    #[test]
+    fn turns_have_the_stones_of_the_rules() {
+        let result = search(&Board::default(), BLACK, 1, 0);
+        assert_eq!(result.best_turn.len(), 1);
+        let (board, side) = play(&Board::default(), BLACK, &result.best_turn);
+        assert_eq!(side, WHITE);
+
+        let result = search(&board, side, 1, 0);
+        assert_eq!(result.best_turn.len(), 2);
+        assert!(result.pv.starts_with(&result.best_turn));
+        assert!(!result.proven);
+        assert_eq!(play(&board, side, &result.best_turn).1, BLACK);
+    }
+
+    #[test]
+    fn small_endgames_are_solved() {
+        let (board, side) = random_position(&mut Rng::new(9), ENDGAME_EMPTIES as usize - 2);
+        let result = search(&board, side, 1, 0);
+        assert!(result.proven);
+        assert_eq!(result.playouts, 0);
+        let solution = EndgameSolver::new(u64::MAX).solve(&board, side).unwrap();
+        assert_eq!(result.score, solution.value * WIN);
+        assert_eq!(play(&board, side, &result.best_turn).1, 1 - side);
+    }
+}
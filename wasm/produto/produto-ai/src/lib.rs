@@ -0,0 +1,4 @@
+pub mod endgame;
+pub mod engine;
+pub mod mcts;
+pub use crjm_search::rng;
@@ -0,0 +1,284 @@
+//! Monte Carlo tree search por peças.
+//!
+//! Uma vez de duas peças tem até 61·60·2 escolhas; a árvore decompõe-na em
+//! duas peças, cada uma com no máximo 122 filhos (casa vazia × cor). Os nós
+//! da segunda peça continuam a ser do mesmo jogador e só têm casas acima da
+//! primeira, para cada par aparecer uma vez.
+//!
+//! Seleção por UCT, expansão de todos os filhos na segunda visita e
+//! simulações que enchem o tabuleiro ao acaso: como o resultado só depende
+//! do tabuleiro final, a simulação nem precisa de alternar os jogadores. A
+//! recompensa é 1 para quem ganha, ½ no empate.
+
+use produto_core::{apply_placement, cells, Board, Cell, Placement};
+
+use crate::endgame::final_value;
+use crate::rng::Rng;
+
+/// Constante de exploração do UCT
+const EXPLORATION: f32 = 0.7;
+/// Nós da árvore (16 bytes cada) antes de deixar de expandir
+pub const MAX_NODES: usize = 1 << 20;
+/// Simulações entre consultas ao relógio
+const CLOCK_INTERVAL: u64 = 64;
+
+/// Limites de uma pesquisa
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    pub deadline_ms: f64,
+    pub max_playouts: u64,
+}
+
+#[derive(Clone, Copy)]
+struct Node {
+    first_child: u32,
+    visits: u32,
+    /// soma das recompensas para quem pôs a peça do nó
+    reward: f32,
+    placement: Placement,
+    /// quem pôs a peça
+    side: u8,
+    children: u8,
+}
+
+impl Node {
+    fn new(placement: Placement, side: u8) -> Self {
+        Self { first_child: 0, visits: 0, reward: 0.0, placement, side, children: 0 }
+    }
+
+    fn mean(&self) -> f32 {
+        if self.visits == 0 {
+            0.0
+        } else {
+            self.reward / self.visits as f32
+        }
+    }
+}
+
+/// Peça da raiz com as estatísticas
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RootStat {
+    pub placement: Placement,
+    pub visits: u32,
+    /// recompensa média para quem joga (0..1)
+    pub mean: f32,
+}
+
+/// Resultado de uma pesquisa
+#[derive(Clone, Debug)]
+pub struct Report {
+    /// peças da raiz, da mais visitada para a menos
+    pub root: Vec<RootStat>,
+    /// linha mais visitada
+    pub pv: Vec<Placement>,
+    pub playouts: u64,
+    pub nodes: usize,
+}
+
+pub struct Mcts<'a> {
+    rng: &'a mut Rng,
+    nodes: Vec<Node>,
+}
+
+impl<'a> Mcts<'a> {
+    pub fn new(rng: &'a mut Rng) -> Self {
+        Self { rng, nodes: Vec::new() }
+    }
+
+    /// Pesquisa a partir de `board` com `side` a jogar
+    pub fn search(&mut self, board: &Board, side: u8, limits: Limits, now: impl Fn() -> f64) -> Report {
+        self.nodes.clear();
+        self.nodes.push(Node::new(Placement { cell: 0, color: 0 }, 1 - side));
+        let mut playouts = 0;
+        // pelo menos uma simulação, para a raiz ter filhos
+        while (playouts == 0 || playouts < limits.max_playouts) && !board.is_full() {
+            if playouts % CLOCK_INTERVAL == 0 && playouts > 0 && now() >= limits.deadline_ms {
+                break;
+            }
+            self.iterate(board, side);
+            playouts += 1;
+        }
+        let mut root: Vec<RootStat> = self
+            .children(0)
+            .map(|i| {
+                let n = &self.nodes[i];
+                RootStat { placement: n.placement, visits: n.visits, mean: n.mean() }
+            })
+            .collect();
+        root.sort_by(|a, b| b.visits.cmp(&a.visits).then(b.mean.total_cmp(&a.mean)));
+        Report { root, pv: self.principal_variation(0), playouts, nodes: self.nodes.len() }
+    }
+
+    /// O gerador da pesquisa (para as escolhas depois dela)
+    pub fn rng(&mut self) -> &mut Rng {
+        self.rng
+    }
+
+    /// Linha mais visitada a partir do nó `from`
+    pub fn principal_variation(&self, mut from: usize) -> Vec<Placement> {
+        let mut pv = Vec::new();
+        while let Some(best) = self.children(from).filter(|&i| self.nodes[i].visits > 0).max_by_key(|&i| self.nodes[i].visits) {
+            pv.push(self.nodes[best].placement);
+            from = best;
+        }
+        pv
+    }
+
+    /// Nó filho da raiz com a peça `p`
+    pub fn root_child(&self, p: Placement) -> Option<usize> {
+        self.children(0).find(|&i| self.nodes[i].placement == p)
+    }
+
+    fn children(&self, node: usize) -> std::ops::Range<usize> {
+        let n = &self.nodes[node];
+        n.first_child as usize..n.first_child as usize + n.children as usize
+    }
+
+    fn iterate(&mut self, root: &Board, root_side: u8) {
+        let mut path = vec![0usize];
+        let (mut board, mut side) = (*root, root_side);
+        // primeira peça desta vez, se a última peça não a acabou
+        let mut after: Option<Cell> = None;
+        loop {
+            let node = *path.last().unwrap();
+            if self.nodes[node].children == 0 {
+                let fresh = self.nodes[node].visits == 0 && node != 0;
+                if fresh || board.is_full() || !self.expand(node, &board, side, after) {
+                    break;
+                }
+            }
+            let child = self.select(node);
+            let p = self.nodes[child].placement;
+            let (next_board, next_side) = apply_placement(&board, side, p);
+            after = (next_side == side).then_some(p.cell);
+            (board, side) = (next_board, next_side);
+            path.push(child);
+        }
+
+        let winner = self.playout(&board).leader();
+        for &i in &path {
+            let node = &mut self.nodes[i];
+            node.visits += 1;
+            node.reward += match winner {
+                None => 0.5,
+                Some(w) if w == node.side => 1.0,
+                Some(_) => 0.0,
+            };
+        }
+    }
+
+    /// Cria os filhos de `node`; `false` sem espaço na árvore
+    fn expand(&mut self, node: usize, board: &Board, side: u8, after: Option<Cell>) -> bool {
+        let above = after.map_or(!0, |c| !0u128 << c << 1);
+        let free = board.empty() & above;
+        let count = 2 * free.count_ones() as usize;
+        if count == 0 || self.nodes.len() + count > MAX_NODES {
+            return false;
+        }
+        self.nodes[node].first_child = self.nodes.len() as u32;
+        self.nodes[node].children = count as u8;
+        for cell in cells(free) {
+            for color in [side, 1 - side] {
+                self.nodes.push(Node::new(Placement { cell, color }, side));
+            }
+        }
+        true
+    }
+
+    /// Filho com maior UCT (os ainda não visitados primeiro, ao acaso)
+    fn select(&mut self, node: usize) -> usize {
+        let range = self.children(node);
+        let unvisited = range.clone().filter(|&i| self.nodes[i].visits == 0).count();
+        if unvisited > 0 {
+            let k = self.rng.below(unvisited);
+            return range.filter(|&i| self.nodes[i].visits == 0).nth(k).unwrap();
+        }
+        let log_n = (self.nodes[node].visits as f32).ln();
+        range
+            .max_by(|&a, &b| {
+                let uct = |i: usize| {
+                    let n = &self.nodes[i];
+                    n.mean() + EXPLORATION * (log_n / n.visits as f32).sqrt()
+                };
+                uct(a).total_cmp(&uct(b))
+            })
+            .unwrap()
+    }
+
+    /// Enche as casas vazias com cores ao acaso
+    fn playout(&mut self, board: &Board) -> Board {
+        let mut board = *board;
+        let mut bits = 0;
+        for (i, c) in cells(board.empty()).enumerate() {
+            if i % 64 == 0 {
+                bits = self.rng.next_u64();
+            }
+            board.place(c, (bits >> (i % 64)) as u8 & 1);
+        }
+        board
+    }
+}
+
+/// Peça que mais melhora a diferença de produtos para `side` (para acabar a
+/// vez quando a árvore não chegou à segunda peça)
+pub fn greedy_placement(board: &Board, side: u8) -> Option<Placement> {
+    let candidates = cells(board.empty()).flat_map(|cell| [side, 1 - side].map(|color| Placement { cell, color }));
+    candidates.max_by_key(|&p| {
+        let (child, _) = apply_placement(board, side, p);
+        let (mine, theirs) = (child.score(side), child.score(1 - side));
+        // o resultado final primeiro, se a peça acaba o jogo; depois menos
+        // peças próprias, o desempate
+        let outcome = if child.is_full() { final_value(&child, side) } else { 0 };
+        (outcome, mine.product as i64 - theirs.product as i64, -(mine.stones as i64))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endgame::tests::random_position;
+    use crate::endgame::EndgameSolver;
+
+    fn limits(max_playouts: u64) -> Limits {
+        Limits { deadline_ms: f64::MAX, max_playouts }
+    }
+
+    #[test]
+    fn small_endgames_converge_to_the_exact_value() {
+        let mut rng = Rng::new(11);
+        let mut checked = 0;
+        while checked < 8 {
+            let (board, side) = random_position(&mut rng, 6);
+            let solution = EndgameSolver::new(u64::MAX).solve(&board, side).unwrap();
+            // só posições onde a primeira peça decide alguma coisa
+            if solution.turns.iter().all(|&(_, v)| v == solution.value) {
+                continue;
+            }
+            let mut search_rng = Rng::new(checked);
+            let mut mcts = Mcts::new(&mut search_rng);
+            let report = mcts.search(&board, side, limits(20_000), || 0.0);
+            let first = report.root[0].placement;
+            let best_with_first =
+                solution.turns.iter().filter(|(turn, _)| turn.contains(&first)).map(|&(_, v)| v).max().unwrap();
+            assert_eq!(best_with_first, solution.value, "{:?}", report.root);
+            checked += 1;
+        }
+    }
+
+    #[test]
+    fn second_stones_stay_with_the_same_player() {
+        let (board, side) = random_position(&mut Rng::new(2), 40);
+        assert_eq!(board.stones_to_place(), 2);
+        let mut rng = Rng::new(1);
+        let mut mcts = Mcts::new(&mut rng);
+        let report = mcts.search(&board, side, limits(3_000), || 0.0);
+        assert_eq!(report.playouts, 3_000);
+        assert_eq!(report.root.len(), 2 * 40);
+        assert_eq!(report.root.iter().map(|s| s.visits as u64).sum::<u64>(), 3_000);
+        // a segunda peça é do mesmo jogador e numa casa mais alta
+        let child = mcts.root_child(report.pv[0]).unwrap();
+        assert_eq!(mcts.nodes[child].side, side);
+        let second = mcts.children(child).map(|i| mcts.nodes[i]).collect::<Vec<_>>();
+        assert!(second.iter().all(|n| n.side == side && n.placement.cell > report.pv[0].cell));
+    }
+}
@@ -0,0 +1,473 @@
+//! Regras de Produto no hexágono de lado 5 (61 casas).
+//!
+//! Coordenadas axiais (q, r) com |q|, |r|, |q + r| <= 4, como em `types.ts`;
+//! a casa (q, r) é o bit `(q + 4) * 9 + (r + 4)` de um `u128` numa grelha
+//! 9×9 (as 20 casas da grelha fora do hexágono nunca têm peças). Por ordem
+//! de bits, as casas aparecem pela ordem de `gerarPosicoesValidas`.
+//!
+//! Cada vez põe duas peças de qualquer cor em casas vazias, exceto a
+//! abertura (as pretas põem só uma). Como as 61 casas são ímpares, o número
+//! de peças no tabuleiro diz em que ponto da vez se está: ímpar no início
+//! de uma vez, par a meio (ou zero na abertura). O jogo acaba com o
+//! tabuleiro cheio; ganha o maior produto dos dois maiores grupos da sua
+//! cor (zero com menos de dois grupos) e, com produtos iguais, quem tiver
+//! menos peças.
+//!
+//! Os grupos só crescem ou se juntam (não há capturas), por isso seguem-se
+//! com union-find e um histograma dos tamanhos de cada cor.
+
+pub const SIDE: usize = 5;
+/// Distância máxima ao centro
+pub const RADIUS: i32 = SIDE as i32 - 1;
+/// Lado da grelha axial
+pub const GRID: usize = 2 * SIDE - 1;
+pub const CELL_COUNT: usize = 3 * SIDE * (SIDE - 1) + 1;
+
+pub const BLACK: u8 = 0;
+pub const WHITE: u8 = 1;
+
+/// Casa = `(q + 4) * 9 + (r + 4)` (0..81, só 61 no hexágono)
+pub type Cell = u8;
+
+const fn hex_mask() -> u128 {
+    let mut mask = 0u128;
+    let mut q = -RADIUS;
+    while q <= RADIUS {
+        let mut r = -RADIUS;
+        while r <= RADIUS {
+            if (q + r).abs() <= RADIUS {
+                mask |= 1 << ((q + RADIUS) as usize * GRID + (r + RADIUS) as usize);
+            }
+            r += 1;
+        }
+        q += 1;
+    }
+    mask
+}
+
+const fn r_mask(r: i32) -> u128 {
+    let mut mask = 0u128;
+    let mut q = 0;
+    while q < GRID {
+        mask |= 1 << (q * GRID + (r + RADIUS) as usize);
+        q += 1;
+    }
+    mask
+}
+
+/// As 61 casas do hexágono
+pub const BOARD_MASK: u128 = hex_mask();
+
+const R_FIRST: u128 = r_mask(-RADIUS);
+const R_LAST: u128 = r_mask(RADIUS);
+
+const fn cell_list() -> [Cell; CELL_COUNT] {
+    let mut list = [0; CELL_COUNT];
+    let (mut bit, mut i) = (0, 0);
+    while bit < GRID * GRID {
+        if BOARD_MASK & (1 << bit) != 0 {
+            list[i] = bit as Cell;
+            i += 1;
+        }
+        bit += 1;
+    }
+    list
+}
+
+/// As casas pela ordem de `gerarPosicoesValidas` (q e depois r crescentes)
+pub const CELLS: [Cell; CELL_COUNT] = cell_list();
+
+/// Casa de (q, r), `None` fora do hexágono
+pub fn cell(q: i32, r: i32) -> Option<Cell> {
+    (q.abs() <= RADIUS && r.abs() <= RADIUS && (q + r).abs() <= RADIUS)
+        .then(|| ((q + RADIUS) as usize * GRID + (r + RADIUS) as usize) as Cell)
+}
+
+/// Coordenadas axiais (q, r) de uma casa
+pub fn axial(c: Cell) -> (i32, i32) {
+    ((c as usize / GRID) as i32 - RADIUS, (c as usize % GRID) as i32 - RADIUS)
+}
+
+/// Posição da casa em [`CELLS`]
+pub fn index_of(c: Cell) -> usize {
+    (BOARD_MASK & ((1u128 << c) - 1)).count_ones() as usize
+}
+
+/// Casas vizinhas de alguma casa de `mask` (podem estar em `mask`), pelas
+/// direções de `DIRECOES_HEX`
+#[inline]
+pub fn adjacent(mask: u128) -> u128 {
+    ((mask << GRID)
+        | (mask >> GRID)
+        | ((mask & !R_FIRST) << (GRID - 1))
+        | ((mask & !R_LAST) >> (GRID - 1))
+        | ((mask & !R_LAST) << 1)
+        | ((mask & !R_FIRST) >> 1))
+        & BOARD_MASK
+}
+
+/// Casas vizinhas de alguma casa de `mask` (sem `mask`)
+#[inline]
+pub fn neighbours(mask: u128) -> u128 {
+    adjacent(mask) & !mask
+}
+
+/// Casas de `mask`, da mais baixa para a mais alta
+#[inline]
+pub fn cells(mut mask: u128) -> impl Iterator<Item = Cell> {
+    std::iter::from_fn(move || {
+        (mask != 0).then(|| {
+            let c = mask.trailing_zeros() as Cell;
+            mask &= mask - 1;
+            c
+        })
+    })
+}
+
+/// Componente conexa de `mask` que contém as casas de `seed`
+pub fn flood(seed: u128, mask: u128) -> u128 {
+    let mut region = seed & mask;
+    loop {
+        let grown = (region | adjacent(region)) & mask;
+        if grown == region {
+            return region;
+        }
+        region = grown;
+    }
+}
+
+/// Grupos de uma cor: union-find com tamanhos e histograma dos tamanhos
+#[derive(Clone, Copy, Debug)]
+pub struct Groups {
+    parent: [u8; GRID * GRID],
+    size: [u8; GRID * GRID],
+    /// `histogram[n]` = grupos com `n` peças
+    histogram: [u8; CELL_COUNT + 1],
+}
+
+impl Default for Groups {
+    fn default() -> Self {
+        let mut parent = [0u8; GRID * GRID];
+        for (i, p) in parent.iter_mut().enumerate() {
+            *p = i as u8;
+        }
+        Self { parent, size: [1; GRID * GRID], histogram: [0; CELL_COUNT + 1] }
+    }
+}
+
+impl Groups {
+    pub fn find(&self, mut i: usize) -> usize {
+        while self.parent[i] as usize != i {
+            i = self.parent[i] as usize;
+        }
+        i
+    }
+
+    fn find_mut(&mut self, mut i: usize) -> usize {
+        while self.parent[i] as usize != i {
+            // compressão por metades
+            self.parent[i] = self.parent[self.parent[i] as usize];
+            i = self.parent[i] as usize;
+        }
+        i
+    }
+
+    /// Nova peça isolada em `c`
+    fn add(&mut self, c: Cell) {
+        self.parent[c as usize] = c;
+        self.size[c as usize] = 1;
+        self.histogram[1] += 1;
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut ra, mut rb) = (self.find_mut(a), self.find_mut(b));
+        if ra == rb {
+            return;
+        }
+        if self.size[ra] < self.size[rb] {
+            std::mem::swap(&mut ra, &mut rb);
+        }
+        let (sa, sb) = (self.size[ra] as usize, self.size[rb] as usize);
+        self.histogram[sa] -= 1;
+        self.histogram[sb] -= 1;
+        self.histogram[sa + sb] += 1;
+        self.parent[rb] = ra as u8;
+        self.size[ra] = (sa + sb) as u8;
+    }
+
+    /// Peças do grupo de `c`
+    pub fn size_of(&self, c: Cell) -> usize {
+        self.size[self.find(c as usize)] as usize
+    }
+
+    /// Tamanhos dos dois maiores grupos (0 se não houver)
+    pub fn top_two(&self) -> (usize, usize) {
+        let mut sizes = (1..=CELL_COUNT).rev().flat_map(|n| std::iter::repeat_n(n, self.histogram[n] as usize));
+        (sizes.next().unwrap_or(0), sizes.next().unwrap_or(0))
+    }
+
+    /// Número de grupos
+    pub fn count(&self) -> usize {
+        self.histogram.iter().map(|&n| n as usize).sum()
+    }
+}
+
+/// Pontuação de uma cor (como `Pontuacao` em `types.ts`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Score {
+    pub largest: usize,
+    pub second: usize,
+    /// maior × segundo maior (0 com menos de dois grupos)
+    pub product: usize,
+    pub stones: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Board {
+    stones: [u128; 2],
+    groups: [Groups; 2],
+}
+
+/// Dois tabuleiros são iguais se tiverem as mesmas peças
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.stones == other.stones
+    }
+}
+
+impl Eq for Board {}
+
+impl Board {
+    pub fn from_stones(black: u128, white: u128) -> Self {
+        let black = black & BOARD_MASK;
+        let mut board = Self::default();
+        for c in cells(black) {
+            board.place(c, BLACK);
+        }
+        for c in cells(white & BOARD_MASK & !black) {
+            board.place(c, WHITE);
+        }
+        board
+    }
+
+    /// Tabuleiro a partir das 61 casas pela ordem de [`CELLS`] (0 = vazia,
+    /// 1 = preta, 2 = branca); `None` com outro tamanho ou outros valores
+    pub fn from_cells(values: &[u8]) -> Option<Self> {
+        if values.len() != CELL_COUNT {
+            return None;
+        }
+        let mut masks = [0u128; 2];
+        for (&c, &v) in CELLS.iter().zip(values) {
+            match v {
+                0 => {}
+                1 | 2 => masks[v as usize - 1] |= 1 << c,
+                _ => return None,
+            }
+        }
+        Some(Self::from_stones(masks[0], masks[1]))
+    }
+
+    #[inline]
+    pub fn stones(&self, color: u8) -> u128 {
+        self.stones[color as usize]
+    }
+
+    #[inline]
+    pub fn empty(&self) -> u128 {
+        !(self.stones[0] | self.stones[1]) & BOARD_MASK
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.empty() == 0
+    }
+
+    pub fn groups(&self, color: u8) -> &Groups {
+        &self.groups[color as usize]
+    }
+
+    /// Peças que faltam pôr nesta vez: 1 na abertura e a meio de uma vez,
+    /// 2 no início de uma vez, 0 com o tabuleiro cheio
+    pub fn stones_to_place(&self) -> u32 {
+        let placed = (self.stones[0] | self.stones[1]).count_ones();
+        if self.is_full() {
+            0
+        } else if placed % 2 == 1 {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Põe uma peça de `color` numa casa vazia
+    pub fn place(&mut self, c: Cell, color: u8) {
+        let bit = 1u128 << c;
+        let me = color as usize;
+        self.stones[me] |= bit;
+        let groups = &mut self.groups[me];
+        groups.add(c);
+        for n in cells(neighbours(bit) & self.stones[me]) {
+            groups.union(c as usize, n as usize);
+        }
+    }
+
+    pub fn score(&self, color: u8) -> Score {
+        let (largest, second) = self.groups[color as usize].top_two();
+        Score { largest, second, product: largest * second, stones: self.stones(color).count_ones() as usize }
+    }
+
+    /// Quem ganharia se o jogo acabasse agora (`None` = empate): maior
+    /// produto e, com produtos iguais, menos peças
+    pub fn leader(&self) -> Option<u8> {
+        let (black, white) = (self.score(BLACK), self.score(WHITE));
+        match black.product.cmp(&white.product).then(white.stones.cmp(&black.stones)) {
+            std::cmp::Ordering::Greater => Some(BLACK),
+            std::cmp::Ordering::Less => Some(WHITE),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+/// Uma peça de uma vez: casa e cor (de qualquer jogador)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Placement {
+    pub cell: Cell,
+    pub color: u8,
+}
+
+impl Placement {
+    /// Código de 8 bits: casa nos bits 0-6 e cor no bit 7
+    pub fn encode(self) -> u8 {
+        self.cell | self.color << 7
+    }
+
+    /// Inverso de [`encode`](Placement::encode); `None` fora do hexágono
+    pub fn decode(code: u8) -> Option<Placement> {
+        let cell = code & 0x7F;
+        (BOARD_MASK & (1u128 << cell) != 0).then_some(Placement { cell, color: code >> 7 })
+    }
+}
+
+pub fn is_legal(board: &Board, p: Placement) -> bool {
+    p.color <= WHITE && (p.cell as usize) < GRID * GRID && board.empty() & (1u128 << p.cell) != 0
+}
+
+/// Todas as peças possíveis: cada casa vazia com cada cor
+pub fn generate_placements(board: &Board) -> Vec<Placement> {
+    cells(board.empty()).flat_map(|cell| [BLACK, WHITE].map(|color| Placement { cell, color })).collect()
+}
+
+/// Põe a peça e devolve o tabuleiro e quem joga a seguir (o mesmo lado a
+/// meio de uma vez)
+pub fn apply_placement(board: &Board, side: u8, p: Placement) -> (Board, u8) {
+    let mut next = *board;
+    next.place(p.cell, p.color);
+    // a vez acaba quando as peças ficam ímpares
+    let done = (next.stones[0] | next.stones[1]).count_ones() % 2 == 1;
+    (next, if done { 1 - side } else { side })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(q: i32, r: i32) -> Cell {
+        cell(q, r).unwrap()
+    }
+
+    fn bits(list: &[(i32, i32)]) -> u128 {
+        list.iter().fold(0, |acc, &(q, r)| acc | 1 << at(q, r))
+    }
+
+    #[test]
+    fn hexagon_and_neighbours_follow_types_ts() {
+        assert_eq!(BOARD_MASK.count_ones() as usize, CELL_COUNT);
+        // ordem de gerarPosicoesValidas
+        assert_eq!(axial(CELLS[0]), (-4, 0));
+        assert_eq!(axial(CELLS[4]), (-4, 4));
+        assert_eq!(axial(CELLS[5]), (-3, -1));
+        assert_eq!(axial(CELLS[30]), (0, 0));
+        assert_eq!(index_of(at(0, 0)), 30);
+        assert_eq!(cell(3, 2), None);
+
+        assert_eq!(neighbours(bits(&[(0, 0)])), bits(&[(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)]));
+        // cantos: três vizinhas, sem dar a volta na grelha
+        assert_eq!(neighbours(bits(&[(4, -4)])), bits(&[(3, -4), (3, -3), (4, -3)]));
+        assert_eq!(neighbours(bits(&[(-4, 4)])), bits(&[(-3, 4), (-3, 3), (-4, 3)]));
+        assert_eq!(neighbours(bits(&[(0, 4)])), bits(&[(1, 3), (0, 3), (-1, 4)]));
+        assert_eq!(neighbours(bits(&[(0, -4)])), bits(&[(-1, -3), (0, -3), (1, -4)]));
+        let degrees: usize = CELLS.iter().map(|&c| neighbours(1 << c).count_ones() as usize).sum();
+        // 6 corners × 3 + 18 outras casas da margem × 4 + 37 interiores × 6
+        assert_eq!(degrees, 6 * 3 + 18 * 4 + 37 * 6);
+    }
+
+    #[test]
+    fn score_is_the_product_of_the_two_largest_groups() {
+        let board = Board::from_stones(bits(&[(0, 0), (1, 0), (0, 1), (-4, 0), (-4, 1), (4, -4)]), bits(&[(2, 2)]));
+        assert_eq!(board.score(BLACK), Score { largest: 3, second: 2, product: 6, stones: 6 });
+        assert_eq!(board.score(WHITE), Score { largest: 1, second: 0, product: 0, stones: 1 });
+        assert_eq!(board.groups(BLACK).count(), 3);
+        assert_eq!(board.leader(), Some(BLACK));
+
+        // produtos iguais: ganha quem tem menos peças
+        let board = Board::from_stones(bits(&[(0, 0), (2, 0)]), bits(&[(-2, 0), (-2, 2), (4, -4)]));
+        assert_eq!(board.score(BLACK).product, 1);
+        assert_eq!(board.score(WHITE).product, 1);
+        assert_eq!(board.leader(), Some(BLACK));
+        let board = Board::from_stones(bits(&[(0, 0), (2, 0)]), bits(&[(-2, 0), (-2, 2)]));
+        assert_eq!(board.leader(), None);
+    }
+
+    #[test]
+    fn opening_places_one_stone_then_two_per_turn() {
+        let board = Board::default();
+        assert_eq!(board.stones_to_place(), 1);
+        let (board, side) = apply_placement(&board, BLACK, Placement { cell: at(0, 0), color: BLACK });
+        assert_eq!((side, board.stones_to_place()), (WHITE, 2));
+        // qualquer cor, e a vez só passa à segunda peça
+        let (board, side) = apply_placement(&board, WHITE, Placement { cell: at(1, 0), color: BLACK });
+        assert_eq!((side, board.stones_to_place()), (WHITE, 1));
+        let (board, side) = apply_placement(&board, WHITE, Placement { cell: at(2, 0), color: WHITE });
+        assert_eq!((side, board.stones_to_place()), (BLACK, 2));
+        assert_eq!(board.groups(BLACK).size_of(at(0, 0)), 2);
+        assert!(!is_legal(&board, Placement { cell: at(1, 0), color: WHITE }));
+        assert_eq!(generate_placements(&board).len(), 2 * (CELL_COUNT - 3));
+
+        for p in generate_placements(&board) {
+            assert_eq!(Placement::decode(p.encode()), Some(p));
+        }
+        assert_eq!(Placement::decode(0x7F), None);
+    }
+
+    #[test]
+    fn incremental_groups_agree_with_flood_fill() {
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        for _ in 0..30 {
+            let (mut board, mut side) = (Board::default(), BLACK);
+            let mut turns = 0;
+            while !board.is_full() {
+                let moves = generate_placements(&board);
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (board, side) = apply_placement(&board, side, moves[state as usize % moves.len()]);
+                for color in [BLACK, WHITE] {
+                    let mut sizes = Vec::new();
+                    let mut left = board.stones(color);
+                    while left != 0 {
+                        let group = flood(left & left.wrapping_neg(), board.stones(color));
+                        sizes.push(group.count_ones() as usize);
+                        left &= !group;
+                    }
+                    sizes.sort_unstable_by(|a, b| b.cmp(a));
+                    sizes.resize(2.max(sizes.len()), 0);
+                    assert_eq!(board.groups(color).top_two(), (sizes[0], sizes[1]));
+                }
+                turns += (board.stones_to_place() != 1) as u32;
+            }
+            // abertura e depois 30 vezes de duas peças
+            assert_eq!(turns, 31);
+            assert_eq!(board.stones_to_place(), 0);
+        }
+    }
+}
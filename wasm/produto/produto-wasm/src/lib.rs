@@ -0,0 +1,165 @@
+//! Produto WASM Engine
+//!
+//! - Side-5 hex board (61 cells), black and white as axial u128 bitboards
+//!   with incremental union-find group sizes
+//! - Monte Carlo tree search that splits each two-stone turn into two
+//!   single-stone plies of the same player (`produto_ai::mcts`)
+//! - Exact endgame solver for the last few empty cells
+//! - Root randomization for easier difficulties (top_n + score_delta)
+//!
+//! Cells cross the boundary as indices into the `gerarPosicoesValidas`
+//! order (q, then r, ascending); colours are 0 = black, 1 = white.
+
+use wasm_bindgen::prelude::*;
+
+use produto_ai::engine as ai_engine;
+use produto_ai::rng::Rng;
+use produto_core::{index_of, Board, Placement};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = Date)]
+    fn now() -> f64;
+}
+
+#[wasm_bindgen(start)]
+pub fn init() {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}
+
+#[wasm_bindgen]
+pub struct SearchResult {
+    pub playouts: u64,
+    /// Tree nodes, or solver nodes when the endgame was solved
+    pub nodes: u64,
+    pub elapsed_ms: f64,
+    /// -1000 (certain loss) to 1000 (certain win) for the side to move
+    pub score: i32,
+    /// Whether `score` is the exact game result
+    pub proven: bool,
+    turn: Vec<Placement>,
+    pv: Vec<Placement>,
+}
+
+fn indices(placements: &[Placement]) -> Vec<u8> {
+    placements.iter().map(|p| index_of(p.cell) as u8).collect()
+}
+
+fn colors(placements: &[Placement]) -> Vec<u8> {
+    placements.iter().map(|p| p.color).collect()
+}
+
+#[wasm_bindgen]
+impl SearchResult {
+    /// Cells of the stones to place this turn (one or two; empty on a full board)
+    #[wasm_bindgen(getter)]
+    pub fn turn_cells(&self) -> Vec<u8> {
+        indices(&self.turn)
+    }
+
+    /// Colours of the stones in `turn_cells`
+    #[wasm_bindgen(getter)]
+    pub fn turn_colors(&self) -> Vec<u8> {
+        colors(&self.turn)
+    }
+
+    /// Cells of the principal variation, one stone per entry, starting with the turn
+    #[wasm_bindgen(getter)]
+    pub fn pv_cells(&self) -> Vec<u8> {
+        indices(&self.pv)
+    }
+
+    /// Colours of the stones in `pv_cells`
+    #[wasm_bindgen(getter)]
+    pub fn pv_colors(&self) -> Vec<u8> {
+        colors(&self.pv)
+    }
+}
+
+fn board_from(cells: &[u8]) -> Result<Board, JsValue> {
+    Board::from_cells(cells).ok_or_else(|| JsValue::from_str("expected 61 cells with values 0 (empty), 1 (black) or 2 (white)"))
+}
+
+/// Stones still to place this turn: 1 on the opening move or after the first
+/// stone of a turn, 2 at the start of a turn, 0 on a full board; `cells`
+/// holds the 61 cells (0 = empty, 1 = black, 2 = white)
+#[wasm_bindgen]
+pub fn stones_to_place(cells: &[u8]) -> Result<u32, JsValue> {
+    Ok(board_from(cells)?.stones_to_place())
+}
+
+/// `[largest group, second largest group, product, stones]` of `color`
+#[wasm_bindgen]
+pub fn score(cells: &[u8], color: u8) -> Result<Vec<u32>, JsValue> {
+    let s = board_from(cells)?.score(color.min(1));
+    Ok([s.largest, s.second, s.product, s.stones].map(|n| n as u32).to_vec())
+}
+
+/// Who would win if the game ended now: 0 = black, 1 = white, -1 = draw
+/// (higher product, then fewer stones)
+#[wasm_bindgen]
+pub fn leader(cells: &[u8]) -> Result<i32, JsValue> {
+    Ok(board_from(cells)?.leader().map_or(-1, i32::from))
+}
+
+#[wasm_bindgen]
+pub struct ProdutoEngine {
+    rng: Rng,
+    seed: u32,
+}
+
+#[wasm_bindgen]
+impl ProdutoEngine {
+    /// `seed` drives every random choice (playouts included), so the same
+    /// seed and the same requests (with playout rather than time limits)
+    /// replay the same turns
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u32) -> ProdutoEngine {
+        ProdutoEngine { rng: Rng::new(seed as u64), seed }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// Restarts the random stream from `seed` (e.g. to replay a reported game)
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed;
+        self.rng = Rng::new(seed as u64);
+    }
+
+    /// Best turn for `side` (0 = black, 1 = white) on `cells` (see
+    /// `stones_to_place`); with `top_n > 0` picks at random among the
+    /// `top_n` best first stones within `score_delta` of the best.
+    pub fn search(
+        &mut self,
+        cells: &[u8],
+        side: u8,
+        time_budget_ms: f64,
+        max_playouts: u32,
+        top_n: u32,
+        score_delta: i32,
+    ) -> Result<SearchResult, JsValue> {
+        let board = board_from(cells)?;
+        if side > 1 {
+            return Err(JsValue::from_str("side must be 0 (black) or 1 (white)"));
+        }
+
+        let start = now();
+        let result = ai_engine::Searcher::new(&mut self.rng, start + time_budget_ms, max_playouts as u64)
+            .search(&board, side, top_n, score_delta, now);
+
+        Ok(SearchResult {
+            playouts: result.playouts,
+            nodes: result.nodes,
+            elapsed_ms: now() - start,
+            score: result.score,
+            proven: result.proven,
+            turn: result.best_turn,
+            pv: result.pv,
+        })
+    }
+}
+
@@ -0,0 +1,62 @@
+//! Fixed-depth benchmark: best move, score and nodes on a fixed position set
+//!
+//! The positions come from random play with a fixed seed, so two builds of
+//! the engine can be compared move for move: a search change that keeps the
+//! results should only move the node counts.
+//!
+//! ```text
+//! cargo run --release --example bench -- [depth=5] [positions=24]
+//! ```
+
+use std::time::Instant;
+
+use quelhas_ai::engine::{History, Searcher};
+use quelhas_ai::rng::Rng;
+use quelhas_ai::tt::TranspositionTable;
+use quelhas_ai::zobrist::ZobristKeys;
+use quelhas_core::{apply_move, generate_all_moves, Occupancy};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let depth: u32 = args.get(1).and_then(|a| a.parse().ok()).unwrap_or(5);
+    let count: usize = args.get(2).and_then(|a| a.parse().ok()).unwrap_or(24);
+
+    let mut state = 0xBE7C_u64;
+    let mut next = || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 33) as usize
+    };
+    // 2 to 9 random moves from the empty board, the side to move not stuck
+    let mut positions = Vec::new();
+    while positions.len() < count {
+        let plies = 2 + next() % 8;
+        let (mut occ, mut side) = (Occupancy::default(), 0u8);
+        for _ in 0..plies {
+            let moves = generate_all_moves(occ, side);
+            if moves.is_empty() {
+                break;
+            }
+            occ = apply_move(occ, moves[next() % moves.len()]);
+            side ^= 1;
+        }
+        if !generate_all_moves(occ, side).is_empty() {
+            positions.push((occ, side));
+        }
+    }
+
+    let zobrist = ZobristKeys::new();
+    let clock = Instant::now();
+    let now = || clock.elapsed().as_secs_f64() * 1000.0;
+    let mut total_nodes = 0u64;
+    for (i, &(occ, side)) in positions.iter().enumerate() {
+        let (mut tt, mut history, mut rng) = (TranspositionTable::new(16), History::new(), Rng::new(1));
+        let result = Searcher::new(&mut tt, &zobrist, &mut history, &mut rng, f64::INFINITY, depth).iterative_deepening(occ, side, 0, 0, now);
+        total_nodes += result.nodes_searched;
+        println!(
+            "{i:3} {:016x}{:016x} {side}: best {:5?} score {:6} nodes {:10}",
+            occ.high, occ.low, result.best_move, result.score, result.nodes_searched
+        );
+    }
+    let seconds = clock.elapsed().as_secs_f64();
+    println!("depth {depth}: {total_nodes} nodes in {seconds:.1} s ({:.0} knps)", total_nodes as f64 / seconds / 1000.0);
+}
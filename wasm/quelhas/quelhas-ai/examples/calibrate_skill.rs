@@ -0,0 +1,130 @@
+//! Calibração dos níveis de força em self-play.
+//!
+//! Cada nível joga contra os níveis âncora (0, 4, 8, 12, 16, 20) a partir de
+//! aberturas aleatórias de dois lances, cada abertura uma vez com cada cor; os
+//! ratings da tabela `ELO_GAP` de `src/skill.rs` são os de máxima
+//! verosimilhança (Bradley-Terry) para todos os resultados, sem suavização,
+//! e os seus erros padrão formam a tabela `ELO_ERROR`. Deve correr sozinho na
+//! máquina: os motores pesquisam contra o relógio e um núcleo ocupado
+//! enfraquece o lado que calhar.
+//!
+//! ```text
+//! cargo run --release -p quelhas-ai --example calibrate_skill -- [jogos_por_par] [ms_por_lance]
+//! ```
+
+use std::time::Instant;
+
+use crjm_search::rating::{fit_ratings, rating_errors};
+use quelhas_ai::engine::{History, Searcher};
+use quelhas_ai::rng::Rng;
+use quelhas_ai::skill::{self, MAX_SKILL_LEVEL};
+use quelhas_ai::tt::TranspositionTable;
+use quelhas_ai::zobrist::ZobristKeys;
+use quelhas_core::{apply_move, generate_all_moves, EncMove, Occupancy};
+
+const MAX_DEPTH: u32 = 64;
+
+struct Player {
+    level: u8,
+    tt: TranspositionTable,
+    history: History,
+    rng: Rng,
+}
+
+impl Player {
+    fn new(level: u8, seed: u64) -> Self {
+        Player {
+            level,
+            tt: TranspositionTable::new(16),
+            history: History::new(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    fn pick(&mut self, zobrist: &ZobristKeys, occ: Occupancy, side: u8, move_ms: f64) -> Option<EncMove> {
+        self.tt.new_search();
+        self.history.age();
+        let clock = Instant::now();
+        let now = || clock.elapsed().as_secs_f64() * 1000.0;
+        Searcher::new(&mut self.tt, zobrist, &mut self.history, &mut self.rng, move_ms, MAX_DEPTH)
+            .with_skill(skill::params(self.level))
+            .iterative_deepening(occ, side, 0, 0, now)
+            .best_move
+    }
+}
+
+/// Joga um jogo; devolve true se `first` (Vertical, joga primeiro) ganha.
+fn game(first: &mut Player, second: &mut Player, zobrist: &ZobristKeys, opening: &[EncMove], move_ms: f64) -> bool {
+    let mut occ = Occupancy::default();
+    let mut side = 0u8;
+    for &mv in opening {
+        occ = apply_move(occ, mv);
+        side ^= 1;
+    }
+    loop {
+        let player = if side == 0 { &mut *first } else { &mut *second };
+        match player.pick(zobrist, occ, side, move_ms) {
+            Some(mv) => occ = apply_move(occ, mv),
+            // misère: quem fica sem lances ganha
+            None => return side == 0,
+        }
+        side ^= 1;
+    }
+}
+
+/// Níveis contra os quais todos os outros jogam
+const ANCHORS: [u8; 6] = [0, 4, 8, 12, 16, 20];
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let openings: usize = args.get(1).and_then(|a| a.parse().ok()).unwrap_or(40) / 2;
+    let move_ms: f64 = args.get(2).and_then(|a| a.parse().ok()).unwrap_or(300.0);
+
+    let zobrist = ZobristKeys::new();
+    let mut rng = Rng::new(0x5EED);
+    let book: Vec<[EncMove; 2]> = (0..openings)
+        .map(|_| {
+            let v = generate_all_moves(Occupancy::default(), 0);
+            let first = v[rng.below(v.len())];
+            let h = generate_all_moves(apply_move(Occupancy::default(), first), 1);
+            [first, h[rng.below(h.len())]]
+        })
+        .collect();
+
+    let levels = MAX_SKILL_LEVEL as usize + 1;
+    // meio jogo ganho para cada lado em cada par jogado: evita ratings infinitos
+    let mut wins = vec![vec![0.0f64; levels]; levels];
+    for a in 0..levels as u8 {
+        for &b in ANCHORS.iter().filter(|&&b| b > a || (b < a && !ANCHORS.contains(&a))) {
+            let (a_idx, b_idx) = (a as usize, b as usize);
+            wins[a_idx][b_idx] += 0.5;
+            wins[b_idx][a_idx] += 0.5;
+            let mut a_points = 0;
+            for (i, opening) in book.iter().enumerate() {
+                for a_first in [true, false] {
+                    let seed = (a as u64) << 24 | (b as u64) << 16 | (i as u64) << 1 | a_first as u64;
+                    let mut pa = Player::new(a, seed);
+                    let mut pb = Player::new(b, seed ^ 0xFFFF);
+                    let a_won = if a_first {
+                        game(&mut pa, &mut pb, &zobrist, opening, move_ms)
+                    } else {
+                        !game(&mut pb, &mut pa, &zobrist, opening, move_ms)
+                    };
+                    if a_won {
+                        a_points += 1;
+                    }
+                }
+            }
+            let games = book.len() * 2;
+            wins[a_idx][b_idx] += a_points as f64;
+            wins[b_idx][a_idx] += (games - a_points) as f64;
+            println!("nível {:2} vs {:2}: {}/{}", a, b, a_points, games);
+        }
+    }
+
+    let ratings = fit_ratings(&wins);
+    let errors = rating_errors(&wins, &ratings);
+    let round = |values: &[f64]| -> Vec<i32> { values.iter().map(|r| (r / 10.0).round() as i32 * 10).collect() };
+    println!("pub const ELO_GAP: [i32; 21] = {:?};", round(&ratings));
+    println!("pub const ELO_ERROR: [i32; 21] = {:?};", round(&errors));
+}
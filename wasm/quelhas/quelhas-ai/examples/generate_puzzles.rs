@@ -0,0 +1,23 @@
+//! Puzzle sets for worksheets: endgames where exactly one move wins
+//!
+//! Samples positions from randomized self-play, keeps those with a unique
+//! winning move and prints them as JSON (solution line, difficulty and
+//! refutations of the most tempting wrong moves), easiest first.
+//!
+//! ```text
+//! cargo run --release --example generate_puzzles -- [count=20] [max_live_cells=20] [seed=1] > puzzles.json
+//! ```
+
+use quelhas_ai::puzzle::{to_json, PuzzleGenerator};
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let count: usize = args.get(1).and_then(|a| a.parse().ok()).unwrap_or(20);
+    let max_live_cells: u32 = args.get(2).and_then(|a| a.parse().ok()).unwrap_or(20);
+    let seed: u64 = args.get(3).and_then(|a| a.parse().ok()).unwrap_or(1);
+
+    let mut generator = PuzzleGenerator::new(seed, max_live_cells);
+    let puzzles = generator.generate(count, count * 20);
+    eprintln!("{} puzzles", puzzles.len());
+    println!("{}", to_json(&puzzles));
+}
@@ -0,0 +1,344 @@
+//! Forma canónica de uma posição, como chave alternativa da TT.
+//!
+//! Uma posição vale o mesmo que outra com:
+//!
+//! - a mesma parte partilhada (as células dos blocos que se cruzam com blocos
+//!   do adversário; o resto do tabuleiro conta como ocupado), a menos de uma
+//!   simetria do tabuleiro;
+//! - o mesmo número de blocos exclusivos de cada classe
+//!   ([`exclusive_class`]) de cada lado.
+//!
+//! As simetrias que mantêm as linhas (identidade, espelhos e meia volta)
+//! mantêm a orientação dos blocos. As que transpõem o tabuleiro trocam
+//! vertical com horizontal: a posição equivalente tem os papéis trocados, por
+//! isso trocam também o lado a jogar e os blocos exclusivos de cada lado. Das
+//! oito imagens fica a menor, e a chave é o hash dela.
+//!
+//! Os lances guardados com esta chave vão no referencial da forma canónica
+//! ([`pack_move`]): os da parte partilhada pela simetria escolhida, os dos
+//! blocos exclusivos pelas classes do bloco e dos restos, e [`unpack_move`]
+//! devolve o lance correspondente de qualquer posição equivalente. A pesquisa
+//! calcula a forma uma vez por nó ([`Canonical`]) e usa-a para as três coisas.
+
+use quelhas_core::{
+    classify_runs, decode_move, encode_move, exclusive_class, extract_runs, generate_moves_dynamic, EncMove, Occupancy, Run,
+    BOARD_SIZE,
+};
+
+use crate::rng::splitmix64;
+
+/// Classes possíveis de um bloco (0 a `BOARD_SIZE / 2`)
+const CLASSES: usize = BOARD_SIZE / 2 + 1;
+
+/// Lance guardado por classes (bloco, resto menor, resto maior) em vez de casas
+const EXCLUSIVE_FLAG: u16 = 1 << 15;
+
+/// Imagem de uma posição por uma simetria, comparável com as outras
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CanonicalForm {
+    /// casas da parte partilhada (bit `linha * 10 + coluna`)
+    pub shared: u128,
+    /// blocos exclusivos de cada classe, por orientação
+    pub exclusive: [[u8; CLASSES]; 2],
+    /// lado a jogar
+    pub side: u8,
+}
+
+impl CanonicalForm {
+    pub fn hash(&self) -> u64 {
+        let pack = |counts: &[u8; CLASSES]| counts.iter().fold(0u64, |acc, &n| acc << 8 | n as u64);
+        let words = [
+            self.shared as u64,
+            (self.shared >> 64) as u64,
+            pack(&self.exclusive[0]),
+            pack(&self.exclusive[1]),
+            self.side as u64,
+        ];
+        words.iter().fold(0, |h, &word| {
+            let mut state = h ^ word;
+            splitmix64(&mut state)
+        })
+    }
+}
+
+/// Casa `idx` pela simetria `symmetry`: bit 2 transpõe, depois o bit 1
+/// espelha as linhas e o bit 0 as colunas
+#[inline]
+fn map_cell(idx: usize, symmetry: u8) -> usize {
+    const LAST: usize = BOARD_SIZE - 1;
+    let (r, c) = (idx / BOARD_SIZE, idx % BOARD_SIZE);
+    let (r, c) = if symmetry & 4 != 0 { (c, r) } else { (r, c) };
+    let r = if symmetry & 2 != 0 { LAST - r } else { r };
+    let c = if symmetry & 1 != 0 { LAST - c } else { c };
+    r * BOARD_SIZE + c
+}
+
+/// Simetria inversa: as que transpõem trocam os dois espelhos
+#[inline]
+fn inverse(symmetry: u8) -> u8 {
+    if symmetry & 4 != 0 {
+        4 | (symmetry & 2) >> 1 | (symmetry & 1) << 1
+    } else {
+        symmetry
+    }
+}
+
+fn map_mask(mask: u128, symmetry: u8) -> u128 {
+    let mut mapped = 0;
+    let mut rest = mask;
+    while rest != 0 {
+        let idx = rest.trailing_zeros() as usize;
+        rest &= rest - 1;
+        mapped |= 1 << map_cell(idx, symmetry);
+    }
+    mapped
+}
+
+/// Lance `mv` pela simetria `symmetry` (a orientação troca nas transpostas)
+fn map_move(mv: EncMove, symmetry: u8) -> EncMove {
+    let (start, len, orient) = decode_move(mv);
+    let delta = if orient == 0 { BOARD_SIZE } else { 1 };
+    let end = start as usize + (len as usize - 1) * delta;
+    let (a, b) = (map_cell(start as usize, symmetry), map_cell(end, symmetry));
+    encode_move(a.min(b) as u8, len, orient ^ (symmetry >> 2))
+}
+
+/// Blocos partilhados (como máscara) e exclusivos de cada orientação
+fn split_runs(occ: Occupancy) -> (u128, [Vec<Run>; 2]) {
+    let (vertical, horizontal) = (extract_runs(occ, 0), extract_runs(occ, 1));
+    let classified = [classify_runs(&vertical, &horizontal), classify_runs(&horizontal, &vertical)];
+    let mut shared = 0u128;
+    let mut exclusive = [Vec::new(), Vec::new()];
+    for (orient, runs) in classified.iter().enumerate() {
+        for r in runs {
+            if r.exclusive {
+                exclusive[orient].push(r.run);
+            } else {
+                r.run.cells().for_each(|idx| shared |= 1 << idx);
+            }
+        }
+    }
+    (shared, exclusive)
+}
+
+/// Menor das oito imagens de uma posição e a simetria que a dá (a primeira,
+/// se várias dão a mesma)
+fn smallest_image(shared: u128, runs: &[Vec<Run>; 2], side: u8) -> (CanonicalForm, u8) {
+    let mut exclusive = [[0u8; CLASSES]; 2];
+    for (orient, runs) in runs.iter().enumerate() {
+        for run in runs {
+            exclusive[orient][exclusive_class(run.len as usize)] += 1;
+        }
+    }
+    (0..8u8)
+        .map(|symmetry| {
+            let swap = (symmetry >> 2) as usize;
+            let form = CanonicalForm {
+                shared: map_mask(shared, symmetry),
+                exclusive: [exclusive[swap], exclusive[1 - swap]],
+                side: side ^ swap as u8,
+            };
+            (form, symmetry)
+        })
+        .min_by_key(|&(form, _)| form)
+        .expect("eight symmetries")
+}
+
+/// Forma canónica de `occ` com `side` a jogar e a simetria que a dá
+pub fn canonical_form(occ: Occupancy, side: u8) -> (CanonicalForm, u8) {
+    let (shared, runs) = split_runs(occ);
+    smallest_image(shared, &runs, side)
+}
+
+/// Chave da TT igual para todas as posições equivalentes a `occ`
+pub fn canonical_hash(occ: Occupancy, side: u8) -> u64 {
+    Canonical::new(occ, side).hash
+}
+
+/// O que a pesquisa precisa da forma canónica de uma posição, calculado uma
+/// vez por nó: a chave e o necessário para levar os lances ao referencial da
+/// forma canónica e de volta
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Canonical {
+    /// chave da TT ([`CanonicalForm::hash`])
+    pub hash: u64,
+    side: u8,
+    /// simetria que leva a posição à forma canónica
+    symmetry: u8,
+    /// casas dos blocos exclusivos de `side`
+    exclusive: u128,
+}
+
+impl Canonical {
+    pub fn new(occ: Occupancy, side: u8) -> Self {
+        let (shared, runs) = split_runs(occ);
+        let (form, symmetry) = smallest_image(shared, &runs, side);
+        let mut exclusive = 0u128;
+        runs[side as usize].iter().flat_map(Run::cells).for_each(|idx| exclusive |= 1 << idx);
+        Self { hash: form.hash(), side, symmetry, exclusive }
+    }
+
+    /// Classes do bloco e dos dois restos (o menor primeiro) de um lance num
+    /// bloco exclusivo. Blocos da mesma orientação nunca se tocam numa linha,
+    /// por isso o bloco são as casas exclusivas seguidas à volta do lance
+    fn exclusive_move(&self, mv: EncMove) -> Option<(usize, usize, usize)> {
+        let (start, len, orient) = decode_move(mv);
+        if orient != self.side || self.exclusive >> start & 1 == 0 {
+            return None;
+        }
+        let segment = Run { start, len, orient };
+        let (first, line) = (segment.first() as usize, segment.line() as usize);
+        let exclusive = |i: usize| {
+            let idx = if orient == 0 { i * BOARD_SIZE + line } else { line * BOARD_SIZE + i };
+            self.exclusive >> idx & 1 != 0
+        };
+        let before = (0..first).rev().take_while(|&i| exclusive(i)).count();
+        let after = (first + len as usize..BOARD_SIZE).take_while(|&i| exclusive(i)).count();
+        let run = before + len as usize + after;
+        let (before, after) = (exclusive_class(before), exclusive_class(after));
+        Some((exclusive_class(run), before.min(after), before.max(after)))
+    }
+
+    /// Lance `mv` de quem joga no referencial da forma canónica
+    pub fn pack_move(&self, mv: EncMove) -> u16 {
+        if let Some((run, a, b)) = self.exclusive_move(mv) {
+            return EXCLUSIVE_FLAG | (run << 6 | a << 3 | b) as u16;
+        }
+        map_move(mv, self.symmetry)
+    }
+
+    /// Inverso de [`pack_move`](Self::pack_move) em `occ` (a posição desta
+    /// forma), com um código que pode vir de outra posição com a mesma forma
+    /// canónica. Um lance exclusivo volta como o primeiro equivalente do
+    /// gerador, o que a ordenação reconhece; sem correspondente devolve um
+    /// código que não é legal
+    pub fn unpack_move(&self, occ: Occupancy, code: u16) -> EncMove {
+        if code & EXCLUSIVE_FLAG == 0 {
+            return map_move(code, inverse(self.symmetry));
+        }
+        let classes = ((code >> 6 & 7) as usize, (code >> 3 & 7) as usize, (code & 7) as usize);
+        generate_moves_dynamic(occ, self.side)
+            .into_iter()
+            .find(|&mv| self.exclusive_move(mv) == Some(classes))
+            .unwrap_or(code)
+    }
+}
+
+/// Lance `mv` de `side` no referencial da forma canónica de `occ`
+pub fn pack_move(occ: Occupancy, side: u8, mv: EncMove) -> u16 {
+    Canonical::new(occ, side).pack_move(mv)
+}
+
+/// Inverso de [`pack_move`] em `occ`, que pode ser outra posição com a mesma
+/// forma canónica
+pub fn unpack_move(occ: Occupancy, side: u8, code: u16) -> EncMove {
+    Canonical::new(occ, side).unpack_move(occ, code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use quelhas_core::{apply_move, generate_all_moves, CELL_COUNT};
+
+    fn map_occupancy(occ: Occupancy, symmetry: u8) -> Occupancy {
+        let mut mapped = Occupancy::default();
+        (0..CELL_COUNT).filter(|&idx| occ.is_set(idx)).for_each(|idx| mapped.set(map_cell(idx, symmetry)));
+        mapped
+    }
+
+    /// Posição ao acaso a meio do jogo
+    fn random_position(rng: &mut Rng, plies: usize) -> (Occupancy, u8) {
+        let (mut occ, mut side) = (Occupancy::default(), rng.below(2) as u8);
+        for _ in 0..plies {
+            let moves = generate_all_moves(occ, side);
+            if moves.is_empty() {
+                break;
+            }
+            occ = apply_move(occ, moves[rng.below(moves.len())]);
+            side = 1 - side;
+        }
+        (occ, side)
+    }
+
+    /// Tabuleiro cheio com as casas de `cells` livres
+    fn board_with_free(cells: &[usize]) -> Occupancy {
+        let mut occ = Occupancy { low: !0, high: (1u64 << 36) - 1 };
+        for &idx in cells {
+            if idx < 64 {
+                occ.low &= !(1u64 << idx);
+            } else {
+                occ.high &= !(1u64 << (idx - 64));
+            }
+        }
+        occ
+    }
+
+    #[test]
+    fn symmetries_compose_into_the_dihedral_group() {
+        for symmetry in 0..8 {
+            for idx in 0..CELL_COUNT {
+                assert_eq!(map_cell(map_cell(idx, symmetry), inverse(symmetry)), idx);
+            }
+        }
+        let images: std::collections::HashSet<Vec<usize>> =
+            (0..8).map(|s| (0..CELL_COUNT).map(|idx| map_cell(idx, s)).collect()).collect();
+        assert_eq!(images.len(), 8);
+    }
+
+    #[test]
+    fn symmetric_positions_share_the_key() {
+        let mut rng = Rng::new(3);
+        for _ in 0..40 {
+            let plies = 4 + rng.below(20);
+            let (occ, side) = random_position(&mut rng, plies);
+            let key = canonical_hash(occ, side);
+            for symmetry in 1..8 {
+                // as transpostas trocam os papéis dos dois lados
+                let image = map_occupancy(occ, symmetry);
+                assert_eq!(canonical_hash(image, side ^ (symmetry >> 2)), key);
+            }
+            assert_ne!(canonical_hash(occ, 1 - side), key);
+        }
+    }
+
+    #[test]
+    fn exclusive_runs_count_by_class() {
+        // coluna 0 livre nas linhas 0..k (bloco vertical exclusivo) e um
+        // quadrado 2x2 partilhado longe dele
+        let position = |len: usize| {
+            let mut free: Vec<usize> = (0..len).map(|r| r * BOARD_SIZE).collect();
+            free.extend([55, 56, 65, 66]);
+            board_with_free(&free)
+        };
+        assert_eq!(canonical_hash(position(4), 0), canonical_hash(position(5), 0));
+        assert_eq!(canonical_hash(position(2), 1), canonical_hash(position(3), 1));
+        assert_ne!(canonical_hash(position(3), 0), canonical_hash(position(4), 0));
+        // casas mortas não contam
+        let mut dead = position(4);
+        dead.low &= !(1u64 << 9);
+        assert_eq!(canonical_hash(dead, 0), canonical_hash(position(4), 0));
+    }
+
+    #[test]
+    fn stored_moves_map_to_equivalent_moves() {
+        let mut rng = Rng::new(8);
+        for _ in 0..40 {
+            let plies = 6 + rng.below(24);
+            let (occ, side) = random_position(&mut rng, plies);
+            let moves = generate_all_moves(occ, side);
+            if moves.is_empty() {
+                continue;
+            }
+            let mv = moves[rng.below(moves.len())];
+            let code = pack_move(occ, side, mv);
+            let child = canonical_hash(apply_move(occ, mv), 1 - side);
+            for symmetry in 0..8 {
+                let (image, image_side) = (map_occupancy(occ, symmetry), side ^ (symmetry >> 2));
+                let back = unpack_move(image, image_side, code);
+                assert!(generate_all_moves(image, image_side).contains(&back), "{mv} {symmetry}");
+                assert_eq!(canonical_hash(apply_move(image, back), 1 - image_side), child);
+            }
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Solver exato de finais misère por decomposição em blocos independentes.
+//!
+//! Um bloco (run) é exclusivo quando nenhuma das suas células pertence a um
+//! bloco do adversário: só o dono lá pode jogar, e isso não muda até ao fim do
+//! jogo (os blocos só encolhem). Cada bloco exclusivo vale então apenas pelo
+//! seu comprimento, e a posição reduz-se a:
+//!
+//! - a parte partilhada (blocos que se cruzam com blocos do adversário);
+//! - a lista de comprimentos dos blocos exclusivos de cada lado.
+//!
+//! Quando já não há parte partilhada o resultado sai de uma conta: cada lance
+//! elimina no máximo um bloco e cobrir um bloco inteiro é sempre legal, por
+//! isso quem joga com `a` blocos contra `b` fica sem jogadas (e ganha) primeiro
+//! sse `a <= b`. Com uma parte partilhada pequena, a posição reduzida é
+//! resolvida por minimax com memo, com distâncias exatas até ao fim.
+//!
+//! Os valores seguem a convenção do motor: `MATE - plies` se quem joga ganha,
+//! `-(MATE - plies)` se perde.
+
+use std::collections::HashSet;
+
+use quelhas_core::{apply_move, classify_runs, extract_runs, generate_all_moves, EncMove, Occupancy};
+
+pub use crjm_search::endgame::Limits;
+
+use crate::engine::MATE;
+
+/// Máximo de células na parte partilhada para tentar resolver a posição
+pub const MAX_SHARED_CELLS: u32 = 16;
+
+/// Solver de finais do Quelhas (memo válido entre pesquisas)
+pub type EndgameSolver = crjm_search::endgame::EndgameSolver<QuelhasEndgame>;
+/// Resultado provado de uma posição do Quelhas
+pub type Solution = crjm_search::endgame::Solution<EncMove>;
+
+/// Posição reduzida: só interessa a parte partilhada e os comprimentos dos
+/// blocos exclusivos (ordenados) de cada orientação
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Reduced {
+    shared: (u64, u64),
+    exclusive: [Vec<u8>; 2],
+    side: u8,
+}
+
+/// Comprimento útil de um bloco exclusivo: 0 e 1 já não dão jogadas, e um
+/// bloco de 3 só pode ser eliminado de uma vez, tal como um de 2
+#[inline]
+fn normalize_len(len: usize) -> Option<u8> {
+    match len {
+        0 | 1 => None,
+        3 => Some(2),
+        l => Some(l as u8),
+    }
+}
+
+/// Passa os blocos exclusivos de `occ` para `exclusive` (marcando as células
+/// como ocupadas) e devolve o resto com o número de células partilhadas
+fn split_exclusive(mut occ: Occupancy, exclusive: &mut [Vec<u8>; 2]) -> (Occupancy, u32) {
+    let (vertical, horizontal) = (extract_runs(occ, 0), extract_runs(occ, 1));
+    let classified = [classify_runs(&vertical, &horizontal), classify_runs(&horizontal, &vertical)];
+    let mut shared = Occupancy::default();
+    for (orient, runs) in classified.iter().enumerate() {
+        for r in runs {
+            if r.exclusive {
+                exclusive[orient].extend(normalize_len(r.run.len as usize));
+                r.run.cells().for_each(|idx| occ.set(idx));
+            } else {
+                r.run.cells().for_each(|idx| shared.set(idx));
+            }
+        }
+    }
+    exclusive[0].sort_unstable();
+    exclusive[1].sort_unstable();
+    (occ, shared.low.count_ones() + shared.high.count_ones())
+}
+
+/// Redução dos finais do Quelhas para o solver partilhado (`crjm_search`)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct QuelhasEndgame;
+
+impl crjm_search::endgame::Reduction for QuelhasEndgame {
+    const MATE: i32 = MATE;
+    type Board = Occupancy;
+    type Move = EncMove;
+    type Reduced = Reduced;
+
+    /// Posição reduzida de `occ`, se a parte partilhada for pequena
+    fn reduce(&self, occ: Occupancy, side: u8, max_shared_cells: u32) -> Option<Reduced> {
+        let mut exclusive = [Vec::new(), Vec::new()];
+        let (shared, cells) = split_exclusive(occ, &mut exclusive);
+        (cells <= max_shared_cells).then_some(Reduced {
+            shared: (shared.low, shared.high),
+            exclusive,
+            side,
+        })
+    }
+
+    fn moves(&self, occ: Occupancy, side: u8) -> Vec<EncMove> {
+        generate_all_moves(occ, side)
+    }
+
+    fn apply(&self, occ: Occupancy, mv: EncMove, _side: u8) -> Occupancy {
+        apply_move(occ, mv)
+    }
+
+    fn settled(&self, pos: &Reduced) -> Option<i32> {
+        let side = pos.side as usize;
+        let shared = Occupancy { low: pos.shared.0, high: pos.shared.1 };
+        if !generate_all_moves(shared, pos.side).is_empty() {
+            return None;
+        }
+        let mine = &pos.exclusive[side];
+        if mine.is_empty() {
+            return Some(MATE);
+        }
+        if !generate_all_moves(shared, 1 - pos.side).is_empty() {
+            return None;
+        }
+        // só blocos exclusivos: cada lado elimina um bloco por lance
+        let (a, b) = (mine.len() as i32, pos.exclusive[1 - side].len() as i32);
+        Some(if a <= b { MATE - 2 * a } else { -(MATE - (2 * b + 1)) })
+    }
+
+    fn children(&self, pos: &Reduced) -> Vec<Reduced> {
+        let side = pos.side as usize;
+        let mine = &pos.exclusive[side];
+        let mut children = Vec::new();
+        // lances num bloco exclusivo: só interessam os restos
+        let mut tried = HashSet::new();
+        for (i, &len) in mine.iter().enumerate() {
+            if i > 0 && mine[i - 1] == len {
+                continue;
+            }
+            let len = len as usize;
+            for seg in 2..=len {
+                for before in 0..=(len - seg) {
+                    let after = len - seg - before;
+                    if !tried.insert((len, before.min(after), before.max(after))) {
+                        continue;
+                    }
+                    let mut child = pos.clone();
+                    child.side = 1 - pos.side;
+                    let list = &mut child.exclusive[side];
+                    list.remove(i);
+                    list.extend(normalize_len(before));
+                    list.extend(normalize_len(after));
+                    list.sort_unstable();
+                    children.push(child);
+                }
+            }
+        }
+        // lances na parte partilhada: podem libertar blocos exclusivos
+        let shared = Occupancy { low: pos.shared.0, high: pos.shared.1 };
+        for mv in generate_all_moves(shared, pos.side) {
+            let mut exclusive = pos.exclusive.clone();
+            let (rest, _) = split_exclusive(apply_move(shared, mv), &mut exclusive);
+            children.push(Reduced {
+                shared: (rest.low, rest.high),
+                exclusive,
+                side: 1 - pos.side,
+            });
+        }
+        children
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use crjm_search::endgame::parent_value;
+    use quelhas_core::BOARD_SIZE;
+    use std::collections::HashMap;
+
+    const EXACT: Limits = Limits { max_shared_cells: MAX_SHARED_CELLS, budget: 1_000_000 };
+
+    /// Minimax direto sobre o tabuleiro, com distâncias
+    fn brute(occ: Occupancy, side: u8, memo: &mut HashMap<(u64, u64, u8), i32>) -> i32 {
+        if let Some(&v) = memo.get(&(occ.low, occ.high, side)) {
+            return v;
+        }
+        let moves = generate_all_moves(occ, side);
+        let v = if moves.is_empty() {
+            MATE
+        } else {
+            moves
+                .into_iter()
+                .map(|mv| parent_value(brute(apply_move(occ, mv), 1 - side, memo)))
+                .max()
+                .unwrap()
+        };
+        memo.insert((occ.low, occ.high, side), v);
+        v
+    }
+
+    /// Tabuleiro cheio com alguns segmentos aleatórios libertados
+    fn random_endgame(rng: &mut Rng, segments: usize) -> Occupancy {
+        let mut occ = Occupancy { low: !0, high: (1u64 << 36) - 1 };
+        for _ in 0..segments {
+            let len = 2 + rng.below(3);
+            let orient = rng.below(2);
+            let (r, c) = (rng.below(BOARD_SIZE), rng.below(BOARD_SIZE - len + 1));
+            for i in 0..len {
+                let idx = if orient == 0 { (c + i) * BOARD_SIZE + r } else { r * BOARD_SIZE + c + i };
+                if idx < 64 {
+                    occ.low &= !(1u64 << idx);
+                } else {
+                    occ.high &= !(1u64 << (idx - 64));
+                }
+            }
+        }
+        occ
+    }
+
+    #[test]
+    fn exclusive_runs_are_counted() {
+        // linha 0 livre (um bloco horizontal) e duas colunas parciais
+        let mut occ = Occupancy { low: !0, high: (1u64 << 36) - 1 };
+        for idx in [0, 1, 2, 3, 35, 45, 55, 68, 78] {
+            if idx < 64 {
+                occ.low &= !(1u64 << idx);
+            } else {
+                occ.high &= !(1u64 << (idx - 64));
+            }
+        }
+        let mut solver = EndgameSolver::new();
+        // vertical: 2 blocos, horizontal: 1 bloco
+        assert_eq!(solver.value(occ, 1, EXACT), Some(MATE - 2));
+        assert_eq!(solver.value(occ, 0, EXACT), Some(-(MATE - 3)));
+        let solution = solver.solve_root(occ, 1, EXACT).unwrap();
+        assert!(solution.wins());
+        // o lance vencedor elimina o bloco (sem deixar restos jogáveis)
+        let after = apply_move(occ, solution.best_move().unwrap());
+        assert!(generate_all_moves(after, 1).is_empty());
+    }
+
+    #[test]
+    fn agrees_with_brute_force() {
+        let mut rng = Rng::new(21);
+        let mut solver = EndgameSolver::new();
+        let mut memo = HashMap::new();
+        let mut solved = 0;
+        for _ in 0..150 {
+            let occ = random_endgame(&mut rng, 5);
+            for side in [0, 1] {
+                let exact = brute(occ, side, &mut memo);
+                if let Some(v) = solver.value(occ, side, EXACT) {
+                    assert_eq!(v, exact, "{:?} {}", occ, side);
+                    solved += 1;
+                }
+                if let Some(solution) = solver.solve_root(occ, side, EXACT) {
+                    assert_eq!(solution.score, exact);
+                    let mv = solution.best_move().unwrap();
+                    assert_eq!(parent_value(brute(apply_move(occ, mv), 1 - side, &mut memo)), exact);
+                }
+            }
+        }
+        assert!(solved > 100, "{}", solved);
+    }
+
+    #[test]
+    fn large_shared_areas_are_left_to_the_search() {
+        let mut solver = EndgameSolver::new();
+        assert_eq!(solver.value(Occupancy::default(), 0, EXACT), None);
+        assert!(solver.solve_root(Occupancy::default(), 0, EXACT).is_none());
+
+        // dentro do limite de células, mas acima do orçamento
+        let mut rng = Rng::new(4);
+        let occ = (0..)
+            .map(|_| random_endgame(&mut rng, 8))
+            .find(|&occ| solver.value(occ, 0, EXACT).is_some_and(|v| v < MATE - 6))
+            .unwrap();
+        let mut fresh = EndgameSolver::new();
+        let tiny = Limits { max_shared_cells: MAX_SHARED_CELLS, budget: 1 };
+        assert_eq!(fresh.value(occ, 0, tiny), None);
+        assert!(fresh.value(occ, 0, EXACT).is_some());
+    }
+}
@@ -1,359 +1,659 @@
+//! Motor de pesquisa: Quelhas (misère) sobre a pesquisa alfa-beta partilhada (`crjm_search`)
+
+use crjm_search::{pick_root, Aspiration, Game, ProgressFn};
+
+use crate::canonical::Canonical;
+use crjm_search::endgame::SolvedRoot;
+
+use crate::endgame::{EndgameSolver, Limits, MAX_SHARED_CELLS};
 use crate::eval;
-use crate::tt::{TTEntry, TranspositionTable};
+use crate::mistakes::MistakeModel;
+use crate::rng::Rng;
+use crate::skill::{self, SkillParams};
+use crate::tt::TranspositionTable;
 use crate::zobrist::ZobristKeys;
-use quelhas_core::{apply_move, decode_move, generate_moves_dynamic, EncMove, Occupancy};
-
-pub struct SearchStats {
-    pub nodes: u64,
-    pub tt_hits: u64,
-    pub tt_probes: u64,
-}
+use quelhas_core::{apply_move, decode_move, generate_all_moves, generate_moves_dynamic, EncMove, Occupancy, CELL_COUNT};
 
 pub struct SearchResult {
     pub best_move: Option<EncMove>,
+    /// variante principal, a começar em `best_move`
+    pub pv: Vec<EncMove>,
     pub depth_reached: u32,
     pub nodes_searched: u64,
     pub tt_hits: u64,
     pub tt_probes: u64,
     pub score: i32,
+    /// o modelo de erros trocou o melhor lance por um erro
+    pub blunder: bool,
 }
 
-pub struct Searcher<'a> {
-    tt: &'a mut TranspositionTable,
-    zobrist: &'a ZobristKeys,
-    pub age: u8,
-    deadline_ms: f64,
-    pub max_depth: u32,
-    pub stats: SearchStats,
-    killers: Vec<[u16; 2]>,
-    history: Vec<i32>,
-}
+pub const HISTORY_SIZE: usize = 4096;
 
-const INF: i32 = 1_000_000;
-const MATE: i32 = 900_000;
+/// Tabela de history (indexada pelo lance codificado), mantida entre pesquisas pelo motor.
+#[derive(Clone)]
+pub struct History {
+    pub scores: Vec<i32>,
+}
 
-impl<'a> Searcher<'a> {
-    pub fn new(
-        tt: &'a mut TranspositionTable,
-        zobrist: &'a ZobristKeys,
-        age: u8,
-        deadline_ms: f64,
-        max_depth: u32,
-    ) -> Self {
+impl History {
+    pub fn new() -> Self {
         Self {
-            tt,
-            zobrist,
-            age,
-            deadline_ms,
-            max_depth,
-            stats: SearchStats {
-                nodes: 0,
-                tt_hits: 0,
-                tt_probes: 0,
-            },
-            killers: Vec::new(),
-            history: vec![0; 4096],
+            scores: vec![0; HISTORY_SIZE],
         }
     }
 
-    #[inline]
-    fn time_up(now_ms: f64, deadline_ms: f64) -> bool {
-        now_ms >= deadline_ms
+    /// Divide os valores a meio no início de cada pesquisa, para que a
+    /// informação antiga perca peso sem ser descartada.
+    pub fn age(&mut self) {
+        for h in self.scores.iter_mut() {
+            *h /= 2;
+        }
     }
 
-    fn order_moves(&mut self, occ: Occupancy, side: u8, depth: usize, moves: &mut [EncMove], tt_best: Option<EncMove>) {
-        let tt_best_u16 = tt_best.unwrap_or(0);
-        if self.killers.len() <= depth {
-            self.killers.resize(depth + 1, [0, 0]);
-        }
-        let killers = self.killers[depth];
+    pub fn clear(&mut self) {
+        self.scores.fill(0);
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Posição para a pesquisa: ocupação, lado a jogar e, com chaves canónicas,
+/// a forma canónica calculada uma vez por nó
+#[derive(Clone, Copy)]
+struct Node {
+    occ: Occupancy,
+    side: u8,
+    canonical: Option<Canonical>,
+}
+
+/// Quelhas para a pesquisa partilhada
+struct Quelhas<'a> {
+    zobrist: &'a ZobristKeys,
+    /// solver de finais nas folhas (desligado nos níveis de força)
+    endgame: Option<EndgameSolver>,
+    /// sal e amplitude do ruído de avaliação do nível de força
+    noise: Option<(u64, i32)>,
+    /// chave da TT pela forma canónica em vez do Zobrist
+    canonical: bool,
+    /// gerador dos lances a pesquisar
+    generate: fn(Occupancy, u8) -> Vec<EncMove>,
+}
 
-        moves.sort_by(|a, b| {
-            let pa = self.move_priority(occ, side, depth, *a, tt_best_u16, killers);
-            let pb = self.move_priority(occ, side, depth, *b, tt_best_u16, killers);
-            pb.cmp(&pa)
-        });
+impl Quelhas<'_> {
+    fn node(&self, occ: Occupancy, side: u8) -> Node {
+        let canonical = self.canonical.then(|| Canonical::new(occ, side));
+        Node { occ, side, canonical }
+    }
+}
+
+impl Game for Quelhas<'_> {
+    type Position = Node;
+    type Move = EncMove;
+    type TableScore = i32;
+    type TableMove = u16;
+
+    const MATE: i32 = MATE;
+    const MATE_BOUND: i32 = MATE_BOUND;
+
+    fn moves(&mut self, &Node { occ, side, .. }: &Node) -> Vec<EncMove> {
+        (self.generate)(occ, side)
     }
 
-    #[inline]
-    fn move_priority(&self, occ: Occupancy, side: u8, depth: usize, mv: EncMove, tt_best: u16, killers: [u16; 2]) -> i32 {
-        let mut p = 0i32;
-        if mv == tt_best {
-            p += 1_000_000;
+    // os candidatos dinâmicos são um subconjunto: a TT pode trazer outros lances legais
+    fn is_legal(&mut self, &Node { occ, side, .. }: &Node, mv: EncMove) -> bool {
+        generate_all_moves(occ, side).contains(&mv)
+    }
+
+    fn apply(&self, &Node { occ, side, .. }: &Node, mv: EncMove) -> Node {
+        self.node(apply_move(occ, mv), 1 - side)
+    }
+
+    // misère: quem fica sem jogadas ganha
+    fn stuck(&self, _pos: &Node, ply: u32) -> i32 {
+        MATE - ply as i32
+    }
+
+    fn evaluate(&mut self, &Node { occ, side, .. }: &Node, key: u64, ply: u32) -> i32 {
+        let empty = CELL_COUNT as u32 - occ.low.count_ones() - occ.high.count_ones();
+        let endgame = self.endgame.as_mut().filter(|_| empty <= LEAF_ENDGAME_MAX_EMPTY);
+        if let Some(v) = endgame.and_then(|e| e.value(occ, side, LEAF_ENDGAME)) {
+            // valor relativo a este nó, distância contada desde a raiz
+            let ply = ply as i32;
+            return if v > 0 { v - ply } else { v + ply };
         }
-        if mv == killers[0] {
-            p += 600_000;
-        } else if mv == killers[1] {
-            p += 450_000;
+        let noise = match self.noise {
+            Some((salt, amplitude)) => skill::eval_noise(key, salt, amplitude),
+            None => 0,
+        };
+        eval::evaluate_misere(occ, side) + noise
+    }
+
+    fn key(&self, pos: &Node) -> u64 {
+        match pos.canonical {
+            Some(canonical) => canonical.hash,
+            None => self.zobrist.hash(pos.occ, pos.side),
         }
-        p += self.history[mv as usize & 4095];
+    }
 
-        let (_start, len, _o) = decode_move(mv);
-        p -= (len as i32) * 10;
+    fn pack_move(&self, pos: &Node, mv: EncMove) -> u16 {
+        pos.canonical.map_or(mv, |canonical| canonical.pack_move(mv))
+    }
 
+    fn unpack_move(&self, pos: &Node, mv: u16) -> EncMove {
+        pos.canonical.map_or(mv, |canonical| canonical.unpack_move(pos.occ, mv))
+    }
+
+    /// Lances curtos primeiro; perto da raiz também a heurística barata
+    fn order_score(&mut self, &Node { occ, side, .. }: &Node, mv: EncMove, depth: u32) -> i32 {
+        let (_start, len, _o) = decode_move(mv);
+        let mut p = -(len as i32) * 10;
         if depth >= 6 {
             p += (eval::cheap_move_score(occ, mv, side) / 10).clamp(-50_000, 50_000);
         }
         p
     }
 
-    pub fn iterative_deepening(&mut self, occ: Occupancy, side: u8, top_n: u32, score_delta: i32, now: impl Fn() -> f64) -> SearchResult {
-        let mut best_move: Option<EncMove> = None;
-        let mut best_score = -INF;
-        let mut depth_reached = 0u32;
-
-        let mut window: i32 = 120;
-
-        let mut root_moves = generate_moves_dynamic(occ, side);
-        if root_moves.is_empty() {
-            return SearchResult {
-                best_move: None,
-                depth_reached: 0,
-                nodes_searched: 0,
-                tt_hits: self.stats.tt_hits,
-                tt_probes: self.stats.tt_probes,
-                score: -MATE,
-            };
-        }
-
-        // ordenar raiz inicialmente por heurística barata (history já começa 0)
-        self.order_moves(occ, side, 1, &mut root_moves, None);
-
-        for depth in 1..=self.max_depth {
-            if Self::time_up(now(), self.deadline_ms) {
-                break;
-            }
+    fn history_slot(&self, _pos: &Node, mv: EncMove) -> Option<usize> {
+        Some(mv as usize & (HISTORY_SIZE - 1))
+    }
+}
 
-            let (alpha, beta) = if depth == 1 {
-                (-INF, INF)
-            } else {
-                (best_score - window, best_score + window)
-            };
+pub struct Searcher<'a> {
+    search: crjm_search::Searcher<'a, Quelhas<'a>>,
+    rng: &'a mut Rng,
+    skill: Option<SkillParams>,
+    mistakes: Option<MistakeModel>,
+}
 
-            let alpha_orig = alpha;
-            let mut alpha_i = alpha;
-            let mut beta_i = beta;
+pub const MATE: i32 = 900_000;
+/// Scores beyond this magnitude are proven wins/losses (MATE - ply)
+pub const MATE_BOUND: i32 = MATE - 1000;
+
+/// Comprimento máximo da variante principal devolvida
+const MAX_PV: usize = 64;
+
+/// Limites do solver de finais na raiz e nas folhas (onde é chamado muitas
+/// vezes, por isso só para finais quase decompostos)
+const ROOT_ENDGAME: Limits = Limits { max_shared_cells: MAX_SHARED_CELLS, budget: 250_000 };
+const LEAF_ENDGAME: Limits = Limits { max_shared_cells: 8, budget: 300 };
+/// Nas folhas, o solver só é tentado com poucas casas livres
+const LEAF_ENDGAME_MAX_EMPTY: u32 = 30;
+
+/// Janela de aspiration (alargada só do lado que falhou)
+const ASPIRATION: Aspiration = Aspiration { initial: 120, min: 60, max: 1200 };
+
+/// Vezes que ainda é a vez de quem joga até o jogo acabar, para um score
+/// provado (`None` para scores heurísticos)
+///
+/// Conta também a vez em que fica sem lances: pelo menos 1 quando ganha (em
+/// misère, ao ficar sem lances; 1: já está sem lances), no máximo -1 quando
+/// perde (o seu último lance deixa o adversário sem lances).
+pub fn mate_in(score: i32) -> Option<i32> {
+    if score >= MATE_BOUND {
+        Some((MATE - score) / 2 + 1)
+    } else if score <= -MATE_BOUND {
+        Some(-((MATE + score + 1) / 2))
+    } else {
+        None
+    }
+}
 
-            let mut iter_best_move = root_moves[0];
-            let mut iter_best_score = -INF;
+impl<'a> Searcher<'a> {
+    pub fn new(
+        tt: &'a mut TranspositionTable,
+        zobrist: &'a ZobristKeys,
+        history: &'a mut History,
+        rng: &'a mut Rng,
+        deadline_ms: f64,
+        max_depth: u32,
+    ) -> Self {
+        let game = Quelhas {
+            zobrist,
+            endgame: Some(EndgameSolver::new()),
+            noise: None,
+            canonical: false,
+            generate: generate_moves_dynamic,
+        };
+        let limits = crjm_search::Limits::new(deadline_ms, max_depth);
+        Self {
+            search: crjm_search::Searcher::new(game, tt, limits)
+                .with_history(&mut history.scores)
+                .with_aspiration(ASPIRATION),
+            rng,
+            skill: None,
+            mistakes: None,
+        }
+    }
 
-            let mut first = true;
-            for &mv in &root_moves {
-                if Self::time_up(now(), self.deadline_ms) {
-                    break;
-                }
-                let child = apply_move(occ, mv);
-                let opp = 1u8 - side;
-
-                let mut score = if first {
-                    first = false;
-                    -self.negamax(child, opp, depth as i32 - 1, -beta_i, -alpha_i, 1, &now)
-                } else {
-                    let narrow = -self.negamax(child, opp, depth as i32 - 1, -alpha_i - 1, -alpha_i, 1, &now);
-                    if narrow > alpha_i && narrow < beta_i {
-                        -self.negamax(child, opp, depth as i32 - 1, -beta_i, -alpha_i, 1, &now)
-                    } else {
-                        narrow
-                    }
-                };
+    /// Joga num nível de força reduzido (em vez da randomização `top_n`).
+    ///
+    /// Os níveis não usam o solver de finais: jogam os finais pela avaliação.
+    pub fn with_skill(mut self, params: SkillParams) -> Self {
+        let game = self.search.game_mut();
+        game.noise = Some((self.rng.next_u64(), params.eval_noise));
+        game.endgame = None;
+        self.search.limits_mut().node_budget = params.node_budget;
+        // com MultiPV os `multipv` melhores lances têm score exato (sem aspiration);
+        // os restantes só ficam limitados pelo k-ésimo melhor
+        self.search = self.search.with_multipv(params.multipv);
+        self.skill = Some(params);
+        self
+    }
 
-                if Self::time_up(now(), self.deadline_ms) {
-                    break;
-                }
+    /// Usa a forma canónica ([`canonical`]) como chave da TT: posições
+    /// equivalentes (simétricas, ou com blocos exclusivos da mesma classe)
+    /// partilham os resultados, a troco de uma chave mais cara por nó.
+    ///
+    /// As chaves não são compatíveis com as do Zobrist: a TT deve vir limpa
+    /// ou de pesquisas no mesmo modo.
+    pub fn with_canonical_keys(mut self) -> Self {
+        self.search.game_mut().canonical = true;
+        self
+    }
 
-                if score > iter_best_score {
-                    iter_best_score = score;
-                    iter_best_move = mv;
-                }
-                if score > alpha_i {
-                    alpha_i = score;
-                }
-                if alpha_i >= beta_i {
-                    break;
-                }
-            }
+    /// Para ao fim de `nodes` nós (depois de acabada a primeira iteração).
+    pub fn with_node_budget(mut self, nodes: u64) -> Self {
+        self.search.limits_mut().node_budget = nodes;
+        self
+    }
 
-            if Self::time_up(now(), self.deadline_ms) {
-                break;
-            }
+    /// Dá score exato aos `multipv` melhores lances da raiz (análise MultiPV).
+    pub fn with_multipv(mut self, multipv: usize) -> Self {
+        self.search = self.search.with_multipv(multipv);
+        self
+    }
 
-            // aspiration fail -> pesquisa total (rápida, mas robusta)
-            if depth > 1 && (iter_best_score <= alpha_orig || iter_best_score >= beta) {
-                window = (window * 2).min(1200);
-                let full = self.negamax(occ, side, depth as i32, -INF, INF, 0, &now);
-                iter_best_score = full;
-                // best move do TT (se existir) passa para frente
-            } else if depth > 1 {
-                window = (window as f64 * 0.75).max(60.0) as i32;
-            }
+    /// Chama `progress` no fim de cada iteração completa (profundidade,
+    /// score, variante principal), para análise ao vivo. Uma raiz resolvida
+    /// pelo solver de finais não passa por iterações.
+    pub fn with_progress(mut self, progress: &'a mut ProgressFn<'a, EncMove>) -> Self {
+        self.search = self.search.with_progress(progress);
+        self
+    }
 
-            best_score = iter_best_score;
-            best_move = Some(iter_best_move);
-            depth_reached = depth;
+    /// Às vezes joga um erro "humano" em vez do lance escolhido.
+    pub fn with_mistakes(mut self, model: MistakeModel) -> Self {
+        self.mistakes = Some(model);
+        self
+    }
 
-            // PV ordering
-            if let Some(pos) = root_moves.iter().position(|&m| m == iter_best_move) {
-                if pos > 0 {
-                    root_moves.remove(pos);
-                    root_moves.insert(0, iter_best_move);
-                }
+    pub fn iterative_deepening(&mut self, occ: Occupancy, side: u8, top_n: u32, score_delta: i32, now: impl Fn() -> f64) -> SearchResult {
+        // final decomposto: resolvido de forma exata, sem pesquisa
+        if !generate_moves_dynamic(occ, side).is_empty() {
+            let root = self.search.game_mut().endgame.as_mut().and_then(|e| e.play_root(occ, side, ROOT_ENDGAME, top_n, self.rng, MAX_PV));
+            if let Some(root) = root {
+                return self.solved_result(occ, side, root);
             }
+        }
 
-            if best_score >= MATE - 1000 {
-                break;
-            }
+        let root = self.search.game_mut().node(occ, side);
+        let outcome = self.search.iterative_deepening(root, now);
+        let mut best_move = outcome.best_move;
+        let mut best_score = outcome.score;
+        let completed = outcome.root;
+        if let (Some(mv), 0) = (best_move, outcome.depth) {
+            // nem a primeira iteração acabou: o score barato do lance da ordenação
+            best_score = eval::cheap_move_score(occ, mv, side);
         }
 
-        // randomização para dificuldades mais baixas
-        if top_n > 0 && root_moves.len() > 1 {
-            let k = (top_n as usize).min(root_moves.len());
-            let mut scored: Vec<(EncMove, i32)> = root_moves[..k]
+        if let Some(skill) = self.skill {
+            let candidates = &completed[..skill.multipv.min(completed.len())];
+            if candidates.len() > 1 {
+                let idx = skill::softmax_choice(candidates, skill.temperature, self.rng);
+                best_move = Some(candidates[idx].0);
+                best_score = candidates[idx].1;
+            }
+        } else if top_n > 0 && completed.len() > 1 {
+            // randomização para dificuldades mais baixas
+            let k = (top_n as usize).min(completed.len());
+            let mut scored: Vec<(EncMove, i32)> = completed[..k]
                 .iter()
-                .map(|&m| (m, eval::cheap_move_score(occ, m, side)))
+                .map(|&(m, _)| (m, eval::cheap_move_score(occ, m, side)))
                 .collect();
-            scored.sort_by(|a, b| b.1.cmp(&a.1));
-            let best = scored[0];
-            let candidates: Vec<EncMove> = scored
-                .into_iter()
-                .filter(|(_, s)| best.1 - *s <= score_delta)
-                .map(|(m, _)| m)
-                .collect();
-            if candidates.len() > 1 {
-                let h = self.zobrist.hash(occ, side);
-                let idx = (h as usize) % candidates.len();
-                best_move = Some(candidates[idx]);
+            scored.sort_by_key(|&(_, s)| std::cmp::Reverse(s));
+            if let Some(idx) = pick_root(scored.iter().map(|&(_, s)| s), top_n, score_delta, self.rng) {
+                best_move = Some(scored[idx].0);
             }
         }
 
+        let (best_move, best_score, blunder) = self.apply_mistakes(occ, side, best_move, best_score, &completed);
+        let mut pv = outcome.pv;
+        if best_move != pv.first().copied() {
+            // a escolha não foi o lance principal: a variante não se aplica
+            pv = best_move.into_iter().collect();
+        }
+
         SearchResult {
             best_move,
-            depth_reached,
-            nodes_searched: self.stats.nodes,
-            tt_hits: self.stats.tt_hits,
-            tt_probes: self.stats.tt_probes,
+            pv,
+            depth_reached: outcome.depth,
+            nodes_searched: outcome.stats.nodes,
+            tt_hits: outcome.stats.tt_hits,
+            tt_probes: outcome.stats.tt_probes,
             score: best_score,
+            blunder,
+        }
+    }
+
+    /// Resultado de uma raiz resolvida pelo solver de finais (o modelo de
+    /// erros continua a aplicar-se)
+    fn solved_result(&mut self, occ: Occupancy, side: u8, root: SolvedRoot<EncMove>) -> SearchResult {
+        let solution = root.solution;
+        let (best_move, score, blunder) = self.apply_mistakes(occ, side, root.best_move, solution.score, &solution.moves);
+        let mut pv = root.pv;
+        if best_move != pv.first().copied() {
+            pv = best_move.into_iter().collect();
+        }
+        SearchResult {
+            best_move,
+            pv,
+            depth_reached: root.plies,
+            nodes_searched: 0,
+            tt_hits: 0,
+            tt_probes: 0,
+            score,
+            blunder,
         }
     }
 
-    fn negamax(
+    /// Às vezes troca o lance escolhido por um erro do modelo (ver `with_mistakes`).
+    fn apply_mistakes(
         &mut self,
         occ: Occupancy,
         side: u8,
-        depth: i32,
-        mut alpha: i32,
-        beta: i32,
-        ply: i32,
-        now: &impl Fn() -> f64,
-    ) -> i32 {
-        self.stats.nodes += 1;
-        if (self.stats.nodes & 2047) == 0 && Self::time_up(now(), self.deadline_ms) {
-            return 0;
-        }
-
-        let key = self.zobrist.hash(occ, side);
-
-        self.stats.tt_probes += 1;
-        let entry = *self.tt.probe(key);
-        let mut tt_best: Option<EncMove> = None;
-        if entry.key == key {
-            self.stats.tt_hits += 1;
-            tt_best = Some(entry.best_move);
-            if entry.depth as i32 >= depth {
-                match entry.flag {
-                    0 => return entry.score,
-                    1 => {
-                        if entry.score >= beta {
-                            return entry.score;
-                        }
-                    }
-                    2 => {
-                        if entry.score <= alpha {
-                            return entry.score;
-                        }
-                    }
-                    _ => {}
-                }
+        best_move: Option<EncMove>,
+        best_score: i32,
+        completed: &[(EncMove, i32)],
+    ) -> (Option<EncMove>, i32, bool) {
+        if let (Some(model), Some(chosen)) = (self.mistakes, best_move) {
+            // o lance escolhido à frente, os outros pela ordem dos scores
+            let mut ranked = vec![(chosen, best_score)];
+            ranked.extend(completed.iter().filter(|&&(m, _)| m != chosen));
+            let idx = model.choose(occ, side, &ranked, self.rng);
+            if idx > 0 {
+                return (Some(ranked[idx].0), ranked[idx].1, true);
             }
         }
+        (best_move, best_score, false)
+    }
+}
 
-        let moves = generate_moves_dynamic(occ, side);
-        if moves.is_empty() {
-            return MATE - ply;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mate_scores_are_relative_to_the_node_in_the_tt() {
+        use crjm_search::tt::{score_from_tt, score_to_tt};
+        let stored = score_to_tt(MATE - 6, 4, MATE_BOUND);
+        assert_eq!(stored, MATE - 2);
+        assert_eq!(score_from_tt(stored, 1, MATE_BOUND), MATE - 3);
+        assert_eq!(score_from_tt(score_to_tt(-(MATE - 5), 3, MATE_BOUND), 0, MATE_BOUND), -(MATE - 2));
+        assert_eq!(score_from_tt(score_to_tt(420, 7, MATE_BOUND), 2, MATE_BOUND), 420);
+    }
 
-        if depth == 0 {
-            return eval::evaluate_misere(occ, side);
-        }
+    fn pick_moves(seed: u64) -> Vec<Option<EncMove>> {
+        let mut tt = TranspositionTable::new(1);
+        let zobrist = ZobristKeys::new();
+        let mut history = History::new();
+        let mut rng = Rng::new(seed);
+        (0..6)
+            .map(|_| {
+                let mut s = Searcher::new(&mut tt, &zobrist, &mut history, &mut rng, f64::MAX, 1);
+                s.iterative_deepening(Occupancy::default(), 0, 8, 100_000, || 0.0).best_move
+            })
+            .collect()
+    }
 
-        let mut moves = moves;
-        self.order_moves(occ, side, depth as usize, &mut moves, tt_best);
+    #[test]
+    fn randomized_choices_replay_with_the_same_seed() {
+        assert_eq!(pick_moves(5), pick_moves(5));
+        assert_ne!(pick_moves(5), pick_moves(6));
+    }
 
-        let alpha_orig = alpha;
-        let mut best_score = -INF;
-        let mut best_move: EncMove = moves[0];
+    #[test]
+    fn skill_levels_stop_at_their_node_budget() {
+        let mut tt = TranspositionTable::new(1);
+        let zobrist = ZobristKeys::new();
+        let mut history = History::new();
+        let mut rng = Rng::new(9);
+        let params = skill::params(4);
+        let result = Searcher::new(&mut tt, &zobrist, &mut history, &mut rng, f64::MAX, 64)
+            .with_skill(params)
+            .iterative_deepening(Occupancy::default(), 0, 0, 0, || 0.0);
+        assert!(result.best_move.is_some());
+        assert!(result.depth_reached >= 1);
+        assert!(result.nodes_searched < params.node_budget + 2_000, "{}", result.nodes_searched);
+    }
 
-        let mut first = true;
-        for mv in moves {
-            if Self::time_up(now(), self.deadline_ms) {
-                break;
-            }
-            let child = apply_move(occ, mv);
-            let opp = 1u8 - side;
-            let score = if first {
-                first = false;
-                -self.negamax(child, opp, depth - 1, -beta, -alpha, ply + 1, now)
+    /// Tabuleiro cheio menos um retângulo `rows`×`cols` (tudo partilhado) e
+    /// algumas casas soltas
+    fn open_rectangle(rng: &mut Rng, rows: usize, cols: usize, holes: usize) -> Occupancy {
+        let mut occ = Occupancy { low: !0, high: (1u64 << 36) - 1 };
+        let mut clear = |idx: usize| {
+            if idx < 64 {
+                occ.low &= !(1u64 << idx);
             } else {
-                let narrow = -self.negamax(child, opp, depth - 1, -alpha - 1, -alpha, ply + 1, now);
-                if narrow > alpha && narrow < beta {
-                    -self.negamax(child, opp, depth - 1, -beta, -alpha, ply + 1, now)
-                } else {
-                    narrow
-                }
-            };
-
-            if score > best_score {
-                best_score = score;
-                best_move = mv;
+                occ.high &= !(1u64 << (idx - 64));
             }
-            if score > alpha {
-                alpha = score;
+        };
+        let (top, left) = (rng.below(11 - rows), rng.below(11 - cols));
+        for r in top..top + rows {
+            for c in left..left + cols {
+                clear(r * 10 + c);
             }
-            if alpha >= beta {
-                // killers + history
-                let d = depth as usize;
-                if self.killers.len() <= d {
-                    self.killers.resize(d + 1, [0, 0]);
+        }
+        for _ in 0..holes {
+            clear(rng.below(100));
+        }
+        occ
+    }
+
+    /// Minimax exato com distâncias (sem cortes nem heurísticas)
+    fn exact(occ: Occupancy, side: u8, memo: &mut std::collections::HashMap<(u64, u64, u8), i32>) -> i32 {
+        if let Some(&v) = memo.get(&(occ.low, occ.high, side)) {
+            return v;
+        }
+        let moves = generate_all_moves(occ, side);
+        let v = if moves.is_empty() {
+            MATE
+        } else {
+            moves
+                .into_iter()
+                .map(|mv| {
+                    let child = -exact(apply_move(occ, mv), 1 - side, memo);
+                    child - child.signum()
+                })
+                .max()
+                .unwrap()
+        };
+        memo.insert((occ.low, occ.high, side), v);
+        v
+    }
+
+    fn assert_legal_line(occ: Occupancy, side: u8, line: &[EncMove]) {
+        let (mut occ, mut side) = (occ, side);
+        for &mv in line {
+            assert!(generate_all_moves(occ, side).contains(&mv), "{:?}", line);
+            occ = apply_move(occ, mv);
+            side = 1 - side;
+        }
+    }
+
+    #[test]
+    fn root_score_and_move_agree_with_exact_values() {
+        let zobrist = ZobristKeys::new();
+        let mut rng = Rng::new(2);
+        let mut memo = std::collections::HashMap::new();
+        for round in 0..6 {
+            let occ = open_rectangle(&mut rng, 3, 6, 3);
+            let side = round % 2;
+            let mut tt = TranspositionTable::new(4);
+            let mut history = History::new();
+            let mut search_rng = Rng::new(1);
+            // cada lance ocupa pelo menos duas casas: chega ao fim do jogo
+            let result = Searcher::new(&mut tt, &zobrist, &mut history, &mut search_rng, f64::MAX, 12)
+                .iterative_deepening(occ, side, 0, 0, || 0.0);
+            let value = exact(occ, side, &mut memo);
+            assert_eq!(result.score, value);
+            let best = result.best_move.unwrap();
+            let child = -exact(apply_move(occ, best), 1 - side, &mut memo);
+            assert_eq!(child - child.signum(), value);
+            assert_eq!(result.pv.first(), Some(&best));
+            assert_legal_line(occ, side, &result.pv);
+        }
+    }
+
+    #[test]
+    fn pruned_moves_keep_search_results_on_exhaustive_small_boards() {
+        let zobrist = ZobristKeys::new();
+        let mut memo = std::collections::HashMap::new();
+        // todas as ocupações de uma janela (o resto do tabuleiro cheio)
+        for (rows, cols) in [(3, 4), (4, 3), (2, 6)] {
+            let window: Vec<usize> = (0..rows).flat_map(|r| (0..cols).map(move |c| r * 10 + c)).collect();
+            let (mut tt_all, mut tt_pruned) = (TranspositionTable::new(1), TranspositionTable::new(1));
+            for mask in 0u32..1 << window.len() {
+                let mut occ = Occupancy { low: !0, high: (1u64 << 36) - 1 };
+                for (i, &idx) in window.iter().enumerate() {
+                    if mask & (1 << i) != 0 {
+                        occ.low &= !(1u64 << idx);
+                    }
                 }
-                let k = self.killers[d];
-                if k[0] != mv {
-                    self.killers[d] = [mv, k[0]];
-                } else if k[1] != mv {
-                    self.killers[d] = [k[0], mv];
+                for side in 0..2 {
+                    let value = exact(occ, side, &mut memo);
+                    let mut search = |tt: &mut TranspositionTable, generate: fn(Occupancy, u8) -> Vec<EncMove>| {
+                        let mut history = History::new();
+                        let mut rng = Rng::new(1);
+                        let mut searcher = Searcher::new(tt, &zobrist, &mut history, &mut rng, f64::MAX, 8);
+                        // pesquisado, não resolvido pelo solver de finais
+                        let game = searcher.search.game_mut();
+                        game.endgame = None;
+                        game.generate = generate;
+                        let result = searcher.iterative_deepening(occ, side, 0, 0, || 0.0);
+                        let best = result.best_move.map(|mv| {
+                            let child = -exact(apply_move(occ, mv), 1 - side, &mut memo);
+                            child - child.signum()
+                        });
+                        (result.score, best)
+                    };
+                    let all = search(&mut tt_all, generate_all_moves);
+                    let pruned = search(&mut tt_pruned, generate_moves_dynamic);
+                    assert_eq!(all, pruned, "{:?} {}", occ, side);
+                    assert_eq!(all.0, value);
+                    assert_eq!(all.1.unwrap_or(value), value);
                 }
-                let idx = (mv as usize) & 4095;
-                self.history[idx] = self.history[idx].saturating_add(depth * depth * 200);
-                break;
             }
         }
+    }
 
-        let flag = if best_score <= alpha_orig {
-            2u8
-        } else if best_score >= beta {
-            1u8
-        } else {
-            0u8
+    #[test]
+    fn canonical_keys_keep_exact_values() {
+        let zobrist = ZobristKeys::new();
+        let mut rng = Rng::new(5);
+        let mut memo = std::collections::HashMap::new();
+        // uma TT só para todas as posições: as equivalentes reaproveitam-se
+        let mut tt = TranspositionTable::new(4);
+        for round in 0..6 {
+            let occ = open_rectangle(&mut rng, 3, 6, 3);
+            let side = round % 2;
+            let mut history = History::new();
+            let mut search_rng = Rng::new(1);
+            let result = Searcher::new(&mut tt, &zobrist, &mut history, &mut search_rng, f64::MAX, 12)
+                .with_canonical_keys()
+                .iterative_deepening(occ, side, 0, 0, || 0.0);
+            // pesquisado, não resolvido na raiz
+            assert!(result.depth_reached >= 1);
+            let value = exact(occ, side, &mut memo);
+            assert_eq!(result.score, value);
+            let child = -exact(apply_move(occ, result.best_move.unwrap()), 1 - side, &mut memo);
+            assert_eq!(child - child.signum(), value);
+            assert_legal_line(occ, side, &result.pv);
+        }
+    }
+
+    #[test]
+    fn interrupted_iterations_are_discarded() {
+        let zobrist = ZobristKeys::new();
+        let occ = open_rectangle(&mut Rng::new(7), 5, 8, 0);
+        let search = |deadline: f64, max_depth: u32| {
+            let mut tt = TranspositionTable::new(4);
+            let mut history = History::new();
+            let mut rng = Rng::new(1);
+            // relógio falso: cada consulta avança 1 ms
+            let clock = std::cell::Cell::new(0.0);
+            let now = || {
+                clock.set(clock.get() + 1.0);
+                clock.get()
+            };
+            Searcher::new(&mut tt, &zobrist, &mut history, &mut rng, deadline, max_depth)
+                .iterative_deepening(occ, 0, 0, 0, now)
         };
+        for deadline in [100.0, 400.0, 700.0] {
+            let cut = search(deadline, 64);
+            assert!(cut.depth_reached >= 1);
+            // o resultado é o da última iteração completa, como se parasse aí
+            let clean = search(f64::MAX, cut.depth_reached);
+            assert_eq!((cut.best_move, cut.score), (clean.best_move, clean.score));
+            assert_eq!(cut.pv, clean.pv);
+            assert_legal_line(occ, 0, &cut.pv);
+        }
+    }
 
-        self.tt.store(TTEntry {
-            key,
-            depth: depth.max(0) as u8,
-            score: best_score,
-            flag,
-            best_move,
-            age: self.age,
-        });
+    #[test]
+    fn progress_follows_the_iterations() {
+        let zobrist = ZobristKeys::new();
+        let occ = open_rectangle(&mut Rng::new(7), 5, 8, 0);
+        let mut tt = TranspositionTable::new(4);
+        let mut history = History::new();
+        let mut rng = Rng::new(1);
+        let mut reports = Vec::new();
+        let mut progress = |p: &crjm_search::Progress<EncMove>| reports.push((p.depth, p.score, p.pv.to_vec()));
+        let result = Searcher::new(&mut tt, &zobrist, &mut history, &mut rng, f64::MAX, 64)
+            .with_node_budget(20_000)
+            .with_progress(&mut progress)
+            .iterative_deepening(occ, 0, 0, 0, || 0.0);
+        assert!(result.nodes_searched <= 20_000);
+        let depths: Vec<u32> = reports.iter().map(|r| r.0).collect();
+        assert_eq!(depths, (1..=result.depth_reached).collect::<Vec<_>>());
+        assert_eq!(reports.last(), Some(&(result.depth_reached, result.score, result.pv.clone())));
+    }
+
+    #[test]
+    fn decomposed_endgames_are_solved_at_the_root() {
+        // linha 0 livre (bloco horizontal exclusivo) e dois blocos verticais
+        let mut occ = Occupancy { low: !0, high: (1u64 << 36) - 1 };
+        for idx in [0usize, 1, 2, 3, 35, 45, 55, 68, 78] {
+            if idx < 64 {
+                occ.low &= !(1u64 << idx);
+            } else {
+                occ.high &= !(1u64 << (idx - 64));
+            }
+        }
+        let mut tt = TranspositionTable::new(1);
+        let zobrist = ZobristKeys::new();
+        let mut history = History::new();
+        let mut rng = Rng::new(1);
+        let result = Searcher::new(&mut tt, &zobrist, &mut history, &mut rng, f64::MAX, 1)
+            .iterative_deepening(occ, 1, 4, 1000, || 0.0);
+        // a horizontal elimina o seu bloco e fica sem jogadas primeiro
+        assert_eq!(result.score, MATE - 2);
+        assert_eq!(mate_in(result.score), Some(2));
+        let after = apply_move(occ, result.best_move.unwrap());
+        assert!(generate_all_moves(after, 1).is_empty());
+        assert_eq!(result.pv.len(), 2);
+        assert_legal_line(occ, 1, &result.pv);
+    }
 
-        best_score
+    #[test]
+    fn mate_in_counts_moves_of_the_side_to_move() {
+        assert_eq!(mate_in(MATE), Some(1));
+        assert_eq!(mate_in(MATE - 4), Some(3));
+        assert_eq!(mate_in(-(MATE - 1)), Some(-1));
+        assert_eq!(mate_in(-(MATE - 3)), Some(-2));
+        assert_eq!(mate_in(-300), None);
     }
 }
-
@@ -1,69 +1,29 @@
-use quelhas_core::{apply_move, decode_move, extract_runs, Occupancy, Run, BOARD_SIZE};
+use quelhas_core::{apply_move, board_metrics, Occupancy, PlayerMetrics};
 
+/// Métricas em `i32`, para a aritmética da avaliação
 #[derive(Clone, Copy, Debug, Default)]
-pub struct Metrics {
-    pub min: i32,
-    pub max: i32,
-    pub min_excl: i32,
-    pub max_excl: i32,
+struct Metrics {
+    min: i32,
+    max: i32,
+    min_excl: i32,
+    max_excl: i32,
 }
 
-#[inline]
-fn add_run_cells(mut mask: Occupancy, run: Run) -> Occupancy {
-    let delta = if run.orient == 0 { BOARD_SIZE } else { 1 };
-    let mut idx = run.start as usize;
-    for _ in 0..(run.len as usize) {
-        mask.set(idx);
-        idx += delta;
-    }
-    mask
-}
-
-#[inline]
-fn run_overlaps(mask: Occupancy, run: Run) -> bool {
-    let delta = if run.orient == 0 { BOARD_SIZE } else { 1 };
-    let mut idx = run.start as usize;
-    for _ in 0..(run.len as usize) {
-        if mask.is_set(idx) {
-            return true;
+impl From<&PlayerMetrics> for Metrics {
+    fn from(m: &PlayerMetrics) -> Self {
+        Metrics {
+            min: m.min as i32,
+            max: m.max as i32,
+            min_excl: m.min_exclusive as i32,
+            max_excl: m.max_exclusive as i32,
         }
-        idx += delta;
     }
-    false
-}
-
-fn compute_metrics(occ: Occupancy, orient: u8, opp_playable_mask: Occupancy) -> Metrics {
-    let runs = extract_runs(occ, orient);
-    let mut m = Metrics::default();
-    for r in runs {
-        m.min += 1;
-        m.max += (r.len as i32) / 2;
-        let exclusive = !run_overlaps(opp_playable_mask, r);
-        if exclusive {
-            m.min_excl += 1;
-            m.max_excl += (r.len as i32) / 2;
-        }
-    }
-    m
 }
 
 pub fn evaluate_misere(occ: Occupancy, side_to_move: u8) -> i32 {
-    let runs_v = extract_runs(occ, 0);
-    let runs_h = extract_runs(occ, 1);
-
-    let mut mask_v = Occupancy::default();
-    let mut mask_h = Occupancy::default();
-    for r in runs_v {
-        mask_v = add_run_cells(mask_v, r);
-    }
-    for r in runs_h {
-        mask_h = add_run_cells(mask_h, r);
-    }
-
-    let m_v = compute_metrics(occ, 0, mask_h);
-    let m_h = compute_metrics(occ, 1, mask_v);
-
-    let (my, opp) = if side_to_move == 0 { (m_v, m_h) } else { (m_h, m_v) };
+    let metrics = board_metrics(occ);
+    let my = Metrics::from(metrics.side(side_to_move));
+    let opp = Metrics::from(metrics.side(1 - side_to_move));
 
     let mut score = 0i32;
     score += (my.max_excl - opp.max_excl) * 50;
@@ -96,18 +56,41 @@ pub fn evaluate_misere(occ: Occupancy, side_to_move: u8) -> i32 {
 
     // pequena penalização por jogadas demasiado longas no imediato (mais controlo)
     // (usado indiretamente no ordering em TS; aqui só influencia folhas)
-    score
+
+    // Os termos acima medem a folga de `my` (reservas exclusivas, flexibilidade)
+    // como numa avaliação de jogo normal. Em misère quem fica sem jogadas ganha:
+    // a folga de quem joga agora é-lhe desfavorável, daí o sinal.
+    -score
 }
 
 pub fn cheap_move_score(occ: Occupancy, mv: u16, side_to_move: u8) -> i32 {
     let child = apply_move(occ, mv);
     // se o adversário ficar sem jogadas, é derrota imediata (misère)
     let opp = 1u8 - side_to_move;
-    let opp_moves = quelhas_core::generate_moves_dynamic(child, opp);
-    if opp_moves.is_empty() {
+    if quelhas_core::extract_runs(child, opp).is_empty() {
         return -1_000_000;
     }
     // score aproximado: avaliação do nó filho do ponto de vista de quem joga agora (adversário)
     -evaluate_misere(child, opp)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn running_out_of_moves_is_good_for_the_side_to_move() {
+        // tudo ocupado exceto a linha 0 (bloco horizontal de 10) e as células
+        // (5,0)-(6,0): a Vertical só tem uma jogada, a Horizontal tem muitas
+        let mut occ = Occupancy { low: !0, high: !0 };
+        for idx in (0..10).chain([50, 60]) {
+            if idx < 64 {
+                occ.low &= !(1u64 << idx);
+            } else {
+                occ.high &= !(1u64 << (idx - 64));
+            }
+        }
+        assert!(evaluate_misere(occ, 0) > 0);
+        assert!(evaluate_misere(occ, 1) < 0);
+    }
+}
@@ -1,5 +1,13 @@
+pub mod canonical;
 pub mod engine;
+pub mod endgame;
 pub mod eval;
+pub mod mistakes;
+pub mod puzzle;
+pub mod review;
+pub use crjm_search::rng;
+pub mod skill;
+pub mod state;
 pub mod tt;
 pub mod zobrist;
 
@@ -0,0 +1,156 @@
+//! Erros "humanos" para a dificuldade de iniciação.
+//!
+//! Em vez de um lance quase-melhor ao acaso, o iniciado erra onde as pessoas
+//! erram: mais em posições complexas (muitos lances, vários com ar de igualmente
+//! bons) e de preferência com lances que gastam blocos exclusivos próprios ou
+//! oferecem blocos exclusivos ao adversário. Nunca escolhe um lance que perde
+//! logo (deixar o adversário sem jogadas perde em misère).
+
+use quelhas_core::{apply_move, board_metrics, generate_all_moves, EncMove, Occupancy};
+use crate::rng::Rng;
+
+/// Lances da raiz a menos disto do melhor parecem igualmente bons
+const CLOSE_SCORE: i32 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MistakeModel {
+    /// Fração alvo de lances que não são o melhor lance do motor
+    pub blunder_rate: f64,
+}
+
+impl MistakeModel {
+    pub fn new(blunder_rate: f64) -> Self {
+        Self {
+            blunder_rate: blunder_rate.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Probabilidade de errar numa posição com esta complexidade; a complexidade
+    /// ronda 1/2 ao longo de um jogo, pelo que a taxa global fica perto da alvo.
+    pub fn blunder_probability(&self, complexity: f64) -> f64 {
+        (self.blunder_rate * (0.5 + complexity)).min(1.0)
+    }
+
+    /// Índice do lance a jogar em `root_moves` (ordenados do melhor para o pior):
+    /// 0 mantém o melhor, outro valor é um erro plausível.
+    pub fn choose(&self, occ: Occupancy, side: u8, root_moves: &[(EncMove, i32)], rng: &mut Rng) -> usize {
+        if root_moves.len() < 2 || self.blunder_rate <= 0.0 {
+            return 0;
+        }
+        if rng.next_f64() >= self.blunder_probability(complexity(root_moves)) {
+            return 0;
+        }
+
+        let weights: Vec<(usize, u32)> = root_moves
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|&(_, &(mv, _))| !is_suicidal(occ, mv, side))
+            .map(|(i, &(mv, _))| (i, 1 + 2 * give_away(occ, mv, side)))
+            .collect();
+        let total: u32 = weights.iter().map(|&(_, w)| w).sum();
+        if total == 0 {
+            return 0;
+        }
+        let mut pick = rng.below(total as usize) as u32;
+        for (i, w) in weights {
+            if pick < w {
+                return i;
+            }
+            pick -= w;
+        }
+        0
+    }
+}
+
+/// Complexidade entre 0 e 1: metade pelo número de lances, metade por quantos
+/// têm score perto do melhor.
+pub fn complexity(root_moves: &[(EncMove, i32)]) -> f64 {
+    let Some(&(_, best)) = root_moves.first() else {
+        return 0.0;
+    };
+    let breadth = (root_moves.len() as f64 / 60.0).min(1.0);
+    let close = root_moves.iter().filter(|&&(_, s)| best - s <= CLOSE_SCORE).count();
+    let ambiguity = ((close - 1) as f64 / 4.0).min(1.0);
+    0.5 * breadth + 0.5 * ambiguity
+}
+
+/// Blocos exclusivos (em jogadas máximas) que o lance gasta a quem joga mais
+/// os que dá ao adversário.
+pub fn give_away(occ: Occupancy, mv: EncMove, side: u8) -> u32 {
+    let (before, after) = (board_metrics(occ), board_metrics(apply_move(occ, mv)));
+    let exclusive = |m: &quelhas_core::BoardMetrics, s: u8| m.side(s).max_exclusive;
+    let opp = 1 - side;
+    let spent = exclusive(&before, side).saturating_sub(exclusive(&after, side));
+    let gifted = exclusive(&after, opp).saturating_sub(exclusive(&before, opp));
+    spent + gifted
+}
+
+/// Lance que perde de imediato: o adversário fica sem jogadas (e ganha, em misère).
+pub fn is_suicidal(occ: Occupancy, mv: EncMove, side: u8) -> bool {
+    generate_all_moves(apply_move(occ, mv), 1 - side).is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quelhas_core::encode_move;
+
+    fn random_position(rng: &mut Rng, plies: usize) -> Option<(Occupancy, u8)> {
+        let mut occ = Occupancy::default();
+        let mut side = 0u8;
+        for _ in 0..plies {
+            let moves = generate_all_moves(occ, side);
+            if moves.is_empty() {
+                return None;
+            }
+            occ = apply_move(occ, moves[rng.below(moves.len())]);
+            side ^= 1;
+        }
+        (generate_all_moves(occ, side).len() > 1).then_some((occ, side))
+    }
+
+    #[test]
+    fn blunder_rate_is_close_to_the_target() {
+        let mut rng = Rng::new(8);
+        let model = MistakeModel::new(0.3);
+        let (mut blunders, mut positions) = (0, 0);
+        for i in 0..600 {
+            let Some((occ, side)) = random_position(&mut rng, i % 30) else {
+                continue;
+            };
+            let root: Vec<(EncMove, i32)> = generate_all_moves(occ, side)
+                .into_iter()
+                .enumerate()
+                .map(|(k, mv)| (mv, -(k as i32) * 20))
+                .collect();
+            positions += 1;
+            if model.choose(occ, side, &root, &mut rng) != 0 {
+                blunders += 1;
+            }
+        }
+        let rate = blunders as f64 / positions as f64;
+        assert!((0.2..0.4).contains(&rate), "{}", rate);
+    }
+
+    #[test]
+    fn never_leaves_the_opponent_without_moves() {
+        // livres: coluna 0 nas linhas 0-3 e a célula (1,1). O único bloco da
+        // Horizontal é (1,0)-(1,1); a Vertical só não o destrói jogando (2,0)-(3,0)
+        let mut occ = Occupancy { low: !0, high: !0 };
+        for idx in [0usize, 10, 20, 30, 11] {
+            occ.low &= !(1u64 << idx);
+        }
+        let safe = encode_move(20, 2, 0);
+        assert!(!is_suicidal(occ, safe, 0));
+        assert!(is_suicidal(occ, encode_move(0, 2, 0), 0));
+        assert!(is_suicidal(occ, encode_move(0, 4, 0), 0));
+
+        let root = [(safe, 0), (encode_move(0, 2, 0), -10), (encode_move(0, 4, 0), -20)];
+        let model = MistakeModel::new(1.0);
+        let mut rng = Rng::new(2);
+        for _ in 0..200 {
+            assert_eq!(model.choose(occ, 0, &root, &mut rng), 0);
+        }
+    }
+}
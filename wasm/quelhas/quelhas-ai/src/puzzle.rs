@@ -0,0 +1,345 @@
+//! Gerador de problemas: finais com exatamente um lance vencedor.
+//!
+//! As posições vêm de jogos de auto-jogo aleatorizados, quando já restam poucas
+//! casas em jogo. Cada lance legal é resolvido de forma exata (pesquisa até ao
+//! fim do jogo) e a posição só fica se um único lance ganhar. A dificuldade é o
+//! comprimento da vitória forçada, ou seja, a profundidade a que é preciso ver
+//! para a encontrar. Os lances errados mais tentadores (os que parecem melhores
+//! à avaliação estática) vêm com a resposta que os refuta.
+//!
+//! Em misère quem fica sem jogadas ganha, por isso a linha da solução termina
+//! com o vencedor a jogar e sem lances.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use quelhas_core::{apply_move, extract_runs, generate_all_moves, EncMove, Occupancy, BOARD_SIZE};
+
+use crate::engine::{History, Searcher};
+use crate::eval::evaluate_misere;
+use crate::rng::Rng;
+use crate::tt::TranspositionTable;
+use crate::zobrist::ZobristKeys;
+
+/// Lances errados tentadores por problema
+const MAX_REFUTATIONS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    /// Até 4 meios-lances é fácil, até 8 médio, acima disso difícil
+    pub fn from_depth(depth: u32) -> Difficulty {
+        match depth {
+            0..=4 => Difficulty::Easy,
+            5..=8 => Difficulty::Medium,
+            _ => Difficulty::Hard,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+}
+
+/// Um lance perdedor e a resposta do adversário que o prova
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Refutation {
+    pub mv: EncMove,
+    pub reply: EncMove,
+}
+
+/// Posição em que exatamente um lance ganha para quem joga
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub occ: Occupancy,
+    /// 0 = vertical, 1 = horizontal
+    pub side: u8,
+    /// linha vencedora: o lance-chave, depois a melhor defesa e as respostas
+    /// vencedoras até o vencedor ficar sem jogadas
+    pub solution: Vec<EncMove>,
+    /// meios-lances da vitória forçada (o comprimento de `solution`)
+    pub depth: u32,
+    /// lances errados mais tentadores, o de melhor aspeto primeiro
+    pub refutations: Vec<Refutation>,
+}
+
+impl Puzzle {
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty::from_depth(self.depth)
+    }
+}
+
+/// Casas em que ainda cabe um segmento de alguma das orientações
+pub fn live_cells(occ: Occupancy) -> u32 {
+    let mut live = Occupancy::default();
+    for run in extract_runs(occ, 0).into_iter().chain(extract_runs(occ, 1)) {
+        let delta = if run.orient == 0 { BOARD_SIZE } else { 1 };
+        for i in 0..run.len as usize {
+            live.set(run.start as usize + i * delta);
+        }
+    }
+    live.low.count_ones() + live.high.count_ones()
+}
+
+/// Amostra e resolve posições; tem a sua própria TT
+pub struct PuzzleGenerator {
+    tt: TranspositionTable,
+    zobrist: ZobristKeys,
+    history: History,
+    rng: Rng,
+    /// só se analisam posições com no máximo estas casas em jogo
+    pub max_live_cells: u32,
+    /// vitória forçada mais curta (em meios-lances) que vale a pena guardar
+    pub min_depth: u32,
+}
+
+impl PuzzleGenerator {
+    pub fn new(seed: u64, max_live_cells: u32) -> Self {
+        Self {
+            tt: TranspositionTable::new(32),
+            zobrist: ZobristKeys::new(),
+            history: History::new(),
+            rng: Rng::new(seed),
+            max_live_cells,
+            min_depth: 4,
+        }
+    }
+
+    /// Score exato para quem joga
+    fn solve(&mut self, occ: Occupancy, side: u8) -> i32 {
+        // cada lance ocupa pelo menos duas casas em jogo: esta profundidade chega ao fim
+        let depth = live_cells(occ) / 2 + 1;
+        self.tt.new_search();
+        Searcher::new(&mut self.tt, &self.zobrist, &mut self.history, &mut self.rng, f64::MAX, depth)
+            .iterative_deepening(occ, side, 0, 0, || 0.0)
+            .score
+    }
+
+    /// Todos os lances com o score exato para quem os joga
+    fn solve_moves(&mut self, occ: Occupancy, side: u8) -> Vec<(EncMove, i32)> {
+        generate_all_moves(occ, side)
+            .into_iter()
+            .map(|mv| (mv, -self.solve(apply_move(occ, mv), 1 - side)))
+            .collect()
+    }
+
+    /// Melhor lance exato: vitória mais rápida ou derrota mais lenta
+    fn best_move(&mut self, occ: Occupancy, side: u8) -> Option<EncMove> {
+        self.solve_moves(occ, side).into_iter().max_by_key(|&(_, s)| s).map(|(mv, _)| mv)
+    }
+
+    /// O problema desta posição, se exatamente um lance ganhar
+    pub fn analyse(&mut self, occ: Occupancy, side: u8) -> Option<Puzzle> {
+        let solved = self.solve_moves(occ, side);
+        if solved.len() < 2 {
+            return None;
+        }
+        let mut winners = solved.iter().filter(|&&(_, s)| s > 0);
+        let &(winner, _) = winners.next()?;
+        if winners.next().is_some() {
+            return None;
+        }
+
+        let solution = self.winning_line(occ, side, winner);
+        let depth = solution.len() as u32;
+        if depth < self.min_depth {
+            return None;
+        }
+
+        // tentador = parece bom para quem joga sem pesquisa
+        let mut losers: Vec<(i32, EncMove)> = solved
+            .iter()
+            .filter(|&&(mv, _)| mv != winner)
+            .map(|&(mv, _)| (-evaluate_misere(apply_move(occ, mv), 1 - side), mv))
+            .collect();
+        losers.sort_by_key(|&(looks, _)| std::cmp::Reverse(looks));
+        let mut refutations = Vec::new();
+        for (_, mv) in losers.into_iter().take(MAX_REFUTATIONS) {
+            if let Some(reply) = self.best_move(apply_move(occ, mv), 1 - side) {
+                refutations.push(Refutation { mv, reply });
+            }
+        }
+
+        Some(Puzzle {
+            occ,
+            side,
+            solution,
+            depth,
+            refutations,
+        })
+    }
+
+    /// `first` e depois os melhores lances exatos dos dois lados até ao fim
+    fn winning_line(&mut self, occ: Occupancy, side: u8, first: EncMove) -> Vec<EncMove> {
+        let mut line = vec![first];
+        let mut occ = apply_move(occ, first);
+        let mut side = 1 - side;
+        while let Some(mv) = self.best_move(occ, side) {
+            line.push(mv);
+            occ = apply_move(occ, mv);
+            side = 1 - side;
+        }
+        line
+    }
+
+    /// Joga um jogo de auto-jogo aleatorizado e devolve os problemas do final
+    pub fn sample_game(&mut self) -> Vec<Puzzle> {
+        let mut occ = Occupancy::default();
+        let mut side = 0u8;
+        let mut found = Vec::new();
+        for ply in 0.. {
+            let moves = generate_all_moves(occ, side);
+            if moves.is_empty() {
+                break;
+            }
+            if live_cells(occ) <= self.max_live_cells {
+                found.extend(self.analyse(occ, side));
+            }
+            // aberturas ao acaso, depois pesquisa curta com a randomização da raiz
+            let mv = if ply < 4 {
+                moves[self.rng.below(moves.len())]
+            } else {
+                self.tt.new_search();
+                Searcher::new(&mut self.tt, &self.zobrist, &mut self.history, &mut self.rng, f64::MAX, 2)
+                    .iterative_deepening(occ, side, 4, 150, || 0.0)
+                    .best_move
+                    .unwrap_or(moves[0])
+            };
+            occ = apply_move(occ, mv);
+            side = 1 - side;
+        }
+        found
+    }
+
+    /// Até `count` problemas distintos de no máximo `max_games` jogos, os mais fáceis primeiro
+    pub fn generate(&mut self, count: usize, max_games: usize) -> Vec<Puzzle> {
+        let mut seen = HashSet::new();
+        let mut puzzles = Vec::new();
+        for _ in 0..max_games {
+            for puzzle in self.sample_game() {
+                if seen.insert((puzzle.occ.low, puzzle.occ.high, puzzle.side)) {
+                    puzzles.push(puzzle);
+                }
+            }
+            if puzzles.len() >= count {
+                break;
+            }
+        }
+        puzzles.truncate(count);
+        puzzles.sort_by_key(|p| p.depth);
+        puzzles
+    }
+}
+
+/// Conjunto de problemas em JSON; o tabuleiro usa as quatro partes de 32 bits
+/// que `QuelhasEngine::search` recebe
+pub fn to_json(puzzles: &[Puzzle]) -> String {
+    let list = |moves: &[EncMove]| moves.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(",");
+    let mut out = String::from("{\"game\":\"quelhas\",\"puzzles\":[");
+    for (i, p) in puzzles.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let refutations: Vec<String> = p
+            .refutations
+            .iter()
+            .map(|r| format!("{{\"move\":{},\"reply\":{}}}", r.mv, r.reply))
+            .collect();
+        let _ = write!(
+            out,
+            "{{\"occupied\":[{},{},{},{}],\"side\":{},\"solution\":[{}],\"depth\":{},\"difficulty\":\"{}\",\"refutations\":[{}]}}",
+            p.occ.low as u32,
+            (p.occ.low >> 32) as u32,
+            p.occ.high as u32,
+            (p.occ.high >> 32) as u32,
+            p.side,
+            list(&p.solution),
+            p.depth,
+            p.difficulty().name(),
+            refutations.join(",")
+        );
+    }
+    out.push_str("]}");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quelhas_core::CELL_COUNT;
+
+    /// Minimax simples: quem joga ganha? (sem jogadas, ganha)
+    fn wins(occ: Occupancy, side: u8) -> bool {
+        let moves = generate_all_moves(occ, side);
+        moves.is_empty() || moves.into_iter().any(|mv| !wins(apply_move(occ, mv), 1 - side))
+    }
+
+    fn nearly_full(rng: &mut Rng, holes: usize) -> Occupancy {
+        let mut occ = Occupancy { low: !0, high: (1u64 << (CELL_COUNT - 64)) - 1 };
+        for _ in 0..holes {
+            let idx = rng.below(CELL_COUNT);
+            if idx < 64 {
+                occ.low &= !(1u64 << idx);
+            } else {
+                occ.high &= !(1u64 << (idx - 64));
+            }
+        }
+        occ
+    }
+
+    #[test]
+    fn agrees_with_brute_force() {
+        let mut rng = Rng::new(5);
+        let mut generator = PuzzleGenerator::new(1, 100);
+        generator.min_depth = 1;
+        let mut puzzles = 0;
+        for _ in 0..40 {
+            let occ = nearly_full(&mut rng, 22);
+            for side in 0..2u8 {
+                let moves = generate_all_moves(occ, side);
+                let winning = moves.iter().filter(|&&mv| !wins(apply_move(occ, mv), 1 - side)).count();
+                let puzzle = generator.analyse(occ, side);
+                assert_eq!(puzzle.is_some(), winning == 1 && moves.len() > 1);
+                if let Some(p) = puzzle {
+                    assert!(!wins(apply_move(occ, p.solution[0]), 1 - side));
+                    puzzles += 1;
+                }
+            }
+        }
+        assert!(puzzles > 0);
+    }
+
+    #[test]
+    fn sampled_puzzles_are_sound() {
+        let mut generator = PuzzleGenerator::new(2, 18);
+        let puzzles = generator.generate(2, 10);
+        assert!(!puzzles.is_empty());
+        for p in &puzzles {
+            assert_eq!(p.depth as usize, p.solution.len());
+            // a linha é legal e termina com o vencedor a jogar, sem lances
+            let (mut occ, mut side) = (p.occ, p.side);
+            for &mv in &p.solution {
+                assert!(generate_all_moves(occ, side).contains(&mv));
+                occ = apply_move(occ, mv);
+                side = 1 - side;
+            }
+            assert!(generate_all_moves(occ, side).is_empty());
+            assert_eq!(side, p.side);
+            for r in &p.refutations {
+                assert_ne!(r.mv, p.solution[0]);
+                assert!(generate_all_moves(apply_move(p.occ, r.mv), 1 - p.side).contains(&r.reply));
+            }
+        }
+        let json = to_json(&puzzles);
+        assert!(json.starts_with("{\"game\":\"quelhas\",\"puzzles\":[{\"occupied\":["));
+    }
+}
@@ -0,0 +1,327 @@
+//! Revisão de um jogo: cada lance classificado face ao lance do motor.
+//!
+//! Cada posição do jogo é pesquisada uma vez. O melhor score de uma posição é
+//! comparado com o score do lance jogado, que é o simétrico do score da posição
+//! seguinte, pelo que um jogo de `n` lances custa `n + 1` pesquisas. Os scores
+//! que a pesquisa prova (mate) dizem também se o resultado teórico mudou.
+
+use std::fmt;
+
+use quelhas_core::{apply_move, generate_all_moves, EncMove, Occupancy};
+
+use crate::engine::{MATE, MATE_BOUND};
+
+/// Scores provados contam como isto ao medir quanto um lance perdeu
+pub const DECIDED_SCORE: i32 = 2000;
+
+/// Perda máxima (em pontos de avaliação) de cada classificação abaixo de `Best`
+pub const GOOD_DROP: i32 = 60;
+pub const INACCURACY_DROP: i32 = 150;
+pub const MISTAKE_DROP: i32 = 400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MoveClass {
+    Best = 0,
+    Good = 1,
+    Inaccuracy = 2,
+    Mistake = 3,
+    Blunder = 4,
+}
+
+/// Resultado teórico para quem joga, quando a pesquisa o provou
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Unknown,
+}
+
+impl Outcome {
+    fn of(score: i32) -> Outcome {
+        if score >= MATE_BOUND {
+            Outcome::Win
+        } else if score <= -MATE_BOUND {
+            Outcome::Loss
+        } else {
+            Outcome::Unknown
+        }
+    }
+
+    /// 1 vitória, -1 derrota, 0 por provar
+    pub fn sign(self) -> i8 {
+        match self {
+            Outcome::Win => 1,
+            Outcome::Loss => -1,
+            Outcome::Unknown => 0,
+        }
+    }
+}
+
+/// Revisão de um lance; scores do ponto de vista de quem jogou
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveReview {
+    /// 0 = vertical, 1 = horizontal
+    pub side: u8,
+    pub played: EncMove,
+    /// lance do motor na posição antes do lance jogado
+    pub best_move: Option<EncMove>,
+    pub best_score: i32,
+    pub played_score: i32,
+    /// pontos perdidos face ao melhor lance (nunca negativo)
+    pub eval_drop: i32,
+    /// resultado antes do lance (com o melhor jogo) e depois dele
+    pub outcome_before: Outcome,
+    pub outcome_after: Outcome,
+    pub class: MoveClass,
+}
+
+impl MoveReview {
+    /// O lance transformou uma vitória provada numa derrota provada
+    pub fn throws_win(&self) -> bool {
+        self.outcome_before == Outcome::Win && self.outcome_after == Outcome::Loss
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameReview {
+    pub moves: Vec<MoveReview>,
+    /// índice em `moves` do lance que decidiu o jogo (ver `critical_move`)
+    pub critical_ply: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewError {
+    /// O lance `ply` não é legal na posição a que se chegou
+    IllegalMove { ply: usize, mv: EncMove },
+}
+
+impl fmt::Display for ReviewError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReviewError::IllegalMove { ply, mv } => write!(f, "move {} ({}) is illegal", ply + 1, mv),
+        }
+    }
+}
+
+impl std::error::Error for ReviewError {}
+
+/// Revê `moves` jogados a partir do tabuleiro vazio, começando `first`.
+///
+/// `search(occ, side)` devolve o lance e o score do motor para quem joga; não é
+/// chamada em posições terminais (quem fica sem jogadas ganha).
+pub fn review_game<F>(moves: &[EncMove], first: u8, search: F) -> Result<GameReview, ReviewError>
+where
+    F: FnMut(Occupancy, u8) -> (Option<EncMove>, i32),
+{
+    review_game_from(Occupancy::default(), first, moves, search)
+}
+
+/// [`review_game`] para lances jogados a partir da posição `start`.
+pub fn review_game_from<F>(start: Occupancy, first: u8, moves: &[EncMove], mut search: F) -> Result<GameReview, ReviewError>
+where
+    F: FnMut(Occupancy, u8) -> (Option<EncMove>, i32),
+{
+    // repetir o jogo primeiro, para um registo ilegal não custar pesquisas
+    let mut positions = Vec::with_capacity(moves.len() + 1);
+    let (mut occ, mut side) = (start, first);
+    for (ply, &mv) in moves.iter().enumerate() {
+        if !generate_all_moves(occ, side).contains(&mv) {
+            return Err(ReviewError::IllegalMove { ply, mv });
+        }
+        positions.push((occ, side));
+        occ = apply_move(occ, mv);
+        side = 1 - side;
+    }
+    positions.push((occ, side));
+
+    let analysed: Vec<(Option<EncMove>, i32)> = positions
+        .iter()
+        .map(|&(occ, side)| {
+            if generate_all_moves(occ, side).is_empty() {
+                (None, MATE)
+            } else {
+                search(occ, side)
+            }
+        })
+        .collect();
+
+    let reviews: Vec<MoveReview> = moves
+        .iter()
+        .enumerate()
+        .map(|(ply, &played)| {
+            let (best_move, best_score) = analysed[ply];
+            let played_score = -analysed[ply + 1].1;
+            let eval_drop = if best_move == Some(played) {
+                0
+            } else {
+                (decided(best_score) - decided(played_score)).max(0)
+            };
+            let outcome_before = Outcome::of(best_score);
+            let outcome_after = Outcome::of(played_score);
+            let class = if best_move == Some(played) || eval_drop == 0 {
+                MoveClass::Best
+            } else if outcome_before != Outcome::Loss && outcome_after == Outcome::Loss {
+                MoveClass::Blunder
+            } else {
+                classify(eval_drop)
+            };
+            MoveReview {
+                side: positions[ply].1,
+                played,
+                best_move,
+                best_score,
+                played_score,
+                eval_drop,
+                outcome_before,
+                outcome_after,
+                class,
+            }
+        })
+        .collect();
+
+    Ok(GameReview {
+        critical_ply: critical_move(&reviews),
+        moves: reviews,
+    })
+}
+
+pub fn classify(eval_drop: i32) -> MoveClass {
+    match eval_drop {
+        d if d <= 0 => MoveClass::Best,
+        d if d <= GOOD_DROP => MoveClass::Good,
+        d if d <= INACCURACY_DROP => MoveClass::Inaccuracy,
+        d if d <= MISTAKE_DROP => MoveClass::Mistake,
+        _ => MoveClass::Blunder,
+    }
+}
+
+/// Lance depois do qual o resultado teórico não voltou a mudar: o último que
+/// deitou fora uma vitória provada. Se a pesquisa nunca provou a vitória antes
+/// do lance perdedor, serve o último lance que passou de uma posição por provar
+/// para uma derrota provada.
+pub fn critical_move(moves: &[MoveReview]) -> Option<usize> {
+    moves.iter().rposition(MoveReview::throws_win).or_else(|| {
+        moves
+            .iter()
+            .rposition(|m| m.outcome_before == Outcome::Unknown && m.outcome_after == Outcome::Loss)
+    })
+}
+
+/// Score com os resultados provados limitados: perder uma vitória pesa como um erro grave
+fn decided(score: i32) -> i32 {
+    if score.abs() >= MATE_BOUND {
+        score.signum() * DECIDED_SCORE
+    } else {
+        score.clamp(-DECIDED_SCORE + 1, DECIDED_SCORE - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{History, Searcher};
+    use crate::rng::Rng;
+    use crate::tt::TranspositionTable;
+    use crate::zobrist::ZobristKeys;
+
+    /// Pesquisa falsa: o primeiro lance legal e os scores do guião, por ordem
+    fn scripted(scores: &[i32]) -> impl FnMut(Occupancy, u8) -> (Option<EncMove>, i32) + '_ {
+        let mut next = scores.iter();
+        move |occ, side| (generate_all_moves(occ, side).first().copied(), *next.next().unwrap())
+    }
+
+    /// O último lance legal em cada vez (nunca o da pesquisa falsa)
+    fn last_moves(plies: usize) -> Vec<EncMove> {
+        let (mut occ, mut side) = (Occupancy::default(), 0u8);
+        (0..plies)
+            .map(|_| {
+                let mv = *generate_all_moves(occ, side).last().unwrap();
+                occ = apply_move(occ, mv);
+                side = 1 - side;
+                mv
+            })
+            .collect()
+    }
+
+    #[test]
+    fn illegal_move_is_reported() {
+        // a horizontal não pode jogar um lance vertical
+        let moves = last_moves(2);
+        let err = review_game(&[moves[0], moves[0]], 0, scripted(&[])).unwrap_err();
+        assert_eq!(err, ReviewError::IllegalMove { ply: 1, mv: moves[0] });
+    }
+
+    #[test]
+    fn moves_are_graded_by_eval_drop() {
+        let first = generate_all_moves(Occupancy::default(), 0)[0];
+        let review = review_game(&[first], 0, scripted(&[90, 700])).unwrap();
+        assert_eq!(review.moves[0].class, MoveClass::Best);
+
+        let review = review_game(&last_moves(1), 0, scripted(&[90, 90])).unwrap();
+        assert_eq!(review.moves[0].eval_drop, 180);
+        assert_eq!(review.moves[0].class, MoveClass::Mistake);
+    }
+
+    #[test]
+    fn last_thrown_win_is_the_critical_move() {
+        let scores = [MATE - 5, MATE - 4, MATE - 7, 10];
+        let review = review_game(&last_moves(3), 0, scripted(&scores)).unwrap();
+        assert!(review.moves[0].throws_win());
+        assert!(review.moves[1].throws_win());
+        assert_eq!(review.moves[1].class, MoveClass::Blunder);
+        assert_eq!(review.critical_ply, Some(1));
+
+        let scores = [30, -40, MATE - 9];
+        let review = review_game(&last_moves(2), 0, scripted(&scores)).unwrap();
+        assert_eq!(review.moves[1].outcome_before, Outcome::Unknown);
+        assert_eq!(review.critical_ply, Some(1));
+    }
+
+    #[test]
+    fn review_of_a_real_game_blames_the_loser() {
+        let mut tt = TranspositionTable::new(4);
+        let zobrist = ZobristKeys::new();
+        let mut history = History::new();
+        let mut rng = Rng::new(3);
+
+        // motor (vertical) contra lances ao acaso (horizontal)
+        let (mut occ, mut side) = (Occupancy::default(), 0u8);
+        let mut moves = Vec::new();
+        loop {
+            let legal = generate_all_moves(occ, side);
+            if legal.is_empty() {
+                break;
+            }
+            let mv = if side == 0 {
+                Searcher::new(&mut tt, &zobrist, &mut history, &mut rng, f64::MAX, 2)
+                    .iterative_deepening(occ, side, 0, 0, || 0.0)
+                    .best_move
+                    .unwrap()
+            } else {
+                legal[rng.below(legal.len())]
+            };
+            occ = apply_move(occ, mv);
+            moves.push(mv);
+            side = 1 - side;
+        }
+        // quem fica sem jogadas ganha
+        let loser = 1 - side;
+
+        let review = review_game(&moves, 0, |occ, side| {
+            tt.new_search();
+            let r = Searcher::new(&mut tt, &zobrist, &mut history, &mut rng, f64::MAX, 3)
+                .iterative_deepening(occ, side, 0, 0, || 0.0);
+            (r.best_move, r.score)
+        })
+        .unwrap();
+        assert_eq!(review.moves.len(), moves.len());
+        for m in &review.moves {
+            assert!(m.eval_drop >= 0);
+            if m.best_move == Some(m.played) {
+                assert_eq!(m.class, MoveClass::Best);
+            }
+        }
+        let critical = review.critical_ply.expect("o fim do jogo fica resolvido");
+        assert_eq!(review.moves[critical].side, loser);
+    }
+}
@@ -0,0 +1,147 @@
+//! Níveis de força 0..=20: enfraquecer o motor de forma controlada e medida.
+//!
+//! Cada nível fixa três parâmetros:
+//! - um orçamento de nós, que limita o que a pesquisa consegue ver;
+//! - ruído nas avaliações das folhas, para que níveis fracos julguem mal;
+//! - uma temperatura de softmax para escolher entre os lances MultiPV da raiz.
+//!
+//! `ELO_GAP` dá o rating de cada nível relativamente ao nível 20 e `ELO_ERROR`
+//! o erro padrão desse rating, medidos em self-play
+//! (`cargo run --release -p quelhas-ai --example calibrate_skill`).
+
+use crate::rng::{splitmix64, Rng};
+
+pub const MAX_SKILL_LEVEL: u8 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkillParams {
+    /// Nós que a pesquisa pode visitar (`u64::MAX` = só tempo e profundidade)
+    pub node_budget: u64,
+    /// Amplitude do ruído somado às avaliações das folhas
+    pub eval_noise: i32,
+    /// Temperatura da softmax sobre os scores da raiz (0 = sempre o melhor)
+    pub temperature: f64,
+    /// Lances da raiz com score exato para a escolha
+    pub multipv: usize,
+}
+
+/// Rating de cada nível relativamente ao nível 20, medido em self-play: 40
+/// jogos por par contra seis níveis âncora, 300 ms por lance, a correr
+/// sozinho num núcleo, sem suavização. Só diferenças bem acima de `ELO_ERROR`
+/// contam: os níveis 0 a 6 não se distinguem, nem 7 a 9 (o ajuste até os põe
+/// por ordem inversa), nem 10 a 13, nem 16 e 17.
+pub const ELO_GAP: [i32; 21] = [
+    -550, -570, -490, -540, -490, -470, -480, -400, -420, -440,
+    -390, -370, -360, -330, -280, -230, -190, -190, -160, -140, 0,
+];
+
+/// Erro padrão de cada valor de `ELO_GAP` (o nível 20 é a referência fixa)
+pub const ELO_ERROR: [i32; 21] = [
+    20, 30, 30, 30, 20, 30, 30, 30, 20, 30,
+    30, 30, 20, 30, 30, 30, 20, 30, 30, 30, 0,
+];
+
+pub fn params(level: u8) -> SkillParams {
+    let level = level.min(MAX_SKILL_LEVEL);
+    if level == MAX_SKILL_LEVEL {
+        return SkillParams {
+            node_budget: u64::MAX,
+            eval_noise: 0,
+            temperature: 0.0,
+            multipv: 1,
+        };
+    }
+
+    let l = level as i32;
+    let temperature = (14 - l).max(0) as f64 * 15.0;
+    SkillParams {
+        // 200 nós no nível 0 até 20k no nível 19 (~1.27x por nível): mesmo o
+        // nível 19 esgota os nós bem antes de 250 ms por lance
+        node_budget: (200.0 * 100f64.powf(l as f64 / 19.0)) as u64,
+        eval_noise: (16 - l).max(0) * 25,
+        temperature,
+        multipv: if temperature > 0.0 { 4 } else { 1 },
+    }
+}
+
+pub fn elo_gap(level: u8) -> i32 {
+    ELO_GAP[level.min(MAX_SKILL_LEVEL) as usize]
+}
+
+pub fn elo_error(level: u8) -> i32 {
+    ELO_ERROR[level.min(MAX_SKILL_LEVEL) as usize]
+}
+
+/// Índice em `scored` escolhido com probabilidade proporcional a
+/// `exp((score - melhor) / temperature)`.
+pub fn softmax_choice<M>(scored: &[(M, i32)], temperature: f64, rng: &mut Rng) -> usize {
+    if scored.len() < 2 || temperature <= 0.0 {
+        return 0;
+    }
+    let best = scored.iter().map(|&(_, s)| s).max().unwrap_or(0);
+    let weights: Vec<f64> = scored
+        .iter()
+        .map(|&(_, s)| ((s - best) as f64 / temperature).exp())
+        .collect();
+    let mut pick = rng.next_f64() * weights.iter().sum::<f64>();
+    for (i, w) in weights.iter().enumerate() {
+        if pick < *w {
+            return i;
+        }
+        pick -= w;
+    }
+    scored.len() - 1
+}
+
+/// Ruído determinístico em `-amplitude..=amplitude` para uma posição: a mesma
+/// posição recebe sempre o mesmo desvio, e a TT continua coerente.
+#[inline]
+pub fn eval_noise(key: u64, salt: u64, amplitude: i32) -> i32 {
+    if amplitude == 0 {
+        return 0;
+    }
+    let mut state = key ^ salt;
+    (splitmix64(&mut state) % (2 * amplitude as u64 + 1)) as i32 - amplitude
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn higher_levels_are_never_weaker() {
+        for level in 1..=MAX_SKILL_LEVEL {
+            let (weak, strong) = (params(level - 1), params(level));
+            assert!(strong.node_budget > weak.node_budget);
+            assert!(strong.eval_noise <= weak.eval_noise);
+            assert!(strong.temperature <= weak.temperature);
+        }
+        assert_eq!(params(MAX_SKILL_LEVEL), params(200));
+    }
+
+    #[test]
+    fn ratings_agree_with_their_error_bars() {
+        assert_eq!((elo_gap(MAX_SKILL_LEVEL), elo_error(MAX_SKILL_LEVEL)), (0, 0));
+        let apart = |a: u8, b: u8| 2.0 * ((elo_error(a).pow(2) + elo_error(b).pow(2)) as f64).sqrt();
+        for level in 1..=MAX_SKILL_LEVEL {
+            // um nível medido abaixo do anterior tem de estar dentro do ruído
+            let drop = elo_gap(level - 1) - elo_gap(level);
+            assert!((drop as f64) < apart(level - 1, level), "nível {level}");
+            assert!(elo_error(level - 1) > 0);
+        }
+        assert!(((elo_gap(MAX_SKILL_LEVEL) - elo_gap(0)) as f64) > apart(0, MAX_SKILL_LEVEL));
+    }
+
+    #[test]
+    fn softmax_favours_higher_scores() {
+        let scored = [(7u16, 300), (9, 250), (4, -500)];
+        let mut rng = Rng::new(1);
+        let mut counts = [0u32; 3];
+        for _ in 0..10_000 {
+            counts[softmax_choice(&scored, 50.0, &mut rng)] += 1;
+        }
+        assert!(counts[0] > counts[1] && counts[1] > counts[2], "{:?}", counts);
+        assert_eq!(counts[2], 0);
+        assert_eq!(softmax_choice(&scored, 0.0, &mut rng), 0);
+    }
+}
@@ -0,0 +1,116 @@
+//! Snapshots binários do estado do motor (TT, chaves Zobrist e history).
+//!
+//! Formato (little-endian):
+//! ```text
+//! magic "CRJM" | version u16 | variant u8 | cells u8
+//! zobrist: n u32, n × u64
+//! history: n u32, n × i32
+//! tt: generation u8, buckets u32, buckets
+//! checksum u64 (FNV-1a de tudo o que vem antes)
+//! ```
+
+use crjm_search::snapshot;
+use quelhas_core::CELL_COUNT;
+
+use crate::engine::{History, HISTORY_SIZE};
+use crate::tt::TranspositionTable;
+use crate::zobrist::ZobristKeys;
+
+pub use crjm_search::snapshot::StateError;
+
+/// Dominório usa 1; Quelhas 2.
+pub const VARIANT_QUELHAS: u8 = 2;
+
+/// Estado completo que um `QuelhasEngine` guarda entre pesquisas.
+pub struct EngineState {
+    pub tt: TranspositionTable,
+    pub zobrist: ZobristKeys,
+    pub history: History,
+}
+
+pub fn export(tt: &TranspositionTable, zobrist: &ZobristKeys, history: &History) -> Vec<u8> {
+    let mut out = snapshot::begin(VARIANT_QUELHAS, CELL_COUNT as u8);
+    out.extend_from_slice(&(CELL_COUNT as u32 + 1).to_le_bytes());
+    for key in zobrist.squares.iter().chain(std::iter::once(&zobrist.side)) {
+        out.extend_from_slice(&key.to_le_bytes());
+    }
+
+    out.extend_from_slice(&(history.scores.len() as u32).to_le_bytes());
+    for h in &history.scores {
+        out.extend_from_slice(&h.to_le_bytes());
+    }
+
+    tt.write_to(&mut out);
+    snapshot::finish(out)
+}
+
+pub fn import(bytes: &[u8]) -> Result<EngineState, StateError> {
+    let mut r = snapshot::open(bytes, VARIANT_QUELHAS, CELL_COUNT as u8)?;
+    if r.u32()? as usize != CELL_COUNT + 1 {
+        return Err(StateError::Corrupt("wrong number of Zobrist keys"));
+    }
+    let mut zobrist = ZobristKeys::new();
+    for key in zobrist.squares.iter_mut() {
+        *key = r.u64()?;
+    }
+    zobrist.side = r.u64()?;
+
+    if r.u32()? as usize != HISTORY_SIZE {
+        return Err(StateError::Corrupt("wrong history size"));
+    }
+    let mut history = History::new();
+    for h in history.scores.iter_mut() {
+        *h = r.i32()?;
+    }
+
+    let tt = r.table()?;
+    r.end()?;
+    Ok(EngineState { tt, zobrist, history })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crjm_search::Bound;
+
+    fn sample() -> Vec<u8> {
+        let mut tt = TranspositionTable::new(1);
+        tt.new_search();
+        tt.store(0x0123_4567_89AB_CDEF, Some(0x0A2C), 9, Bound::Lower, -(crate::engine::MATE - 12));
+        let mut history = History::new();
+        history.scores[0x0A2C] = 7_200;
+        export(&tt, &ZobristKeys::new(), &history)
+    }
+
+    #[test]
+    fn round_trip_keeps_tt_and_history() {
+        let state = import(&sample()).unwrap();
+        let e = state.tt.probe(0x0123_4567_89AB_CDEF).unwrap();
+        assert_eq!(
+            (e.depth, e.score, e.bound, e.best_move),
+            (9, -(crate::engine::MATE - 12), Bound::Lower, Some(0x0A2C))
+        );
+        assert_eq!(state.history.scores[0x0A2C], 7_200);
+        assert_eq!(state.zobrist.squares, ZobristKeys::new().squares);
+    }
+
+    #[test]
+    fn corrupted_or_foreign_snapshots_are_rejected() {
+        let mut bytes = sample();
+        let last = bytes.len() - 20;
+        bytes[last] ^= 1;
+        assert_eq!(import(&bytes).err(), Some(StateError::ChecksumMismatch));
+
+        let mut bytes = sample();
+        bytes[6] = 1;
+        bytes[7] = 64;
+        assert_eq!(import(&bytes).err(), Some(StateError::WrongVariant { variant: 1, cells: 64 }));
+
+        let mut bytes = sample();
+        bytes[5] = 1;
+        assert_eq!(import(&bytes).err(), Some(StateError::UnsupportedVersion(0x101)));
+
+        assert_eq!(import(&sample()[..5]).err(), Some(StateError::Truncated));
+        assert_eq!(import(b"garbage-bytes").err(), Some(StateError::BadMagic));
+    }
+}
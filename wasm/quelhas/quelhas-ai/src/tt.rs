@@ -1,61 +1,48 @@
-#[derive(Clone, Copy, Debug)]
-pub struct TTEntry {
-    pub key: u64,
-    pub depth: u8,
-    pub score: i32,
-    pub flag: u8, // 0 exact, 1 lower, 2 upper
-    pub best_move: u16,
-    pub age: u8,
-}
+//! Tabela de transposição: a tabela por buckets de `crjm_search` com scores
+//! de 32 bits e lances codificados em 16 bits (4 entradas em 48 bytes).
 
-pub struct TranspositionTable {
-    entries: Vec<TTEntry>,
-    mask: usize,
-}
+pub type TranspositionTable = crjm_search::TranspositionTable<i32, u16>;
 
-impl TranspositionTable {
-    pub fn new(size: usize) -> Self {
-        let mut entries = Vec::with_capacity(size);
-        entries.resize_with(size, || TTEntry {
-            key: 0,
-            depth: 0,
-            score: 0,
-            flag: 0,
-            best_move: 0,
-            age: 0,
-        });
-        Self { entries, mask: size - 1 }
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crjm_search::Bound;
 
-    #[inline]
-    pub fn clear(&mut self) {
-        for e in self.entries.iter_mut() {
-            e.key = 0;
-            e.depth = 0;
-            e.score = 0;
-            e.flag = 0;
-            e.best_move = 0;
-            e.age = 0;
-        }
+    fn colliding_keys(n: u64) -> Vec<u64> {
+        (1..=n).map(|i| (i << 32) | 3).collect()
     }
 
-    #[inline]
-    pub fn probe(&self, key: u64) -> &TTEntry {
-        &self.entries[(key as usize) & self.mask]
+    #[test]
+    fn any_size_is_accepted() {
+        let tt = TranspositionTable::new(3);
+        assert_eq!(tt.buckets(), 3 * 1024 * 1024 / 48);
+        assert_eq!(TranspositionTable::new(0).buckets(), 1);
     }
 
-    #[inline]
-    pub fn store(&mut self, entry: TTEntry) {
-        let idx = (entry.key as usize) & self.mask;
-        let cur = self.entries[idx];
-
-        // preferir: nova entrada com maior depth ou mais recente
-        if cur.key == 0
-            || entry.depth >= cur.depth
-            || entry.age != cur.age
-        {
-            self.entries[idx] = entry;
+    #[test]
+    fn shallow_entries_use_the_always_replace_slot() {
+        let mut tt = TranspositionTable::new(0);
+        let keys = colliding_keys(5);
+        for (i, &key) in keys[..3].iter().enumerate() {
+            tt.store(key, Some(i as u16), 8 + i as u8, Bound::Exact, 0);
         }
+        tt.store(keys[3], Some(3), 1, Bound::Exact, 0);
+        tt.store(keys[4], Some(4), 1, Bound::Exact, 0);
+
+        assert!(keys[..3].iter().all(|&k| tt.probe(k).is_some()));
+        assert!(tt.probe(keys[3]).is_none());
+        assert_eq!(tt.probe(keys[4]).unwrap().best_move, Some(4));
     }
-}
 
+    #[test]
+    fn hashfull_counts_the_current_generation() {
+        let mut tt = TranspositionTable::new(0);
+        tt.new_search();
+        tt.store(11, Some(9), 5, Bound::Upper, -40);
+        assert_eq!(tt.hashfull(), 250);
+        let e = tt.probe(11).unwrap();
+        assert_eq!((e.score, e.bound, e.generation), (-40, Bound::Upper, 1));
+        tt.new_search();
+        assert_eq!(tt.hashfull(), 0);
+    }
+}
@@ -1,30 +1,24 @@
 use quelhas_core::{Occupancy, CELL_COUNT};
 
+use crjm_search::zobrist::KeyStream;
+
 #[derive(Clone)]
 pub struct ZobristKeys {
     pub squares: [u64; CELL_COUNT],
     pub side: u64,
 }
 
-fn splitmix64(mut x: u64) -> u64 {
-    x = x.wrapping_add(0x9E3779B97F4A7C15);
-    let mut z = x;
-    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
-    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
-    z ^ (z >> 31)
-}
+pub const DEFAULT_ZOBRIST_SEED: u64 = 0xC1F6_9D2A;
 
 impl ZobristKeys {
+    /// Chaves fixas (iguais em todas as execuções).
     pub fn new() -> Self {
-        let mut squares = [0u64; CELL_COUNT];
-        let mut seed = 0xC1F6_9D2Au64;
-        for i in 0..CELL_COUNT {
-            seed = splitmix64(seed);
-            squares[i] = seed;
-        }
-        seed = splitmix64(seed);
-        let side = seed;
-        Self { squares, side }
+        Self::with_seed(DEFAULT_ZOBRIST_SEED)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        let mut keys = KeyStream::new(seed);
+        Self { squares: keys.keys(), side: keys.key() }
     }
 
     #[inline]
@@ -42,3 +36,8 @@ impl ZobristKeys {
     }
 }
 
+impl Default for ZobristKeys {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -66,13 +66,43 @@ pub fn apply_move(mut occ: Occupancy, m: EncMove) -> Occupancy {
     occ
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Run {
     pub start: u8,
     pub len: u8,
     pub orient: u8,
 }
 
+impl Run {
+    /// Índices das células do bloco, do início para o fim
+    #[inline]
+    pub fn cells(&self) -> impl Iterator<Item = usize> {
+        let delta = if self.orient == 0 { BOARD_SIZE } else { 1 };
+        let start = self.start as usize;
+        (0..self.len as usize).map(move |i| start + i * delta)
+    }
+
+    /// Linha (vertical) ou coluna (horizontal) onde o bloco começa
+    #[inline]
+    pub fn first(&self) -> u8 {
+        if self.orient == 0 {
+            self.start / BOARD_SIZE as u8
+        } else {
+            self.start % BOARD_SIZE as u8
+        }
+    }
+
+    /// Coluna (vertical) ou linha (horizontal) do bloco
+    #[inline]
+    pub fn line(&self) -> u8 {
+        if self.orient == 0 {
+            self.start % BOARD_SIZE as u8
+        } else {
+            self.start / BOARD_SIZE as u8
+        }
+    }
+}
+
 pub fn extract_runs(occ: Occupancy, orient: u8) -> Vec<Run> {
     let mut runs = Vec::new();
     if orient == 0 {
@@ -121,6 +151,97 @@ pub fn extract_runs(occ: Occupancy, orient: u8) -> Vec<Run> {
     runs
 }
 
+/// Células cobertas por `runs`
+pub fn runs_mask(runs: &[Run]) -> Occupancy {
+    let mut mask = Occupancy::default();
+    for run in runs {
+        run.cells().for_each(|idx| mask.set(idx));
+    }
+    mask
+}
+
+/// Bloco com a sua classificação face aos blocos do adversário
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClassifiedRun {
+    pub run: Run,
+    /// nenhuma célula pertence a um bloco do adversário
+    pub exclusive: bool,
+}
+
+/// Classifica `mine` como exclusivos ou partilhados face a `theirs`
+pub fn classify_runs(mine: &[Run], theirs: &[Run]) -> Vec<ClassifiedRun> {
+    let mask = runs_mask(theirs);
+    mine.iter()
+        .map(|&run| ClassifiedRun {
+            run,
+            exclusive: !run.cells().any(|idx| mask.is_set(idx)),
+        })
+        .collect()
+}
+
+/// Métricas estratégicas de um jogador (as `MetricasJogador` da interface).
+///
+/// Cada bloco pode ser consumido numa jogada (o segmento inteiro) ou em até
+/// `comprimento / 2` jogadas de tamanho 2, daí `min` e `max`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlayerMetrics {
+    pub runs: Vec<ClassifiedRun>,
+    pub min: u32,
+    pub max: u32,
+    pub min_exclusive: u32,
+    pub max_exclusive: u32,
+    pub min_shared: u32,
+    pub max_shared: u32,
+}
+
+impl PlayerMetrics {
+    pub fn from_runs(runs: Vec<ClassifiedRun>) -> Self {
+        let mut m = PlayerMetrics::default();
+        for r in &runs {
+            let (min, max) = (1, r.run.len as u32 / 2);
+            m.min += min;
+            m.max += max;
+            if r.exclusive {
+                m.min_exclusive += min;
+                m.max_exclusive += max;
+            } else {
+                m.min_shared += min;
+                m.max_shared += max;
+            }
+        }
+        m.runs = runs;
+        m
+    }
+}
+
+/// Métricas dos dois jogadores (as `MetricasCompletas` da interface)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BoardMetrics {
+    pub vertical: PlayerMetrics,
+    pub horizontal: PlayerMetrics,
+}
+
+impl BoardMetrics {
+    /// Métricas de `orient` (0 = vertical, 1 = horizontal)
+    pub fn side(&self, orient: u8) -> &PlayerMetrics {
+        if orient == 0 {
+            &self.vertical
+        } else {
+            &self.horizontal
+        }
+    }
+}
+
+/// Blocos dos dois lados, classificados, com as respetivas métricas
+pub fn board_metrics(occ: Occupancy) -> BoardMetrics {
+    let vertical = extract_runs(occ, 0);
+    let horizontal = extract_runs(occ, 1);
+    BoardMetrics {
+        vertical: PlayerMetrics::from_runs(classify_runs(&vertical, &horizontal)),
+        horizontal: PlayerMetrics::from_runs(classify_runs(&horizontal, &vertical)),
+    }
+}
+
 pub fn estimate_moves_from_runs(runs: &[Run]) -> u32 {
     let mut total: u32 = 0;
     for run in runs {
@@ -197,7 +318,7 @@ pub fn generate_candidate_moves(occ: Occupancy, orient: u8) -> Vec<EncMove> {
         add(base, l);
         if l >= 3 {
             add(base, l - 1);
-            add(base + 1 * delta, l - 1);
+            add(base + delta, l - 1);
         }
 
         let mut best: Option<(f64, usize, usize)> = None;
@@ -243,16 +364,304 @@ pub fn generate_candidate_moves(occ: Occupancy, orient: u8) -> Vec<EncMove> {
     moves
 }
 
+/// Classe de um bloco exclusivo de comprimento `len`: blocos da mesma classe
+/// têm árvores de jogo iguais.
+///
+/// Num bloco exclusivo só o dono joga, e os restos de um lance também são
+/// exclusivos (os blocos do adversário só encolhem), por isso o bloco é um
+/// jogo à parte cujos lances o trocam por dois blocos mais pequenos. Restos
+/// de 0 ou 1 células já não contam, e por indução um bloco de `2k` células
+/// tem exatamente os mesmos lances (a menos da classe dos restos) que um de
+/// `2k + 1`: a classe é `len / 2`.
+#[inline]
+pub fn exclusive_class(len: usize) -> usize {
+    len / 2
+}
+
+/// Tira de `moves` os lances equivalentes a um lance anterior da lista.
+///
+/// Um lance num bloco exclusivo troca a classe do bloco pelas classes dos
+/// dois restos; dois lances que dão as mesmas classes (no mesmo bloco, por
+/// simetria, ou em blocos exclusivos diferentes da mesma classe) levam a
+/// posições com árvores de jogo iguais, com o mesmo valor exato e a mesma
+/// distância até ao fim. Fica o primeiro de cada grupo, por isso a ordem do
+/// gerador mantém-se. Os lances em blocos partilhados ficam todos.
+///
+/// Só se tiram lances equivalentes, nunca lances dominados: em misère um
+/// lance que deixa mais opções pode ser pior, e não há critério de
+/// dominância provado.
+pub fn prune_equivalent_moves(occ: Occupancy, orient: u8, moves: Vec<EncMove>) -> Vec<EncMove> {
+    prune_in_runs(occ, orient, &extract_runs(occ, orient), moves)
+}
+
+/// A célula vazia `idx` tem uma vizinha vazia na direção de `orient` (está
+/// num bloco de `orient`)
+#[inline]
+fn in_run(occ: Occupancy, idx: usize, orient: u8) -> bool {
+    let (r, c) = (idx / BOARD_SIZE, idx % BOARD_SIZE);
+    let empty = |r: usize, c: usize| r < BOARD_SIZE && c < BOARD_SIZE && !occ.is_set(r * BOARD_SIZE + c);
+    if orient == 0 {
+        (r > 0 && empty(r - 1, c)) || empty(r + 1, c)
+    } else {
+        (c > 0 && empty(r, c - 1)) || empty(r, c + 1)
+    }
+}
+
+/// [`prune_equivalent_moves`] com os blocos de `orient` já extraídos
+fn prune_in_runs(occ: Occupancy, orient: u8, runs: &[Run], moves: Vec<EncMove>) -> Vec<EncMove> {
+    const CLASSES: usize = BOARD_SIZE / 2 + 1;
+    let exclusive: Vec<&Run> = runs.iter().filter(|run| !run.cells().any(|idx| in_run(occ, idx, 1 - orient))).collect();
+    if exclusive.is_empty() {
+        return moves;
+    }
+
+    let mut seen = [false; CLASSES * CLASSES * CLASSES];
+    moves
+        .into_iter()
+        .filter(|&m| {
+            let (start, len, _) = decode_move(m);
+            let segment = Run { start, len, orient };
+            let (first, line) = (segment.first(), segment.line());
+            let Some(run) = exclusive.iter().find(|r| r.line() == line && r.first() <= first && first < r.first() + r.len) else {
+                return true;
+            };
+            let before = (first - run.first()) as usize;
+            let after = run.len as usize - before - len as usize;
+            let (a, b) = (exclusive_class(before), exclusive_class(after));
+            let key = (exclusive_class(run.len as usize) * CLASSES + a.min(b)) * CLASSES + a.max(b);
+            !std::mem::replace(&mut seen[key], true)
+        })
+        .collect()
+}
+
 pub fn generate_moves_dynamic(occ: Occupancy, orient: u8) -> Vec<EncMove> {
     let runs = extract_runs(occ, orient);
     if runs.is_empty() {
         return Vec::new();
     }
     let est = estimate_moves_from_runs(&runs);
-    if est <= 220 {
+    let moves = if est <= 220 {
         generate_all_moves(occ, orient)
     } else {
         generate_candidate_moves(occ, orient)
-    }
+    };
+    prune_in_runs(occ, orient, &runs, moves)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// '.' = vazia, '#' = ocupada, uma linha de texto por linha do tabuleiro
+    fn parse(ascii: &str) -> Occupancy {
+        let mut occ = Occupancy::default();
+        for (r, line) in ascii.lines().enumerate() {
+            for (c, ch) in line.chars().enumerate() {
+                if ch == '#' {
+                    occ.set(r * BOARD_SIZE + c);
+                }
+            }
+        }
+        occ
+    }
+
+    #[test]
+    fn empty_board_metrics() {
+        let m = board_metrics(Occupancy::default());
+        for side in [&m.vertical, &m.horizontal] {
+            assert_eq!((side.min, side.max), (10, 50));
+            assert_eq!((side.min_exclusive, side.max_exclusive), (0, 0));
+            assert_eq!((side.min_shared, side.max_shared), (10, 50));
+            assert_eq!(side.runs.len(), 10);
+        }
+    }
+
+    #[test]
+    fn metrics_match_the_interface_example() {
+        // o exemplo de `logic.test.ts` em que a vertical já ganhou
+        let occ = parse(
+            ".#.#.#.###\n\
+             .#.#.#.###\n\
+             .#.#.#.#..\n\
+             .#.#.#.#..\n\
+             .#.#.#.#..\n\
+             .#.#.#.#..\n\
+             .#.#.#.#..\n\
+             .#.#.#.#..\n\
+             .#.#.#.#..\n\
+             .###.###..",
+        );
+        let m = board_metrics(occ);
+        let v = &m.vertical;
+        // colunas 0, 2, 4, 6 exclusivas (10, 9, 10, 9); 8 e 9 partilhadas (8 cada)
+        assert_eq!((v.min, v.max), (6, 26));
+        assert_eq!((v.min_exclusive, v.max_exclusive), (4, 18));
+        assert_eq!((v.min_shared, v.max_shared), (2, 8));
+        let h = &m.horizontal;
+        assert_eq!((h.min, h.max), (8, 8));
+        assert_eq!((h.min_exclusive, h.max_exclusive), (0, 0));
+
+        let col8 = v.runs.iter().find(|r| r.run.line() == 8).unwrap();
+        assert_eq!((col8.run.first(), col8.run.len, col8.exclusive), (2, 8, false));
+        let row9 = h.runs.iter().find(|r| r.run.line() == 9).unwrap();
+        assert_eq!((row9.run.first(), row9.run.len), (8, 2));
+        assert_eq!(m.side(1), h);
+    }
+
+    #[test]
+    fn metrics_match_the_typescript_cases() {
+        // os mesmos casos de `logic.test.ts`: o painel de estratégia
+        // (calcularMetricasCompletas) e o motor contam os blocos da mesma maneira
+        let cases = include_str!("../../../../src/games/quelhas/metricas.casos.txt");
+        let mut checked = 0;
+        for case in cases.split("\n\n") {
+            let lines: Vec<&str> = case.lines().filter(|l| !l.is_empty() && !l.starts_with("//")).collect();
+            if lines.is_empty() {
+                continue;
+            }
+            let board = lines[..BOARD_SIZE].join("\n");
+            let m = board_metrics(parse(&board));
+            for line in &lines[BOARD_SIZE..] {
+                let (side, expected) = line.split_once(' ').unwrap();
+                let p = m.side(if side == "v" { 0 } else { 1 });
+                let counts = [p.min, p.max, p.min_exclusive, p.max_exclusive, p.min_shared, p.max_shared];
+                let runs = p.runs.iter().map(|r| {
+                    let class = if r.exclusive { 'e' } else { 'p' };
+                    format!("{}:{}:{}{class}", r.run.line(), r.run.first(), r.run.len)
+                });
+                let got: Vec<String> = counts.iter().map(u32::to_string).chain(runs).collect();
+                assert_eq!(got.join(" "), expected, "{side}\n{board}");
+            }
+            checked += 1;
+        }
+        assert_eq!(checked, 8);
+    }
+
+    /// Opções de um bloco exclusivo: as classes dos restos, sem os vazios
+    fn options(len: usize, class: &[usize]) -> std::collections::BTreeSet<Vec<usize>> {
+        let mut set = std::collections::BTreeSet::new();
+        for take in MIN_LEN..=len {
+            for before in 0..=len - take {
+                let mut rest: Vec<usize> =
+                    [before, len - take - before].into_iter().filter(|&l| l >= MIN_LEN).map(|l| class[l]).collect();
+                rest.sort_unstable();
+                set.insert(rest);
+            }
+        }
+        set
+    }
+
+    #[test]
+    fn exclusive_classes_have_identical_game_trees() {
+        // classes por indução: um bloco é da classe de um mais curto com as
+        // mesmas opções, senão abre uma classe nova
+        let mut class = vec![0; BOARD_SIZE + 1];
+        let mut seen: Vec<std::collections::BTreeSet<Vec<usize>>> = vec![Default::default()];
+        for len in MIN_LEN..=BOARD_SIZE {
+            let opts = options(len, &class);
+            class[len] = match seen.iter().position(|o| *o == opts) {
+                Some(c) => c,
+                None => {
+                    seen.push(opts);
+                    seen.len() - 1
+                }
+            };
+        }
+        let expected: Vec<usize> = (0..=BOARD_SIZE).map(exclusive_class).collect();
+        assert_eq!(class, expected);
+    }
+
+    /// Valor misère exato (quem fica sem jogadas ganha) com a distância:
+    /// `100 - plies` a ganhar, `plies - 100` a perder
+    fn exact(
+        occ: Occupancy,
+        side: u8,
+        generate: fn(Occupancy, u8) -> Vec<EncMove>,
+        memo: &mut std::collections::HashMap<(u64, u64, u8), i32>,
+    ) -> i32 {
+        if let Some(&v) = memo.get(&(occ.low, occ.high, side)) {
+            return v;
+        }
+        let moves = generate(occ, side);
+        let v = if moves.is_empty() {
+            100
+        } else {
+            moves
+                .into_iter()
+                .map(|m| {
+                    let child = -exact(apply_move(occ, m), 1 - side, generate, memo);
+                    child - child.signum()
+                })
+                .max()
+                .unwrap()
+        };
+        memo.insert((occ.low, occ.high, side), v);
+        v
+    }
+
+    fn pruned(occ: Occupancy, orient: u8) -> Vec<EncMove> {
+        prune_equivalent_moves(occ, orient, generate_all_moves(occ, orient))
+    }
+
+    #[test]
+    fn pruning_keeps_exact_values_on_exhaustive_small_boards() {
+        // todas as ocupações de uma janela (o resto do tabuleiro cheio)
+        for (rows, cols) in [(3, 4), (4, 3), (2, 6)] {
+            let window: Vec<usize> = (0..rows).flat_map(|r| (0..cols).map(move |c| r * BOARD_SIZE + c)).collect();
+            let (mut full, mut fewer) = (Default::default(), Default::default());
+            let mut removed = 0;
+            for mask in 0u32..1 << window.len() {
+                let mut occ = Occupancy { low: !0, high: !0 };
+                for (i, &idx) in window.iter().enumerate() {
+                    if mask & (1 << i) != 0 {
+                        if idx < 64 {
+                            occ.low &= !(1 << idx);
+                        } else {
+                            occ.high &= !(1 << (idx - 64));
+                        }
+                    }
+                }
+                for side in 0..2 {
+                    let all = generate_all_moves(occ, side);
+                    let kept = pruned(occ, side);
+                    assert!(kept.iter().all(|m| all.contains(m)));
+                    assert_eq!(kept.is_empty(), all.is_empty());
+                    removed += all.len() - kept.len();
+                    assert_eq!(
+                        exact(occ, side, pruned, &mut fewer),
+                        exact(occ, side, generate_all_moves, &mut full),
+                        "{:?} {}",
+                        occ,
+                        side
+                    );
+                }
+            }
+            assert!(removed > 0);
+        }
+    }
+
+    #[test]
+    fn equivalent_moves_in_exclusive_runs_are_removed() {
+        // colunas 0 e 2 exclusivas (10 células, sem blocos horizontais)
+        let occ = parse(
+            ".#.#######\n\
+             .#.#######\n\
+             .#.#######\n\
+             .#.#######\n\
+             .#.#######\n\
+             .#.#######\n\
+             .#.#######\n\
+             .#.#######\n\
+             .#.#######\n\
+             .#.#######",
+        );
+        assert_eq!(generate_all_moves(occ, 0).len(), 2 * 45);
+        let kept = pruned(occ, 0);
+        // a coluna 2 é igual à 0; na 0 ficam os pares de classes dos restos
+        // (a, b) com a <= b e a + b <= 4
+        assert_eq!(kept.len(), 9);
+        assert!(kept.iter().all(|&m| (decode_move(m).0 as usize).is_multiple_of(BOARD_SIZE)));
+        assert!(generate_moves_dynamic(occ, 1).is_empty());
+    }
+}
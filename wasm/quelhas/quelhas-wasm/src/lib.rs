@@ -3,20 +3,41 @@
 //! - 10x10 board (100 bits) packed into 2×u64
 //! - Negamax + alpha-beta + PVS, TT, killers/history
 //! - Root randomization for easier difficulties (top_n + score_delta)
+//! - Calibrated skill levels 0..=20 (node budget, eval noise, softmax over MultiPV)
+//! - Post-game review grading every move (`review_game`)
+//! - Exact play once the position splits into exclusive runs (`quelhas_ai::endgame`)
+//! - Optional canonical transposition keys shared by symmetric and
+//!   class-equivalent positions (`set_canonical_keys`)
+//! - Strategic run metrics for the strategy panel (`board_metrics`)
 
 use wasm_bindgen::prelude::*;
 
-use quelhas_ai::engine as ai_engine;
+use quelhas_ai::engine::{self as ai_engine, History};
+use quelhas_ai::mistakes::MistakeModel;
+use quelhas_ai::review;
+use quelhas_ai::rng::Rng;
+use quelhas_ai::skill;
+use quelhas_ai::state;
 use quelhas_ai::tt::TranspositionTable;
 use quelhas_ai::zobrist::ZobristKeys;
-use quelhas_core::Occupancy;
+use quelhas_core::{EncMove, Occupancy};
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = Date)]
     fn now() -> f64;
 }
 
+/// System clock in milliseconds, for native builds and tests
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
 #[wasm_bindgen(start)]
 pub fn init() {
     #[cfg(feature = "console_error_panic_hook")]
@@ -32,32 +53,311 @@ pub struct SearchResult {
     pub tt_hits: u64,
     pub tt_probes: u64,
     pub score: i32,
+    /// Permille of the transposition table filled by this search
+    pub hashfull: u32,
+    /// Turns of the side to move until a proven win (≥ 1) or loss (≤ -1)
+    pub mate_in: Option<i32>,
+    /// The move is a deliberate mistake (see `set_blunder_rate`)
+    pub blunder: bool,
+    pv: Vec<EncMove>,
+}
+
+#[wasm_bindgen]
+impl SearchResult {
+    /// Principal variation, starting with `best_move` (empty if there is none)
+    #[wasm_bindgen(getter)]
+    pub fn pv(&self) -> Vec<EncMove> {
+        self.pv.clone()
+    }
+}
+
+/// Post-game review, one entry per move in each array
+#[wasm_bindgen]
+pub struct GameReview {
+    inner: review::GameReview,
+}
+
+#[wasm_bindgen]
+impl GameReview {
+    /// Engine's preferred move before each played move (-1 if none)
+    #[wasm_bindgen(getter)]
+    pub fn best_moves(&self) -> Vec<i32> {
+        self.inner.moves.iter().map(|m| m.best_move.map_or(-1, |mv| mv as i32)).collect()
+    }
+
+    /// Score of the best move, for the side that played
+    #[wasm_bindgen(getter)]
+    pub fn best_scores(&self) -> Vec<i32> {
+        self.inner.moves.iter().map(|m| m.best_score).collect()
+    }
+
+    /// Score of the played move, for the side that played
+    #[wasm_bindgen(getter)]
+    pub fn played_scores(&self) -> Vec<i32> {
+        self.inner.moves.iter().map(|m| m.played_score).collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn eval_drops(&self) -> Vec<i32> {
+        self.inner.moves.iter().map(|m| m.eval_drop).collect()
+    }
+
+    /// 0 = best, 1 = good, 2 = inaccuracy, 3 = mistake, 4 = blunder
+    #[wasm_bindgen(getter)]
+    pub fn classes(&self) -> Vec<u8> {
+        self.inner.moves.iter().map(|m| m.class as u8).collect()
+    }
+
+    /// Proven outcome for the mover before each move: 1 win, -1 loss, 0 unknown
+    #[wasm_bindgen(getter)]
+    pub fn outcomes_before(&self) -> Vec<i8> {
+        self.inner.moves.iter().map(|m| m.outcome_before.sign()).collect()
+    }
+
+    /// Proven outcome for the mover after each move: 1 win, -1 loss, 0 unknown
+    #[wasm_bindgen(getter)]
+    pub fn outcomes_after(&self) -> Vec<i8> {
+        self.inner.moves.iter().map(|m| m.outcome_after.sign()).collect()
+    }
+
+    /// Index of the move that decided the game, if the search proved one
+    #[wasm_bindgen(getter)]
+    pub fn critical_ply(&self) -> Option<u32> {
+        self.inner.critical_ply.map(|p| p as u32)
+    }
+}
+
+/// Strategic metrics of one player, as in the strategy panel's `MetricasJogador`
+///
+/// Runs are listed in board order (columns for vertical, rows for
+/// horizontal); the `run_*` arrays have one entry per run.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct PlayerMetrics {
+    inner: quelhas_core::PlayerMetrics,
+}
+
+#[wasm_bindgen]
+impl PlayerMetrics {
+    /// Fewest moves to use up every run (one per run)
+    #[wasm_bindgen(getter)]
+    pub fn min(&self) -> u32 {
+        self.inner.min
+    }
+
+    /// Most moves the runs allow (length / 2 per run)
+    #[wasm_bindgen(getter)]
+    pub fn max(&self) -> u32 {
+        self.inner.max
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn min_exclusive(&self) -> u32 {
+        self.inner.min_exclusive
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_exclusive(&self) -> u32 {
+        self.inner.max_exclusive
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn min_shared(&self) -> u32 {
+        self.inner.min_shared
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_shared(&self) -> u32 {
+        self.inner.max_shared
+    }
+
+    /// First row (vertical) or column (horizontal) of each run (`inicio`)
+    #[wasm_bindgen(getter)]
+    pub fn run_starts(&self) -> Vec<u8> {
+        self.inner.runs.iter().map(|r| r.run.first()).collect()
+    }
+
+    /// Length of each run (`comprimento`)
+    #[wasm_bindgen(getter)]
+    pub fn run_lengths(&self) -> Vec<u8> {
+        self.inner.runs.iter().map(|r| r.run.len).collect()
+    }
+
+    /// Column (vertical) or row (horizontal) of each run (`indiceFixo`)
+    #[wasm_bindgen(getter)]
+    pub fn run_lines(&self) -> Vec<u8> {
+        self.inner.runs.iter().map(|r| r.run.line()).collect()
+    }
+
+    /// 1 if no cell of the run belongs to an opponent run (`exclusivo`), else 0
+    #[wasm_bindgen(getter)]
+    pub fn run_exclusive(&self) -> Vec<u8> {
+        self.inner.runs.iter().map(|r| r.exclusive as u8).collect()
+    }
+}
+
+/// Metrics of both players (`MetricasCompletas`)
+#[wasm_bindgen]
+pub struct BoardMetrics {
+    vertical: PlayerMetrics,
+    horizontal: PlayerMetrics,
+}
+
+#[wasm_bindgen]
+impl BoardMetrics {
+    #[wasm_bindgen(getter)]
+    pub fn vertical(&self) -> PlayerMetrics {
+        self.vertical.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn horizontal(&self) -> PlayerMetrics {
+        self.horizontal.clone()
+    }
+}
+
+/// Run metrics of a position, computed exactly as the engine's evaluation sees them
+#[wasm_bindgen]
+pub fn board_metrics(low_lo: u32, low_hi: u32, high_lo: u32, high_hi: u32) -> BoardMetrics {
+    let occ = Occupancy::from_u32_parts(low_lo, low_hi, high_lo, high_hi);
+    let metrics = quelhas_core::board_metrics(occ);
+    BoardMetrics {
+        vertical: PlayerMetrics { inner: metrics.vertical },
+        horizontal: PlayerMetrics { inner: metrics.horizontal },
+    }
 }
 
+const MAX_TT_MB: u32 = 256;
+
 #[wasm_bindgen]
 pub struct QuelhasEngine {
     tt: TranspositionTable,
     zobrist: ZobristKeys,
-    age: u8,
+    history: History,
+    rng: Rng,
+    seed: u32,
+    skill_level: Option<u8>,
+    mistakes: Option<MistakeModel>,
+    canonical_keys: bool,
 }
 
 #[wasm_bindgen]
 impl QuelhasEngine {
+    /// Engine with a transposition table of `tt_mb` megabytes; `seed` drives
+    /// every random choice, so the same seed and the same requests (with
+    /// depth rather than time limits) replay the same moves
     #[wasm_bindgen(constructor)]
-    pub fn new(tt_size_bits: u32) -> QuelhasEngine {
-        let tt_size = 1usize << tt_size_bits.min(20);
+    pub fn new(tt_mb: u32, seed: u32) -> QuelhasEngine {
         QuelhasEngine {
-            tt: TranspositionTable::new(tt_size),
+            tt: TranspositionTable::new(tt_mb.min(MAX_TT_MB) as usize),
             zobrist: ZobristKeys::new(),
-            age: 0,
+            history: History::new(),
+            rng: Rng::new(seed as u64),
+            seed,
+            skill_level: None,
+            mistakes: None,
+            canonical_keys: false,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// Restarts the random stream from `seed` (e.g. to replay a reported game)
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed;
+        self.rng = Rng::new(seed as u64);
+    }
+
+    /// Plays at skill level `level` (0 = beginner, 20 = full strength); while
+    /// a level is set `search` ignores `top_n` and `score_delta`. Clears the
+    /// transposition table, since weakened searches store noisy scores
+    pub fn set_skill_level(&mut self, level: u8) {
+        self.skill_level = Some(level.min(skill::MAX_SKILL_LEVEL));
+        self.clear_tt();
+    }
+
+    /// Back to the `top_n` / `score_delta` randomization of `search`
+    pub fn clear_skill_level(&mut self) {
+        if self.skill_level.take().is_some() {
+            self.clear_tt();
         }
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn skill_level(&self) -> Option<u8> {
+        self.skill_level
+    }
+
+    /// Measured rating of `level` relative to level 20 (from self-play)
+    pub fn skill_elo_gap(level: u8) -> i32 {
+        skill::elo_gap(level)
+    }
+
+    /// Standard error of `skill_elo_gap(level)`
+    pub fn skill_elo_error(level: u8) -> i32 {
+        skill::elo_error(level)
+    }
+
+    /// Makes human-like mistakes in about `rate` of the moves (0 = never):
+    /// more often in complex positions, preferring moves that spend or give
+    /// away exclusive blocks, and never a move that leaves the opponent
+    /// without moves
+    pub fn set_blunder_rate(&mut self, rate: f64) {
+        self.mistakes = (rate > 0.0).then(|| MistakeModel::new(rate));
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn blunder_rate(&self) -> f64 {
+        self.mistakes.map_or(0.0, |m| m.blunder_rate)
+    }
+
+    /// Keys the transposition table by the canonical form of the run
+    /// structure, so that mirrored or transposed boards and boards whose
+    /// exclusive runs only differ within a class share results; slower per
+    /// node. Clears the transposition table when the mode changes
+    pub fn set_canonical_keys(&mut self, enabled: bool) {
+        if self.canonical_keys != enabled {
+            self.canonical_keys = enabled;
+            self.clear_tt();
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn canonical_keys(&self) -> bool {
+        self.canonical_keys
+    }
+
     pub fn clear_tt(&mut self) {
         self.tt.clear();
-        self.age = 0;
+        self.history.clear();
     }
 
+    /// Reallocates the transposition table with `tt_mb` megabytes (clears it)
+    pub fn resize_tt(&mut self, tt_mb: u32) {
+        self.tt.resize(tt_mb.min(MAX_TT_MB) as usize);
+    }
+
+    /// Permille of the transposition table used by the current search
+    pub fn hashfull(&self) -> u32 {
+        self.tt.hashfull()
+    }
+
+    /// Serializes the transposition table, Zobrist keys and history table
+    /// into a versioned, checksummed snapshot
+    pub fn export_state(&self) -> Vec<u8> {
+        state::export(&self.tt, &self.zobrist, &self.history)
+    }
+
+    /// Restores a snapshot from `export_state`; corrupted, truncated or
+    /// non-Quelhas snapshots are rejected and leave the engine untouched
+    pub fn import_state(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        self.try_import_state(bytes).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn search(
         &mut self,
         low_lo: u32,
@@ -72,11 +372,28 @@ impl QuelhasEngine {
     ) -> SearchResult {
         let occ = Occupancy::from_u32_parts(low_lo, low_hi, high_lo, high_hi);
 
-        self.age = self.age.wrapping_add(1);
+        self.tt.new_search();
+        self.history.age();
         let deadline = now() + time_budget_ms;
 
-        let mut searcher = ai_engine::Searcher::new(&mut self.tt, &self.zobrist, self.age, deadline, max_depth);
-        let result = searcher.iterative_deepening(occ, side, top_n, score_delta, || now());
+        let mut searcher = ai_engine::Searcher::new(
+            &mut self.tt,
+            &self.zobrist,
+            &mut self.history,
+            &mut self.rng,
+            deadline,
+            max_depth,
+        );
+        if self.canonical_keys {
+            searcher = searcher.with_canonical_keys();
+        }
+        if let Some(level) = self.skill_level {
+            searcher = searcher.with_skill(skill::params(level));
+        }
+        if let Some(model) = self.mistakes {
+            searcher = searcher.with_mistakes(model);
+        }
+        let result = searcher.iterative_deepening(occ, side, top_n, score_delta, now);
 
         SearchResult {
             best_move: result.best_move.map(|m| m as i32).unwrap_or(-1),
@@ -86,7 +403,109 @@ impl QuelhasEngine {
             tt_hits: result.tt_hits,
             tt_probes: result.tt_probes,
             score: result.score,
+            hashfull: self.tt.hashfull(),
+            mate_in: ai_engine::mate_in(result.score),
+            blunder: result.blunder,
+            pv: result.pv,
         }
     }
+
+    /// Reviews a game played from the empty board (`first_side` moving first):
+    /// every position is searched for up to `time_per_position_ms` and
+    /// `max_depth` at full strength, whatever the skill level, and every move
+    /// graded. The review uses a table, history and random stream of its own
+    /// and leaves the game's untouched.
+    pub fn review_game(
+        &self,
+        moves: &[EncMove],
+        first_side: u8,
+        time_per_position_ms: f64,
+        max_depth: u32,
+    ) -> Result<GameReview, JsValue> {
+        self.try_review_game(moves, first_side, time_per_position_ms, max_depth)
+            .map(|inner| GameReview { inner })
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+impl QuelhasEngine {
+    /// Native counterpart of `review_game`
+    pub fn try_review_game(
+        &self,
+        moves: &[EncMove],
+        first_side: u8,
+        time_per_position_ms: f64,
+        max_depth: u32,
+    ) -> Result<review::GameReview, review::ReviewError> {
+        // The game's table may hold scores searched with skill-level noise and
+        // the game's random stream must not move: the review gets its own
+        let mut tt = self.tt.empty_like();
+        let mut history = History::new();
+        let mut rng = Rng::new(self.seed as u64);
+        review::review_game(moves, first_side, |occ, side| {
+            tt.new_search();
+            history.age();
+            let deadline = now() + time_per_position_ms;
+            let mut searcher = ai_engine::Searcher::new(&mut tt, &self.zobrist, &mut history, &mut rng, deadline, max_depth);
+            if self.canonical_keys {
+                searcher = searcher.with_canonical_keys();
+            }
+            let result = searcher.iterative_deepening(occ, side, 0, 0, now);
+            (result.best_move, result.score)
+        })
+    }
+
+    /// Native counterpart of `import_state`, for tools that persist sessions to disk
+    pub fn try_import_state(&mut self, bytes: &[u8]) -> Result<(), state::StateError> {
+        let restored = state::import(bytes)?;
+        self.tt = restored.tt;
+        self.zobrist = restored.zobrist;
+        self.history = restored.history;
+        Ok(())
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quelhas_core::{apply_move, generate_all_moves};
+
+    fn search(engine: &mut QuelhasEngine, occ: Occupancy, side: u8) -> i32 {
+        let (low, high) = (occ.low, occ.high);
+        engine
+            .search(low as u32, (low >> 32) as u32, high as u32, (high >> 32) as u32, side, f64::MAX, 4, 0, 0)
+            .best_move
+    }
+
+    #[test]
+    fn review_ignores_the_games_table_and_random_stream() {
+        let mut rng = Rng::new(0);
+        let (mut occ, mut side) = (Occupancy::default(), 0u8);
+        let mut moves = Vec::new();
+        for _ in 0..24 {
+            let legal = generate_all_moves(occ, side);
+            let mv = legal[rng.below(legal.len())];
+            moves.push(mv);
+            occ = apply_move(occ, mv);
+            side ^= 1;
+        }
+        let clean = QuelhasEngine::new(4, 3).try_review_game(&moves, 0, f64::MAX, 2).unwrap();
+
+        // Level 0 searches along the game fill the table with noisy scores
+        // and draw from the random stream
+        let mut played = QuelhasEngine::new(4, 3);
+        let mut twin = QuelhasEngine::new(4, 3);
+        for engine in [&mut played, &mut twin] {
+            engine.set_skill_level(0);
+            let (mut occ, mut side) = (Occupancy::default(), 0u8);
+            for &mv in &moves {
+                search(engine, occ, side);
+                occ = apply_move(occ, mv);
+                side ^= 1;
+            }
+        }
+
+        assert_eq!(played.try_review_game(&moves, 0, f64::MAX, 2).unwrap(), clean);
+        assert_eq!(search(&mut played, occ, side), search(&mut twin, occ, side));
+    }
+}